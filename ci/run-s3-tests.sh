@@ -0,0 +1,26 @@
+#!/bin/bash
+set -euo pipefail
+
+function stop_minio() {
+    echo stopping minio container
+    docker stop minio-test &> /dev/null || true
+    docker rm minio-test &> /dev/null || true
+}
+
+trap 'stop_minio' EXIT
+
+stop_minio
+
+echo starting minio container
+docker run -d --name minio-test -e MINIO_ROOT_USER=minio -e MINIO_ROOT_PASSWORD=password -p 9000:9000 minio/minio server /data
+
+while ! docker exec minio-test mc alias set local http://localhost:9000 minio password &> /dev/null; do
+    echo waiting for minio to accept connections
+    sleep 1
+done
+
+echo creating bucket
+docker exec minio-test mc mb local/test-registry
+
+echo running tests
+WARG_S3_ENDPOINT=http://localhost:9000 WARG_S3_BUCKET=test-registry WARG_S3_ACCESS_KEY_ID=minio WARG_S3_SECRET_ACCESS_KEY=password cargo test --features s3 $@
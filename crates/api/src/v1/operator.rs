@@ -0,0 +1,181 @@
+//! Types relating to the operator API.
+
+use crate::Status;
+use serde::{de::Unexpected, Deserialize, Serialize, Serializer};
+use std::borrow::Cow;
+use std::str::FromStr;
+use thiserror::Error;
+use warg_crypto::hash::AnyHash;
+use warg_protocol::{
+    registry::{RecordId, RegistryIndex},
+    ProtoEnvelopeBody,
+};
+
+/// Represents a request to publish a record to the operator log.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PublishOperatorRecordRequest<'a> {
+    /// The publish record to add to the operator log.
+    pub record: Cow<'a, ProtoEnvelopeBody>,
+}
+
+/// Represents an operator record API entity in a registry.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperatorRecord {
+    /// The identifier of the operator record.
+    pub record_id: RecordId,
+    /// The current state of the operator record.
+    #[serde(flatten)]
+    pub state: OperatorRecordState,
+}
+
+/// Represents an operator record in one of the following states:
+/// * `processing` - The record is being processed.
+/// * `rejected` - The record was rejected.
+/// * `published` - The record was published to the log.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "camelCase")]
+pub enum OperatorRecordState {
+    /// The operator record is processing.
+    #[serde(rename_all = "camelCase")]
+    Processing,
+    /// The operator record is rejected.
+    #[serde(rename_all = "camelCase")]
+    Rejected {
+        /// The reason the record was rejected.
+        reason: String,
+    },
+    /// The operator record was successfully published to the log.
+    #[serde(rename_all = "camelCase")]
+    Published {
+        /// The published index of the record in the registry log.
+        registry_index: RegistryIndex,
+    },
+}
+
+/// Represents an operator API error.
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum OperatorError {
+    /// The provided record was not found.
+    #[error("record `{0}` was not found")]
+    RecordNotFound(RecordId),
+    /// The operation was not authorized by the registry.
+    #[error("unauthorized operation: {0}")]
+    Unauthorized(String),
+    /// The record was rejected by the registry.
+    #[error("the operator record was rejected by the registry: {0}")]
+    Rejection(String),
+    /// An error with a message occurred.
+    #[error("{message}")]
+    Message {
+        /// The HTTP status code.
+        status: u16,
+        /// The error message
+        message: String,
+    },
+}
+
+impl OperatorError {
+    /// Returns the HTTP status code of the error.
+    pub fn status(&self) -> u16 {
+        match self {
+            Self::Unauthorized(_) => 401,
+            Self::RecordNotFound(_) => 404,
+            Self::Rejection(_) => 422,
+            Self::Message { status, .. } => *status,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum EntityType {
+    Record,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged, rename_all = "camelCase")]
+enum RawError<'a, T>
+where
+    T: Clone + ToOwned,
+    <T as ToOwned>::Owned: Serialize + for<'b> Deserialize<'b>,
+{
+    Unauthorized {
+        status: Status<401>,
+        message: Cow<'a, str>,
+    },
+    NotFound {
+        status: Status<404>,
+        #[serde(rename = "type")]
+        ty: EntityType,
+        id: Cow<'a, T>,
+    },
+    Rejection {
+        status: Status<422>,
+        message: Cow<'a, str>,
+    },
+    Message {
+        status: u16,
+        message: Cow<'a, str>,
+    },
+}
+
+impl Serialize for OperatorError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Unauthorized(message) => RawError::Unauthorized::<()> {
+                status: Status::<401>,
+                message: Cow::Borrowed(message),
+            }
+            .serialize(serializer),
+            Self::RecordNotFound(record_id) => RawError::NotFound {
+                status: Status::<404>,
+                ty: EntityType::Record,
+                id: Cow::Borrowed(record_id),
+            }
+            .serialize(serializer),
+            Self::Rejection(message) => RawError::Rejection::<()> {
+                status: Status::<422>,
+                message: Cow::Borrowed(message),
+            }
+            .serialize(serializer),
+            Self::Message { status, message } => RawError::Message::<()> {
+                status: *status,
+                message: Cow::Borrowed(message),
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for OperatorError {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match RawError::<String>::deserialize(deserializer)? {
+            RawError::Unauthorized { status: _, message } => {
+                Ok(Self::Unauthorized(message.into_owned()))
+            }
+            RawError::NotFound { status: _, ty, id } => match ty {
+                EntityType::Record => Ok(Self::RecordNotFound(
+                    AnyHash::from_str(&id)
+                        .map_err(|_| {
+                            serde::de::Error::invalid_value(
+                                Unexpected::Str(&id),
+                                &"a valid record id",
+                            )
+                        })?
+                        .into(),
+                )),
+            },
+            RawError::Rejection { status: _, message } => Ok(Self::Rejection(message.into_owned())),
+            RawError::Message { status, message } => Ok(Self::Message {
+                status,
+                message: message.into_owned(),
+            }),
+        }
+    }
+}
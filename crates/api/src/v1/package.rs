@@ -140,6 +140,15 @@ pub enum PackageError {
     /// The package was rejected by the registry.
     #[error("the package was rejected by the registry: {0}")]
     Rejection(String),
+    /// The signing key has published too many records recently.
+    #[error("the publish rate limit was exceeded: {0}")]
+    RateLimitExceeded(String),
+    /// The uploaded content exceeds the maximum content size of the registry.
+    #[error("content exceeds the maximum size of {0} bytes")]
+    ContentTooLarge(u64),
+    /// The provided package's namespace has no storage quota left for the content.
+    #[error("namespace `{0}` has exceeded its storage quota")]
+    NamespaceQuotaExceeded(String),
     /// An error with a message occurred.
     #[error("{message}")]
     Message {
@@ -158,8 +167,11 @@ impl PackageError {
             Self::LogNotFound(_) | Self::RecordNotFound(_) | Self::NamespaceNotDefined(_) => 404,
             Self::NamespaceImported(_) | Self::ConflictPendingPublish(_) => 409,
             Self::RecordNotSourcing => 405,
+            Self::ContentTooLarge(_) => 413,
             Self::Rejection(_) => 422,
+            Self::RateLimitExceeded(_) => 429,
             Self::NotSupported(_) => 501,
+            Self::NamespaceQuotaExceeded(_) => 507,
             Self::Message { status, .. } => *status,
         }
     }
@@ -201,14 +213,29 @@ where
     RecordNotSourcing {
         status: Status<405>,
     },
+    #[serde(rename_all = "camelCase")]
+    ContentTooLarge {
+        status: Status<413>,
+        max_size: u64,
+    },
     Rejection {
         status: Status<422>,
         message: Cow<'a, str>,
     },
+    RateLimitExceeded {
+        status: Status<429>,
+        message: Cow<'a, str>,
+    },
     NotSupported {
         status: Status<501>,
         message: Cow<'a, str>,
     },
+    InsufficientStorage {
+        status: Status<507>,
+        #[serde(rename = "type")]
+        ty: EntityType,
+        id: Cow<'a, T>,
+    },
     Message {
         status: u16,
         message: Cow<'a, str>,
@@ -267,6 +294,22 @@ impl Serialize for PackageError {
                 message: Cow::Borrowed(message),
             }
             .serialize(serializer),
+            Self::RateLimitExceeded(message) => RawError::RateLimitExceeded::<()> {
+                status: Status::<429>,
+                message: Cow::Borrowed(message),
+            }
+            .serialize(serializer),
+            Self::ContentTooLarge(max_size) => RawError::ContentTooLarge::<()> {
+                status: Status::<413>,
+                max_size: *max_size,
+            }
+            .serialize(serializer),
+            Self::NamespaceQuotaExceeded(namespace) => RawError::InsufficientStorage {
+                status: Status::<507>,
+                ty: EntityType::Namespace,
+                id: Cow::Borrowed(namespace),
+            }
+            .serialize(serializer),
             Self::Message { status, message } => RawError::Message::<()> {
                 status: *status,
                 message: Cow::Borrowed(message),
@@ -331,6 +374,20 @@ impl<'de> Deserialize<'de> for PackageError {
             RawError::NotSupported { status: _, message } => {
                 Ok(Self::NotSupported(message.into_owned()))
             }
+            RawError::RateLimitExceeded { status: _, message } => {
+                Ok(Self::RateLimitExceeded(message.into_owned()))
+            }
+            RawError::ContentTooLarge {
+                status: _,
+                max_size,
+            } => Ok(Self::ContentTooLarge(max_size)),
+            RawError::InsufficientStorage { status: _, ty, id } => match ty {
+                EntityType::Namespace => Ok(Self::NamespaceQuotaExceeded(id.into_owned())),
+                _ => Err(serde::de::Error::invalid_value(
+                    Unexpected::Enum,
+                    &"a valid entity type",
+                )),
+            },
             RawError::Message { status, message } => Ok(Self::Message {
                 status,
                 message: message.into_owned(),
@@ -1,7 +1,7 @@
 //! The paths of the Warg REST API.
 
-use warg_crypto::hash::AnyHash;
-use warg_protocol::registry::{LogId, RecordId};
+use warg_crypto::{hash::AnyHash, signing::KeyID};
+use warg_protocol::registry::{LogId, RecordId, RegistryLen};
 
 /// The path of the "fetch logs" API.
 pub fn fetch_logs() -> &'static str {
@@ -13,6 +13,21 @@ pub fn fetch_checkpoint() -> &'static str {
     "v1/fetch/checkpoint"
 }
 
+/// The path of the "fetch checkpoint note" API.
+pub fn fetch_checkpoint_note() -> &'static str {
+    "v1/fetch/checkpoint/note"
+}
+
+/// The path of the latest checkpoint note alongside the log tiles.
+pub fn tiles_checkpoint() -> &'static str {
+    "tiles/checkpoint"
+}
+
+/// The path of a log tile given its path relative to the tiles root.
+pub fn tile(tile_path: &str) -> String {
+    format!("tiles/{tile_path}")
+}
+
 /// The path of the "fetch package names" API.
 pub fn fetch_package_names() -> &'static str {
     "v1/fetch/names"
@@ -28,6 +43,16 @@ pub fn publish_package_record(log_id: &LogId) -> String {
     format!("v1/package/{log_id}/record")
 }
 
+/// The path of the "publish operator record" API.
+pub fn publish_operator_record() -> &'static str {
+    "v1/operator/record"
+}
+
+/// The path for an operator record.
+pub fn operator_record(record_id: &RecordId) -> String {
+    format!("v1/operator/record/{record_id}")
+}
+
 /// The path to request download of content digest.
 pub fn content_sources(digest: &AnyHash) -> String {
     format!("v1/content/{digest}")
@@ -52,3 +77,23 @@ pub fn prove_inclusion() -> &'static str {
 pub fn verify_checkpoint() -> &'static str {
     "v1/verify/checkpoint"
 }
+
+/// The path for submitting a witness cosignature of a checkpoint.
+pub fn cosign_checkpoint() -> &'static str {
+    "v1/witness/cosign"
+}
+
+/// The path for the witness cosignatures of a checkpoint.
+pub fn checkpoint_cosignatures(log_length: RegistryLen) -> String {
+    format!("v1/witness/checkpoint/{log_length}/cosignatures")
+}
+
+/// The path for the most recently cosigned checkpoints.
+pub fn cosigned_checkpoints() -> &'static str {
+    "v1/witness/cosigned"
+}
+
+/// The path for the latest checkpoint cosigned by a witness key.
+pub fn witness_checkpoint(key_id: &KeyID) -> String {
+    format!("v1/witness/key/{key_id}/checkpoint")
+}
@@ -36,6 +36,26 @@ pub struct InclusionRequest {
     pub log_length: RegistryLen,
     /// The log leaf indexes in the registry log to check for inclusion.
     pub leafs: Vec<RegistryIndex>,
+    /// The requested format of the map inclusion proof.
+    #[serde(default)]
+    pub map_format: MapProofFormat,
+    /// The package log IDs to prove the absence of in the map.
+    ///
+    /// Absence proofs are only part of a map proof bundle, so requesting any
+    /// always responds with the bundle format.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub absent: Vec<LogId>,
+}
+
+/// Represents the format of a map inclusion proof.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MapProofFormat {
+    /// A bundle of an inclusion proof per log leaf.
+    #[default]
+    Bundle,
+    /// A single multiproof for all of the log leafs.
+    Multiproof,
 }
 
 /// Represents an inclusion proof response.
@@ -46,9 +66,14 @@ pub struct InclusionResponse {
     /// The bytes of the log log proof bundle.
     #[serde_as(as = "Base64")]
     pub log: Vec<u8>,
-    /// The bytes of the map inclusion proof bundle.
+    /// The bytes of the map inclusion proof.
     #[serde_as(as = "Base64")]
     pub map: Vec<u8>,
+    /// The format of the map inclusion proof.
+    ///
+    /// Servers that predate multiproofs omit this and always respond with a bundle.
+    #[serde(default)]
+    pub map_format: MapProofFormat,
 }
 
 /// Represents a proof API error.
@@ -64,6 +89,9 @@ pub enum ProofError {
     /// Failed to prove inclusion of a package.
     #[error("failed to prove inclusion of package log `{0}`")]
     PackageLogNotIncluded(LogId),
+    /// Failed to prove absence of a package.
+    #[error("failed to prove absence of package log `{0}`")]
+    PackageLogIncluded(LogId),
     /// The provided root for an inclusion proof was incorrect.
     #[error("failed to prove inclusion: found root `{found}` but was given root `{root}`")]
     IncorrectProof {
@@ -92,6 +120,7 @@ impl ProofError {
             Self::CheckpointNotFound(_) | Self::LeafNotFound(_) => 404,
             Self::BundleFailure(_)
             | Self::PackageLogNotIncluded(_)
+            | Self::PackageLogIncluded(_)
             | Self::IncorrectProof { .. } => 422,
             Self::Message { status, .. } => *status,
         }
@@ -111,6 +140,9 @@ enum BundleError<'a> {
     PackageNotIncluded {
         log_id: Cow<'a, LogId>,
     },
+    PackageIncluded {
+        log_id: Cow<'a, LogId>,
+    },
     IncorrectProof {
         root: Cow<'a, AnyHash>,
         found: Cow<'a, AnyHash>,
@@ -162,6 +194,13 @@ impl Serialize for ProofError {
                 },
             }
             .serialize(serializer),
+            Self::PackageLogIncluded(log_id) => RawError::BundleError {
+                status: Status::<422>,
+                error: BundleError::PackageIncluded {
+                    log_id: Cow::Borrowed(log_id),
+                },
+            }
+            .serialize(serializer),
             Self::IncorrectProof { root, found } => RawError::BundleError {
                 status: Status::<422>,
                 error: BundleError::IncorrectProof {
@@ -200,6 +239,9 @@ impl<'de> Deserialize<'de> for ProofError {
                 BundleError::PackageNotIncluded { log_id } => {
                     Ok(Self::PackageLogNotIncluded(log_id.into_owned()))
                 }
+                BundleError::PackageIncluded { log_id } => {
+                    Ok(Self::PackageLogIncluded(log_id.into_owned()))
+                }
                 BundleError::IncorrectProof { root, found } => Ok(Self::IncorrectProof {
                     root: root.into_owned(),
                     found: found.into_owned(),
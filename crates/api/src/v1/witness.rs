@@ -0,0 +1,175 @@
+//! Types relating to the witness API.
+
+use crate::Status;
+use serde::{Deserialize, Serialize, Serializer};
+use std::borrow::Cow;
+use thiserror::Error;
+use warg_protocol::{
+    registry::{RegistryLen, TimestampedCheckpoint},
+    Cosignature, SerdeEnvelope,
+};
+
+/// Represents a request to cosign a checkpoint.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CosignCheckpointRequest<'a> {
+    /// The log length of the last checkpoint cosigned by the witness, which
+    /// the witness verified the cosigned checkpoint to be consistent with.
+    ///
+    /// This is `None` if the witness has not cosigned a checkpoint before.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_log_length: Option<RegistryLen>,
+    /// The log length of the checkpoint being cosigned.
+    pub log_length: RegistryLen,
+    /// The witness cosignature of the checkpoint, excluding its timestamp.
+    pub cosignature: Cow<'a, Cosignature>,
+}
+
+/// Represents the witness cosignatures of a checkpoint.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CheckpointCosignaturesResponse {
+    /// The log length of the checkpoint.
+    pub log_length: RegistryLen,
+    /// The witness cosignatures of the checkpoint.
+    pub cosignatures: Vec<Cosignature>,
+}
+
+/// Represents a checkpoint along with its witness cosignatures.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CosignedCheckpoint {
+    /// The checkpoint signed by the registry.
+    pub checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+    /// The witness cosignatures of the checkpoint.
+    pub cosignatures: Vec<Cosignature>,
+}
+
+/// Represents the latest checkpoint cosigned by a witness.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WitnessCheckpointResponse {
+    /// The latest checkpoint cosigned by the witness, if it has cosigned one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint: Option<CosignedCheckpoint>,
+}
+
+/// Represents the most recently cosigned checkpoints of a registry.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CosignedCheckpointsResponse {
+    /// The cosigned checkpoints, newest first.
+    pub checkpoints: Vec<CosignedCheckpoint>,
+}
+
+/// Represents a witness API error.
+#[non_exhaustive]
+#[derive(Debug, Error)]
+pub enum WitnessError {
+    /// The provided checkpoint was not found.
+    #[error("checkpoint log length `{0}` was not found")]
+    CheckpointNotFound(RegistryLen),
+    /// The cosignature was not made by a witness key of the registry.
+    #[error("unauthorized operation: {0}")]
+    Unauthorized(String),
+    /// The cosigned checkpoint does not follow the last checkpoint cosigned
+    /// by the witness.
+    #[error("{0}")]
+    Conflict(String),
+    /// An error with a message occurred.
+    #[error("{message}")]
+    Message {
+        /// The HTTP status code.
+        status: u16,
+        /// The error message
+        message: String,
+    },
+}
+
+impl WitnessError {
+    /// Returns the HTTP status code of the error.
+    pub fn status(&self) -> u16 {
+        match self {
+            Self::Unauthorized(_) => 401,
+            Self::CheckpointNotFound(_) => 404,
+            Self::Conflict(_) => 409,
+            Self::Message { status, .. } => *status,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum EntityType {
+    LogLength,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged, rename_all = "camelCase")]
+enum RawError<'a> {
+    Unauthorized {
+        status: Status<401>,
+        message: Cow<'a, str>,
+    },
+    CheckpointNotFound {
+        status: Status<404>,
+        #[serde(rename = "type")]
+        ty: EntityType,
+        id: RegistryLen,
+    },
+    Conflict {
+        status: Status<409>,
+        message: Cow<'a, str>,
+    },
+    Message {
+        status: u16,
+        message: Cow<'a, str>,
+    },
+}
+
+impl Serialize for WitnessError {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Unauthorized(message) => RawError::Unauthorized {
+                status: Status::<401>,
+                message: Cow::Borrowed(message),
+            }
+            .serialize(serializer),
+            Self::CheckpointNotFound(log_length) => RawError::CheckpointNotFound {
+                status: Status::<404>,
+                ty: EntityType::LogLength,
+                id: *log_length,
+            }
+            .serialize(serializer),
+            Self::Conflict(message) => RawError::Conflict {
+                status: Status::<409>,
+                message: Cow::Borrowed(message),
+            }
+            .serialize(serializer),
+            Self::Message { status, message } => RawError::Message {
+                status: *status,
+                message: Cow::Borrowed(message),
+            }
+            .serialize(serializer),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for WitnessError {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        match RawError::deserialize(deserializer)? {
+            RawError::Unauthorized { status: _, message } => {
+                Ok(Self::Unauthorized(message.into_owned()))
+            }
+            RawError::CheckpointNotFound { id, .. } => Ok(Self::CheckpointNotFound(id)),
+            RawError::Conflict { status: _, message } => Ok(Self::Conflict(message.into_owned())),
+            RawError::Message { status, message } => Ok(Self::Message {
+                status,
+                message: message.into_owned(),
+            }),
+        }
+    }
+}
@@ -21,24 +21,38 @@ use warg_api::{
         },
         ledger::{LedgerError, LedgerSourcesResponse},
         monitor::{CheckpointVerificationResponse, MonitorError},
+        operator::{OperatorError, OperatorRecord, PublishOperatorRecordRequest},
         package::{ContentSource, PackageError, PackageRecord, PublishRecordRequest},
         paths,
         proof::{
             ConsistencyRequest, ConsistencyResponse, InclusionRequest, InclusionResponse,
-            ProofError,
+            MapProofFormat, ProofError,
+        },
+        witness::{
+            CheckpointCosignaturesResponse, CosignCheckpointRequest, CosignedCheckpointsResponse,
+            WitnessCheckpointResponse, WitnessError,
         },
         REGISTRY_HEADER_NAME, REGISTRY_HINT_HEADER_NAME,
     },
     WellKnownConfig, WELL_KNOWN_PATH,
 };
-use warg_crypto::hash::{AnyHash, HashError, Sha256};
+use warg_crypto::{
+    hash::{AnyHash, HashError, Sha256},
+    signing::KeyID,
+};
 use warg_protocol::{
-    registry::{Checkpoint, LogId, LogLeaf, MapLeaf, RecordId, TimestampedCheckpoint},
+    registry::{
+        Checkpoint, LogId, LogLeaf, MapLeaf, RecordId, RegistryIndex, RegistryLen,
+        TimestampedCheckpoint,
+    },
     SerdeEnvelope,
 };
 use warg_transparency::{
-    log::{ConsistencyProofError, InclusionProofError, LogProofBundle, ProofBundle},
-    map::MapProofBundle,
+    log::{
+        tiles::TileError, ConsistencyProofError, InclusionProofError, LogData, LogProofBundle,
+        Node, ProofBundle, Tile, TiledLog,
+    },
+    map::{MapProofBundle, MultiProof as MapMultiProof},
 };
 
 use crate::{registry_url::RegistryUrl, storage::RegistryDomain};
@@ -48,6 +62,9 @@ pub enum ClientError {
     /// An error was returned from the fetch API.
     #[error(transparent)]
     Fetch(#[from] FetchError),
+    /// An error was returned from the operator API.
+    #[error(transparent)]
+    Operator(#[from] OperatorError),
     /// An error was returned from the package API.
     #[error(transparent)]
     Package(#[from] PackageError),
@@ -63,6 +80,9 @@ pub enum ClientError {
     /// An error was returned from the ledger API.
     #[error(transparent)]
     Ledger(#[from] LedgerError),
+    /// An error was returned from the witness API.
+    #[error(transparent)]
+    Witness(#[from] WitnessError),
     /// An error occurred while communicating with the registry.
     #[error("failed to send request to registry server: {0}")]
     Communication(#[from] reqwest::Error),
@@ -93,6 +113,9 @@ pub enum ClientError {
     /// The client failed an inclusion proof.
     #[error("the client failed an inclusion proof: {0}")]
     InclusionProof(#[from] InclusionProofError),
+    /// A log tile returned from the server was invalid.
+    #[error("the server returned an invalid log tile: {0}")]
+    Tile(#[from] TileError),
     /// The record was not published.
     #[error("record `{0}` has not been published")]
     RecordNotPublished(RecordId),
@@ -300,6 +323,101 @@ impl Client {
         into_result::<_, MonitorError>(response).await
     }
 
+    /// Submits a witness cosignature of a checkpoint to the registry.
+    pub async fn cosign_checkpoint(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        request: CosignCheckpointRequest<'_>,
+    ) -> Result<CheckpointCosignaturesResponse, ClientError> {
+        let url = self.url.join(paths::cosign_checkpoint());
+        tracing::debug!(
+            log_length = request.log_length,
+            url,
+            registry_header = ?registry_domain,
+            "cosigning checkpoint",
+        );
+
+        let response = self
+            .client
+            .post(url)
+            .json(&request)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .send()
+            .await?;
+        into_result::<_, WitnessError>(response).await
+    }
+
+    /// Gets the witness cosignatures of a checkpoint.
+    pub async fn checkpoint_cosignatures(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        log_length: RegistryLen,
+    ) -> Result<CheckpointCosignaturesResponse, ClientError> {
+        let url = self.url.join(&paths::checkpoint_cosignatures(log_length));
+        tracing::debug!(
+            log_length,
+            url,
+            registry_header = ?registry_domain,
+            "getting checkpoint cosignatures",
+        );
+        into_result::<_, WitnessError>(
+            self.client
+                .get(url)
+                .warg_header(registry_domain)?
+                .auth(self.auth_token())
+                .send()
+                .await?,
+        )
+        .await
+    }
+
+    /// Gets the most recently cosigned checkpoints, newest first.
+    pub async fn cosigned_checkpoints(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+    ) -> Result<CosignedCheckpointsResponse, ClientError> {
+        let url = self.url.join(paths::cosigned_checkpoints());
+        tracing::debug!(
+            url,
+            registry_header = ?registry_domain,
+            "getting cosigned checkpoints",
+        );
+        into_result::<_, WitnessError>(
+            self.client
+                .get(url)
+                .warg_header(registry_domain)?
+                .auth(self.auth_token())
+                .send()
+                .await?,
+        )
+        .await
+    }
+
+    /// Gets the latest checkpoint cosigned by a witness key.
+    pub async fn witness_checkpoint(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        key_id: &KeyID,
+    ) -> Result<WitnessCheckpointResponse, ClientError> {
+        let url = self.url.join(&paths::witness_checkpoint(key_id));
+        tracing::debug!(
+            %key_id,
+            url,
+            registry_header = ?registry_domain,
+            "getting witness checkpoint",
+        );
+        into_result::<_, WitnessError>(
+            self.client
+                .get(url)
+                .warg_header(registry_domain)?
+                .auth(self.auth_token())
+                .send()
+                .await?,
+        )
+        .await
+    }
+
     /// Fetches package log entries from the registry.
     pub async fn fetch_logs(
         &self,
@@ -377,6 +495,53 @@ impl Client {
         .await
     }
 
+    /// Publish a new record to the operator log.
+    pub async fn publish_operator_record(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        request: PublishOperatorRecordRequest<'_>,
+    ) -> Result<OperatorRecord, ClientError> {
+        let url = self.url.join(paths::publish_operator_record());
+        tracing::debug!(
+            url,
+            registry_header = ?registry_domain,
+            "publishing to operator",
+        );
+        let response = self
+            .client
+            .post(url)
+            .json(&request)
+            .warg_header(registry_domain)?
+            .auth(self.auth_token())
+            .send()
+            .await?;
+        into_result::<_, OperatorError>(response).await
+    }
+
+    /// Gets an operator record from the registry.
+    pub async fn get_operator_record(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        record_id: &RecordId,
+    ) -> Result<OperatorRecord, ClientError> {
+        let url = self.url.join(&paths::operator_record(record_id));
+        tracing::debug!(
+            record_id = record_id.to_string(),
+            url,
+            registry_header = ?registry_domain,
+            "getting operator record",
+        );
+        into_result::<_, OperatorError>(
+            self.client
+                .get(url)
+                .warg_header(registry_domain)?
+                .auth(self.auth_token())
+                .send()
+                .await?,
+        )
+        .await
+    }
+
     /// Publish a new record to a package log.
     pub async fn publish_package_record(
         &self,
@@ -518,7 +683,114 @@ impl Client {
         )
         .await?;
 
-        Self::validate_inclusion_response(response, checkpoint, leafs)
+        Self::validate_log_inclusion_response(&response, checkpoint, leafs)?;
+        Self::validate_map_inclusion_response(&response, checkpoint, leafs)
+    }
+
+    /// Proves the inclusion of the given package log heads in the registry's
+    /// map, ignoring the log inclusion proofs in the response.
+    ///
+    /// This is used when log inclusion is proven from the log tiles instead.
+    pub async fn prove_map_inclusion(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        request: InclusionRequest,
+        checkpoint: &Checkpoint,
+        leafs: &[LogLeaf],
+    ) -> Result<(), ClientError> {
+        let url = self.url.join(paths::prove_inclusion());
+        tracing::debug!(
+            url,
+            registry_header = ?registry_domain,
+            "proving checkpoint map inclusion",
+        );
+        let response = into_result::<InclusionResponse, ProofError>(
+            self.client
+                .post(url)
+                .json(&request)
+                .warg_header(registry_domain)?
+                .auth(self.auth_token())
+                .send()
+                .await?,
+        )
+        .await?;
+
+        Self::validate_map_inclusion_response(&response, checkpoint, leafs)
+    }
+
+    /// Proves the inclusion of the given log leafs at the given registry
+    /// indexes in the registry log using the log's hash tiles.
+    pub async fn prove_log_inclusion_from_tiles(
+        &self,
+        checkpoint: &Checkpoint,
+        leafs: &[(RegistryIndex, LogLeaf)],
+    ) -> Result<(), ClientError> {
+        let mut log = TiledLog::<Sha256, LogLeaf>::new(checkpoint.log_length);
+        let mut tiles = Vec::new();
+        let proofs = leafs
+            .iter()
+            .map(|(index, _)| {
+                // Leafs are stored at every other node of the log tree
+                let proof = log.prove_inclusion(Node(index * 2), checkpoint.log_length);
+                tiles.extend(log.missing_tiles_for_inclusion(&proof)?);
+                Ok(proof)
+            })
+            .collect::<Result<Vec<_>, ClientError>>()?;
+        self.fetch_tiles(&mut log, tiles).await?;
+
+        let root = checkpoint.log_root.clone().try_into()?;
+        for ((_, leaf), proof) in leafs.iter().zip(proofs.iter()) {
+            let found = proof.evaluate_value(&log, leaf)?;
+            if found != root {
+                return Err(ClientError::Proof(ProofError::IncorrectProof {
+                    root: checkpoint.log_root.clone(),
+                    found: found.into(),
+                }));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Proves the absence of the given package logs in the registry's map.
+    ///
+    /// Returns `false` if the registry predates absence proofs, which it
+    /// either fails to bundle without any log leafs or responds without.
+    pub async fn prove_absence(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        checkpoint: &Checkpoint,
+        log_ids: &[LogId],
+    ) -> Result<bool, ClientError> {
+        let url = self.url.join(paths::prove_inclusion());
+        tracing::debug!(
+            url,
+            registry_header = ?registry_domain,
+            "proving checkpoint absence",
+        );
+        let request = InclusionRequest {
+            log_length: checkpoint.log_length,
+            leafs: Vec::new(),
+            map_format: MapProofFormat::Bundle,
+            absent: log_ids.to_vec(),
+        };
+        let response = match into_result::<InclusionResponse, ProofError>(
+            self.client
+                .post(url)
+                .json(&request)
+                .warg_header(registry_domain)?
+                .auth(self.auth_token())
+                .send()
+                .await?,
+        )
+        .await
+        {
+            Ok(response) => response,
+            Err(ClientError::Proof(ProofError::BundleFailure(_))) => return Ok(false),
+            Err(e) => return Err(e),
+        };
+
+        Self::validate_absence_response(&response, checkpoint, log_ids)
     }
 
     /// Proves consistency between two log roots.
@@ -561,6 +833,84 @@ impl Client {
             .evaluate(&log_data)
             .map(|(from, to)| (AnyHash::from(from), AnyHash::from(to)))?;
 
+        Self::validate_consistency_roots(from_log_root, to_log_root, from, to)
+    }
+
+    /// Proves consistency between two log roots using the log's hash tiles.
+    pub async fn prove_log_consistency_from_tiles(
+        &self,
+        request: ConsistencyRequest,
+        from_log_root: Cow<'_, AnyHash>,
+        to_log_root: Cow<'_, AnyHash>,
+    ) -> Result<(), ClientError> {
+        let mut log = TiledLog::<Sha256, LogLeaf>::new(request.to);
+        let proof = log.prove_consistency(request.from, request.to);
+        let tiles = log.missing_tiles_for_consistency(&proof)?;
+        self.fetch_tiles(&mut log, tiles).await?;
+
+        let (from, to) = proof
+            .evaluate(&log)
+            .map(|(from, to)| (AnyHash::from(from), AnyHash::from(to)))?;
+
+        Self::validate_consistency_roots(from_log_root, to_log_root, from, to)
+    }
+
+    /// Fetches the given hash tiles of the registry log.
+    async fn fetch_tiles(
+        &self,
+        log: &mut TiledLog<Sha256, LogLeaf>,
+        mut tiles: Vec<Tile>,
+    ) -> Result<(), ClientError> {
+        tiles.sort();
+        tiles.dedup();
+        for tile in tiles {
+            let (tile, bytes) = self.fetch_tile(tile).await?;
+            log.add_tile(tile, &bytes)?;
+        }
+
+        Ok(())
+    }
+
+    /// Fetches a hash tile of the registry log.
+    ///
+    /// As partial tiles may be removed once the full tile is written, the
+    /// full tile is fetched if a partial tile is not found.
+    async fn fetch_tile(&self, tile: Tile) -> Result<(Tile, Bytes), ClientError> {
+        let candidates = if tile.is_partial() {
+            vec![tile, tile.full()]
+        } else {
+            vec![tile]
+        };
+
+        for candidate in candidates {
+            let url = self.url.join(&paths::tile(&candidate.path()));
+            tracing::debug!(url, "fetching log tile");
+
+            let response = self.client.get(url).send().await?;
+            match response.status() {
+                StatusCode::NOT_FOUND => continue,
+                status if status.is_success() => return Ok((candidate, response.bytes().await?)),
+                status => {
+                    return Err(ClientError::UnexpectedResponse {
+                        status,
+                        message: format!("failed to fetch log tile `{path}`", path = tile.path()),
+                    })
+                }
+            }
+        }
+
+        Err(ClientError::UnexpectedResponse {
+            status: StatusCode::NOT_FOUND,
+            message: format!("log tile `{path}` was not found", path = tile.path()),
+        })
+    }
+
+    fn validate_consistency_roots(
+        from_log_root: Cow<'_, AnyHash>,
+        to_log_root: Cow<'_, AnyHash>,
+        from: AnyHash,
+        to: AnyHash,
+    ) -> Result<(), ClientError> {
         if from_log_root.as_ref() != &from {
             return Err(ClientError::IncorrectConsistencyProof {
                 root: from_log_root.into_owned(),
@@ -609,11 +959,20 @@ impl Client {
             })
             .collect::<Result<HeaderMap, ClientError>>()?;
 
+        // Only authenticate uploads to the registry itself, so that the token
+        // is never sent to another host
+        let auth_token = if url.starts_with(&self.url.to_string()) {
+            self.auth_token()
+        } else {
+            &None
+        };
+
         tracing::debug!("uploading content to `{url}`");
 
         let response = self
             .client
             .request(method, url)
+            .auth(auth_token)
             .headers(headers)
             .body(content)
             .send()
@@ -627,8 +986,8 @@ impl Client {
         Ok(())
     }
 
-    fn validate_inclusion_response(
-        response: InclusionResponse,
+    fn validate_log_inclusion_response(
+        response: &InclusionResponse,
         checkpoint: &Checkpoint,
         leafs: &[LogLeaf],
     ) -> Result<(), ClientError> {
@@ -646,6 +1005,44 @@ impl Client {
             }
         }
 
+        Ok(())
+    }
+
+    fn validate_map_inclusion_response(
+        response: &InclusionResponse,
+        checkpoint: &Checkpoint,
+        leafs: &[LogLeaf],
+    ) -> Result<(), ClientError> {
+        if response.map_format == MapProofFormat::Multiproof {
+            let multiproof: MapMultiProof<Sha256, LogId, MapLeaf> =
+                MapMultiProof::decode(response.map.as_slice())?;
+            let leafs = leafs
+                .iter()
+                .map(|leaf| {
+                    (
+                        leaf.log_id.clone(),
+                        MapLeaf {
+                            record_id: leaf.record_id.clone(),
+                        },
+                    )
+                })
+                .collect::<Vec<_>>();
+            let found = multiproof.evaluate(&leafs).ok_or_else(|| {
+                ClientError::Proof(ProofError::BundleFailure(
+                    "map multiproof does not match the log leafs".to_string(),
+                ))
+            })?;
+            let root = checkpoint.map_root.clone().try_into()?;
+            if found != root {
+                return Err(ClientError::Proof(ProofError::IncorrectProof {
+                    root: checkpoint.map_root.clone(),
+                    found: found.into(),
+                }));
+            }
+
+            return Ok(());
+        }
+
         let map_proof_bundle: MapProofBundle<Sha256, LogId, MapLeaf> =
             MapProofBundle::decode(response.map.as_slice())?;
         let map_inclusions = map_proof_bundle.unbundle();
@@ -667,6 +1064,40 @@ impl Client {
 
         Ok(())
     }
+
+    fn validate_absence_response(
+        response: &InclusionResponse,
+        checkpoint: &Checkpoint,
+        log_ids: &[LogId],
+    ) -> Result<bool, ClientError> {
+        let map_proof_bundle: MapProofBundle<Sha256, LogId, MapLeaf> =
+            MapProofBundle::decode(response.map.as_slice())?;
+        let map_absences = map_proof_bundle.absence_proofs();
+        if map_absences.is_empty() {
+            return Ok(false);
+        }
+
+        if map_absences.len() != log_ids.len() {
+            return Err(ClientError::Proof(ProofError::BundleFailure(format!(
+                "expected {expected} absence proofs but found {found}",
+                expected = log_ids.len(),
+                found = map_absences.len()
+            ))));
+        }
+
+        for (log_id, proof) in log_ids.iter().zip(map_absences.iter()) {
+            let found = proof.evaluate(log_id);
+            let root = checkpoint.map_root.clone().try_into()?;
+            if found != root {
+                return Err(ClientError::Proof(ProofError::IncorrectProof {
+                    root: checkpoint.map_root.clone(),
+                    found: found.into(),
+                }));
+            }
+        }
+
+        Ok(true)
+    }
 }
 
 fn validate_stream(
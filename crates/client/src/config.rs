@@ -11,6 +11,7 @@ use std::{
     fs::{self, File},
     path::{Component, Path, PathBuf},
 };
+use warg_crypto::signing::PublicKey;
 
 static CACHE_DIR: Lazy<Option<PathBuf>> = Lazy::new(dirs::cache_dir);
 static CONFIG_DIR: Lazy<Option<PathBuf>> = Lazy::new(dirs::config_dir);
@@ -135,6 +136,23 @@ pub struct Config {
     /// Use the specified backend for keyring access.
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub keyring_backend: Option<String>,
+
+    /// The public keys of witnesses trusted to cosign registry checkpoints.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub witness_keys: Vec<PublicKey>,
+
+    /// The number of cosignatures by distinct witness keys a checkpoint
+    /// requires before it is accepted; `0` disables the requirement.
+    ///
+    /// If the latest checkpoint lacks enough cosignatures, the newest
+    /// sufficiently cosigned checkpoint is used instead.
+    #[serde(default)]
+    pub witness_threshold: usize,
+
+    /// Verify log proofs of the home registry using its log tiles rather
+    /// than its proof API.
+    #[serde(default)]
+    pub use_tiles: bool,
 }
 
 impl Config {
@@ -218,6 +236,9 @@ impl Config {
             disable_auto_package_init: self.disable_auto_package_init,
             disable_interactive: self.disable_interactive,
             keyring_backend: self.keyring_backend.clone(),
+            witness_keys: self.witness_keys.clone(),
+            witness_threshold: self.witness_threshold,
+            use_tiles: self.use_tiles,
         };
 
         serde_json::to_writer_pretty(
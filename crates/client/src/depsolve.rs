@@ -10,10 +10,9 @@ use wasm_encoder::{
 };
 use wasmparser::{Chunk, ComponentImportSectionReader, Parser, Payload};
 
-use super::Client;
+use super::{warn_deprecated, Client};
 use crate::storage::{ContentStorage, NamespaceMapStorage, PackageInfo, RegistryStorage};
 use crate::version_util::{DependencyImportParser, Import, ImportKind};
-/// Import Kinds found in components
 
 /// Creates list of dependenies for locking components
 pub struct LockListBuilder {
@@ -118,6 +117,7 @@ impl LockListBuilder {
                     {
                         let release = info.state.releases().last();
                         if let Some(r) = release {
+                            Self::check_deprecation(&info, r);
                             if let Some(bytes) = self.release_bytes(r, client)? {
                                 self.parse_package(client, &bytes).await?;
                             }
@@ -135,6 +135,7 @@ impl LockListBuilder {
                         {
                             let release = info.state.releases().last();
                             if let Some(r) = release {
+                                Self::check_deprecation(&info, r);
                                 if let Some(bytes) = self.release_bytes(r, client)? {
                                     self.parse_package(client, &bytes).await?;
                                 }
@@ -149,13 +150,20 @@ impl LockListBuilder {
         Ok(())
     }
 
+    /// Logs a warning if the resolved release of a dependency is deprecated.
+    fn check_deprecation(info: &PackageInfo, release: &Release) {
+        if let Some(deprecation) = info.state.deprecation(&release.version) {
+            warn_deprecated(&info.name, &release.version, deprecation);
+        }
+    }
+
     fn release_bytes<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage>(
         &self,
         release: &Release,
         client: &Client<R, C, N>,
     ) -> Result<Option<Vec<u8>>> {
         let state = &release.state;
-        if let ReleaseState::Released { content } = state {
+        if let ReleaseState::Released { content, .. } = state {
             let path = client.content().content_location(content);
             if let Some(p) = path {
                 return Ok(Some(fs::read(p)?));
@@ -178,8 +186,9 @@ impl LockListBuilder {
     {
         let release = info.state.releases().last();
         if let Some(r) = release {
+            Self::check_deprecation(info, r);
             let state = &r.state;
-            if let ReleaseState::Released { content } = state {
+            if let ReleaseState::Released { content, .. } = state {
                 let path = client.content().content_location(content);
                 if let Some(p) = path {
                     let bytes = fs::read(p)?;
@@ -250,7 +259,7 @@ where
                     };
                     if let Some(r) = release {
                         let release_state = &r.state;
-                        if let ReleaseState::Released { content } = release_state {
+                        if let ReleaseState::Released { content, .. } = release_state {
                             let path = self.client.content().content_location(content);
                             if let Some(p) = path {
                                 let bytes = fs::read(p)?;
@@ -189,6 +189,27 @@ impl Keyring {
         registry_url: Option<&str>,
         keys: &IndexSet<String>,
         home_url: Option<&str>,
+    ) -> Result<keyring::Entry> {
+        self.key_entry("warg-signing-key", registry_url, keys, home_url)
+    }
+
+    /// Gets the entry of the signing key that is pending a key rotation for
+    /// the given registry and key name.
+    pub fn get_pending_signing_key_entry(
+        &self,
+        registry_url: Option<&str>,
+        keys: &IndexSet<String>,
+        home_url: Option<&str>,
+    ) -> Result<keyring::Entry> {
+        self.key_entry("warg-pending-signing-key", registry_url, keys, home_url)
+    }
+
+    fn key_entry(
+        &self,
+        service: &str,
+        registry_url: Option<&str>,
+        keys: &IndexSet<String>,
+        home_url: Option<&str>,
     ) -> Result<keyring::Entry> {
         if let Some(registry_url) = registry_url {
             let user = if keys.contains(registry_url) {
@@ -196,17 +217,14 @@ impl Keyring {
             } else {
                 "default"
             };
-            let cred = self
-                .imp
-                .build(None, "warg-signing-key", user)
-                .map_err(|e| {
-                    KeyringError::signing_key_access_error(
-                        self.name,
-                        Some(registry_url),
-                        KeyringAction::Open,
-                        e,
-                    )
-                })?;
+            let cred = self.imp.build(None, service, user).map_err(|e| {
+                KeyringError::signing_key_access_error(
+                    self.name,
+                    Some(registry_url),
+                    KeyringAction::Open,
+                    e,
+                )
+            })?;
             Ok(keyring::Entry::new_with_credential(cred))
         } else {
             if let Some(url) = home_url {
@@ -215,7 +233,7 @@ impl Keyring {
                         .imp
                         .build(
                             None,
-                            "warg-signing-key",
+                            service,
                             &RegistryUrl::new(url)
                                 .map_err(|e| {
                                     KeyringError::signing_key_access_error(
@@ -240,17 +258,14 @@ impl Keyring {
             }
 
             if keys.contains("default") {
-                let cred = self
-                    .imp
-                    .build(None, "warg-signing-key", "default")
-                    .map_err(|e| {
-                        KeyringError::signing_key_access_error(
-                            self.name,
-                            None::<&str>,
-                            KeyringAction::Open,
-                            e,
-                        )
-                    })?;
+                let cred = self.imp.build(None, service, "default").map_err(|e| {
+                    KeyringError::signing_key_access_error(
+                        self.name,
+                        None::<&str>,
+                        KeyringAction::Open,
+                        e,
+                    )
+                })?;
                 return Ok(keyring::Entry::new_with_credential(cred));
             }
 
@@ -301,6 +316,71 @@ impl Keyring {
         })
     }
 
+    /// Gets the signing key that is pending a key rotation for the given
+    /// registry and key name.
+    ///
+    /// Returns `None` if no key rotation is pending.
+    pub fn get_pending_signing_key(
+        &self,
+        registry_url: Option<&str>,
+        keys: &IndexSet<String>,
+        home_url: Option<&str>,
+    ) -> Result<Option<PrivateKey>> {
+        let entry = self.get_pending_signing_key_entry(registry_url, keys, home_url)?;
+
+        match entry.get_password() {
+            Ok(secret) => PrivateKey::decode(secret).map(Some).map_err(|e| {
+                KeyringError::signing_key_access_error(
+                    self.name,
+                    registry_url,
+                    KeyringAction::Get,
+                    anyhow::Error::from(e),
+                )
+            }),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(KeyringError::signing_key_access_error(
+                self.name,
+                registry_url,
+                KeyringAction::Get,
+                e,
+            )),
+        }
+    }
+
+    /// Sets the signing key that is pending a key rotation for the given
+    /// registry and key name.
+    pub fn set_pending_signing_key(
+        &self,
+        registry_url: Option<&str>,
+        key: &PrivateKey,
+        keys: &IndexSet<String>,
+        home_url: Option<&str>,
+    ) -> Result<()> {
+        let entry = self.get_pending_signing_key_entry(registry_url, keys, home_url)?;
+        entry.set_password(&key.encode()).map_err(|e| {
+            KeyringError::signing_key_access_error(self.name, registry_url, KeyringAction::Set, e)
+        })
+    }
+
+    /// Deletes the signing key that is pending a key rotation for the given
+    /// registry and key name.
+    pub fn delete_pending_signing_key(
+        &self,
+        registry_url: Option<&str>,
+        keys: &IndexSet<String>,
+        home_url: Option<&str>,
+    ) -> Result<()> {
+        let entry = self.get_pending_signing_key_entry(registry_url, keys, home_url)?;
+        entry.delete_credential().map_err(|e| {
+            KeyringError::signing_key_access_error(
+                self.name,
+                registry_url,
+                KeyringAction::Delete,
+                e,
+            )
+        })
+    }
+
     /// Deletes the signing key for the given registry host and key name.
     pub fn delete_signing_key(
         &self,
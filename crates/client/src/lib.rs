@@ -11,30 +11,37 @@ use reqwest::{Body, IntoUrl};
 use secrecy::Secret;
 use semver::{Version, VersionReq};
 use std::cmp::Ordering;
+use std::collections::BTreeSet;
 use std::fs;
 use std::str::FromStr;
-use std::{borrow::Cow, path::PathBuf, time::Duration};
+use std::{
+    borrow::Cow,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
 use storage::{
-    ContentStorage, FileSystemContentStorage, FileSystemNamespaceMapStorage,
+    ContentStorage, Cosigning, FileSystemContentStorage, FileSystemNamespaceMapStorage,
     FileSystemRegistryStorage, NamespaceMapStorage, PublishInfo, RegistryDomain, RegistryStorage,
 };
 use thiserror::Error;
 use tokio_util::io::ReaderStream;
 use warg_api::v1::{
     fetch::{FetchError, FetchLogsRequest},
+    operator::{OperatorError, OperatorRecordState, PublishOperatorRecordRequest},
     package::{
         MissingContent, PackageError, PackageRecord, PackageRecordState, PublishRecordRequest,
         UploadEndpoint,
     },
-    proof::{ConsistencyRequest, InclusionRequest},
+    proof::{ConsistencyRequest, InclusionRequest, MapProofFormat},
+    witness::CosignCheckpointRequest,
 };
-use warg_crypto::hash::Sha256;
+use warg_crypto::hash::{HashAlgorithm, Sha256};
 use warg_crypto::{hash::AnyHash, signing, Encode, Signable};
-use warg_protocol::package::ReleaseState;
+use warg_protocol::package::{PackageDeprecation, ReleaseState};
 use warg_protocol::{
     operator, package,
     registry::{LogId, LogLeaf, PackageName, RecordId, RegistryLen, TimestampedCheckpoint},
-    PublishedProtoEnvelope,
+    Cosignature, ProtoEnvelope, PublishedProtoEnvelope, SerdeEnvelope,
 };
 use wasm_compose::graph::{CompositionGraph, EncodeOptions, ExportIndex, InstanceId};
 use wasmparser::Validator;
@@ -79,6 +86,9 @@ where
     disable_interactive: bool,
     keyring_backend: Option<String>,
     keys: IndexSet<String>,
+    witness_keys: Vec<signing::PublicKey>,
+    witness_threshold: usize,
+    use_tiles: bool,
 }
 
 impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C, N> {
@@ -97,6 +107,9 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
         disable_interactive: bool,
         keyring_backend: Option<String>,
         keys: IndexSet<String>,
+        witness_keys: Vec<signing::PublicKey>,
+        witness_threshold: usize,
+        use_tiles: bool,
     ) -> ClientResult<Self> {
         let api = api::Client::new(url, auth_token)?;
         Ok(Self {
@@ -110,6 +123,9 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
             disable_interactive,
             keyring_backend,
             keys,
+            witness_keys,
+            witness_threshold,
+            use_tiles,
         })
     }
 
@@ -229,7 +245,7 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
 
                 if let Some(r) = release {
                     let state = &r.state;
-                    if let ReleaseState::Released { content } = state {
+                    if let ReleaseState::Released { content, .. } = state {
                         let locked_package = locked_package(&package.name, r, content);
                         let path = self.content().content_location(content);
                         if let Some(p) = path {
@@ -237,9 +253,7 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
                                 digest: content.clone(),
                             })?;
 
-                            let read_digest =
-                                AnyHash::from_str(&format!("sha256:{}", sha256::digest(bytes)))
-                                    .unwrap();
+                            let read_digest = content.algorithm().digest(&bytes);
                             if content != &read_digest {
                                 return Err(ClientError::IncorrectContent {
                                     digest: read_digest,
@@ -428,7 +442,12 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
                 }) => {
                     if !initializing {
                         if !self.disable_auto_package_init {
-                            info.entries.insert(0, crate::storage::PublishEntry::Init);
+                            info.entries.insert(
+                                0,
+                                crate::storage::PublishEntry::Init {
+                                    hash_algorithm: HashAlgorithm::default(),
+                                },
+                            );
                             initializing = true;
                             accepted_prompt_to_initialize = true;
                         } else {
@@ -456,7 +475,12 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
                                         .interact()
                                         .unwrap()
                                 {
-                                    info.entries.insert(0, PublishEntry::Init);
+                                    info.entries.insert(
+                                        0,
+                                        PublishEntry::Init {
+                                            hash_algorithm: HashAlgorithm::default(),
+                                        },
+                                    );
                                     initializing = true;
                                     accepted_prompt_to_initialize = true;
                                 } else {
@@ -474,9 +498,16 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
             };
             let registry_domain = self.get_warg_registry(package.name.namespace()).await?;
 
+            // Release content must be digested with the hash algorithm of the package log
+            let algorithm = info
+                .hash_algorithm()
+                .or(package.state.algorithm())
+                .unwrap_or_default();
+            self.rehash_release_content(&mut info, algorithm).await?;
+
             let log_id = LogId::package_log::<Sha256>(&package.name);
             let record = info.finalize(signing_key)?;
-            let record_id = RecordId::package_record::<Sha256>(&record);
+            let record_id = RecordId::package_record_for_log(&record);
             let record = match self
                 .api
                 .publish_package_record(
@@ -589,6 +620,37 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
         Ok(record.record_id)
     }
 
+    /// Prepares the provided publish information for collecting cosignatures.
+    ///
+    /// The head of the package log and the timestamp of the record are fixed
+    /// so that the record that is cosigned is the record that is published.
+    ///
+    /// Cosignatures may then be added with `PublishInfo::cosign` before the
+    /// record is submitted with `publish_with_info`.
+    ///
+    /// Publish information that initializes a package cannot be cosigned.
+    pub async fn prepare_cosigning(&self, info: &mut PublishInfo) -> ClientResult<()> {
+        if info.initializing() {
+            return Err(ClientError::CannotCosignInitialization {
+                name: info.name.clone(),
+            });
+        }
+
+        let package = self.fetch_package(&info.name).await?;
+        if info.head.is_none() {
+            info.head = package.state.head().as_ref().map(|h| h.digest.clone());
+        }
+
+        let algorithm = package.state.algorithm().unwrap_or_default();
+        self.rehash_release_content(info, algorithm).await?;
+
+        info.cosigning = Some(Cosigning {
+            timestamp: SystemTime::now(),
+            cosignatures: Vec::new(),
+        });
+        Ok(())
+    }
+
     /// Waits for a package record to transition to the `published` state.
     ///
     /// The `interval` is the amount of time to wait between checks.
@@ -632,6 +694,193 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
         }
     }
 
+    /// Submits a record with the given entries to the operator log of the home registry.
+    ///
+    /// The operator log is updated to the latest registry checkpoint before the
+    /// record is signed so that it extends the current head of the log.
+    ///
+    /// Returns the identifier of the record that was published.
+    ///
+    /// Use `wait_for_operator_publish` to wait for the record to transition to the
+    /// `published` state.
+    pub async fn publish_operator_record(
+        &self,
+        signing_key: &signing::PrivateKey,
+        entries: Vec<operator::OperatorEntry>,
+    ) -> ClientResult<RecordId> {
+        if entries.is_empty() {
+            return Err(ClientError::NothingToPublishToOperator);
+        }
+
+        tracing::info!("publishing operator record");
+        tracing::debug!("entries: {entries:?}");
+
+        self.update_operator().await?;
+        let operator = self
+            .registry
+            .load_operator(None)
+            .await?
+            .ok_or(ClientError::NoOperatorRecords)?;
+
+        let record = operator::OperatorRecord {
+            prev: operator.state.head().as_ref().map(|h| h.digest.clone()),
+            version: operator::OPERATOR_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries,
+        };
+        let record = ProtoEnvelope::signed_contents(signing_key, record)
+            .map_err(|e| ClientError::Other(e.into()))?;
+        let record_id = RecordId::operator_record::<Sha256>(&record);
+
+        let record = self
+            .api
+            .publish_operator_record(
+                None,
+                PublishOperatorRecordRequest {
+                    record: Cow::Owned(record.into()),
+                },
+            )
+            .await
+            .map_err(|e| match e {
+                api::ClientError::Operator(OperatorError::Rejection(reason)) => {
+                    ClientError::OperatorPublishRejected { record_id, reason }
+                }
+                api::ClientError::Operator(OperatorError::Unauthorized(reason)) => {
+                    ClientError::Unauthorized(reason)
+                }
+                e => e.into(),
+            })?;
+
+        Ok(record.record_id)
+    }
+
+    /// Waits for an operator record to transition to the `published` state.
+    ///
+    /// The `interval` is the amount of time to wait between checks.
+    ///
+    /// Returns an error if the operator record was rejected.
+    pub async fn wait_for_operator_publish(
+        &self,
+        record_id: &RecordId,
+        interval: Duration,
+    ) -> ClientResult<()> {
+        loop {
+            let current = self.api.get_operator_record(None, record_id).await?;
+            match current.state {
+                OperatorRecordState::Published { .. } => {
+                    self.update_operator().await?;
+                    return Ok(());
+                }
+                OperatorRecordState::Rejected { reason } => {
+                    return Err(ClientError::OperatorPublishRejected {
+                        record_id: record_id.clone(),
+                        reason,
+                    });
+                }
+                OperatorRecordState::Processing => {
+                    tokio::time::sleep(interval).await;
+                }
+            }
+        }
+    }
+
+    /// Updates the operator log of the home registry in client registry storage
+    /// to the latest registry checkpoint.
+    async fn update_operator(&self) -> ClientResult<()> {
+        self.update_packages_and_return_federated_packages(None, std::iter::empty(), true)
+            .await?;
+        Ok(())
+    }
+
+    /// Cosigns the latest checkpoint of the home registry as a witness.
+    ///
+    /// The checkpoint is only cosigned after its signature is verified and it
+    /// is proven consistent with the last checkpoint in client registry storage
+    /// and with the last checkpoint cosigned by the witness.
+    ///
+    /// Returns all witness cosignatures of the checkpoint known to the registry.
+    pub async fn cosign_checkpoint(
+        &self,
+        witness_key: &signing::PrivateKey,
+    ) -> ClientResult<Vec<Cosignature>> {
+        // A witness must not wait on other witnesses before cosigning
+        self.update_packages_and_return_federated_packages(None, std::iter::empty(), false)
+            .await?;
+
+        let ts_checkpoint = self
+            .registry
+            .load_checkpoint(None)
+            .await?
+            .ok_or_else(|| anyhow!("no checkpoint was stored for the registry"))?;
+        let checkpoint = &ts_checkpoint.as_ref().checkpoint;
+
+        // The last checkpoint cosigned by the witness is served by the
+        // registry, so it is only trusted with a valid cosignature of the witness
+        let public_key = witness_key.public_key();
+        let key_id = public_key.fingerprint();
+        let old_log_length = match self.api.witness_checkpoint(None, &key_id).await?.checkpoint {
+            Some(old) => {
+                let old_checkpoint = &old.checkpoint.as_ref().checkpoint;
+                if !old.cosignatures.iter().any(|c| {
+                    c.key_id == key_id && old_checkpoint.verify_cosignature(&public_key, c).is_ok()
+                }) {
+                    return Err(ClientError::InvalidWitnessCheckpoint {
+                        log_length: old_checkpoint.log_length,
+                    });
+                }
+
+                match old_checkpoint.log_length.cmp(&checkpoint.log_length) {
+                    Ordering::Greater => {
+                        return Err(ClientError::CheckpointLogLengthRewind {
+                            from: old_checkpoint.log_length,
+                            to: checkpoint.log_length,
+                        });
+                    }
+                    Ordering::Less => {
+                        self.api
+                            .prove_log_consistency(
+                                None,
+                                ConsistencyRequest {
+                                    from: old_checkpoint.log_length,
+                                    to: checkpoint.log_length,
+                                },
+                                Cow::Borrowed(&old_checkpoint.log_root),
+                                Cow::Borrowed(&checkpoint.log_root),
+                            )
+                            .await?
+                    }
+                    Ordering::Equal => {
+                        if old_checkpoint != checkpoint {
+                            return Err(ClientError::CheckpointChangedLogRootOrMapRoot {
+                                log_length: checkpoint.log_length,
+                            });
+                        }
+                    }
+                }
+
+                Some(old_checkpoint.log_length)
+            }
+            None => None,
+        };
+
+        let cosignature = checkpoint
+            .cosign(witness_key)
+            .map_err(|e| ClientError::Other(e.into()))?;
+
+        let response = self
+            .api
+            .cosign_checkpoint(
+                None,
+                CosignCheckpointRequest {
+                    old_log_length,
+                    log_length: checkpoint.log_length,
+                    cosignature: Cow::Owned(cosignature),
+                },
+            )
+            .await?;
+        Ok(response.cosignatures)
+    }
+
     /// Updates all package logs in client registry storage to the latest registry checkpoint.
     pub async fn update(&self) -> ClientResult<()> {
         tracing::info!("updating downloaded package logs");
@@ -656,6 +905,8 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
     ///
     /// Returns the path within client storage of the package contents for
     /// the resolved version.
+    ///
+    /// A warning is logged if the resolved version has been deprecated.
     pub async fn download(
         &self,
         package: &PackageName,
@@ -681,10 +932,15 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
                 let path = self
                     .download_content(registry_domain.as_ref(), &digest)
                     .await?;
+                let deprecation = info.state.deprecation(&release.version).cloned();
+                if let Some(deprecation) = &deprecation {
+                    warn_deprecated(package, &release.version, deprecation);
+                }
                 Ok(Some(PackageDownload {
                     version: release.version.clone(),
                     digest,
                     path,
+                    deprecation,
                 }))
             }
             None => Ok(None),
@@ -780,6 +1036,7 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
         Ok(PackageDownload {
             version: version.clone(),
             digest: digest.clone(),
+            deprecation: info.state.deprecation(version).cloned(),
             path: self
                 .download_content(registry_domain.as_ref(), digest)
                 .await?,
@@ -837,8 +1094,14 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
         &self,
         registry_domain: Option<&RegistryDomain>,
         packages: impl IntoIterator<Item = &'a mut PackageInfo>,
+        verify_witnesses: bool,
     ) -> Result<IndexMap<Option<RegistryDomain>, Vec<&'a mut PackageInfo>>, ClientError> {
-        let ts_checkpoint = self.api.latest_checkpoint(registry_domain).await?;
+        let mut ts_checkpoint = self.api.latest_checkpoint(registry_domain).await?;
+        if verify_witnesses {
+            ts_checkpoint = self
+                .witnessed_checkpoint(registry_domain, ts_checkpoint)
+                .await?;
+        }
         let checkpoint = &ts_checkpoint.as_ref().checkpoint;
 
         tracing::debug!(
@@ -909,6 +1172,22 @@ impl<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage> Client<R, C,
                     api::ClientError::Fetch(FetchError::LogNotFound(log_id))
                     | api::ClientError::Package(PackageError::LogNotFound(log_id)) => {
                         if let Some(name) = packages.get(log_id).map(|p| p.name.clone()) {
+                            // Don't trust the registry; verify the log is absent at the checkpoint
+                            // unless the registry predates absence proofs
+                            if !self
+                                .api
+                                .prove_absence(
+                                    registry_domain,
+                                    checkpoint,
+                                    std::slice::from_ref(log_id),
+                                )
+                                .await?
+                            {
+                                tracing::warn!(
+                                    "registry did not prove the absence of package `{name}`"
+                                );
+                            }
+
                             Err(ClientError::PackageDoesNotExist {
                                 name,
                                 has_auth_token,
@@ -1117,18 +1396,47 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
             }
         }
 
+        // Tiles are only served for the home registry's own log
+        let use_tiles = self.use_tiles && registry_domain.is_none();
+
         if !leafs.is_empty() {
-            self.api
-                .prove_inclusion(
-                    registry_domain,
-                    InclusionRequest {
-                        log_length: checkpoint.log_length,
-                        leafs: leaf_indices,
-                    },
-                    checkpoint,
-                    &leafs,
-                )
-                .await?;
+            if use_tiles {
+                let indexed_leafs = leaf_indices
+                    .iter()
+                    .copied()
+                    .zip(leafs.iter().cloned())
+                    .collect::<Vec<_>>();
+                self.api
+                    .prove_log_inclusion_from_tiles(checkpoint, &indexed_leafs)
+                    .await?;
+                self.api
+                    .prove_map_inclusion(
+                        registry_domain,
+                        InclusionRequest {
+                            log_length: checkpoint.log_length,
+                            leafs: leaf_indices,
+                            map_format: MapProofFormat::Multiproof,
+                            absent: Vec::new(),
+                        },
+                        checkpoint,
+                        &leafs,
+                    )
+                    .await?;
+            } else {
+                self.api
+                    .prove_inclusion(
+                        registry_domain,
+                        InclusionRequest {
+                            log_length: checkpoint.log_length,
+                            leafs: leaf_indices,
+                            map_format: MapProofFormat::Multiproof,
+                            absent: Vec::new(),
+                        },
+                        checkpoint,
+                        &leafs,
+                    )
+                    .await?;
+            }
         }
 
         if let Some(from) = self.registry.load_checkpoint(registry_domain).await? {
@@ -1143,17 +1451,26 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
                     });
                 }
                 Ordering::Less => {
-                    self.api
-                        .prove_log_consistency(
-                            registry_domain,
-                            ConsistencyRequest {
-                                from: from_log_length,
-                                to: to_log_length,
-                            },
-                            Cow::Borrowed(&from.as_ref().checkpoint.log_root),
-                            Cow::Borrowed(&ts_checkpoint.as_ref().checkpoint.log_root),
-                        )
-                        .await?
+                    let request = ConsistencyRequest {
+                        from: from_log_length,
+                        to: to_log_length,
+                    };
+                    let from_log_root = Cow::Borrowed(&from.as_ref().checkpoint.log_root);
+                    let to_log_root = Cow::Borrowed(&ts_checkpoint.as_ref().checkpoint.log_root);
+                    if use_tiles {
+                        self.api
+                            .prove_log_consistency_from_tiles(request, from_log_root, to_log_root)
+                            .await?
+                    } else {
+                        self.api
+                            .prove_log_consistency(
+                                registry_domain,
+                                request,
+                                from_log_root,
+                                to_log_root,
+                            )
+                            .await?
+                    }
                 }
                 Ordering::Equal => {
                     if from.as_ref().checkpoint.log_root
@@ -1195,6 +1512,88 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
         Ok(federated_packages)
     }
 
+    /// Gets the newest checkpoint cosigned by the configured number of
+    /// trusted witnesses.
+    ///
+    /// The latest checkpoint is used if it was cosigned; otherwise the client
+    /// falls back to the most recently cosigned checkpoints, as witnesses may
+    /// not have cosigned the latest checkpoint yet. Checkpoints older than the
+    /// one the client last updated to are not considered.
+    async fn witnessed_checkpoint(
+        &self,
+        registry_domain: Option<&RegistryDomain>,
+        latest: SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, ClientError> {
+        if self.witness_threshold == 0 {
+            return Ok(latest);
+        }
+
+        let log_length = latest.as_ref().checkpoint.log_length;
+        let cosignatures = self
+            .api
+            .checkpoint_cosignatures(registry_domain, log_length)
+            .await?
+            .cosignatures;
+        let found = self.count_witness_cosignatures(&latest, &cosignatures);
+        if found >= self.witness_threshold {
+            return Ok(latest);
+        }
+
+        let min_log_length = self
+            .registry
+            .load_checkpoint(registry_domain)
+            .await?
+            .map(|c| c.as_ref().checkpoint.log_length)
+            .unwrap_or_default();
+        for cosigned in self
+            .api
+            .cosigned_checkpoints(registry_domain)
+            .await?
+            .checkpoints
+        {
+            let cosigned_log_length = cosigned.checkpoint.as_ref().checkpoint.log_length;
+            if cosigned_log_length < min_log_length || cosigned_log_length > log_length {
+                continue;
+            }
+
+            if self.count_witness_cosignatures(&cosigned.checkpoint, &cosigned.cosignatures)
+                >= self.witness_threshold
+            {
+                tracing::info!(
+                    "latest checkpoint log length `{log_length}` has {found} valid witness \
+                     cosignature(s); using cosigned checkpoint log length `{cosigned_log_length}`"
+                );
+                return Ok(cosigned.checkpoint);
+            }
+        }
+
+        Err(ClientError::InsufficientWitnessCosignatures {
+            log_length,
+            required: self.witness_threshold,
+            found,
+        })
+    }
+
+    /// Counts the trusted witnesses with a valid cosignature of a checkpoint.
+    fn count_witness_cosignatures(
+        &self,
+        ts_checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
+        cosignatures: &[Cosignature],
+    ) -> usize {
+        let checkpoint = &ts_checkpoint.as_ref().checkpoint;
+        self.witness_keys
+            .iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .filter(|key| {
+                let key_id = key.fingerprint();
+                cosignatures
+                    .iter()
+                    .any(|c| c.key_id == key_id && checkpoint.verify_cosignature(key, c).is_ok())
+            })
+            .count()
+    }
+
     /// Update checkpoint for list of packages
     async fn update_checkpoints<'a>(
         &self,
@@ -1214,7 +1613,11 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
 
         while let Some((registry_domain, packages)) = federated_packages.pop() {
             for (registry_domain, packages) in self
-                .update_packages_and_return_federated_packages(registry_domain.as_ref(), packages)
+                .update_packages_and_return_federated_packages(
+                    registry_domain.as_ref(),
+                    packages,
+                    true,
+                )
                 .await?
                 .into_iter()
             {
@@ -1307,6 +1710,61 @@ current_registry = registry_domain.map(|d| d.as_str()).unwrap_or(&self.url().saf
         Ok(record)
     }
 
+    /// Rehashes the content of any releases in the given publish information
+    /// to the given hash algorithm.
+    async fn rehash_release_content(
+        &self,
+        info: &mut PublishInfo,
+        algorithm: HashAlgorithm,
+    ) -> Result<(), ClientError> {
+        for entry in &mut info.entries {
+            if let crate::storage::PublishEntry::Release { content, .. } = entry {
+                if content.algorithm() != algorithm {
+                    *content = self.rehash_content(content, algorithm).await?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stores a copy of existing content in client storage under a digest
+    /// computed with the given hash algorithm.
+    ///
+    /// Returns the new digest of the content.
+    async fn rehash_content(
+        &self,
+        digest: &AnyHash,
+        algorithm: HashAlgorithm,
+    ) -> Result<AnyHash, ClientError> {
+        let not_found = || ClientError::ContentNotFound {
+            digest: digest.clone(),
+        };
+
+        let mut stream = self
+            .content
+            .load_content(digest)
+            .await?
+            .ok_or_else(not_found)?;
+        let mut hasher = algorithm.hasher();
+        while let Some(bytes) = stream.next().await.transpose()? {
+            hasher.update(&bytes);
+        }
+        let rehashed = hasher.finalize();
+
+        self.content
+            .store_content(
+                self.content
+                    .load_content(digest)
+                    .await?
+                    .ok_or_else(not_found)?,
+                Some(&rehashed),
+            )
+            .await?;
+
+        Ok(rehashed)
+    }
+
     /// Downloads the content for the specified digest into client storage.
     ///
     /// If the content already exists in client storage, the existing path
@@ -1465,6 +1923,9 @@ impl FileSystemClient {
             disable_interactive,
             keyring_backend,
             keys,
+            config.witness_keys.clone(),
+            config.witness_threshold,
+            config.use_tiles,
         )?))
     }
 
@@ -1530,6 +1991,9 @@ impl FileSystemClient {
             disable_interactive,
             keyring_backend,
             keys,
+            config.witness_keys.clone(),
+            config.witness_threshold,
+            config.use_tiles,
         )
     }
 
@@ -1556,6 +2020,26 @@ pub struct PackageDownload {
     pub digest: AnyHash,
     /// The path to the downloaded package contents.
     pub path: PathBuf,
+    /// The deprecation of the downloaded version, if it is deprecated.
+    pub deprecation: Option<PackageDeprecation>,
+}
+
+/// Logs a warning that the given version of a package is deprecated.
+pub(crate) fn warn_deprecated(
+    package: &PackageName,
+    version: &Version,
+    deprecation: &PackageDeprecation,
+) {
+    match &deprecation.successor {
+        Some(successor) => tracing::warn!(
+            "version {version} of package `{package}` is deprecated: {message} (use `{successor}` instead)",
+            message = deprecation.message
+        ),
+        None => tracing::warn!(
+            "version {version} of package `{package}` is deprecated: {message}",
+            message = deprecation.message
+        ),
+    }
 }
 
 /// Represents information about a downloaded package.
@@ -1625,6 +2109,13 @@ pub enum ClientError {
         has_auth_token: bool,
     },
 
+    /// A publish that initializes a package cannot be cosigned.
+    #[error("the publish for package `{name}` initializes the package and cannot be cosigned")]
+    CannotCosignInitialization {
+        /// The name of the package being initialized.
+        name: PackageName,
+    },
+
     /// There is no publish operation in progress.
     #[error("there is no publish operation in progress")]
     NotPublishing,
@@ -1636,6 +2127,19 @@ pub enum ClientError {
         name: PackageName,
     },
 
+    /// The operator record has no entries to publish.
+    #[error("the operator record has no entries to publish")]
+    NothingToPublishToOperator,
+
+    /// An operator record publish was rejected.
+    #[error("the publishing of operator record `{record_id}` was rejected due to: {reason}")]
+    OperatorPublishRejected {
+        /// The record identifier for the record that was rejected.
+        record_id: RecordId,
+        /// The reason it was rejected.
+        reason: String,
+    },
+
     /// The package does not exist.
     #[error("package `{name}` does not exist")]
     PackageDoesNotExist {
@@ -1752,6 +2256,25 @@ pub enum ClientError {
         log_length: RegistryLen,
     },
 
+    /// The checkpoint was not cosigned by enough trusted witnesses.
+    #[error("checkpoint log length `{log_length}` has {found} valid witness cosignature(s) but {required} are required")]
+    InsufficientWitnessCosignatures {
+        /// The checkpoint log length.
+        log_length: RegistryLen,
+        /// The number of witness cosignatures required.
+        required: usize,
+        /// The number of valid witness cosignatures found.
+        found: usize,
+    },
+
+    /// The last checkpoint cosigned by a witness, as served by the registry,
+    /// has no valid cosignature of the witness.
+    #[error("the registry's last checkpoint cosigned by the witness (log length `{log_length}`) has no valid cosignature of the witness")]
+    InvalidWitnessCheckpoint {
+        /// The checkpoint log length.
+        log_length: RegistryLen,
+    },
+
     /// An error occurred while accessing the keyring.
     #[error(transparent)]
     Keyring(#[from] crate::keyring::KeyringError),
@@ -1,6 +1,6 @@
 //! A module for client storage implementations.
 
-use anyhow::{Error, Result};
+use anyhow::{bail, Error, Result};
 use async_trait::async_trait;
 use bytes::Bytes;
 use futures_util::Stream;
@@ -11,12 +11,16 @@ use std::{fmt, path::PathBuf, pin::Pin, str::FromStr, time::SystemTime};
 use warg_crypto::{
     hash::{AnyHash, HashAlgorithm},
     signing::{self, KeyID, PublicKey},
+    Encode,
 };
 use warg_protocol::{
     operator,
-    package::{self, PackageRecord, Permission, PACKAGE_RECORD_VERSION},
+    package::{
+        self, PackageDeprecation, PackageMetadata, PackageRecord, Permission, Role,
+        PACKAGE_RECORD_VERSION,
+    },
     registry::{Checkpoint, PackageName, RecordId, RegistryIndex, TimestampedCheckpoint},
-    ProtoEnvelope, SerdeEnvelope, Version,
+    Cosignature, ProtoEnvelope, SerdeEnvelope, Version, VersionReq,
 };
 
 mod fs;
@@ -161,6 +165,9 @@ pub trait ContentStorage: Send + Sync {
     /// content matches the given digest. If the digests do not match, an
     /// error is returned.
     ///
+    /// The content is hashed with the algorithm of `expected_digest`, or with
+    /// the default hash algorithm if no digest is expected.
+    ///
     /// Returns the hash of the written content.
     async fn store_content(
         &self,
@@ -250,7 +257,12 @@ impl PackageInfo {
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum PublishEntry {
     /// The package is being initialized.
-    Init,
+    #[serde(rename_all = "camelCase")]
+    Init {
+        /// The hash algorithm used by the package log.
+        #[serde(default)]
+        hash_algorithm: HashAlgorithm,
+    },
     /// A new release entry is being published.
     Release {
         /// The version of the release.
@@ -263,6 +275,28 @@ pub enum PublishEntry {
         /// The version of the release being yanked.
         version: Version,
     },
+    /// A yanked release is being restored.
+    Unyank {
+        /// The version of the release being unyanked.
+        version: Version,
+    },
+    /// The package metadata is being set.
+    Metadata {
+        /// The new metadata of the package.
+        metadata: PackageMetadata,
+    },
+    /// The package or some of its versions are being deprecated.
+    Deprecate {
+        /// The deprecation being published.
+        deprecation: PackageDeprecation,
+    },
+    /// The deprecations of the package or some of its versions are being retracted.
+    Undeprecate {
+        /// The version requirement of the deprecations being retracted.
+        ///
+        /// If `None`, the deprecations of the entire package are retracted.
+        versions: Option<VersionReq>,
+    },
     /// A key is being granted permission(s).
     Grant {
         /// The public key being granted to.
@@ -277,6 +311,50 @@ pub enum PublishEntry {
         /// The permission(s) being revoked.
         permissions: Vec<Permission>,
     },
+    /// A key is being granted permission(s) for a range of versions.
+    GrantVersions {
+        /// The public key being granted to.
+        key: PublicKey,
+        /// The permission(s) being granted.
+        permissions: Vec<Permission>,
+        /// The versions the permission(s) are limited to.
+        versions: VersionReq,
+    },
+    /// A key's permission(s) for a range of versions are being revoked.
+    RevokeVersions {
+        /// The key ID being revoked from.
+        key_id: KeyID,
+        /// The permission(s) being revoked.
+        permissions: Vec<Permission>,
+        /// The versions of the grant being revoked.
+        versions: VersionReq,
+    },
+    /// A key is being granted a role.
+    GrantRole {
+        /// The public key being granted to.
+        key: PublicKey,
+        /// The role being granted.
+        role: Role,
+    },
+    /// A key's role is being revoked.
+    RevokeRole {
+        /// The key ID being revoked from.
+        key_id: KeyID,
+        /// The role being revoked.
+        role: Role,
+    },
+    /// A key is being rotated, transferring its permissions to a new key.
+    RotateKey {
+        /// The key ID being rotated.
+        key_id: KeyID,
+        /// The public key replacing it.
+        key: PublicKey,
+    },
+    /// The number of signatures required on each record is being set.
+    SignatureThreshold {
+        /// The number of signatures required.
+        threshold: u32,
+    },
 }
 
 /// Represents information about a package publish.
@@ -292,25 +370,102 @@ pub struct PublishInfo {
     pub head: Option<RecordId>,
     /// The new record entries to publish.
     pub entries: Vec<PublishEntry>,
+    /// The cosigning state of the publish.
+    ///
+    /// If `None`, the record has not been prepared for cosigning.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cosigning: Option<Cosigning>,
+}
+
+/// Represents the cosigning state of a package publish.
+///
+/// Cosignatures are over the exact record being published, so the record's
+/// timestamp is fixed once cosigning has started.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Cosigning {
+    /// The timestamp of the record being cosigned.
+    pub timestamp: SystemTime,
+    /// The cosignatures collected so far.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub cosignatures: Vec<Cosignature>,
 }
 
 impl PublishInfo {
     /// Determines if the publish information is initializing the package.
     pub fn initializing(&self) -> bool {
-        self.entries.iter().any(|e| matches!(e, PublishEntry::Init))
+        self.entries
+            .iter()
+            .any(|e| matches!(e, PublishEntry::Init { .. }))
+    }
+
+    /// Gets the hash algorithm of the package log being initialized.
+    ///
+    /// Returns `None` if the publish is not initializing the package.
+    pub fn hash_algorithm(&self) -> Option<HashAlgorithm> {
+        self.entries.iter().find_map(|e| match e {
+            PublishEntry::Init { hash_algorithm } => Some(*hash_algorithm),
+            _ => None,
+        })
+    }
+
+    /// Adds a cosignature of the record being published.
+    ///
+    /// The publish must have been prepared for cosigning and must not be
+    /// initializing the package.
+    ///
+    /// Any previous cosignature by the same key is replaced.
+    pub fn cosign(&mut self, signing_key: &signing::PrivateKey) -> Result<()> {
+        if self.initializing() {
+            bail!(
+                "cannot cosign a publish that initializes package `{name}`",
+                name = self.name
+            );
+        }
+
+        let Some(cosigning) = &self.cosigning else {
+            bail!(
+                "the publish for package `{name}` has not been prepared for cosigning",
+                name = self.name
+            );
+        };
+
+        // The init key is only needed for initializing publishes
+        let record = self.record(&signing_key.public_key(), cosigning.timestamp);
+        let cosignature = Cosignature::sign::<PackageRecord>(signing_key, &record.encode())?;
+
+        let cosigning = self.cosigning.as_mut().unwrap();
+        cosigning
+            .cosignatures
+            .retain(|c| c.key_id != cosignature.key_id);
+        cosigning.cosignatures.push(cosignature);
+        Ok(())
     }
 
     pub(crate) fn finalize(
         self,
         signing_key: &signing::PrivateKey,
     ) -> Result<ProtoEnvelope<PackageRecord>> {
+        let (timestamp, cosignatures) = match &self.cosigning {
+            Some(cosigning) => (cosigning.timestamp, cosigning.cosignatures.clone()),
+            // TODO: this seems wrong to record the current time client-side
+            // How can we guarantee that the timestamps are monotonic?
+            // Should incrementing timestamps even be a requirement?
+            None => (SystemTime::now(), Vec::new()),
+        };
+
+        let record = self.record(&signing_key.public_key(), timestamp);
+        Ok(ProtoEnvelope::signed_contents(signing_key, record)?.with_cosignatures(cosignatures))
+    }
+
+    fn record(&self, init_key: &PublicKey, timestamp: SystemTime) -> PackageRecord {
         let mut entries = Vec::with_capacity(self.entries.len());
-        for entry in self.entries {
+        for entry in self.entries.iter().cloned() {
             match entry {
-                PublishEntry::Init => {
+                PublishEntry::Init { hash_algorithm } => {
                     entries.push(package::PackageEntry::Init {
-                        hash_algorithm: HashAlgorithm::Sha256,
-                        key: signing_key.public_key(),
+                        hash_algorithm,
+                        key: init_key.clone(),
                     });
                 }
                 PublishEntry::Release { version, content } => {
@@ -319,6 +474,18 @@ impl PublishInfo {
                 PublishEntry::Yank { version } => {
                     entries.push(package::PackageEntry::Yank { version })
                 }
+                PublishEntry::Unyank { version } => {
+                    entries.push(package::PackageEntry::Unyank { version })
+                }
+                PublishEntry::Metadata { metadata } => {
+                    entries.push(package::PackageEntry::Metadata { metadata })
+                }
+                PublishEntry::Deprecate { deprecation } => {
+                    entries.push(package::PackageEntry::Deprecate { deprecation })
+                }
+                PublishEntry::Undeprecate { versions } => {
+                    entries.push(package::PackageEntry::Undeprecate { versions })
+                }
                 PublishEntry::Grant { key, permissions } => {
                     entries.push(package::PackageEntry::GrantFlat { key, permissions })
                 }
@@ -329,19 +496,44 @@ impl PublishInfo {
                     key_id,
                     permissions,
                 }),
+                PublishEntry::GrantVersions {
+                    key,
+                    permissions,
+                    versions,
+                } => entries.push(package::PackageEntry::GrantVersions {
+                    key,
+                    permissions,
+                    versions,
+                }),
+                PublishEntry::RevokeVersions {
+                    key_id,
+                    permissions,
+                    versions,
+                } => entries.push(package::PackageEntry::RevokeVersions {
+                    key_id,
+                    permissions,
+                    versions,
+                }),
+                PublishEntry::GrantRole { key, role } => {
+                    entries.push(package::PackageEntry::GrantRole { key, role })
+                }
+                PublishEntry::RevokeRole { key_id, role } => {
+                    entries.push(package::PackageEntry::RevokeRole { key_id, role })
+                }
+                PublishEntry::RotateKey { key_id, key } => {
+                    entries.push(package::PackageEntry::RotateKey { key_id, key })
+                }
+                PublishEntry::SignatureThreshold { threshold } => {
+                    entries.push(package::PackageEntry::SignatureThreshold { threshold })
+                }
             }
         }
 
-        let record = package::PackageRecord {
-            prev: self.head,
+        package::PackageRecord {
+            prev: self.head.clone(),
             version: PACKAGE_RECORD_VERSION,
-            // TODO: this seems wrong to record the current time client-side
-            // How can we guarantee that the timestamps are monotonic?
-            // Should incrementing timestamps even be a requirement?
-            timestamp: SystemTime::now(),
+            timestamp,
             entries,
-        };
-
-        Ok(ProtoEnvelope::signed_contents(signing_key, record)?)
+        }
     }
 }
@@ -22,7 +22,7 @@ use tempfile::NamedTempFile;
 use tokio::io::{AsyncWriteExt, BufReader, BufWriter};
 use tokio_util::io::ReaderStream;
 use walkdir::WalkDir;
-use warg_crypto::hash::{AnyHash, Digest, Hash, Sha256};
+use warg_crypto::hash::{AnyHash, Sha256};
 use warg_protocol::{
     registry::{LogId, PackageName, TimestampedCheckpoint},
     SerdeEnvelope,
@@ -343,7 +343,10 @@ impl ContentStorage for FileSystemContentStorage {
     ) -> Result<AnyHash> {
         let (file, path) = self.temp_file()?.into_parts();
         let mut writer = BufWriter::new(tokio::fs::File::from_std(file));
-        let mut hasher = Sha256::new();
+        let mut hasher = expected_digest
+            .map(AnyHash::algorithm)
+            .unwrap_or_default()
+            .hasher();
 
         while let Some(bytes) = stream.next().await.transpose()? {
             hasher.update(&bytes);
@@ -353,7 +356,7 @@ impl ContentStorage for FileSystemContentStorage {
                 .with_context(|| format!("failed to write to `{path}`", path = path.display()))?;
         }
 
-        let hash = AnyHash::from(Hash::<Sha256>::from(hasher.finalize()));
+        let hash = hasher.finalize();
 
         if let Some(expected) = expected_digest {
             if hash != *expected {
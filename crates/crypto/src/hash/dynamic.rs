@@ -1,4 +1,5 @@
-use super::{Digest, HashAlgorithm, Sha256};
+use super::{Blake3, Digest, HashAlgorithm, Sha256, Sha512};
+use crate::ByteVisitor;
 use anyhow::Error;
 use serde::{Deserialize, Serialize};
 use std::{fmt, ops::Deref, str::FromStr};
@@ -6,28 +7,42 @@ use thiserror::Error;
 
 pub enum Hasher {
     Sha256(Sha256),
+    Sha512(Sha512),
+    Blake3(Box<Blake3>),
 }
 
 impl Hasher {
     pub fn update(&mut self, bytes: &[u8]) {
         match self {
             Self::Sha256(d) => d.update(bytes),
+            Self::Sha512(d) => d.update(bytes),
+            Self::Blake3(d) => Digest::update(d.as_mut(), bytes),
         }
     }
 
     pub fn finalize(self) -> AnyHash {
         let (algo, bytes) = match self {
             Self::Sha256(d) => (HashAlgorithm::Sha256, d.finalize().deref().into()),
+            Self::Sha512(d) => (HashAlgorithm::Sha512, d.finalize().deref().into()),
+            Self::Blake3(d) => (HashAlgorithm::Blake3, Digest::finalize(*d).deref().into()),
         };
 
         AnyHash { algo, bytes }
     }
 }
 
+impl ByteVisitor for Hasher {
+    fn visit_bytes(&mut self, bytes: impl AsRef<[u8]>) {
+        self.update(bytes.as_ref())
+    }
+}
+
 impl HashAlgorithm {
     pub fn hasher(&self) -> Hasher {
         match self {
             HashAlgorithm::Sha256 => Hasher::Sha256(Sha256::new()),
+            HashAlgorithm::Sha512 => Hasher::Sha512(Sha512::new()),
+            HashAlgorithm::Blake3 => Hasher::Blake3(Box::new(<Blake3 as Digest>::new())),
         }
     }
 
@@ -38,6 +53,16 @@ impl HashAlgorithm {
                 d.update(content_bytes);
                 d.finalize().deref().into()
             }
+            HashAlgorithm::Sha512 => {
+                let mut d = Sha512::new();
+                d.update(content_bytes);
+                d.finalize().deref().into()
+            }
+            HashAlgorithm::Blake3 => {
+                let mut d = <Blake3 as Digest>::new();
+                Digest::update(&mut d, content_bytes);
+                Digest::finalize(d).deref().into()
+            }
         };
 
         AnyHash {
@@ -143,6 +168,28 @@ mod tests {
         assert_eq!(output, expected)
     }
 
+    #[test]
+    fn test_sha512_labeled_digest() {
+        let input = b"abc";
+        let output = HashAlgorithm::Sha512.digest(input);
+        let output = format!("{}", output);
+
+        let expected = "sha512:ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f";
+
+        assert_eq!(output, expected)
+    }
+
+    #[test]
+    fn test_blake3_labeled_digest() {
+        let input = b"abc";
+        let output = HashAlgorithm::Blake3.digest(input);
+        let output = format!("{}", output);
+
+        let expected = "blake3:6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85";
+
+        assert_eq!(output, expected)
+    }
+
     #[test]
     fn test_labeled_digest_parse_rejects_uppercase() {
         let digest_str = "sha256:7d38b5cd25a2baf85ad3bb5b9311383e671a8a142eb302b324d4a5fba8748c69";
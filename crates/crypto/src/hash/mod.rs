@@ -7,26 +7,34 @@ use thiserror::Error;
 mod dynamic;
 mod r#static;
 
+pub use blake3::Hasher as Blake3;
 pub use digest::{Digest, Output};
 pub use dynamic::{AnyHash, AnyHashError};
 pub use r#static::Hash;
-pub use sha2::Sha256;
+pub use sha2::{Sha256, Sha512};
 
 use crate::VisitBytes;
 
 use self::r#static::IncorrectLengthError;
 
-#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[derive(
+    Clone, Copy, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
 #[serde(rename_all = "camelCase")]
 #[non_exhaustive]
 pub enum HashAlgorithm {
+    #[default]
     Sha256,
+    Sha512,
+    Blake3,
 }
 
 impl fmt::Display for HashAlgorithm {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             HashAlgorithm::Sha256 => write!(f, "sha256"),
+            HashAlgorithm::Sha512 => write!(f, "sha512"),
+            HashAlgorithm::Blake3 => write!(f, "blake3"),
         }
     }
 }
@@ -37,13 +45,15 @@ impl FromStr for HashAlgorithm {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "sha256" => Ok(HashAlgorithm::Sha256),
+            "sha512" => Ok(HashAlgorithm::Sha512),
+            "blake3" => Ok(HashAlgorithm::Blake3),
             _ => Err(Error::msg(format!("Illegal hash algorithm '{}'", s))),
         }
     }
 }
 
-static EMPTY_TREE_HASH: Lazy<Vec<Hash<Sha256>>> = Lazy::new(|| {
-    let mut v: Vec<Hash<Sha256>> = Vec::with_capacity(257);
+fn empty_tree_hashes<D: SupportedDigest>() -> Vec<Hash<D>> {
+    let mut v: Vec<Hash<D>> = Vec::with_capacity(257);
     fn empty_tree_hash<D: SupportedDigest>(v: &mut Vec<Hash<D>>, height: u32) -> Hash<D> {
         let hash: Hash<D> = if height == 0 {
             hash_empty()
@@ -56,7 +66,11 @@ static EMPTY_TREE_HASH: Lazy<Vec<Hash<Sha256>>> = Lazy::new(|| {
     }
     empty_tree_hash(&mut v, 256);
     v
-});
+}
+
+static EMPTY_TREE_HASH: Lazy<Vec<Hash<Sha256>>> = Lazy::new(empty_tree_hashes);
+static EMPTY_TREE_HASH_SHA512: Lazy<Vec<Hash<Sha512>>> = Lazy::new(empty_tree_hashes);
+static EMPTY_TREE_HASH_BLAKE3: Lazy<Vec<Hash<Blake3>>> = Lazy::new(empty_tree_hashes);
 
 // If updating this function, also update `hash_empty` in transparency map
 pub(crate) fn hash_empty<D: SupportedDigest>() -> Hash<D> {
@@ -92,11 +106,28 @@ impl SupportedDigest for Sha256 {
     }
 }
 
+impl SupportedDigest for Sha512 {
+    const ALGORITHM: HashAlgorithm = HashAlgorithm::Sha512;
+    fn empty_tree_hash(height: usize) -> &'static Hash<Sha512> {
+        &EMPTY_TREE_HASH_SHA512[height]
+    }
+}
+
+impl SupportedDigest for Blake3 {
+    const ALGORITHM: HashAlgorithm = HashAlgorithm::Blake3;
+    fn empty_tree_hash(height: usize) -> &'static Hash<Blake3> {
+        &EMPTY_TREE_HASH_BLAKE3[height]
+    }
+}
+
 mod private {
-    use sha2::Sha256;
+    use super::Blake3;
+    use sha2::{Sha256, Sha512};
 
     pub trait Sealed {}
     impl Sealed for Sha256 {}
+    impl Sealed for Sha512 {}
+    impl Sealed for Blake3 {}
 }
 
 impl<D: SupportedDigest> From<Hash<D>> for AnyHash {
@@ -0,0 +1,334 @@
+//! Signed notes in the [C2SP signed-note] format.
+//!
+//! A note is UTF-8 text followed by a blank line and one or more signature
+//! lines. Each signature line names the signing key and carries a short key
+//! hash so that verifiers can find the signature made by a key they trust.
+//!
+//! [C2SP signed-note]: https://c2sp.org/signed-note
+
+use super::{PrivateKey, PublicKey, Signature, SignatureError};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use sha2::{Digest, Sha256};
+use std::{fmt, str::FromStr};
+use thiserror::Error;
+
+/// The signature type identifier of Ed25519 keys.
+pub const SIGNATURE_TYPE_ED25519: u8 = 0x01;
+
+/// The signature type identifier of ECDSA P-256 keys.
+pub const SIGNATURE_TYPE_ECDSA_P256: u8 = 0x02;
+
+/// The prefix of every signature line of a note.
+const SIGNATURE_LINE_PREFIX: &str = "\u{2014} ";
+
+/// Errors that may occur when creating, parsing or verifying a note.
+#[derive(Error, Debug)]
+pub enum NoteError {
+    #[error("note text must be non-empty, end with a newline and contain no control characters")]
+    InvalidText,
+
+    #[error("`{0}` is not a valid key name")]
+    InvalidName(String),
+
+    #[error("malformed note: {0}")]
+    Malformed(&'static str),
+
+    #[error("malformed verifier key: {0}")]
+    InvalidVerifierKey(&'static str),
+
+    #[error("note has no signature from key `{0}`")]
+    MissingSignature(String),
+
+    #[error("note signature from key `{0}` is invalid")]
+    InvalidSignature(String),
+
+    #[error("base64 decode failed")]
+    Base64DecodeError(#[from] base64::DecodeError),
+
+    #[error("failed to sign note: {0}")]
+    SignatureError(#[from] SignatureError),
+}
+
+/// Gets the signature type identifier of the given key.
+pub fn signature_type(key: &PublicKey) -> u8 {
+    match key {
+        PublicKey::EcdsaP256(_) => SIGNATURE_TYPE_ECDSA_P256,
+        PublicKey::Ed25519(_) => SIGNATURE_TYPE_ED25519,
+    }
+}
+
+/// Computes the key hash identifying the given named key in signature lines.
+pub fn key_hash(name: &str, key: &PublicKey) -> [u8; 4] {
+    let digest = Sha256::new()
+        .chain_update(name.as_bytes())
+        .chain_update(b"\n")
+        .chain_update([signature_type(key)])
+        .chain_update(key.bytes())
+        .finalize();
+    [digest[0], digest[1], digest[2], digest[3]]
+}
+
+/// Encodes the given named key as a verifier key (`<name>+<hash>+<key>`).
+pub fn verifier_key(name: &str, key: &PublicKey) -> String {
+    let mut bytes = vec![signature_type(key)];
+    bytes.extend(key.bytes());
+    format!(
+        "{name}+{hash}+{key}",
+        hash = hex::encode(key_hash(name, key)),
+        key = STANDARD.encode(bytes)
+    )
+}
+
+/// Parses a verifier key (`<name>+<hash>+<key>`) into its name and public key.
+pub fn parse_verifier_key(s: &str) -> Result<(String, PublicKey), NoteError> {
+    let mut parts = s.splitn(3, '+');
+    let (Some(name), Some(hash), Some(key)) = (parts.next(), parts.next(), parts.next()) else {
+        return Err(NoteError::InvalidVerifierKey("expected three parts"));
+    };
+    validate_name(name)?;
+
+    let bytes = STANDARD.decode(key)?;
+    let key = match bytes.split_first() {
+        Some((&SIGNATURE_TYPE_ED25519, key)) => PublicKey::Ed25519(
+            ed25519_dalek::VerifyingKey::try_from(key)
+                .map_err(|_| NoteError::InvalidVerifierKey("invalid Ed25519 key"))?,
+        ),
+        Some((&SIGNATURE_TYPE_ECDSA_P256, key)) => PublicKey::EcdsaP256(
+            p256::ecdsa::VerifyingKey::from_sec1_bytes(key)
+                .map_err(|_| NoteError::InvalidVerifierKey("invalid ECDSA P-256 key"))?,
+        ),
+        _ => return Err(NoteError::InvalidVerifierKey("unsupported signature type")),
+    };
+
+    if hex::decode(hash).ok().as_deref() != Some(&key_hash(name, &key)) {
+        return Err(NoteError::InvalidVerifierKey("key hash does not match key"));
+    }
+
+    Ok((name.to_string(), key))
+}
+
+fn validate_name(name: &str) -> Result<(), NoteError> {
+    if name.is_empty() || name.contains(|c: char| c.is_whitespace() || c == '+') {
+        return Err(NoteError::InvalidName(name.to_string()));
+    }
+
+    Ok(())
+}
+
+/// A signature line of a note.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NoteSignature {
+    /// The name of the signing key.
+    pub name: String,
+    /// The hash of the signing key's name and public key.
+    pub key_hash: [u8; 4],
+    /// The signature bytes.
+    pub signature: Vec<u8>,
+}
+
+impl fmt::Display for NoteSignature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bytes = [self.key_hash.as_slice(), &self.signature].concat();
+        write!(
+            f,
+            "{SIGNATURE_LINE_PREFIX}{name} {sig}",
+            name = self.name,
+            sig = STANDARD.encode(bytes)
+        )
+    }
+}
+
+impl FromStr for NoteSignature {
+    type Err = NoteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, sig) = s
+            .strip_prefix(SIGNATURE_LINE_PREFIX)
+            .and_then(|s| s.split_once(' '))
+            .ok_or(NoteError::Malformed("invalid signature line"))?;
+        validate_name(name)?;
+
+        let bytes = STANDARD.decode(sig)?;
+        if bytes.len() <= 4 {
+            return Err(NoteError::Malformed("signature is too short"));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            key_hash: [bytes[0], bytes[1], bytes[2], bytes[3]],
+            signature: bytes[4..].to_vec(),
+        })
+    }
+}
+
+/// A note and its signatures.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Note {
+    text: String,
+    signatures: Vec<NoteSignature>,
+}
+
+impl Note {
+    /// Creates an unsigned note with the given text.
+    pub fn new(text: impl Into<String>) -> Result<Self, NoteError> {
+        let text = text.into();
+        if !text.ends_with('\n') || text.contains(|c: char| c.is_control() && c != '\n') {
+            return Err(NoteError::InvalidText);
+        }
+
+        Ok(Self {
+            text,
+            signatures: Vec::new(),
+        })
+    }
+
+    /// Gets the text of the note.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Gets the signatures of the note.
+    pub fn signatures(&self) -> &[NoteSignature] {
+        &self.signatures
+    }
+
+    /// Adds an existing signature to the note.
+    pub fn add_signature(&mut self, signature: NoteSignature) {
+        self.signatures.push(signature);
+    }
+
+    /// Signs the note with the given named key.
+    pub fn sign(&mut self, name: &str, key: &PrivateKey) -> Result<(), NoteError> {
+        validate_name(name)?;
+        let signature = key.sign(self.text.as_bytes())?;
+        self.signatures.push(NoteSignature {
+            name: name.to_string(),
+            key_hash: key_hash(name, &key.public_key()),
+            signature: signature.bytes(),
+        });
+        Ok(())
+    }
+
+    /// Verifies that the note was signed by the given named key.
+    pub fn verify(&self, name: &str, key: &PublicKey) -> Result<(), NoteError> {
+        let hash = key_hash(name, key);
+        let line = self
+            .signatures
+            .iter()
+            .find(|s| s.name == name && s.key_hash == hash)
+            .ok_or_else(|| NoteError::MissingSignature(name.to_string()))?;
+
+        let invalid = || NoteError::InvalidSignature(name.to_string());
+        let signature = match key {
+            PublicKey::EcdsaP256(_) => Signature::P256(
+                p256::ecdsa::Signature::from_der(&line.signature).map_err(|_| invalid())?,
+            ),
+            PublicKey::Ed25519(_) => Signature::Ed25519(
+                ed25519_dalek::Signature::from_slice(&line.signature).map_err(|_| invalid())?,
+            ),
+        };
+
+        key.verify(self.text.as_bytes(), &signature)
+            .map_err(|_| invalid())
+    }
+}
+
+impl fmt::Display for Note {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{text}", text = self.text)?;
+        for signature in &self.signatures {
+            writeln!(f, "{signature}")?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Note {
+    type Err = NoteError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let split = s
+            .rfind("\n\n")
+            .ok_or(NoteError::Malformed("missing signature block"))?;
+        let (text, signatures) = (&s[..=split], &s[split + 2..]);
+
+        let mut note = Self::new(text)?;
+        let signatures = signatures
+            .strip_suffix('\n')
+            .ok_or(NoteError::Malformed("note must end with a newline"))?;
+        for line in signatures.split('\n') {
+            note.add_signature(line.parse()?);
+        }
+
+        Ok(note)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::signing::{generate_ed25519_pair, generate_p256_pair};
+    use pretty_assertions::assert_eq;
+
+    const NEUMANN_VERIFIER_KEY: &str =
+        "PeterNeumann+c74f20a3+ARpc2QcUPDhMQegwxbzhKqiBfsVkmqq/LDE4izWy10TW";
+
+    const NEUMANN_NOTE: &str = "If you think cryptography is the answer to your problem,
+then you don't know what your problem is.
+
+\u{2014} PeterNeumann x08go/ZJkuBS9UG/SffcvIAQxVBtiFupLLr8pAcElZInNIuGUgYN1FFYC2pZSNXgKvqfqdngotpRZb6KE6RyyBwJnAM=
+";
+
+    #[test]
+    fn test_verifier_key_roundtrip() {
+        let (name, key) = parse_verifier_key(NEUMANN_VERIFIER_KEY).unwrap();
+        assert_eq!(name, "PeterNeumann");
+        assert_eq!(verifier_key(&name, &key), NEUMANN_VERIFIER_KEY);
+
+        assert!(parse_verifier_key(
+            "PeterNeumann+c74f20a4+ARpc2QcUPDhMQegwxbzhKqiBfsVkmqq/LDE4izWy10TW"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_verify_known_note() {
+        let (name, key) = parse_verifier_key(NEUMANN_VERIFIER_KEY).unwrap();
+        let note: Note = NEUMANN_NOTE.parse().unwrap();
+        note.verify(&name, &key).unwrap();
+        assert_eq!(note.to_string(), NEUMANN_NOTE);
+
+        let tampered: Note = NEUMANN_NOTE
+            .replacen("problem", "issue", 1)
+            .parse()
+            .unwrap();
+        assert!(matches!(
+            tampered.verify(&name, &key),
+            Err(NoteError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn test_sign_and_verify() {
+        for (public, private) in [generate_p256_pair(), generate_ed25519_pair()] {
+            let mut note = Note::new("example.com/log\n42\n").unwrap();
+            note.sign("example.com/log", &private).unwrap();
+
+            let parsed: Note = note.to_string().parse().unwrap();
+            assert_eq!(parsed, note);
+            parsed.verify("example.com/log", &public).unwrap();
+            assert!(matches!(
+                parsed.verify("other", &public),
+                Err(NoteError::MissingSignature(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_invalid_notes() {
+        assert!(Note::new("no trailing newline").is_err());
+        assert!("text\n".parse::<Note>().is_err());
+        assert!("text\n\n\u{2014} name\n".parse::<Note>().is_err());
+        assert!("text\n\n\u{2014} name AAAA\n".parse::<Note>().is_err());
+    }
+}
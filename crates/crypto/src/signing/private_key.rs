@@ -12,6 +12,7 @@ pub struct PrivateKey(Secret<PrivateKeyInner>);
 
 pub enum PrivateKeyInner {
     EcdsaP256(p256::ecdsa::SigningKey),
+    Ed25519(ed25519_dalek::SigningKey),
 }
 
 impl PrivateKey {
@@ -30,6 +31,9 @@ impl PrivateKey {
             SignatureAlgorithm::EcdsaP256 => PrivateKeyInner::EcdsaP256(
                 p256::ecdsa::SigningKey::from_slice(bytes.expose_secret())?,
             ),
+            SignatureAlgorithm::Ed25519 => PrivateKeyInner::Ed25519(
+                ed25519_dalek::SigningKey::try_from(bytes.expose_secret().as_slice())?,
+            ),
         };
 
         Ok(PrivateKey(Secret::from(key)))
@@ -48,6 +52,7 @@ impl PrivateKey {
     pub fn signature_algorithm(&self) -> SignatureAlgorithm {
         match self.0.expose_secret() {
             PrivateKeyInner::EcdsaP256(_) => SignatureAlgorithm::EcdsaP256,
+            PrivateKeyInner::Ed25519(_) => SignatureAlgorithm::Ed25519,
         }
     }
 
@@ -55,6 +60,7 @@ impl PrivateKey {
     pub fn bytes(&self) -> Vec<u8> {
         match self.0.expose_secret() {
             PrivateKeyInner::EcdsaP256(key) => key.to_bytes().to_vec(),
+            PrivateKeyInner::Ed25519(key) => key.to_bytes().to_vec(),
         }
     }
 
@@ -62,6 +68,7 @@ impl PrivateKey {
     pub fn sign(&self, msg: &[u8]) -> Result<Signature, SignatureError> {
         match self.0.expose_secret() {
             PrivateKeyInner::EcdsaP256(key) => Ok(Signature::P256(key.try_sign(msg)?)),
+            PrivateKeyInner::Ed25519(key) => Ok(Signature::Ed25519(key.try_sign(msg)?)),
         }
     }
 
@@ -70,6 +77,7 @@ impl PrivateKey {
             PrivateKeyInner::EcdsaP256(key) => {
                 PublicKey::EcdsaP256(p256::ecdsa::VerifyingKey::from(key))
             }
+            PrivateKeyInner::Ed25519(key) => PublicKey::Ed25519(key.verifying_key()),
         }
     }
 }
@@ -93,6 +101,9 @@ impl TryFrom<String> for PrivateKey {
             SignatureAlgorithm::EcdsaP256 => PrivateKeyInner::EcdsaP256(
                 p256::ecdsa::SigningKey::from_bytes(bytes.as_slice().into())?,
             ),
+            SignatureAlgorithm::Ed25519 => {
+                PrivateKeyInner::Ed25519(ed25519_dalek::SigningKey::try_from(bytes.as_slice())?)
+            }
         };
 
         Ok(PrivateKey(Secret::from(key)))
@@ -125,6 +136,11 @@ impl Zeroize for PrivateKeyInner {
                 );
                 drop(std::mem::replace(sk, mostly_zero));
             }
+            PrivateKeyInner::Ed25519(sk) => {
+                // SigningKey zeroizes on Drop
+                let zero = ed25519_dalek::SigningKey::from_bytes(&Default::default());
+                drop(std::mem::replace(sk, zero));
+            }
         }
     }
 }
@@ -135,6 +151,12 @@ impl From<p256::ecdsa::SigningKey> for PrivateKey {
     }
 }
 
+impl From<ed25519_dalek::SigningKey> for PrivateKey {
+    fn from(key: ed25519_dalek::SigningKey) -> Self {
+        PrivateKey(Secret::from(PrivateKeyInner::Ed25519(key)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -146,4 +168,15 @@ mod tests {
         let key = PrivateKey::decode(key_str.to_string()).unwrap();
         assert_eq!(key_str, &*key.encode());
     }
+
+    #[test]
+    fn test_roundtrip_ed25519() {
+        let key_str = "ed25519:nWGxne/9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A=";
+        let key = PrivateKey::decode(key_str.to_string()).unwrap();
+        assert_eq!(key_str, &*key.encode());
+        assert_eq!(
+            key.public_key().to_string(),
+            "ed25519:11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo="
+        );
+    }
 }
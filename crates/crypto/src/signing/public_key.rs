@@ -3,12 +3,13 @@ use base64::{engine::general_purpose::STANDARD, Engine};
 use core::fmt;
 use serde::{Deserialize, Serialize};
 use signature::{Error as SignatureError, Verifier};
-use std::str::FromStr;
+use std::{cmp::Ordering, str::FromStr};
 use thiserror::Error;
 
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PublicKey {
     EcdsaP256(p256::ecdsa::VerifyingKey),
+    Ed25519(ed25519_dalek::VerifyingKey),
 }
 
 impl PublicKey {
@@ -16,6 +17,7 @@ impl PublicKey {
     pub fn signature_algorithm(&self) -> SignatureAlgorithm {
         match self {
             PublicKey::EcdsaP256(_) => SignatureAlgorithm::EcdsaP256,
+            PublicKey::Ed25519(_) => SignatureAlgorithm::Ed25519,
         }
     }
 
@@ -23,6 +25,7 @@ impl PublicKey {
     pub fn bytes(&self) -> Vec<u8> {
         match self {
             PublicKey::EcdsaP256(key) => key.to_encoded_point(true).as_bytes().to_vec(),
+            PublicKey::Ed25519(key) => key.to_bytes().to_vec(),
         }
     }
 
@@ -30,6 +33,11 @@ impl PublicKey {
     pub fn verify(&self, msg: &[u8], signature: &Signature) -> Result<(), SignatureError> {
         match (self, signature) {
             (PublicKey::EcdsaP256(key), Signature::P256(signature)) => key.verify(msg, signature),
+            (PublicKey::Ed25519(key), Signature::Ed25519(signature)) => {
+                key.verify_strict(msg, signature)
+            }
+            // The signature was not produced by this key's algorithm
+            _ => Err(SignatureError::new()),
         }
     }
 
@@ -59,7 +67,7 @@ impl FromStr for PublicKey {
     type Err = PublicKeyParseError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split(|c| c == ':').collect();
+        let parts: Vec<&str> = s.split(':').collect();
         if parts.len() != 2 {
             return Err(PublicKeyParseError::IncorrectStructure(parts.len()));
         }
@@ -70,12 +78,32 @@ impl FromStr for PublicKey {
             SignatureAlgorithm::EcdsaP256 => {
                 PublicKey::EcdsaP256(p256::ecdsa::VerifyingKey::from_sec1_bytes(&bytes)?)
             }
+            SignatureAlgorithm::Ed25519 => {
+                PublicKey::Ed25519(ed25519_dalek::VerifyingKey::try_from(bytes.as_slice())?)
+            }
         };
 
         Ok(key)
     }
 }
 
+impl PartialOrd for PublicKey {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for PublicKey {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (PublicKey::EcdsaP256(a), PublicKey::EcdsaP256(b)) => a.cmp(b),
+            (PublicKey::Ed25519(a), PublicKey::Ed25519(b)) => a.as_bytes().cmp(b.as_bytes()),
+            (PublicKey::EcdsaP256(_), PublicKey::Ed25519(_)) => Ordering::Less,
+            (PublicKey::Ed25519(_), PublicKey::EcdsaP256(_)) => Ordering::Greater,
+        }
+    }
+}
+
 impl Serialize for PublicKey {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -115,6 +143,12 @@ impl From<p256::ecdsa::VerifyingKey> for PublicKey {
     }
 }
 
+impl From<ed25519_dalek::VerifyingKey> for PublicKey {
+    fn from(key: ed25519_dalek::VerifyingKey) -> Self {
+        PublicKey::Ed25519(key)
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct KeyID(String);
@@ -155,4 +189,12 @@ mod tests {
         let pub_key: PublicKey = key_str.parse().unwrap();
         assert_eq!(key_str, &format!("{pub_key}"));
     }
+
+    #[test]
+    fn test_roundtrip_ed25519() {
+        let key_str = "ed25519:11qYAYKxCrfVS/7TyWQHOg7hcvPapiMlrwIaaPcHURo=";
+        let pub_key: PublicKey = key_str.parse().unwrap();
+        assert_eq!(key_str, &format!("{pub_key}"));
+        assert!(pub_key.fingerprint().to_string().starts_with("sha256:"));
+    }
 }
@@ -9,7 +9,8 @@ pub mod registry;
 mod serde_envelope;
 
 pub use proto_envelope::{
-    ProtoEnvelope, ProtoEnvelopeBody, PublishedProtoEnvelope, PublishedProtoEnvelopeBody,
+    Cosignature, ProtoEnvelope, ProtoEnvelopeBody, PublishedProtoEnvelope,
+    PublishedProtoEnvelopeBody,
 };
 pub use semver::{Version, VersionReq};
 pub use serde_envelope::SerdeEnvelope;
@@ -36,7 +37,7 @@ pub trait Validator:
     fn validate(self, record: &ProtoEnvelope<Self::Record>) -> Result<Self, Self::Error>;
 }
 
-/// Helpers for converting to and from protobuf
+// Helpers for converting to and from protobuf
 
 fn prost_to_pbjson_timestamp(timestamp: prost_types::Timestamp) -> pbjson_types::Timestamp {
     pbjson_types::Timestamp {
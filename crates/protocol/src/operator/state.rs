@@ -38,6 +38,9 @@ pub enum ValidationError {
     #[error("unable to verify signature: {0}")]
     SignatureError(#[from] signing::SignatureError),
 
+    #[error("operator records cannot have cosignatures")]
+    CosignaturesNotSupported,
+
     #[error("record hash uses {found} algorithm but {expected} was expected")]
     IncorrectHashAlgorithm {
         found: HashAlgorithm,
@@ -209,6 +212,11 @@ impl LogState {
         // Validate the envelope signature
         model::OperatorRecord::verify(key, envelope.content_bytes(), envelope.signature())?;
 
+        // Operator records are signed by a single key
+        if !envelope.cosignatures().is_empty() {
+            return Err(ValidationError::CosignaturesNotSupported);
+        }
+
         // Update the state head
         self.head = Some(Head {
             digest: RecordId::operator_record::<Sha256>(envelope),
@@ -9,8 +9,10 @@ use crate::{pbjson_to_prost_timestamp, prost_to_pbjson_timestamp, registry::Reco
 mod model;
 mod state;
 
-pub use model::{PackageEntry, PackageRecord, Permission};
-pub use state::{LogState, Release, ReleaseState, ValidationError};
+pub use model::{
+    PackageDeprecation, PackageEntry, PackageMetadata, PackageRecord, Permission, Role,
+};
+pub use state::{LogState, Release, ReleaseState, Unyanked, ValidationError};
 
 /// The currently supported package protocol version.
 pub const PACKAGE_RECORD_VERSION: u32 = 0;
@@ -83,6 +85,41 @@ impl TryFrom<protobuf::PackageEntry> for model::PackageEntry {
                     .map(TryInto::try_into)
                     .collect::<Result<_, _>>()?,
             },
+            Contents::GrantVersions(grant_versions) => model::PackageEntry::GrantVersions {
+                key: grant_versions.key.parse()?,
+                permissions: grant_versions
+                    .permissions
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+                versions: grant_versions.versions.parse()?,
+            },
+            Contents::RevokeVersions(revoke_versions) => model::PackageEntry::RevokeVersions {
+                key_id: revoke_versions.key_id.into(),
+                permissions: revoke_versions
+                    .permissions
+                    .into_iter()
+                    .map(TryInto::try_into)
+                    .collect::<Result<_, _>>()?,
+                versions: revoke_versions.versions.parse()?,
+            },
+            Contents::GrantRole(grant_role) => model::PackageEntry::GrantRole {
+                key: grant_role.key.parse()?,
+                role: grant_role.role.try_into()?,
+            },
+            Contents::RevokeRole(revoke_role) => model::PackageEntry::RevokeRole {
+                key_id: revoke_role.key_id.into(),
+                role: revoke_role.role.try_into()?,
+            },
+            Contents::RotateKey(rotate_key) => model::PackageEntry::RotateKey {
+                key_id: rotate_key.key_id.into(),
+                key: rotate_key.key.parse()?,
+            },
+            Contents::SignatureThreshold(signature_threshold) => {
+                model::PackageEntry::SignatureThreshold {
+                    threshold: signature_threshold.threshold,
+                }
+            }
             Contents::Release(release) => model::PackageEntry::Release {
                 version: release
                     .version
@@ -93,6 +130,36 @@ impl TryFrom<protobuf::PackageEntry> for model::PackageEntry {
             Contents::Yank(yank) => model::PackageEntry::Yank {
                 version: yank.version.parse()?,
             },
+            Contents::Unyank(unyank) => model::PackageEntry::Unyank {
+                version: unyank.version.parse()?,
+            },
+            Contents::Metadata(metadata) => model::PackageEntry::Metadata {
+                metadata: model::PackageMetadata {
+                    description: metadata.description,
+                    license: metadata.license,
+                    repository: metadata.repository,
+                    keywords: metadata.keywords,
+                },
+            },
+            Contents::Deprecate(deprecate) => model::PackageEntry::Deprecate {
+                deprecation: model::PackageDeprecation {
+                    versions: deprecate
+                        .versions
+                        .map(|versions| versions.parse())
+                        .transpose()?,
+                    message: deprecate.message,
+                    successor: deprecate
+                        .successor
+                        .map(|successor| successor.parse())
+                        .transpose()?,
+                },
+            },
+            Contents::Undeprecate(undeprecate) => model::PackageEntry::Undeprecate {
+                versions: undeprecate
+                    .versions
+                    .map(|versions| versions.parse())
+                    .transpose()?,
+            },
         };
         Ok(output)
     }
@@ -124,6 +191,25 @@ struct PermissionParseError {
     value: i32,
 }
 
+impl TryFrom<i32> for model::Role {
+    type Error = Error;
+
+    fn try_from(role: i32) -> Result<Self, Self::Error> {
+        let proto_role =
+            protobuf::PackageRole::try_from(role).map_err(|_| RoleParseError { value: role })?;
+        match proto_role {
+            protobuf::PackageRole::Unspecified => Err(Error::new(RoleParseError { value: role })),
+            protobuf::PackageRole::Admin => Ok(model::Role::Admin),
+        }
+    }
+}
+
+#[derive(Error, Debug)]
+#[error("the value {value} could not be parsed as a role")]
+struct RoleParseError {
+    value: i32,
+}
+
 // Serialization
 
 impl Signable for model::PackageRecord {
@@ -172,6 +258,47 @@ impl<'a> From<&'a model::PackageEntry> for protobuf::PackageEntry {
                 key_id: key_id.to_string(),
                 permissions: permissions.iter().map(Into::into).collect(),
             }),
+            model::PackageEntry::GrantVersions {
+                key,
+                permissions,
+                versions,
+            } => Contents::GrantVersions(protobuf::PackageGrantVersions {
+                key: key.to_string(),
+                permissions: permissions.iter().map(Into::into).collect(),
+                versions: versions.to_string(),
+            }),
+            model::PackageEntry::RevokeVersions {
+                key_id,
+                permissions,
+                versions,
+            } => Contents::RevokeVersions(protobuf::PackageRevokeVersions {
+                key_id: key_id.to_string(),
+                permissions: permissions.iter().map(Into::into).collect(),
+                versions: versions.to_string(),
+            }),
+            model::PackageEntry::GrantRole { key, role } => {
+                Contents::GrantRole(protobuf::PackageGrantRole {
+                    key: key.to_string(),
+                    role: role.into(),
+                })
+            }
+            model::PackageEntry::RevokeRole { key_id, role } => {
+                Contents::RevokeRole(protobuf::PackageRevokeRole {
+                    key_id: key_id.to_string(),
+                    role: role.into(),
+                })
+            }
+            model::PackageEntry::RotateKey { key_id, key } => {
+                Contents::RotateKey(protobuf::PackageRotateKey {
+                    key_id: key_id.to_string(),
+                    key: key.to_string(),
+                })
+            }
+            model::PackageEntry::SignatureThreshold { threshold } => {
+                Contents::SignatureThreshold(protobuf::PackageSignatureThreshold {
+                    threshold: *threshold,
+                })
+            }
             model::PackageEntry::Release { version, content } => {
                 Contents::Release(protobuf::PackageRelease {
                     version: version.to_string(),
@@ -181,6 +308,29 @@ impl<'a> From<&'a model::PackageEntry> for protobuf::PackageEntry {
             model::PackageEntry::Yank { version } => Contents::Yank(protobuf::PackageYank {
                 version: version.to_string(),
             }),
+            model::PackageEntry::Unyank { version } => Contents::Unyank(protobuf::PackageUnyank {
+                version: version.to_string(),
+            }),
+            model::PackageEntry::Metadata { metadata } => {
+                Contents::Metadata(protobuf::PackageMetadata {
+                    description: metadata.description.clone(),
+                    license: metadata.license.clone(),
+                    repository: metadata.repository.clone(),
+                    keywords: metadata.keywords.clone(),
+                })
+            }
+            model::PackageEntry::Deprecate { deprecation } => {
+                Contents::Deprecate(protobuf::PackageDeprecate {
+                    versions: deprecation.versions.as_ref().map(ToString::to_string),
+                    message: deprecation.message.clone(),
+                    successor: deprecation.successor.as_ref().map(ToString::to_string),
+                })
+            }
+            model::PackageEntry::Undeprecate { versions } => {
+                Contents::Undeprecate(protobuf::PackageUndeprecate {
+                    versions: versions.as_ref().map(ToString::to_string),
+                })
+            }
         };
         let contents = Some(contents);
         protobuf::PackageEntry { contents }
@@ -197,6 +347,15 @@ impl<'a> From<&'a model::Permission> for i32 {
     }
 }
 
+impl<'a> From<&'a model::Role> for i32 {
+    fn from(role: &'a model::Role) -> Self {
+        let proto_role = match role {
+            model::Role::Admin => protobuf::PackageRole::Admin,
+        };
+        proto_role.into()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -207,8 +366,8 @@ mod tests {
 
     use warg_crypto::hash::HashAlgorithm;
 
-    use crate::ProtoEnvelope;
-    use warg_crypto::signing::generate_p256_pair;
+    use crate::{Cosignature, ProtoEnvelope};
+    use warg_crypto::signing::{generate_ed25519_pair, generate_p256_pair};
 
     #[test]
     fn test_envelope_roundtrip() {
@@ -254,4 +413,69 @@ mod tests {
 
         assert_eq!(first_envelope, second_envelope);
     }
+
+    #[test]
+    fn test_ed25519_envelope_roundtrip() {
+        let (alice_pub, alice_priv) = generate_ed25519_pair();
+
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::PackageEntry::Init {
+                hash_algorithm: HashAlgorithm::Sha256,
+                key: alice_pub.clone(),
+            }],
+        };
+
+        let first_envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        assert_eq!(first_envelope.key_id(), &alice_pub.fingerprint());
+
+        let bytes = first_envelope.to_protobuf();
+        let second_envelope: ProtoEnvelope<model::PackageRecord> =
+            ProtoEnvelope::from_protobuf(&bytes).unwrap();
+
+        assert_eq!(first_envelope, second_envelope);
+        model::PackageRecord::verify(
+            &alice_pub,
+            second_envelope.content_bytes(),
+            second_envelope.signature(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_cosigned_envelope_roundtrip() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let (bob_pub, bob_priv) = generate_ed25519_pair();
+
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::PackageEntry::Init {
+                hash_algorithm: HashAlgorithm::Sha256,
+                key: alice_pub,
+            }],
+        };
+
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let cosignature =
+            Cosignature::sign::<model::PackageRecord>(&bob_priv, envelope.content_bytes()).unwrap();
+        let first_envelope = envelope.with_cosignatures([cosignature]);
+
+        let bytes = first_envelope.to_protobuf();
+        let second_envelope: ProtoEnvelope<model::PackageRecord> =
+            ProtoEnvelope::from_protobuf(&bytes).unwrap();
+
+        assert_eq!(first_envelope, second_envelope);
+        let cosignature = &second_envelope.cosignatures()[0];
+        assert_eq!(cosignature.key_id, bob_pub.fingerprint());
+        model::PackageRecord::verify(
+            &bob_pub,
+            second_envelope.content_bytes(),
+            &cosignature.signature,
+        )
+        .unwrap();
+    }
 }
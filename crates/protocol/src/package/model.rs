@@ -1,7 +1,7 @@
-use crate::registry::RecordId;
+use crate::registry::{PackageName, RecordId};
 use core::fmt;
 use indexmap::IndexSet;
-use semver::Version;
+use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use std::{str::FromStr, time::SystemTime};
 use warg_crypto::hash::{AnyHash, HashAlgorithm};
@@ -66,6 +66,35 @@ impl FromStr for Permission {
     }
 }
 
+/// Each role represents authority over the package log itself rather than
+/// over its releases
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum Role {
+    /// May grant and revoke permissions and roles.
+    Admin,
+}
+
+impl fmt::Display for Role {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Role::Admin => write!(f, "admin"),
+        }
+    }
+}
+
+impl FromStr for Role {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "admin" => Ok(Role::Admin),
+            _ => Err(format!("invalid role {s:?}")),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum PackageEntry {
@@ -78,32 +107,101 @@ pub enum PackageEntry {
         key: signing::PublicKey,
     },
     /// Grant the specified key a permission.
-    /// The author of this entry must have the permission.
+    /// The author of this entry must have the permission or the admin role.
     GrantFlat {
         key: signing::PublicKey,
         permissions: Vec<Permission>,
     },
     /// Remove a permission from a key.
-    /// The author of this entry must have the permission.
+    /// The author of this entry must have the permission or the admin role.
     RevokeFlat {
         key_id: signing::KeyID,
         permissions: Vec<Permission>,
     },
+    /// Grant the specified key a permission for the versions matching a
+    /// version requirement.
+    /// The author of this entry must have the permission or the admin role.
+    GrantVersions {
+        key: signing::PublicKey,
+        permissions: Vec<Permission>,
+        versions: VersionReq,
+    },
+    /// Remove a permission previously granted for a version requirement.
+    /// The author of this entry must have the permission or the admin role.
+    RevokeVersions {
+        key_id: signing::KeyID,
+        permissions: Vec<Permission>,
+        versions: VersionReq,
+    },
+    /// Grant the specified key a role.
+    /// The author of this entry must have the admin role.
+    GrantRole { key: signing::PublicKey, role: Role },
+    /// Remove a role from a key.
+    /// The author of this entry must have the admin role.
+    RevokeRole { key_id: signing::KeyID, role: Role },
+    /// Transfer all permissions of a key to a new key, retiring the old key.
+    /// The author of this entry must have every permission of the old key.
+    RotateKey {
+        key_id: signing::KeyID,
+        key: signing::PublicKey,
+    },
+    /// Set the number of signatures from keys with permissions that are
+    /// required on each subsequent record of the package log.
+    /// The author of this entry must have every permission.
+    SignatureThreshold { threshold: u32 },
     /// Release a version of a package.
     /// The version must not have been released yet.
     Release { version: Version, content: AnyHash },
     /// Yank a version of a package.
     /// The version must have been released and not yanked.
     Yank { version: Version },
+    /// Unyank a version of a package, restoring its release.
+    /// The version must have been released and yanked.
+    Unyank { version: Version },
+    /// Set the metadata of a package.
+    /// Replaces any metadata previously set for the package.
+    Metadata { metadata: PackageMetadata },
+    /// Deprecate a package or a range of its versions.
+    /// A later deprecation takes precedence over earlier ones.
+    /// The author of this entry must have the yank permission.
+    Deprecate { deprecation: PackageDeprecation },
+    /// Retract the deprecations of a package or a range of its versions.
+    /// A deprecation with the same version requirement must exist.
+    /// The author of this entry must have the yank permission.
+    Undeprecate { versions: Option<VersionReq> },
 }
 
 impl PackageEntry {
     /// Check permission is required to submit this entry
     pub fn required_permission(&self) -> Option<Permission> {
         match self {
-            Self::Init { .. } | Self::GrantFlat { .. } | Self::RevokeFlat { .. } => None,
-            Self::Release { .. } => Some(Permission::Release),
-            Self::Yank { .. } => Some(Permission::Yank),
+            Self::Init { .. }
+            | Self::GrantFlat { .. }
+            | Self::RevokeFlat { .. }
+            | Self::GrantVersions { .. }
+            | Self::RevokeVersions { .. }
+            | Self::GrantRole { .. }
+            | Self::RevokeRole { .. }
+            | Self::RotateKey { .. }
+            | Self::SignatureThreshold { .. } => None,
+            Self::Release { .. } | Self::Metadata { .. } => Some(Permission::Release),
+            Self::Yank { .. } | Self::Unyank { .. } => Some(Permission::Yank),
+            // Deprecating discourages the use of released versions, which is
+            // a lighter form of yanking them, so it requires the yank
+            // permission rather than a permission of its own.
+            Self::Deprecate { .. } | Self::Undeprecate { .. } => Some(Permission::Yank),
+        }
+    }
+
+    /// Gets the version the entry applies to.
+    ///
+    /// Returns `None` if the entry does not apply to a single version.
+    pub fn version(&self) -> Option<&Version> {
+        match self {
+            Self::Release { version, .. } | Self::Yank { version } | Self::Unyank { version } => {
+                Some(version)
+            }
+            _ => None,
         }
     }
 
@@ -117,3 +215,47 @@ impl PackageEntry {
         }
     }
 }
+
+/// Descriptive metadata about a package.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageMetadata {
+    /// A short description of the package.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// The SPDX license expression of the package.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub license: Option<String>,
+    /// The URL of the package's source repository.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+    /// Keywords used to categorize the package.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+}
+
+/// A deprecation of a package or a range of its versions.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageDeprecation {
+    /// The versions that are deprecated.
+    ///
+    /// If `None`, every version of the package is deprecated.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub versions: Option<VersionReq>,
+    /// The message explaining the deprecation.
+    pub message: String,
+    /// The package that should be used instead, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub successor: Option<PackageName>,
+}
+
+impl PackageDeprecation {
+    /// Determines if the deprecation applies to the given version.
+    pub fn applies_to(&self, version: &Version) -> bool {
+        self.versions
+            .as_ref()
+            .map(|req| req.matches(version))
+            .unwrap_or(true)
+    }
+}
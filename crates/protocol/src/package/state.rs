@@ -6,7 +6,7 @@ use semver::{Version, VersionReq};
 use serde::{Deserialize, Serialize};
 use std::time::SystemTime;
 use thiserror::Error;
-use warg_crypto::hash::{AnyHash, HashAlgorithm, Sha256};
+use warg_crypto::hash::{AnyHash, HashAlgorithm};
 use warg_crypto::{signing, Signable};
 
 #[derive(Error, Debug)]
@@ -29,12 +29,61 @@ pub enum ValidationError {
         needed_permission: model::Permission,
     },
 
+    #[error("the key with ID {key_id} did not have required permission {needed_permission} for version {version}")]
+    UnauthorizedVersionAction {
+        key_id: signing::KeyID,
+        needed_permission: model::Permission,
+        version: Version,
+    },
+
+    #[error("the key with ID {key_id} did not have required role {needed_role}")]
+    UnauthorizedRoleAction {
+        key_id: signing::KeyID,
+        needed_role: model::Role,
+    },
+
     #[error("attempted to remove permission {permission} from key {key_id} which did not have it")]
     PermissionNotFoundToRevoke {
         permission: model::Permission,
         key_id: signing::KeyID,
     },
 
+    #[error("attempted to remove permission {permission} for versions `{versions}` from key {key_id} which did not have it")]
+    VersionPermissionNotFoundToRevoke {
+        permission: model::Permission,
+        versions: VersionReq,
+        key_id: signing::KeyID,
+    },
+
+    #[error("attempted to remove role {role} from key {key_id} which did not have it")]
+    RoleNotFoundToRevoke {
+        role: model::Role,
+        key_id: signing::KeyID,
+    },
+
+    #[error("attempted to rotate key {key_id} which has no permissions")]
+    RotationOfKeyWithoutPermissions { key_id: signing::KeyID },
+
+    #[error("attempted to rotate to key {key_id} which already has permissions")]
+    RotationToKeyWithPermissions { key_id: signing::KeyID },
+
+    #[error("the key with ID {key_id} was rotated and replaced by key {replaced_by}")]
+    KeyRotated {
+        key_id: signing::KeyID,
+        replaced_by: signing::KeyID,
+    },
+
+    #[error("signature threshold {threshold} must be between 1 and the {maintainers} key(s) with permissions")]
+    InvalidSignatureThreshold { threshold: u32, maintainers: usize },
+
+    #[error("the record was signed more than once by the key with ID {key_id}")]
+    DuplicateSignature { key_id: signing::KeyID },
+
+    #[error(
+        "the record requires signatures from {threshold} key(s) with permissions but had {found}"
+    )]
+    InsufficientSignatures { threshold: u32, found: usize },
+
     #[error("an entry attempted to release version {version} which is already released")]
     ReleaseOfReleased { version: Version },
 
@@ -44,6 +93,26 @@ pub enum ValidationError {
     #[error("an entry attempted to yank version {version} which is already yanked")]
     YankOfYanked { version: Version },
 
+    #[error("an entry attempted to unyank version {version} which had not yet been released")]
+    UnyankOfUnreleased { version: Version },
+
+    #[error("an entry attempted to unyank version {version} which is not yanked")]
+    UnyankOfUnyanked { version: Version },
+
+    #[error(
+        "an entry attempted to unyank version {version} whose content was not recorded when it was yanked"
+    )]
+    UnyankOfUnknownContent { version: Version },
+
+    #[error("package metadata is invalid: {message}")]
+    InvalidMetadata { message: String },
+
+    #[error("a deprecation must have a message")]
+    DeprecationWithoutMessage,
+
+    #[error("an entry attempted to retract a deprecation of {versions} that does not exist")]
+    UndeprecateOfUndeprecated { versions: String },
+
     #[error("unable to verify signature")]
     SignatureError(#[from] signing::SignatureError),
 
@@ -53,6 +122,12 @@ pub enum ValidationError {
         expected: HashAlgorithm,
     },
 
+    #[error("release content digest uses {found} algorithm but {expected} was expected")]
+    IncorrectContentHashAlgorithm {
+        found: HashAlgorithm,
+        expected: HashAlgorithm,
+    },
+
     #[error("previous record hash does not match")]
     RecordHashDoesNotMatch,
 
@@ -77,6 +152,9 @@ pub enum ReleaseState {
     Released {
         /// The content digest associated with the release.
         content: AnyHash,
+        /// The unyank that restored the release, if it was yanked.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        unyanked: Option<Unyanked>,
     },
     /// The release has been yanked.
     Yanked {
@@ -85,9 +163,26 @@ pub enum ReleaseState {
         /// The timestamp of the yank.
         #[serde(with = "crate::timestamp")]
         timestamp: SystemTime,
+        /// The content digest of the release, restored if the release is unyanked.
+        ///
+        /// This is `None` for releases yanked in state stored before unyanking
+        /// was supported; such releases cannot be unyanked.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content: Option<AnyHash>,
     },
 }
 
+/// Represents information about the unyank of a release.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Unyanked {
+    /// The key id that unyanked the release.
+    pub by: signing::KeyID,
+    /// The timestamp of the unyank.
+    #[serde(with = "crate::timestamp")]
+    pub timestamp: SystemTime,
+}
+
 /// Represents information about a release.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -116,7 +211,7 @@ impl Release {
     /// Returns `None` if the release has been yanked.
     pub fn content(&self) -> Option<&AnyHash> {
         match &self.state {
-            ReleaseState::Released { content } => Some(content),
+            ReleaseState::Released { content, .. } => Some(content),
             ReleaseState::Yanked { .. } => None,
         }
     }
@@ -149,12 +244,38 @@ pub struct LogState {
     /// The permissions of each key.
     #[serde(skip_serializing_if = "IndexMap::is_empty")]
     permissions: IndexMap<signing::KeyID, IndexSet<model::Permission>>,
+    /// The permissions of each key limited to the versions matching a version requirement.
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    version_permissions:
+        IndexMap<signing::KeyID, IndexMap<VersionReq, IndexSet<model::Permission>>>,
+    /// The roles explicitly granted to each key.
+    ///
+    /// The admin role of the key that initialized the package log is not
+    /// stored here, as it is derived from the init key (see `init_admin`).
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    roles: IndexMap<signing::KeyID, IndexSet<model::Role>>,
+    /// Whether the admin role derived from the init key has been revoked.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    init_admin_revoked: bool,
     /// The releases in the package log.
     #[serde(skip_serializing_if = "IndexMap::is_empty")]
     releases: IndexMap<Version, Release>,
     /// The keys known to the state.
     #[serde(skip_serializing_if = "IndexMap::is_empty")]
     keys: IndexMap<signing::KeyID, signing::PublicKey>,
+    /// The metadata of the package, if any has been set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    metadata: Option<model::PackageMetadata>,
+    /// The deprecations of the package, in package log order.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    deprecations: Vec<model::PackageDeprecation>,
+    /// The keys that have been rotated, mapped to the keys that replaced them.
+    #[serde(skip_serializing_if = "IndexMap::is_empty")]
+    rotated_keys: IndexMap<signing::KeyID, signing::KeyID>,
+    /// The number of signatures required on each record.
+    /// This is `None` until a signature threshold is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    signature_threshold: Option<u32>,
 }
 
 impl LogState {
@@ -163,6 +284,13 @@ impl LogState {
         Self::default()
     }
 
+    /// Gets the hash algorithm used by the package log.
+    ///
+    /// Returns `None` if the init record has not been validated yet.
+    pub fn algorithm(&self) -> Option<HashAlgorithm> {
+        self.algorithm
+    }
+
     /// Gets the current head of the state.
     ///
     /// Returns `None` if no records have been validated yet.
@@ -211,6 +339,32 @@ impl LogState {
             .max_by(|a, b| a.version.cmp(&b.version))
     }
 
+    /// Gets the metadata of the package.
+    ///
+    /// Returns `None` if no metadata has been set.
+    pub fn metadata(&self) -> Option<&model::PackageMetadata> {
+        self.metadata.as_ref()
+    }
+
+    /// Gets the deprecations of the package.
+    ///
+    /// The deprecations are returned in package log order.
+    pub fn deprecations(&self) -> impl Iterator<Item = &model::PackageDeprecation> {
+        self.deprecations.iter()
+    }
+
+    /// Gets the deprecation that applies to the given version.
+    ///
+    /// If multiple deprecations apply, the most recent one is returned.
+    ///
+    /// Returns `None` if the version is not deprecated.
+    pub fn deprecation(&self, version: &Version) -> Option<&model::PackageDeprecation> {
+        self.deprecations
+            .iter()
+            .rev()
+            .find(|deprecation| deprecation.applies_to(version))
+    }
+
     /// Gets the public key of the given key id.
     ///
     /// Returns `None` if the key id is not recognized.
@@ -218,6 +372,19 @@ impl LogState {
         self.keys.get(key_id)
     }
 
+    /// Gets the key that replaced the given key id by rotation.
+    ///
+    /// Returns `None` if the key id has not been rotated.
+    pub fn rotated_key(&self, key_id: &signing::KeyID) -> Option<&signing::KeyID> {
+        self.rotated_keys.get(key_id)
+    }
+
+    /// Gets the number of signatures from keys with permissions that are
+    /// required on each record.
+    pub fn signature_threshold(&self) -> u32 {
+        self.signature_threshold.unwrap_or(1)
+    }
+
     /// Gets the key permissions.
     ///
     /// Returns `None` if the key id is not recognized.
@@ -225,6 +392,50 @@ impl LogState {
         self.permissions.get(key_id)
     }
 
+    /// Gets the key permissions limited to versions, keyed by version requirement.
+    ///
+    /// Returns `None` if the key has never been granted version-limited permissions.
+    pub fn key_version_permissions(
+        &self,
+        key_id: &signing::KeyID,
+    ) -> Option<&IndexMap<VersionReq, IndexSet<model::Permission>>> {
+        self.version_permissions.get(key_id)
+    }
+
+    /// Gets the key roles.
+    ///
+    /// This includes the admin role of the key that initialized the package
+    /// log (or the key it was rotated to), unless it has been revoked.
+    pub fn key_roles(&self, key_id: &signing::KeyID) -> IndexSet<model::Role> {
+        let mut roles = self.roles.get(key_id).cloned().unwrap_or_default();
+        if self.init_admin() == Some(key_id) {
+            roles.insert(model::Role::Admin);
+        }
+        roles
+    }
+
+    /// Gets the key that holds the admin role of the package log's init key.
+    ///
+    /// The init key is always the first known key, and the role follows it
+    /// through key rotations. This is derived rather than stored so that the
+    /// state of logs validated before roles existed remains the same.
+    fn init_admin(&self) -> Option<&signing::KeyID> {
+        if self.init_admin_revoked {
+            return None;
+        }
+
+        let (mut key_id, _) = self.keys.first()?;
+        while let Some(replaced_by) = self.rotated_keys.get(key_id) {
+            key_id = replaced_by;
+        }
+        Some(key_id)
+    }
+
+    fn has_role(&self, key_id: &signing::KeyID, role: model::Role) -> bool {
+        (role == model::Role::Admin && self.init_admin() == Some(key_id))
+            || self.roles.get(key_id).is_some_and(|r| r.contains(&role))
+    }
+
     fn initialized(&self) -> bool {
         // The package log is initialized if the hash algorithm is set
         self.algorithm.is_some()
@@ -235,7 +446,15 @@ impl LogState {
         envelope: &ProtoEnvelope<model::PackageRecord>,
     ) -> Result<(), ValidationError> {
         let record = envelope.as_ref();
-        let record_id = RecordId::package_record::<Sha256>(envelope);
+        let record_id = RecordId::package_record_for_log(envelope);
+
+        // Validate the envelope was not signed by a rotated key
+        if let Some(replaced_by) = self.rotated_keys.get(envelope.key_id()) {
+            return Err(ValidationError::KeyRotated {
+                key_id: envelope.key_id().clone(),
+                replaced_by: replaced_by.clone(),
+            });
+        }
 
         // Validate previous hash
         self.validate_record_hash(record)?;
@@ -246,6 +465,14 @@ impl LogState {
         // Validate timestamp
         self.validate_record_timestamp(record)?;
 
+        // The signature threshold in effect before the record applies to it,
+        // as do the keys that had permissions at that point
+        let threshold = self.signature_threshold;
+        let maintainers = match threshold {
+            Some(_) => self.record_maintainers(&record.entries),
+            None => IndexSet::new(),
+        };
+
         // Validate entries
         self.validate_record_entries(
             &record_id,
@@ -254,6 +481,18 @@ impl LogState {
             &record.entries,
         )?;
 
+        // The keys left with permissions must still be able to meet the
+        // signature threshold, such as after permissions are revoked
+        if let Some(threshold) = self.signature_threshold {
+            let maintainers = self.maintainer_count();
+            if threshold as usize > maintainers {
+                return Err(ValidationError::InvalidSignatureThreshold {
+                    threshold,
+                    maintainers,
+                });
+            }
+        }
+
         // At this point the digest algorithm must be set via an init entry
         let _algorithm = self
             .algorithm
@@ -269,6 +508,42 @@ impl LogState {
         // Validate the envelope signature
         model::PackageRecord::verify(key, envelope.content_bytes(), envelope.signature())?;
 
+        // Validate the envelope cosignatures
+        let mut signers = IndexSet::from([envelope.key_id()]);
+        for cosignature in envelope.cosignatures() {
+            if let Some(replaced_by) = self.rotated_keys.get(&cosignature.key_id) {
+                return Err(ValidationError::KeyRotated {
+                    key_id: cosignature.key_id.clone(),
+                    replaced_by: replaced_by.clone(),
+                });
+            }
+
+            let key = self.keys.get(&cosignature.key_id).ok_or_else(|| {
+                ValidationError::KeyIDNotRecognized {
+                    key_id: cosignature.key_id.clone(),
+                }
+            })?;
+
+            if !signers.insert(&cosignature.key_id) {
+                return Err(ValidationError::DuplicateSignature {
+                    key_id: cosignature.key_id.clone(),
+                });
+            }
+
+            model::PackageRecord::verify(key, envelope.content_bytes(), &cosignature.signature)?;
+        }
+
+        // Validate the signature threshold
+        if let Some(threshold) = threshold {
+            let found = signers
+                .iter()
+                .filter(|key_id| maintainers.contains(**key_id))
+                .count();
+            if found < threshold as usize {
+                return Err(ValidationError::InsufficientSignatures { threshold, found });
+            }
+        }
+
         // Update the state head
         self.head = Some(Head {
             digest: record_id,
@@ -335,7 +610,12 @@ impl LogState {
     ) -> Result<(), ValidationError> {
         for entry in entries {
             if let Some(permission) = entry.required_permission() {
-                self.check_key_permissions(signer_key_id, &[permission])?;
+                match entry.version() {
+                    Some(version) => {
+                        self.check_key_version_permission(signer_key_id, permission, version)?
+                    }
+                    None => self.check_key_permissions(signer_key_id, &[permission])?,
+                }
             }
 
             // Process an init entry specially
@@ -362,6 +642,35 @@ impl LogState {
                     key_id,
                     permissions,
                 } => self.validate_revoke_entry(signer_key_id, key_id, permissions)?,
+                model::PackageEntry::GrantVersions {
+                    key,
+                    permissions,
+                    versions,
+                } => {
+                    self.validate_grant_versions_entry(signer_key_id, key, permissions, versions)?
+                }
+                model::PackageEntry::RevokeVersions {
+                    key_id,
+                    permissions,
+                    versions,
+                } => self.validate_revoke_versions_entry(
+                    signer_key_id,
+                    key_id,
+                    permissions,
+                    versions,
+                )?,
+                model::PackageEntry::GrantRole { key, role } => {
+                    self.validate_grant_role_entry(signer_key_id, key, *role)?
+                }
+                model::PackageEntry::RevokeRole { key_id, role } => {
+                    self.validate_revoke_role_entry(signer_key_id, key_id, *role)?
+                }
+                model::PackageEntry::RotateKey { key_id, key } => {
+                    self.validate_rotate_key_entry(signer_key_id, key_id, key)?
+                }
+                model::PackageEntry::SignatureThreshold { threshold } => {
+                    self.validate_signature_threshold_entry(signer_key_id, *threshold)?
+                }
                 model::PackageEntry::Release { version, content } => self.validate_release_entry(
                     record_id,
                     signer_key_id,
@@ -372,6 +681,18 @@ impl LogState {
                 model::PackageEntry::Yank { version } => {
                     self.validate_yank_entry(signer_key_id, timestamp, version)?
                 }
+                model::PackageEntry::Unyank { version } => {
+                    self.validate_unyank_entry(signer_key_id, timestamp, version)?
+                }
+                model::PackageEntry::Metadata { metadata } => {
+                    self.validate_metadata_entry(metadata)?
+                }
+                model::PackageEntry::Deprecate { deprecation } => {
+                    self.validate_deprecate_entry(deprecation)?
+                }
+                model::PackageEntry::Undeprecate { versions } => {
+                    self.validate_undeprecate_entry(versions.as_ref())?
+                }
             }
         }
 
@@ -395,6 +716,7 @@ impl LogState {
         }
 
         assert!(self.permissions.is_empty());
+        assert!(self.roles.is_empty());
         assert!(self.releases.is_empty());
         assert!(self.keys.is_empty());
 
@@ -415,7 +737,7 @@ impl LogState {
         permissions: &[model::Permission],
     ) -> Result<(), ValidationError> {
         // Check that the current key has the permission they're trying to grant
-        self.check_key_permissions(signer_key_id, permissions)?;
+        self.check_grant_authority(signer_key_id, permissions)?;
 
         let grant_key_id = key.fingerprint();
         self.keys.insert(grant_key_id.clone(), key.clone());
@@ -434,7 +756,7 @@ impl LogState {
         permissions: &[model::Permission],
     ) -> Result<(), ValidationError> {
         // Check that the current key has the permission they're trying to revoke
-        self.check_key_permissions(signer_key_id, permissions)?;
+        self.check_grant_authority(signer_key_id, permissions)?;
 
         for permission in permissions {
             if !self
@@ -452,6 +774,241 @@ impl LogState {
         Ok(())
     }
 
+    fn validate_grant_versions_entry(
+        &mut self,
+        signer_key_id: &signing::KeyID,
+        key: &signing::PublicKey,
+        permissions: &[model::Permission],
+        versions: &VersionReq,
+    ) -> Result<(), ValidationError> {
+        // Check that the current key has the permission they're trying to grant
+        self.check_grant_authority(signer_key_id, permissions)?;
+
+        let grant_key_id = key.fingerprint();
+        self.keys.insert(grant_key_id.clone(), key.clone());
+        self.version_permissions
+            .entry(grant_key_id)
+            .or_default()
+            .entry(versions.clone())
+            .or_default()
+            .extend(permissions);
+
+        Ok(())
+    }
+
+    fn validate_revoke_versions_entry(
+        &mut self,
+        signer_key_id: &signing::KeyID,
+        key_id: &signing::KeyID,
+        permissions: &[model::Permission],
+        versions: &VersionReq,
+    ) -> Result<(), ValidationError> {
+        // Check that the current key has the permission they're trying to revoke
+        self.check_grant_authority(signer_key_id, permissions)?;
+
+        for permission in permissions {
+            if !self
+                .version_permissions
+                .get_mut(key_id)
+                .and_then(|grants| grants.get_mut(versions))
+                .map(|set| set.swap_remove(permission))
+                .unwrap_or(false)
+            {
+                return Err(ValidationError::VersionPermissionNotFoundToRevoke {
+                    permission: *permission,
+                    versions: versions.clone(),
+                    key_id: key_id.clone(),
+                });
+            }
+        }
+
+        // Remove the grant entirely once it has no permissions left
+        if let Some(grants) = self.version_permissions.get_mut(key_id) {
+            if grants.get(versions).is_some_and(IndexSet::is_empty) {
+                grants.shift_remove(versions);
+            }
+
+            if grants.is_empty() {
+                self.version_permissions.shift_remove(key_id);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_grant_role_entry(
+        &mut self,
+        signer_key_id: &signing::KeyID,
+        key: &signing::PublicKey,
+        role: model::Role,
+    ) -> Result<(), ValidationError> {
+        self.check_key_role(signer_key_id, model::Role::Admin)?;
+
+        let grant_key_id = key.fingerprint();
+        self.keys.insert(grant_key_id.clone(), key.clone());
+        self.roles.entry(grant_key_id).or_default().insert(role);
+
+        Ok(())
+    }
+
+    fn validate_revoke_role_entry(
+        &mut self,
+        signer_key_id: &signing::KeyID,
+        key_id: &signing::KeyID,
+        role: model::Role,
+    ) -> Result<(), ValidationError> {
+        self.check_key_role(signer_key_id, model::Role::Admin)?;
+
+        let derived = role == model::Role::Admin && self.init_admin() == Some(key_id);
+        if derived {
+            self.init_admin_revoked = true;
+        }
+
+        if !self
+            .roles
+            .get_mut(key_id)
+            .map(|set| set.swap_remove(&role))
+            .unwrap_or(false)
+            && !derived
+        {
+            return Err(ValidationError::RoleNotFoundToRevoke {
+                role,
+                key_id: key_id.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn validate_rotate_key_entry(
+        &mut self,
+        signer_key_id: &signing::KeyID,
+        key_id: &signing::KeyID,
+        key: &signing::PublicKey,
+    ) -> Result<(), ValidationError> {
+        let permissions = self.permissions.get(key_id).cloned().unwrap_or_default();
+        let version_permissions = self
+            .version_permissions
+            .get(key_id)
+            .cloned()
+            .unwrap_or_default();
+        let roles = self.key_roles(key_id);
+        if permissions.is_empty() && version_permissions.is_empty() && roles.is_empty() {
+            return Err(ValidationError::RotationOfKeyWithoutPermissions {
+                key_id: key_id.clone(),
+            });
+        }
+
+        // A key may always rotate itself; otherwise, check that the current key
+        // has every permission and role being transferred
+        if signer_key_id != key_id {
+            for role in &roles {
+                self.check_key_role(signer_key_id, *role)?;
+            }
+
+            let transferred = permissions
+                .iter()
+                .chain(version_permissions.values().flatten())
+                .copied()
+                .collect::<Vec<_>>();
+            self.check_grant_authority(signer_key_id, &transferred)?;
+        }
+
+        let new_key_id = key.fingerprint();
+        if let Some(replaced_by) = self.rotated_keys.get(&new_key_id) {
+            return Err(ValidationError::KeyRotated {
+                key_id: new_key_id,
+                replaced_by: replaced_by.clone(),
+            });
+        }
+
+        if self
+            .permissions
+            .get(&new_key_id)
+            .is_some_and(|permissions| !permissions.is_empty())
+            || self.version_permissions.contains_key(&new_key_id)
+            || !self.key_roles(&new_key_id).is_empty()
+        {
+            return Err(ValidationError::RotationToKeyWithPermissions { key_id: new_key_id });
+        }
+
+        if self.permissions.shift_remove(key_id).is_some() {
+            self.permissions.insert(new_key_id.clone(), permissions);
+        }
+        if self.version_permissions.shift_remove(key_id).is_some() {
+            self.version_permissions
+                .insert(new_key_id.clone(), version_permissions);
+        }
+        // A derived admin role follows the rotation without being stored
+        if let Some(roles) = self.roles.shift_remove(key_id) {
+            self.roles.insert(new_key_id.clone(), roles);
+        }
+        self.keys.insert(new_key_id.clone(), key.clone());
+        self.rotated_keys.insert(key_id.clone(), new_key_id);
+
+        Ok(())
+    }
+
+    /// Gets the keys whose signatures count towards the signature threshold
+    /// of a record with the given entries.
+    ///
+    /// Keys with permissions for all versions count for every record. Keys
+    /// with permissions limited to some versions only count for records that
+    /// solely release, yank or unyank versions they may do so for.
+    fn record_maintainers(&self, entries: &[model::PackageEntry]) -> IndexSet<signing::KeyID> {
+        let maintainers = self
+            .permissions
+            .iter()
+            .filter(|(_, permissions)| !permissions.is_empty())
+            .map(|(key_id, _)| key_id);
+
+        let version_maintainers = self.version_permissions.keys().filter(|key_id| {
+            !entries.is_empty()
+                && entries.iter().all(|entry| {
+                    match (entry.required_permission(), entry.version()) {
+                        (Some(permission), Some(version)) => self
+                            .check_key_version_permission(key_id, permission, version)
+                            .is_ok(),
+                        _ => false,
+                    }
+                })
+        });
+
+        maintainers.chain(version_maintainers).cloned().collect()
+    }
+
+    /// Gets the number of keys with permissions for all versions.
+    ///
+    /// Only these keys can sign any record, so a signature threshold may not
+    /// exceed their number.
+    fn maintainer_count(&self) -> usize {
+        self.permissions
+            .values()
+            .filter(|permissions| !permissions.is_empty())
+            .count()
+    }
+
+    fn validate_signature_threshold_entry(
+        &mut self,
+        signer_key_id: &signing::KeyID,
+        threshold: u32,
+    ) -> Result<(), ValidationError> {
+        // Check that the current key has every permission
+        self.check_key_permissions(signer_key_id, &model::Permission::all())?;
+
+        // The threshold must be attainable by the keys with permissions
+        let maintainers = self.maintainer_count();
+        if threshold == 0 || threshold as usize > maintainers {
+            return Err(ValidationError::InvalidSignatureThreshold {
+                threshold,
+                maintainers,
+            });
+        }
+
+        self.signature_threshold = Some(threshold);
+        Ok(())
+    }
+
     fn validate_release_entry(
         &mut self,
         record_id: &RecordId,
@@ -460,6 +1017,15 @@ impl LogState {
         version: &Version,
         content: &AnyHash,
     ) -> Result<(), ValidationError> {
+        // Content digests must use the hash algorithm of the log
+        let expected = self.algorithm.expect("log should be initialized");
+        if content.algorithm() != expected {
+            return Err(ValidationError::IncorrectContentHashAlgorithm {
+                found: content.algorithm(),
+                expected,
+            });
+        }
+
         match self.releases.entry(version.clone()) {
             Entry::Occupied(e) => {
                 return Err(ValidationError::ReleaseOfReleased {
@@ -475,6 +1041,7 @@ impl LogState {
                     timestamp,
                     state: ReleaseState::Released {
                         content: content.clone(),
+                        unyanked: None,
                     },
                 });
             }
@@ -490,14 +1057,15 @@ impl LogState {
         version: &Version,
     ) -> Result<(), ValidationError> {
         match self.releases.get_mut(version) {
-            Some(e) => match e.state {
+            Some(e) => match &e.state {
                 ReleaseState::Yanked { .. } => Err(ValidationError::YankOfYanked {
                     version: version.clone(),
                 }),
-                ReleaseState::Released { .. } => {
+                ReleaseState::Released { content, .. } => {
                     e.state = ReleaseState::Yanked {
                         by: signer_key_id.clone(),
                         timestamp,
+                        content: Some(content.clone()),
                     };
                     Ok(())
                 }
@@ -508,22 +1076,198 @@ impl LogState {
         }
     }
 
-    fn check_key_permissions(
-        &self,
-        key_id: &signing::KeyID,
-        permissions: &[model::Permission],
+    fn validate_unyank_entry(
+        &mut self,
+        signer_key_id: &signing::KeyID,
+        timestamp: SystemTime,
+        version: &Version,
     ) -> Result<(), ValidationError> {
-        for permission in permissions {
-            if !self
-                .permissions
-                .get(key_id)
-                .map(|p| p.contains(permission))
-                .unwrap_or(false)
-            {
-                return Err(ValidationError::UnauthorizedAction {
-                    key_id: key_id.clone(),
-                    needed_permission: *permission,
-                });
+        match self.releases.get_mut(version) {
+            Some(e) => match &e.state {
+                ReleaseState::Released { .. } => Err(ValidationError::UnyankOfUnyanked {
+                    version: version.clone(),
+                }),
+                ReleaseState::Yanked { content: None, .. } => {
+                    Err(ValidationError::UnyankOfUnknownContent {
+                        version: version.clone(),
+                    })
+                }
+                ReleaseState::Yanked {
+                    content: Some(content),
+                    ..
+                } => {
+                    e.state = ReleaseState::Released {
+                        content: content.clone(),
+                        unyanked: Some(Unyanked {
+                            by: signer_key_id.clone(),
+                            timestamp,
+                        }),
+                    };
+                    Ok(())
+                }
+            },
+            None => Err(ValidationError::UnyankOfUnreleased {
+                version: version.clone(),
+            }),
+        }
+    }
+
+    fn validate_metadata_entry(
+        &mut self,
+        metadata: &model::PackageMetadata,
+    ) -> Result<(), ValidationError> {
+        for (field, value) in [
+            ("description", &metadata.description),
+            ("license", &metadata.license),
+            ("repository", &metadata.repository),
+        ] {
+            if value.as_ref().is_some_and(|v| v.trim().is_empty()) {
+                return Err(ValidationError::InvalidMetadata {
+                    message: format!("the {field} cannot be empty"),
+                });
+            }
+        }
+
+        if let Some(license) = &metadata.license {
+            spdx::Expression::parse(license).map_err(|e| ValidationError::InvalidMetadata {
+                message: format!("the license `{license}` is not a valid SPDX expression: {e}"),
+            })?;
+        }
+
+        if let Some(repository) = &metadata.repository {
+            if !url::Url::parse(repository).is_ok_and(|url| url.has_host()) {
+                return Err(ValidationError::InvalidMetadata {
+                    message: format!("the repository `{repository}` is not a valid URL"),
+                });
+            }
+        }
+
+        let mut keywords = IndexSet::new();
+        for keyword in &metadata.keywords {
+            if keyword.trim().is_empty() {
+                return Err(ValidationError::InvalidMetadata {
+                    message: "keywords cannot be empty".to_string(),
+                });
+            }
+
+            if !keywords.insert(keyword) {
+                return Err(ValidationError::InvalidMetadata {
+                    message: format!("duplicate keyword `{keyword}`"),
+                });
+            }
+        }
+
+        self.metadata = Some(metadata.clone());
+        Ok(())
+    }
+
+    fn validate_deprecate_entry(
+        &mut self,
+        deprecation: &model::PackageDeprecation,
+    ) -> Result<(), ValidationError> {
+        if deprecation.message.trim().is_empty() {
+            return Err(ValidationError::DeprecationWithoutMessage);
+        }
+
+        self.deprecations.push(deprecation.clone());
+        Ok(())
+    }
+
+    fn validate_undeprecate_entry(
+        &mut self,
+        versions: Option<&VersionReq>,
+    ) -> Result<(), ValidationError> {
+        let len = self.deprecations.len();
+        self.deprecations
+            .retain(|deprecation| deprecation.versions.as_ref() != versions);
+
+        if self.deprecations.len() == len {
+            return Err(ValidationError::UndeprecateOfUndeprecated {
+                versions: versions
+                    .map(|req| format!("versions `{req}`"))
+                    .unwrap_or_else(|| "all versions".to_string()),
+            });
+        }
+
+        Ok(())
+    }
+
+    fn check_key_version_permission(
+        &self,
+        key_id: &signing::KeyID,
+        permission: model::Permission,
+        version: &Version,
+    ) -> Result<(), ValidationError> {
+        if self
+            .permissions
+            .get(key_id)
+            .is_some_and(|p| p.contains(&permission))
+        {
+            return Ok(());
+        }
+
+        if self.version_permissions.get(key_id).is_some_and(|grants| {
+            grants
+                .iter()
+                .any(|(versions, p)| p.contains(&permission) && versions.matches(version))
+        }) {
+            return Ok(());
+        }
+
+        Err(ValidationError::UnauthorizedVersionAction {
+            key_id: key_id.clone(),
+            needed_permission: permission,
+            version: version.clone(),
+        })
+    }
+
+    /// Checks that a key may grant or revoke the given permissions.
+    ///
+    /// Administrators may grant and revoke any permission; other keys may
+    /// only grant and revoke permissions they have themselves.
+    fn check_grant_authority(
+        &self,
+        key_id: &signing::KeyID,
+        permissions: &[model::Permission],
+    ) -> Result<(), ValidationError> {
+        if self.has_role(key_id, model::Role::Admin) {
+            return Ok(());
+        }
+
+        self.check_key_permissions(key_id, permissions)
+    }
+
+    fn check_key_role(
+        &self,
+        key_id: &signing::KeyID,
+        role: model::Role,
+    ) -> Result<(), ValidationError> {
+        if self.has_role(key_id, role) {
+            Ok(())
+        } else {
+            Err(ValidationError::UnauthorizedRoleAction {
+                key_id: key_id.clone(),
+                needed_role: role,
+            })
+        }
+    }
+
+    fn check_key_permissions(
+        &self,
+        key_id: &signing::KeyID,
+        permissions: &[model::Permission],
+    ) -> Result<(), ValidationError> {
+        for permission in permissions {
+            if !self
+                .permissions
+                .get(key_id)
+                .map(|p| p.contains(permission))
+                .unwrap_or(false)
+            {
+                return Err(ValidationError::UnauthorizedAction {
+                    key_id: key_id.clone(),
+                    needed_permission: *permission,
+                });
             }
         }
         Ok(())
@@ -542,9 +1286,10 @@ impl crate::Validator for LogState {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::Cosignature;
     use pretty_assertions::assert_eq;
     use std::time::{Duration, SystemTime};
-    use warg_crypto::hash::HashAlgorithm;
+    use warg_crypto::hash::{HashAlgorithm, Sha256, Sha512};
     use warg_crypto::signing::generate_p256_pair;
 
     #[test]
@@ -579,8 +1324,15 @@ mod tests {
                     alice_id.clone(),
                     IndexSet::from([model::Permission::Release, model::Permission::Yank]),
                 )]),
+                version_permissions: IndexMap::new(),
+                roles: IndexMap::new(),
+                init_admin_revoked: false,
                 releases: IndexMap::default(),
                 keys: IndexMap::from([(alice_id, alice_pub)]),
+                metadata: None,
+                deprecations: vec![],
+                rotated_keys: IndexMap::new(),
+                signature_threshold: None,
             }
         );
     }
@@ -641,7 +1393,8 @@ mod tests {
                 by: bob_id.clone(),
                 timestamp: timestamp1,
                 state: ReleaseState::Released {
-                    content: content.clone()
+                    content: content.clone(),
+                    unyanked: None,
                 }
             })
         );
@@ -655,7 +1408,10 @@ mod tests {
                 version: Version::new(1, 1, 0),
                 by: bob_id.clone(),
                 timestamp: timestamp1,
-                state: ReleaseState::Released { content }
+                state: ReleaseState::Released {
+                    content: content.clone(),
+                    unyanked: None,
+                }
             }]
         );
 
@@ -689,7 +1445,8 @@ mod tests {
                 timestamp: timestamp1,
                 state: ReleaseState::Yanked {
                     by: alice_id.clone(),
-                    timestamp: timestamp2
+                    timestamp: timestamp2,
+                    content: Some(content.clone()),
                 }
             }]
         );
@@ -709,6 +1466,9 @@ mod tests {
                     ),
                     (bob_id.clone(), IndexSet::default()),
                 ]),
+                version_permissions: IndexMap::new(),
+                roles: IndexMap::new(),
+                init_admin_revoked: false,
                 releases: IndexMap::from([(
                     Version::new(1, 1, 0),
                     Release {
@@ -718,11 +1478,16 @@ mod tests {
                         timestamp: timestamp1,
                         state: ReleaseState::Yanked {
                             by: alice_id.clone(),
-                            timestamp: timestamp2
+                            timestamp: timestamp2,
+                            content: Some(content),
                         }
                     }
                 )]),
                 keys: IndexMap::from([(alice_id, alice_pub), (bob_id, bob_pub),]),
+                metadata: None,
+                deprecations: vec![],
+                rotated_keys: IndexMap::new(),
+                signature_threshold: None,
             }
         );
     }
@@ -760,7 +1525,14 @@ mod tests {
                 alice_id.clone(),
                 IndexSet::from([model::Permission::Release, model::Permission::Yank]),
             )]),
+            version_permissions: IndexMap::new(),
+            roles: IndexMap::new(),
+            init_admin_revoked: false,
             keys: IndexMap::from([(alice_id, alice_pub)]),
+            metadata: None,
+            deprecations: vec![],
+            rotated_keys: IndexMap::new(),
+            signature_threshold: None,
         };
 
         assert_eq!(state, expected);
@@ -792,4 +1564,856 @@ mod tests {
             _ => panic!("expected a different error"),
         }
     }
+
+    #[test]
+    fn test_rotate_key_transfers_permissions() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let alice_id = alice_pub.fingerprint();
+        let (bob_pub, bob_priv) = generate_p256_pair();
+        let bob_id = bob_pub.fingerprint();
+
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: alice_pub.clone(),
+                },
+                model::PackageEntry::RotateKey {
+                    key_id: alice_id.clone(),
+                    key: bob_pub.clone(),
+                },
+            ],
+        };
+
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = LogState::default().validate(&envelope).unwrap();
+        assert_eq!(state.key_permissions(&alice_id), None);
+        assert_eq!(
+            state.key_permissions(&bob_id),
+            Some(&IndexSet::from(model::Permission::all()))
+        );
+        assert_eq!(state.rotated_key(&alice_id), Some(&bob_id));
+        let prev = RecordId::package_record::<Sha256>(&envelope);
+
+        // The rotated key can no longer sign records
+        let record = model::PackageRecord {
+            prev: Some(prev.clone()),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record.clone()).unwrap();
+        match state.clone().validate(&envelope).unwrap_err() {
+            ValidationError::KeyRotated {
+                key_id,
+                replaced_by,
+            } => {
+                assert_eq!(key_id, alice_id);
+                assert_eq!(replaced_by, bob_id);
+            }
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // The new key cannot rotate back to the retired key
+        let record = model::PackageRecord {
+            entries: vec![model::PackageEntry::RotateKey {
+                key_id: bob_id,
+                key: alice_pub,
+            }],
+            ..record
+        };
+        let envelope = ProtoEnvelope::signed_contents(&bob_priv, record).unwrap();
+        match state.validate(&envelope).unwrap_err() {
+            ValidationError::KeyRotated { key_id, .. } => assert_eq!(key_id, alice_id),
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_version_and_role_permissions() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let (bob_pub, bob_priv) = generate_p256_pair();
+        let bob_id = bob_pub.fingerprint();
+        let (carol_pub, carol_priv) = generate_p256_pair();
+        let carol_id = carol_pub.fingerprint();
+        let content = HashAlgorithm::Sha256.digest(&[0, 1, 2, 3]);
+
+        // Bob may only release 1.x; carol is an administrator
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: alice_pub,
+                },
+                model::PackageEntry::GrantVersions {
+                    key: bob_pub,
+                    permissions: vec![model::Permission::Release],
+                    versions: "^1".parse().unwrap(),
+                },
+                model::PackageEntry::GrantRole {
+                    key: carol_pub,
+                    role: model::Role::Admin,
+                },
+            ],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = LogState::default().validate(&envelope).unwrap();
+        assert_eq!(
+            state.key_roles(&carol_id),
+            IndexSet::from([model::Role::Admin])
+        );
+        let prev = RecordId::package_record::<Sha256>(&envelope);
+
+        // Bob can release a version matching the grant
+        let record = model::PackageRecord {
+            prev: Some(prev),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::PackageEntry::Release {
+                version: Version::new(1, 2, 0),
+                content: content.clone(),
+            }],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&bob_priv, record.clone()).unwrap();
+        let state = state.validate(&envelope).unwrap();
+        let prev = RecordId::package_record::<Sha256>(&envelope);
+
+        // Bob cannot release a version outside of the grant
+        let record = model::PackageRecord {
+            prev: Some(prev),
+            entries: vec![model::PackageEntry::Release {
+                version: Version::new(2, 0, 0),
+                content,
+            }],
+            ..record
+        };
+        let envelope = ProtoEnvelope::signed_contents(&bob_priv, record.clone()).unwrap();
+        match state.clone().validate(&envelope).unwrap_err() {
+            ValidationError::UnauthorizedVersionAction {
+                key_id, version, ..
+            } => {
+                assert_eq!(key_id, bob_id);
+                assert_eq!(version, Version::new(2, 0, 0));
+            }
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // Carol cannot release without the permission...
+        let envelope = ProtoEnvelope::signed_contents(&carol_priv, record.clone()).unwrap();
+        match state.clone().validate(&envelope).unwrap_err() {
+            ValidationError::UnauthorizedVersionAction { key_id, .. } => {
+                assert_eq!(key_id, carol_id)
+            }
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // ...but can revoke bob's grant without holding it
+        let record = model::PackageRecord {
+            entries: vec![model::PackageEntry::RevokeVersions {
+                key_id: bob_id.clone(),
+                permissions: vec![model::Permission::Release],
+                versions: "^1".parse().unwrap(),
+            }],
+            ..record
+        };
+        let envelope = ProtoEnvelope::signed_contents(&carol_priv, record.clone()).unwrap();
+        let state = state.validate(&envelope).unwrap();
+        assert_eq!(state.key_version_permissions(&bob_id), None);
+        let prev = RecordId::package_record::<Sha256>(&envelope);
+
+        // Bob cannot grant roles
+        let record = model::PackageRecord {
+            prev: Some(prev),
+            entries: vec![model::PackageEntry::RevokeRole {
+                key_id: carol_id,
+                role: model::Role::Admin,
+            }],
+            ..record
+        };
+        let envelope = ProtoEnvelope::signed_contents(&bob_priv, record).unwrap();
+        match state.validate(&envelope).unwrap_err() {
+            ValidationError::UnauthorizedRoleAction {
+                key_id,
+                needed_role,
+            } => {
+                assert_eq!(key_id, bob_id);
+                assert_eq!(needed_role, model::Role::Admin);
+            }
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_signature_threshold() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let (bob_pub, bob_priv) = generate_p256_pair();
+        let (carol_pub, _) = generate_p256_pair();
+
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: alice_pub,
+                },
+                model::PackageEntry::GrantFlat {
+                    key: bob_pub,
+                    permissions: vec![model::Permission::Release],
+                },
+                model::PackageEntry::GrantFlat {
+                    key: carol_pub,
+                    permissions: vec![model::Permission::Release],
+                },
+                model::PackageEntry::SignatureThreshold { threshold: 2 },
+            ],
+        };
+
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = LogState::default().validate(&envelope).unwrap();
+        assert_eq!(state.signature_threshold(), 2);
+
+        let record = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::PackageEntry::Release {
+                version: Version::new(1, 0, 0),
+                content: HashAlgorithm::Sha256.digest(&[0, 1, 2, 3]),
+            }],
+        };
+
+        // A single signature is not enough
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record.clone()).unwrap();
+        match state.clone().validate(&envelope).unwrap_err() {
+            ValidationError::InsufficientSignatures { threshold, found } => {
+                assert_eq!(threshold, 2);
+                assert_eq!(found, 1);
+            }
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // A key cannot sign twice
+        let cosignature =
+            Cosignature::sign::<model::PackageRecord>(&alice_priv, envelope.content_bytes())
+                .unwrap();
+        match state
+            .clone()
+            .validate(&envelope.clone().with_cosignatures([cosignature]))
+            .unwrap_err()
+        {
+            ValidationError::DuplicateSignature { .. } => {}
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // A cosignature from another maintainer meets the threshold
+        let cosignature =
+            Cosignature::sign::<model::PackageRecord>(&bob_priv, envelope.content_bytes()).unwrap();
+        let state = state
+            .validate(&envelope.with_cosignatures([cosignature]))
+            .unwrap();
+        assert!(state.release(&Version::new(1, 0, 0)).is_some());
+    }
+
+    #[test]
+    fn test_signature_threshold_version_permissions() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let (bob_pub, bob_priv) = generate_p256_pair();
+        let (carol_pub, carol_priv) = generate_p256_pair();
+
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: alice_pub,
+                },
+                model::PackageEntry::GrantFlat {
+                    key: bob_pub,
+                    permissions: vec![model::Permission::Release],
+                },
+                model::PackageEntry::GrantVersions {
+                    key: carol_pub,
+                    permissions: vec![model::Permission::Release],
+                    versions: "^1".parse().unwrap(),
+                },
+                model::PackageEntry::SignatureThreshold { threshold: 2 },
+            ],
+        };
+
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = LogState::default().validate(&envelope).unwrap();
+        let prev = RecordId::package_record::<Sha256>(&envelope);
+
+        let cosigned = |entries: Vec<model::PackageEntry>| {
+            let record = model::PackageRecord {
+                prev: Some(prev.clone()),
+                version: PACKAGE_RECORD_VERSION,
+                timestamp: SystemTime::now(),
+                entries,
+            };
+            let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+            let cosignature =
+                Cosignature::sign::<model::PackageRecord>(&carol_priv, envelope.content_bytes())
+                    .unwrap();
+            envelope.with_cosignatures([cosignature])
+        };
+        let release = |version: Version| model::PackageEntry::Release {
+            version,
+            content: HashAlgorithm::Sha256.digest(&[0, 1, 2, 3]),
+        };
+
+        // A key granted the released version counts toward the threshold
+        let state_1 = state
+            .clone()
+            .validate(&cosigned(vec![release(Version::new(1, 0, 0))]))
+            .unwrap();
+        assert!(state_1.release(&Version::new(1, 0, 0)).is_some());
+
+        // It does not count for other versions or for other entries
+        for entries in [
+            vec![release(Version::new(2, 0, 0))],
+            vec![
+                release(Version::new(1, 0, 0)),
+                release(Version::new(2, 0, 0)),
+            ],
+            vec![
+                release(Version::new(1, 0, 0)),
+                model::PackageEntry::SignatureThreshold { threshold: 1 },
+            ],
+        ] {
+            match state.clone().validate(&cosigned(entries)).unwrap_err() {
+                ValidationError::InsufficientSignatures { threshold, found } => {
+                    assert_eq!(threshold, 2);
+                    assert_eq!(found, 1);
+                }
+                e => panic!("unexpected error: {e}"),
+            }
+        }
+
+        // Nor is it counted toward the keys able to meet the threshold
+        let record = model::PackageRecord {
+            prev: Some(prev.clone()),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::PackageEntry::SignatureThreshold { threshold: 3 }],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let cosignature =
+            Cosignature::sign::<model::PackageRecord>(&bob_priv, envelope.content_bytes()).unwrap();
+        match state
+            .validate(&envelope.with_cosignatures([cosignature]))
+            .unwrap_err()
+        {
+            ValidationError::InvalidSignatureThreshold {
+                threshold,
+                maintainers,
+            } => {
+                assert_eq!(threshold, 3);
+                assert_eq!(maintainers, 2);
+            }
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_init_key_admin_role_is_derived() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let (bob_pub, bob_priv) = generate_p256_pair();
+        let alice_id = alice_pub.fingerprint();
+        let bob_id = bob_pub.fingerprint();
+
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::PackageEntry::Init {
+                hash_algorithm: HashAlgorithm::Sha256,
+                key: alice_pub,
+            }],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = LogState::default().validate(&envelope).unwrap();
+        assert_eq!(
+            state.key_roles(&alice_id),
+            IndexSet::from([model::Role::Admin])
+        );
+
+        // State serialized before roles existed is the same as a fresh replay
+        let mut json = serde_json::to_value(&state).unwrap();
+        assert!(json.as_object_mut().unwrap().remove("roles").is_none());
+        assert_eq!(serde_json::from_value::<LogState>(json).unwrap(), state);
+
+        // The role follows a rotation of the init key
+        let record = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::PackageEntry::RotateKey {
+                key_id: alice_id.clone(),
+                key: bob_pub,
+            }],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = state.validate(&envelope).unwrap();
+        assert!(state.key_roles(&alice_id).is_empty());
+        assert_eq!(
+            state.key_roles(&bob_id),
+            IndexSet::from([model::Role::Admin])
+        );
+
+        // The role can be revoked
+        let record = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::PackageEntry::RevokeRole {
+                key_id: bob_id.clone(),
+                role: model::Role::Admin,
+            }],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&bob_priv, record).unwrap();
+        let state = state.validate(&envelope).unwrap();
+        assert!(state.key_roles(&bob_id).is_empty());
+    }
+
+    #[test]
+    fn test_revoke_below_signature_threshold() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let (bob_pub, bob_priv) = generate_p256_pair();
+        let bob_id = bob_pub.fingerprint();
+
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: alice_pub,
+                },
+                model::PackageEntry::GrantFlat {
+                    key: bob_pub,
+                    permissions: vec![model::Permission::Release],
+                },
+                model::PackageEntry::SignatureThreshold { threshold: 2 },
+            ],
+        };
+
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = LogState::default().validate(&envelope).unwrap();
+        let prev = RecordId::package_record::<Sha256>(&envelope);
+
+        let cosigned = |entries: Vec<model::PackageEntry>| {
+            let record = model::PackageRecord {
+                prev: Some(prev.clone()),
+                version: PACKAGE_RECORD_VERSION,
+                timestamp: SystemTime::now(),
+                entries,
+            };
+            let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+            let cosignature =
+                Cosignature::sign::<model::PackageRecord>(&bob_priv, envelope.content_bytes())
+                    .unwrap();
+            envelope.with_cosignatures([cosignature])
+        };
+        let revoke = model::PackageEntry::RevokeFlat {
+            key_id: bob_id.clone(),
+            permissions: vec![model::Permission::Release],
+        };
+
+        // Revoking leaves too few keys with permissions to meet the threshold
+        match state
+            .clone()
+            .validate(&cosigned(vec![revoke.clone()]))
+            .unwrap_err()
+        {
+            ValidationError::InvalidSignatureThreshold {
+                threshold,
+                maintainers,
+            } => {
+                assert_eq!(threshold, 2);
+                assert_eq!(maintainers, 1);
+            }
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // Lowering the threshold in the same record allows the revocation
+        let state = state
+            .validate(&cosigned(vec![
+                revoke,
+                model::PackageEntry::SignatureThreshold { threshold: 1 },
+            ]))
+            .unwrap();
+        assert_eq!(state.signature_threshold(), 1);
+        assert!(state.key_permissions(&bob_id).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_unyank_requires_yanked_release() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let version = Version::new(1, 0, 0);
+
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: alice_pub,
+                },
+                model::PackageEntry::Release {
+                    version: version.clone(),
+                    content: HashAlgorithm::Sha256.digest(&[0, 1, 2, 3]),
+                },
+            ],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = LogState::default().validate(&envelope).unwrap();
+        let prev = RecordId::package_record::<Sha256>(&envelope);
+
+        let unyank = |version: Version| model::PackageRecord {
+            prev: Some(prev.clone()),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::PackageEntry::Unyank { version }],
+        };
+
+        let envelope =
+            ProtoEnvelope::signed_contents(&alice_priv, unyank(version.clone())).unwrap();
+        match state.clone().validate(&envelope).unwrap_err() {
+            ValidationError::UnyankOfUnyanked { version: v } => assert_eq!(v, version),
+            e => panic!("unexpected error: {e}"),
+        }
+
+        let envelope =
+            ProtoEnvelope::signed_contents(&alice_priv, unyank(Version::new(2, 0, 0))).unwrap();
+        match state.validate(&envelope).unwrap_err() {
+            ValidationError::UnyankOfUnreleased { version: v } => {
+                assert_eq!(v, Version::new(2, 0, 0))
+            }
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_unyank_records_signer_and_timestamp() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let alice_id = alice_pub.fingerprint();
+        let version = Version::new(1, 0, 0);
+        let content = HashAlgorithm::Sha256.digest(&[0, 1, 2, 3]);
+
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: alice_pub,
+                },
+                model::PackageEntry::Release {
+                    version: version.clone(),
+                    content: content.clone(),
+                },
+                model::PackageEntry::Yank {
+                    version: version.clone(),
+                },
+            ],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = LogState::default().validate(&envelope).unwrap();
+
+        let timestamp = SystemTime::now();
+        let unyank = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp,
+            entries: vec![model::PackageEntry::Unyank {
+                version: version.clone(),
+            }],
+        };
+        let unyank = ProtoEnvelope::signed_contents(&alice_priv, unyank).unwrap();
+
+        // State stored before unyanking was supported has no yanked content
+        let mut json = serde_json::to_value(&state).unwrap();
+        json["releases"]["1.0.0"]["state"]
+            .as_object_mut()
+            .unwrap()
+            .remove("content")
+            .unwrap();
+        let old_state: LogState = serde_json::from_value(json).unwrap();
+        match old_state.validate(&unyank).unwrap_err() {
+            ValidationError::UnyankOfUnknownContent { version: v } => assert_eq!(v, version),
+            e => panic!("unexpected error: {e}"),
+        }
+
+        let state = state.validate(&unyank).unwrap();
+        assert_eq!(
+            state.release(&version).unwrap().state,
+            ReleaseState::Released {
+                content,
+                unyanked: Some(Unyanked {
+                    by: alice_id,
+                    timestamp,
+                }),
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_metadata_is_rejected() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+
+        for (metadata, expected) in [
+            (
+                model::PackageMetadata {
+                    description: Some("a package".to_string()),
+                    keywords: vec!["wasm".to_string(), "wasm".to_string()],
+                    ..Default::default()
+                },
+                "duplicate keyword `wasm`",
+            ),
+            (
+                model::PackageMetadata {
+                    license: Some("Apache-2.0 OR".to_string()),
+                    ..Default::default()
+                },
+                "the license `Apache-2.0 OR` is not a valid SPDX expression",
+            ),
+            (
+                model::PackageMetadata {
+                    license: Some("Not-A-License".to_string()),
+                    ..Default::default()
+                },
+                "the license `Not-A-License` is not a valid SPDX expression",
+            ),
+            (
+                model::PackageMetadata {
+                    repository: Some("github.com/example/repo".to_string()),
+                    ..Default::default()
+                },
+                "the repository `github.com/example/repo` is not a valid URL",
+            ),
+        ] {
+            let record = model::PackageRecord {
+                prev: None,
+                version: PACKAGE_RECORD_VERSION,
+                timestamp: SystemTime::now(),
+                entries: vec![
+                    model::PackageEntry::Init {
+                        hash_algorithm: HashAlgorithm::Sha256,
+                        key: alice_pub.clone(),
+                    },
+                    model::PackageEntry::Metadata { metadata },
+                ],
+            };
+
+            let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+            match LogState::default().validate(&envelope).unwrap_err() {
+                ValidationError::InvalidMetadata { message } => {
+                    assert!(message.starts_with(expected), "{message}")
+                }
+                e => panic!("unexpected error: {e}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_latest_deprecation_applies() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let deprecate = |versions: Option<&str>, message: &str| model::PackageEntry::Deprecate {
+            deprecation: model::PackageDeprecation {
+                versions: versions.map(|v| v.parse().unwrap()),
+                message: message.to_string(),
+                successor: None,
+            },
+        };
+
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: alice_pub,
+                },
+                deprecate(None, "use something else"),
+                deprecate(Some("<1.0.0"), "pre-release versions are unsupported"),
+            ],
+        };
+
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = LogState::default().validate(&envelope).unwrap();
+        assert_eq!(
+            state.deprecation(&Version::new(0, 1, 0)).unwrap().message,
+            "pre-release versions are unsupported"
+        );
+        assert_eq!(
+            state.deprecation(&Version::new(1, 0, 0)).unwrap().message,
+            "use something else"
+        );
+
+        let record = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![deprecate(None, " ")],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        match state.validate(&envelope).unwrap_err() {
+            ValidationError::DeprecationWithoutMessage => {}
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_undeprecate_retracts_deprecation() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+        let deprecate = |versions: Option<&str>, message: &str| model::PackageEntry::Deprecate {
+            deprecation: model::PackageDeprecation {
+                versions: versions.map(|v| v.parse().unwrap()),
+                message: message.to_string(),
+                successor: None,
+            },
+        };
+        let undeprecate = |versions: Option<&str>| model::PackageEntry::Undeprecate {
+            versions: versions.map(|v| v.parse().unwrap()),
+        };
+
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![
+                model::PackageEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                    key: alice_pub,
+                },
+                deprecate(None, "use something else"),
+                deprecate(Some("<1.0.0"), "pre-release versions are unsupported"),
+                undeprecate(Some("<1.0.0")),
+            ],
+        };
+
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = LogState::default().validate(&envelope).unwrap();
+        assert_eq!(
+            state.deprecation(&Version::new(0, 1, 0)).unwrap().message,
+            "use something else"
+        );
+
+        let record = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![undeprecate(None)],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = state.validate(&envelope).unwrap();
+        assert!(state.deprecation(&Version::new(0, 1, 0)).is_none());
+        assert_eq!(state.deprecations().count(), 0);
+
+        let record = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![undeprecate(None)],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        match state.validate(&envelope).unwrap_err() {
+            ValidationError::UndeprecateOfUndeprecated { .. } => {}
+            e => panic!("unexpected error: {e}"),
+        }
+    }
+
+    #[test]
+    fn test_validate_sha512_log() {
+        let (alice_pub, alice_priv) = generate_p256_pair();
+
+        let record = model::PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::PackageEntry::Init {
+                hash_algorithm: HashAlgorithm::Sha512,
+                key: alice_pub,
+            }],
+        };
+
+        let envelope0 = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = LogState::default().validate(&envelope0).unwrap();
+        let record_id0 = RecordId::package_record::<Sha512>(&envelope0);
+        assert_eq!(state.algorithm(), Some(HashAlgorithm::Sha512));
+        assert_eq!(state.head().as_ref().unwrap().digest, record_id0);
+
+        // Linking with a different algorithm than the log's is rejected
+        let record = model::PackageRecord {
+            prev: Some(RecordId::package_record::<Sha256>(&envelope0)),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::PackageEntry::Release {
+                version: Version::new(1, 0, 0),
+                content: HashAlgorithm::Sha512.digest(&[0, 1, 2, 3]),
+            }],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        match state.clone().validate(&envelope).unwrap_err() {
+            ValidationError::IncorrectHashAlgorithm { found, expected } => {
+                assert_eq!(found, HashAlgorithm::Sha256);
+                assert_eq!(expected, HashAlgorithm::Sha512);
+            }
+            e => panic!("unexpected error: {e}"),
+        }
+
+        // Releasing content with a different algorithm than the log's is rejected
+        let record = model::PackageRecord {
+            prev: Some(record_id0.clone()),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::PackageEntry::Release {
+                version: Version::new(1, 0, 0),
+                content: HashAlgorithm::Sha256.digest(&[0, 1, 2, 3]),
+            }],
+        };
+        let envelope = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        match state.clone().validate(&envelope).unwrap_err() {
+            ValidationError::IncorrectContentHashAlgorithm { found, expected } => {
+                assert_eq!(found, HashAlgorithm::Sha256);
+                assert_eq!(expected, HashAlgorithm::Sha512);
+            }
+            e => panic!("unexpected error: {e}"),
+        }
+
+        let content = HashAlgorithm::Sha512.digest(&[0, 1, 2, 3]);
+        let record = model::PackageRecord {
+            prev: Some(record_id0),
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![model::PackageEntry::Release {
+                version: Version::new(1, 0, 0),
+                content: content.clone(),
+            }],
+        };
+        let envelope1 = ProtoEnvelope::signed_contents(&alice_priv, record).unwrap();
+        let state = state.validate(&envelope1).unwrap();
+        let record_id1 = RecordId::package_record_for_log(&envelope1);
+        assert_eq!(record_id1, RecordId::package_record::<Sha512>(&envelope1));
+        assert_eq!(state.head().as_ref().unwrap().digest, record_id1);
+        assert_eq!(
+            state.release(&Version::new(1, 0, 0)).unwrap().content(),
+            Some(&content)
+        );
+    }
 }
@@ -1,12 +1,18 @@
-use crate::{operator::OperatorRecord, package::PackageRecord, ProtoEnvelope};
+use crate::{
+    operator::OperatorRecord,
+    package::{PackageEntry, PackageRecord},
+    Cosignature, ProtoEnvelope,
+};
 use anyhow::bail;
+use base64::{engine::general_purpose::STANDARD, Engine};
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::str::FromStr;
 use std::time::SystemTime;
 use warg_crypto::hash::{AnyHash, Hash, HashAlgorithm, SupportedDigest};
 use warg_crypto::prefix::VisitPrefixEncode;
-use warg_crypto::{prefix, ByteVisitor, Signable, VisitBytes};
+use warg_crypto::signing::{PrivateKey, PublicKey, SignatureError};
+use warg_crypto::{prefix, ByteVisitor, Encode, Signable, VisitBytes};
 use wasmparser::names::KebabStr;
 
 /// Type alias for registry log index
@@ -39,6 +45,83 @@ impl VisitBytes for Checkpoint {
     }
 }
 
+// The signing prefix of witness cosignatures, which differs from the prefix of
+// registry checkpoint signatures so that neither can be used as the other
+const WITNESS_COSIGNATURE_PREFIX: &[u8] = b"WARG-WITNESS-COSIGNATURE-V0";
+
+impl Checkpoint {
+    /// Cosigns the checkpoint as a witness with the given key.
+    ///
+    /// Witnesses cosign the checkpoint rather than the timestamped checkpoint,
+    /// as the registry periodically re-signs its latest checkpoint with a new
+    /// timestamp.
+    pub fn cosign(&self, witness_key: &PrivateKey) -> Result<Cosignature, SignatureError> {
+        Ok(Cosignature {
+            key_id: witness_key.public_key().fingerprint(),
+            signature: witness_key.sign(&self.witness_message())?,
+        })
+    }
+
+    /// Verifies a witness cosignature of the checkpoint with the given key.
+    pub fn verify_cosignature(
+        &self,
+        witness_key: &PublicKey,
+        cosignature: &Cosignature,
+    ) -> Result<(), SignatureError> {
+        witness_key.verify(&self.witness_message(), &cosignature.signature)
+    }
+
+    fn witness_message(&self) -> Vec<u8> {
+        [WITNESS_COSIGNATURE_PREFIX, b":", &self.encode()].concat()
+    }
+
+    /// Formats the checkpoint as the text of a [C2SP tlog-checkpoint] note
+    /// for the log with the given origin.
+    ///
+    /// The map root is included as the first extension line.
+    ///
+    /// [C2SP tlog-checkpoint]: https://c2sp.org/tlog-checkpoint
+    pub fn to_note_text(&self, origin: &str) -> String {
+        format!(
+            "{origin}\n{log_length}\n{log_root}\n{map_root}\n",
+            log_length = self.log_length,
+            log_root = STANDARD.encode(self.log_root.bytes()),
+            map_root = self.map_root,
+        )
+    }
+
+    /// Parses the text of a tlog-checkpoint note produced by
+    /// [`Checkpoint::to_note_text`], returning the log origin and checkpoint.
+    pub fn from_note_text(text: &str) -> anyhow::Result<(String, Self)> {
+        let mut lines = text
+            .strip_suffix('\n')
+            .ok_or_else(|| anyhow::anyhow!("checkpoint note must end with a newline"))?
+            .split('\n');
+        let (Some(origin), Some(log_length), Some(log_root), Some(map_root)) =
+            (lines.next(), lines.next(), lines.next(), lines.next())
+        else {
+            bail!("checkpoint note is missing the origin, size, root hash or map root");
+        };
+
+        if origin.is_empty() {
+            bail!("checkpoint note has an empty origin");
+        }
+
+        // Both roots are produced with the same hash algorithm
+        let map_root: AnyHash = map_root.parse()?;
+        let log_root = AnyHash::new(map_root.algorithm(), STANDARD.decode(log_root)?);
+
+        Ok((
+            origin.to_string(),
+            Self {
+                log_root,
+                log_length: log_length.parse()?,
+                map_root,
+            },
+        ))
+    }
+}
+
 #[derive(Debug, Clone, Hash, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct TimestampedCheckpoint {
@@ -122,6 +205,38 @@ impl VisitBytes for LogLeaf {
     }
 }
 
+impl LogLeaf {
+    /// Decodes a log leaf from the bytes it is hashed from in the log, such
+    /// as an entry of a log data tile.
+    pub fn decode(bytes: &[u8]) -> anyhow::Result<Self> {
+        let mut bytes = bytes
+            .strip_prefix(b"WARG-LOG-LEAF-V0".as_slice())
+            .ok_or_else(|| anyhow::anyhow!("log leaf has an invalid prefix"))?;
+
+        let mut read_str = || -> anyhow::Result<&str> {
+            let len = leb128::read::unsigned(&mut bytes)? as usize;
+            if bytes.len() < len {
+                bail!("log leaf is truncated");
+            }
+
+            let (s, rest) = bytes.split_at(len);
+            bytes = rest;
+            Ok(std::str::from_utf8(s)?)
+        };
+        let log_id: AnyHash = read_str()?.parse()?;
+        let record_id: AnyHash = read_str()?.parse()?;
+
+        if !bytes.is_empty() {
+            bail!("log leaf has trailing bytes");
+        }
+
+        Ok(Self {
+            log_id: log_id.into(),
+            record_id: record_id.into(),
+        })
+    }
+}
+
 /// Represents a valid package name in the registry.
 ///
 /// Valid package names conform to the component model specification.
@@ -225,15 +340,29 @@ pub struct LogId(AnyHash);
 
 impl LogId {
     pub fn operator_log<D: SupportedDigest>() -> Self {
-        let prefix: &[u8] = b"WARG-OPERATOR-LOG-ID-V0".as_slice();
-        let hash: Hash<D> = Hash::of(prefix);
-        Self(hash.into())
+        Self::operator_log_with(D::ALGORITHM)
     }
 
     pub fn package_log<D: SupportedDigest>(name: &PackageName) -> Self {
+        Self::package_log_with(D::ALGORITHM, name)
+    }
+
+    /// Computes the identifier of the operator log using the given hash
+    /// algorithm.
+    pub fn operator_log_with(algorithm: HashAlgorithm) -> Self {
+        let prefix: &[u8] = b"WARG-OPERATOR-LOG-ID-V0".as_slice();
+        let mut hasher = algorithm.hasher();
+        prefix.visit(&mut hasher);
+        Self(hasher.finalize())
+    }
+
+    /// Computes the identifier of a package log using the given hash
+    /// algorithm.
+    pub fn package_log_with(algorithm: HashAlgorithm, name: &PackageName) -> Self {
         let prefix: &[u8] = b"WARG-PACKAGE-LOG-ID-V0:".as_slice();
-        let hash: Hash<D> = Hash::of((prefix, name));
-        Self(hash.into())
+        let mut hasher = algorithm.hasher();
+        (prefix, name).visit(&mut hasher);
+        Self(hasher.finalize())
     }
 }
 
@@ -277,9 +406,19 @@ impl RecordId {
     }
 
     pub fn operator_record<D: SupportedDigest>(record: &ProtoEnvelope<OperatorRecord>) -> Self {
-        let prefix: &[u8] = b"WARG-OPERATOR-LOG-RECORD-V0:".as_slice();
-        let hash: Hash<D> = Hash::of((prefix, record.content_bytes()));
-        Self(hash.into())
+        Self::operator_record_with(D::ALGORITHM, record)
+    }
+
+    /// Computes the identifier of an operator record using the given hash
+    /// algorithm.
+    pub fn operator_record_with(
+        algorithm: HashAlgorithm,
+        record: &ProtoEnvelope<OperatorRecord>,
+    ) -> Self {
+        let mut hasher = algorithm.hasher();
+        hasher.update(b"WARG-OPERATOR-LOG-RECORD-V0:");
+        hasher.update(record.content_bytes());
+        Self(hasher.finalize())
     }
 
     pub fn package_record<D: SupportedDigest>(record: &ProtoEnvelope<PackageRecord>) -> Self {
@@ -287,6 +426,35 @@ impl RecordId {
         let hash: Hash<D> = Hash::of((prefix, record.content_bytes()));
         Self(hash.into())
     }
+
+    /// Computes the identifier of a package record using the hash algorithm
+    /// of the package log it belongs to.
+    ///
+    /// The algorithm is taken from the record's previous record hash or, for
+    /// the first record of a log, from its init entry.
+    pub fn package_record_for_log(record: &ProtoEnvelope<PackageRecord>) -> Self {
+        let algorithm = record
+            .as_ref()
+            .prev
+            .as_ref()
+            .map(|prev| prev.algorithm())
+            .or_else(|| {
+                record
+                    .as_ref()
+                    .entries
+                    .iter()
+                    .find_map(|entry| match entry {
+                        PackageEntry::Init { hash_algorithm, .. } => Some(*hash_algorithm),
+                        _ => None,
+                    })
+            })
+            .unwrap_or_default();
+
+        let mut hasher = algorithm.hasher();
+        hasher.update(b"WARG-PACKAGE-LOG-RECORD-V0:");
+        hasher.update(record.content_bytes());
+        Self(hasher.finalize())
+    }
 }
 
 impl fmt::Display for RecordId {
@@ -316,7 +484,7 @@ impl AsRef<[u8]> for RecordId {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use warg_crypto::hash::Sha256;
+    use warg_crypto::{hash::Sha256, Encode};
     use warg_transparency::map::Map;
 
     #[test]
@@ -329,4 +497,109 @@ mod tests {
             proof.evaluate(&LogId::operator_log::<Sha256>(), &"foobar")
         );
     }
+
+    #[test]
+    fn log_leaf_decode() {
+        let leaf = LogLeaf {
+            log_id: LogId::operator_log::<Sha256>(),
+            record_id: RecordId::from(AnyHash::from(Hash::<Sha256>::of("record"))),
+        };
+
+        let bytes = leaf.encode();
+        assert_eq!(LogLeaf::decode(&bytes).unwrap(), leaf);
+        assert!(LogLeaf::decode(&bytes[..bytes.len() - 1]).is_err());
+        assert!(LogLeaf::decode(&[bytes.as_slice(), b"x"].concat()).is_err());
+        assert!(LogLeaf::decode(&bytes[1..]).is_err());
+    }
+
+    #[test]
+    fn checkpoint_note_text() {
+        let checkpoint = Checkpoint {
+            log_root: Hash::<Sha256>::of("log").into(),
+            log_length: 42,
+            map_root: Hash::<Sha256>::of("map").into(),
+        };
+
+        let text = checkpoint.to_note_text("example.com/registry");
+        let mut lines = text.lines();
+        assert_eq!(lines.next(), Some("example.com/registry"));
+        assert_eq!(lines.next(), Some("42"));
+        assert_eq!(
+            lines.next().map(|l| STANDARD.decode(l).unwrap()),
+            Some(checkpoint.log_root.bytes().to_vec())
+        );
+
+        let (origin, parsed) = Checkpoint::from_note_text(&text).unwrap();
+        assert_eq!(origin, "example.com/registry");
+        assert_eq!(parsed, checkpoint);
+
+        assert!(Checkpoint::from_note_text("example.com/registry\n42\n").is_err());
+    }
+
+    #[test]
+    fn log_ids_with_algorithm() {
+        let name = PackageName::new("test:name").unwrap();
+        let prefix: &[u8] = b"WARG-PACKAGE-LOG-ID-V0:".as_slice();
+        let expected: AnyHash = Hash::<Sha256>::of((prefix, &name)).into();
+        assert_eq!(
+            LogId::package_log_with(HashAlgorithm::Sha256, &name),
+            LogId::from(expected)
+        );
+
+        let sha512 = LogId::package_log_with(HashAlgorithm::Sha512, &name);
+        assert_eq!(
+            sha512,
+            LogId::package_log::<warg_crypto::hash::Sha512>(&name)
+        );
+        assert_ne!(sha512, LogId::package_log::<Sha256>(&name));
+
+        let blake3 = LogId::operator_log_with(HashAlgorithm::Blake3);
+        assert_eq!(AnyHash::from(blake3).algorithm(), HashAlgorithm::Blake3);
+    }
+
+    #[test]
+    fn checkpoint_cosignature() {
+        let (public_key, private_key) = warg_crypto::signing::generate_ed25519_pair();
+        let ts_checkpoint = TimestampedCheckpoint {
+            checkpoint: Checkpoint {
+                log_root: Hash::<Sha256>::of("log").into(),
+                log_length: 42,
+                map_root: Hash::<Sha256>::of("map").into(),
+            },
+            timestamp: 0,
+        };
+        let checkpoint = &ts_checkpoint.checkpoint;
+
+        let cosignature = checkpoint.cosign(&private_key).unwrap();
+        assert_eq!(cosignature.key_id, public_key.fingerprint());
+        checkpoint
+            .verify_cosignature(&public_key, &cosignature)
+            .unwrap();
+
+        // A witness cosignature is not a registry checkpoint signature
+        assert!(TimestampedCheckpoint::verify(
+            &public_key,
+            &ts_checkpoint.encode(),
+            &cosignature.signature
+        )
+        .is_err());
+        assert!(TimestampedCheckpoint::verify(
+            &public_key,
+            &checkpoint.encode(),
+            &cosignature.signature
+        )
+        .is_err());
+
+        // Nor is a registry checkpoint signature a witness cosignature
+        let signature = ts_checkpoint.sign(&private_key).unwrap();
+        assert!(checkpoint
+            .verify_cosignature(
+                &public_key,
+                &Cosignature {
+                    key_id: public_key.fingerprint(),
+                    signature,
+                }
+            )
+            .is_err());
+    }
 }
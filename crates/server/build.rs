@@ -1,4 +1,5 @@
 fn main() {
     // Re-run diesel_migrations::embed_migrations! on change
-    println!("cargo:rerun-if-changed=src/datastore/postgres/migrations")
+    println!("cargo:rerun-if-changed=src/datastore/postgres/migrations");
+    println!("cargo:rerun-if-changed=src/datastore/sqlite/migrations");
 }
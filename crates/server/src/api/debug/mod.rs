@@ -10,14 +10,11 @@ use axum::{
     Router,
 };
 use serde::Serialize;
-use warg_crypto::{
-    hash::{AnyHash, Sha256},
-    signing::KeyID,
-};
+use warg_crypto::{hash::AnyHash, signing::KeyID};
 use warg_protocol::{
-    package::{LogState, Permission, Release},
+    package::{LogState, Permission, Release, Role},
     registry::{LogId, PackageName, RecordId},
-    Version,
+    Version, VersionReq,
 };
 
 use crate::{api::v1::Json, services::CoreService};
@@ -80,6 +77,10 @@ struct EntryInfo {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     permissions: Vec<Permission>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    versions: Option<VersionReq>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    role: Option<Role>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     version: Option<Version>,
     #[serde(skip_serializing_if = "Option::is_none")]
     content: Option<AnyHash>,
@@ -98,7 +99,7 @@ async fn get_package_info(
         .context("get_latest_checkpoint")?;
     let checkpoint_log_length = checkpoint.as_ref().checkpoint.log_length;
 
-    let log_id = LogId::package_log::<Sha256>(&package_name);
+    let log_id = LogId::package_log_with(config.core_service.hash_algorithm(), &package_name);
     let records = store
         .get_package_records(&log_id, checkpoint_log_length, None, u16::MAX)
         .await
@@ -111,7 +112,7 @@ async fn get_package_info(
         .map(|record| {
             let state = std::mem::take(&mut package_state);
             package_state = state.validate(&record.envelope).context("validate")?;
-            let record_id = RecordId::package_record::<Sha256>(&record.envelope);
+            let record_id = RecordId::package_record_for_log(&record.envelope);
             let timestamp = record
                 .envelope
                 .as_ref()
@@ -147,6 +148,50 @@ async fn get_package_info(
                             permissions: permissions.clone(),
                             ..Default::default()
                         },
+                        GrantVersions {
+                            key,
+                            permissions,
+                            versions,
+                        } => EntryInfo {
+                            kind: "grant-versions",
+                            key: Some(key.to_string()),
+                            permissions: permissions.clone(),
+                            versions: Some(versions.clone()),
+                            ..Default::default()
+                        },
+                        RevokeVersions {
+                            key_id,
+                            permissions,
+                            versions,
+                        } => EntryInfo {
+                            kind: "revoke-versions",
+                            key_id: Some(key_id.clone()),
+                            permissions: permissions.clone(),
+                            versions: Some(versions.clone()),
+                            ..Default::default()
+                        },
+                        GrantRole { key, role } => EntryInfo {
+                            kind: "grant-role",
+                            key: Some(key.to_string()),
+                            role: Some(*role),
+                            ..Default::default()
+                        },
+                        RevokeRole { key_id, role } => EntryInfo {
+                            kind: "revoke-role",
+                            key_id: Some(key_id.clone()),
+                            role: Some(*role),
+                            ..Default::default()
+                        },
+                        RotateKey { key_id, key } => EntryInfo {
+                            kind: "rotate-key",
+                            key_id: Some(key_id.clone()),
+                            key: Some(key.to_string()),
+                            ..Default::default()
+                        },
+                        SignatureThreshold { .. } => EntryInfo {
+                            kind: "signature-threshold",
+                            ..Default::default()
+                        },
                         Release { version, content } => EntryInfo {
                             kind: "release",
                             version: Some(version.clone()),
@@ -158,6 +203,23 @@ async fn get_package_info(
                             version: Some(version.clone()),
                             ..Default::default()
                         },
+                        Unyank { version } => EntryInfo {
+                            kind: "unyank",
+                            version: Some(version.clone()),
+                            ..Default::default()
+                        },
+                        Metadata { .. } => EntryInfo {
+                            kind: "metadata",
+                            ..Default::default()
+                        },
+                        Deprecate { .. } => EntryInfo {
+                            kind: "deprecate",
+                            ..Default::default()
+                        },
+                        Undeprecate { .. } => EntryInfo {
+                            kind: "undeprecate",
+                            ..Default::default()
+                        },
                         _ => EntryInfo {
                             kind: "UNKNOWN",
                             ..Default::default()
@@ -0,0 +1,125 @@
+use crate::services::CoreService;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use std::{
+    sync::{Arc, OnceLock},
+    time::{Duration, SystemTime},
+};
+
+/// The configuration of the health endpoints.
+///
+/// The endpoints are served while the server is initializing, so the core
+/// service is only set once it has been initialized.
+#[derive(Clone)]
+pub struct Config {
+    core_service: Arc<OnceLock<CoreService>>,
+    max_checkpoint_age: Duration,
+}
+
+impl Config {
+    /// Creates a new health endpoint configuration for a server that is
+    /// still initializing.
+    ///
+    /// The server is not ready if the latest checkpoint is older than
+    /// `max_checkpoint_age`.
+    pub fn new(max_checkpoint_age: Duration) -> Self {
+        Self {
+            core_service: Default::default(),
+            max_checkpoint_age,
+        }
+    }
+
+    /// Marks the server as initialized with the given core service.
+    pub fn set_initialized(&self, core_service: CoreService) {
+        if self.core_service.set(core_service).is_err() {
+            tracing::warn!("the server was already marked as initialized");
+        }
+    }
+
+    /// Creates the router for the health endpoints.
+    pub fn into_router(self) -> Router {
+        Router::new()
+            .route("/healthz", get(healthz))
+            .route("/readyz", get(readyz))
+            .with_state(self)
+    }
+}
+
+/// Reports that the server is alive.
+async fn healthz() -> impl IntoResponse {
+    (StatusCode::OK, "ok")
+}
+
+/// Reports whether the server is ready to serve requests.
+///
+/// The server is ready once it has been initialized, if the data store is
+/// reachable and the core service is still storing checkpoints.
+async fn readyz(State(config): State<Config>) -> Response {
+    let Some(core_service) = config.core_service.get() else {
+        return not_ready("the server is initializing".to_string());
+    };
+
+    let checkpoint = match core_service.store().get_latest_checkpoint().await {
+        Ok(checkpoint) => checkpoint,
+        Err(e) => {
+            tracing::warn!("readiness check failed to get the latest checkpoint: {e}");
+            return not_ready(format!("failed to get the latest checkpoint: {e}"));
+        }
+    };
+
+    // Checkpoint timestamps are truncated to seconds, so allow for that
+    let stored = SystemTime::UNIX_EPOCH + Duration::from_secs(checkpoint.as_ref().timestamp);
+    let age = SystemTime::now()
+        .duration_since(stored)
+        .unwrap_or_default()
+        .saturating_sub(Duration::from_secs(1));
+    if age > config.max_checkpoint_age {
+        tracing::warn!(
+            "readiness check found the latest checkpoint is {age} second(s) old",
+            age = age.as_secs()
+        );
+        return not_ready(format!(
+            "the latest checkpoint is {age} second(s) old, which exceeds the maximum of \
+             {max} second(s)",
+            age = age.as_secs(),
+            max = config.max_checkpoint_age.as_secs()
+        ));
+    }
+
+    (StatusCode::OK, "ok").into_response()
+}
+
+fn not_ready(message: String) -> Response {
+    (StatusCode::SERVICE_UNAVAILABLE, message).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, http::Request};
+    use tower::ServiceExt;
+
+    async fn get(router: &Router, path: &str) -> StatusCode {
+        router
+            .clone()
+            .oneshot(Request::get(path).body(Body::empty()).unwrap())
+            .await
+            .unwrap()
+            .status()
+    }
+
+    #[tokio::test]
+    async fn it_is_alive_but_not_ready_while_initializing() {
+        let router = Config::new(Duration::from_secs(60)).into_router();
+        assert_eq!(get(&router, "/healthz").await, StatusCode::OK);
+        assert_eq!(
+            get(&router, "/readyz").await,
+            StatusCode::SERVICE_UNAVAILABLE
+        );
+    }
+}
@@ -0,0 +1,69 @@
+use crate::services::CoreService;
+use axum::{
+    extract::State,
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Router,
+};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use tokio::sync::Mutex;
+
+// How long record counts are reused for before being queried again
+const RECORD_COUNTS_MAX_AGE: Duration = Duration::from_secs(10);
+
+/// The configuration of the metrics endpoint.
+#[derive(Clone)]
+pub struct Config {
+    core_service: CoreService,
+    // When the record counts were last queried
+    counts_updated: Arc<Mutex<Option<Instant>>>,
+}
+
+impl Config {
+    /// Creates a new metrics endpoint configuration.
+    pub fn new(core_service: CoreService) -> Self {
+        Self {
+            core_service,
+            counts_updated: Default::default(),
+        }
+    }
+
+    /// Creates the router for the metrics endpoint.
+    pub fn into_router(self) -> Router {
+        Router::new()
+            .route("/metrics", get(metrics))
+            .with_state(self)
+    }
+}
+
+/// Serves the server metrics in the OpenMetrics text format.
+async fn metrics(State(config): State<Config>) -> impl IntoResponse {
+    let core = &config.core_service;
+
+    // Record counts are only needed when scraped, so query them here; the
+    // lock is held while querying so that concurrent scrapes query them once
+    let mut counts_updated = config.counts_updated.lock().await;
+    if counts_updated.map_or(true, |updated| updated.elapsed() >= RECORD_COUNTS_MAX_AGE) {
+        match core.store().get_record_counts().await {
+            Ok(counts) => {
+                core.metrics().set_record_counts(&counts);
+                *counts_updated = Some(Instant::now());
+            }
+            Err(e) => tracing::error!("failed to get record counts for metrics: {e}"),
+        }
+    }
+    drop(counts_updated);
+
+    (
+        StatusCode::OK,
+        [(
+            header::CONTENT_TYPE,
+            "application/openmetrics-text; version=1.0.0; charset=utf-8",
+        )],
+        core.metrics().encode(),
+    )
+}
@@ -1,32 +1,52 @@
 use crate::{
+    auth::TokenStore,
+    contentstore::ContentStore,
+    limits::Limits,
     policy::{content::ContentPolicy, record::RecordPolicy},
     services::CoreService,
 };
-use axum::{body::Body, http::Request, Router};
+use axum::{
+    body::Body,
+    http::{header, HeaderValue, Request, Response},
+    Router,
+};
 use std::{path::PathBuf, sync::Arc};
 use tower::ServiceBuilder;
 use tower_http::{
     cors::{Any, CorsLayer},
-    services::ServeDir,
+    services::{ServeDir, ServeFile},
+    set_header::SetResponseHeaderLayer,
     trace::{DefaultMakeSpan, DefaultOnResponse, TraceLayer},
     LatencyUnit,
 };
 use tracing::{Level, Span};
-use url::Url;
+use warg_crypto::signing::PublicKey;
 
+pub mod health;
+pub mod metrics;
 pub mod v1;
 
 #[cfg(feature = "debug")]
 pub mod debug;
 
 /// Creates the router for the API.
+///
+/// The router does not serve the metrics endpoint, which may be served on a
+/// separate address, or the health endpoints, which are served while the
+/// server is initializing; see [`metrics::Config`] and [`health::Config`].
+#[allow(clippy::too_many_arguments)]
 pub fn create_router(
-    content_base_url: Url,
+    checkpoint_origin: String,
+    witness_keys: Vec<PublicKey>,
     core: CoreService,
+    content_store: Arc<dyn ContentStore>,
     temp_dir: PathBuf,
     files_dir: PathBuf,
+    tiles_dir: PathBuf,
     content_policy: Option<Arc<dyn ContentPolicy>>,
     record_policy: Option<Arc<dyn RecordPolicy>>,
+    token_store: Option<Arc<dyn TokenStore>>,
+    limits: Limits,
 ) -> Router {
     let router = Router::new();
     #[cfg(feature = "debug")]
@@ -35,15 +55,31 @@ pub fn create_router(
         .nest(
             "/v1",
             v1::create_router(
-                content_base_url,
+                checkpoint_origin,
+                witness_keys,
                 core,
+                content_store,
                 temp_dir,
-                files_dir.clone(),
                 content_policy,
                 record_policy,
+                token_store,
+                limits,
             ),
         )
         .nest_service("/content", ServeDir::new(files_dir))
+        // Tiles never change once written, unlike the checkpoint
+        .nest_service(
+            "/tiles/tile",
+            ServiceBuilder::new()
+                .layer(cache_control("public, max-age=31536000, immutable"))
+                .service(ServeDir::new(tiles_dir.join("tile"))),
+        )
+        .route_service(
+            "/tiles/checkpoint",
+            ServiceBuilder::new()
+                .layer(cache_control("no-cache"))
+                .service(ServeFile::new(tiles_dir.join("checkpoint"))),
+        )
         .layer(
             ServiceBuilder::new()
                 .layer(
@@ -69,3 +105,15 @@ pub fn create_router(
                 ),
         )
 }
+
+/// Sets the `Cache-Control` header of successful responses.
+fn cache_control<B>(
+    value: &'static str,
+) -> SetResponseHeaderLayer<impl Fn(&Response<B>) -> Option<HeaderValue> + Clone> {
+    SetResponseHeaderLayer::overriding(header::CACHE_CONTROL, move |response: &Response<B>| {
+        response
+            .status()
+            .is_success()
+            .then(|| HeaderValue::from_static(value))
+    })
+}
@@ -1,25 +1,21 @@
 use super::{Json, Path, RegistryHeader};
+use crate::contentstore::{ContentStore, ContentStoreError};
 use axum::{
     debug_handler, extract::State, http::StatusCode, response::IntoResponse, routing::get, Router,
 };
 use indexmap::IndexMap;
-use std::path::PathBuf;
-use url::Url;
-use warg_api::v1::content::{ContentError, ContentSource, ContentSourcesResponse};
+use std::sync::Arc;
+use warg_api::v1::content::{ContentError, ContentSourcesResponse};
 use warg_crypto::hash::AnyHash;
 
 #[derive(Clone)]
 pub struct Config {
-    content_base_url: Url,
-    files_dir: PathBuf,
+    content_store: Arc<dyn ContentStore>,
 }
 
 impl Config {
-    pub fn new(content_base_url: Url, files_dir: PathBuf) -> Self {
-        Self {
-            content_base_url,
-            files_dir,
-        }
+    pub fn new(content_store: Arc<dyn ContentStore>) -> Self {
+        Self { content_store }
     }
 
     pub fn into_router(self) -> Router {
@@ -27,31 +23,28 @@ impl Config {
             .route("/:digest", get(get_content))
             .with_state(self)
     }
+}
 
-    fn content_present(&self, digest: &AnyHash) -> bool {
-        self.content_path(digest).is_file()
-    }
-
-    fn content_file_name(&self, digest: &AnyHash) -> String {
-        digest.to_string().replace(':', "-")
-    }
-
-    fn content_path(&self, digest: &AnyHash) -> PathBuf {
-        self.files_dir.join(self.content_file_name(digest))
-    }
+struct ContentApiError(ContentError);
 
-    fn content_url(&self, digest: &AnyHash) -> String {
-        self.content_base_url
-            .join("content/")
-            .unwrap()
-            .join(&self.content_file_name(digest))
-            .unwrap()
-            .to_string()
+impl From<ContentStoreError> for ContentApiError {
+    fn from(e: ContentStoreError) -> Self {
+        Self(match e {
+            ContentStoreError::ContentNotFound(digest) => {
+                ContentError::ContentDigestNotFound(digest)
+            }
+            // Other errors are internal server errors
+            e => {
+                tracing::error!("unexpected content store error: {e}");
+                ContentError::Message {
+                    status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                    message: "an error occurred while processing the request".into(),
+                }
+            }
+        })
     }
 }
 
-struct ContentApiError(ContentError);
-
 impl IntoResponse for ContentApiError {
     fn into_response(self) -> axum::response::Response {
         (StatusCode::from_u16(self.0.status()).unwrap(), Json(self.0)).into_response()
@@ -64,20 +57,10 @@ async fn get_content(
     Path(digest): Path<AnyHash>,
     RegistryHeader(_registry_header): RegistryHeader,
 ) -> Result<Json<ContentSourcesResponse>, ContentApiError> {
-    if !config.content_present(&digest) {
-        return Err(ContentApiError(ContentError::ContentDigestNotFound(digest)));
-    }
+    let sources = config.content_store.content_sources(&digest).await?;
 
     let mut content_sources = IndexMap::with_capacity(1);
-    let url = config.content_url(&digest);
-    content_sources.insert(
-        digest,
-        vec![ContentSource::HttpGet {
-            url,
-            accept_ranges: false,
-            size: None,
-        }],
-    );
+    content_sources.insert(digest, sources);
 
     Ok(Json(ContentSourcesResponse { content_sources }))
 }
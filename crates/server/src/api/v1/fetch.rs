@@ -1,7 +1,7 @@
 use super::{Json, RegistryHeader};
 use crate::datastore::DataStoreError;
 use crate::services::CoreService;
-use axum::http::StatusCode;
+use axum::http::{header, StatusCode};
 use axum::{
     debug_handler,
     extract::State,
@@ -14,7 +14,7 @@ use warg_api::v1::fetch::{
     FetchError, FetchLogsRequest, FetchLogsResponse, FetchPackageNamesRequest,
     FetchPackageNamesResponse, PublishedRecord,
 };
-use warg_crypto::hash::{AnyHash, Sha256};
+use warg_crypto::hash::AnyHash;
 use warg_protocol::registry::{LogId, RecordId, TimestampedCheckpoint};
 use warg_protocol::SerdeEnvelope;
 
@@ -26,16 +26,21 @@ const MAX_PACKAGE_NAMES_LIMIT: usize = 1000;
 #[derive(Clone)]
 pub struct Config {
     core_service: CoreService,
+    checkpoint_origin: String,
 }
 
 impl Config {
-    pub fn new(core_service: CoreService) -> Self {
-        Self { core_service }
+    pub fn new(core_service: CoreService, checkpoint_origin: String) -> Self {
+        Self {
+            core_service,
+            checkpoint_origin,
+        }
     }
 
     pub fn into_router(self) -> Router {
         Router::new()
             .route("/checkpoint", get(fetch_checkpoint))
+            .route("/checkpoint/note", get(fetch_checkpoint_note))
             .route("/logs", post(fetch_logs))
             .route("/names", post(fetch_package_names))
             .with_state(self)
@@ -106,7 +111,7 @@ async fn fetch_logs(
         .core_service
         .store()
         .get_operator_records(
-            &LogId::operator_log::<Sha256>(),
+            &LogId::operator_log_with(config.core_service.hash_algorithm()),
             body.log_length,
             operator_fetch_token.as_ref(),
             limit,
@@ -115,7 +120,11 @@ async fn fetch_logs(
         .into_iter()
         .map(|envelope| {
             // use the record ID as the fetch token
-            let fetch_token = RecordId::operator_record::<Sha256>(&envelope.envelope).to_string();
+            let fetch_token = RecordId::operator_record_with(
+                config.core_service.hash_algorithm(),
+                &envelope.envelope,
+            )
+            .to_string();
             PublishedRecord {
                 envelope: envelope.into(),
                 fetch_token,
@@ -144,8 +153,7 @@ async fn fetch_logs(
             .into_iter()
             .map(|envelope| {
                 // use the record ID as the fetch token
-                let fetch_token =
-                    RecordId::package_record::<Sha256>(&envelope.envelope).to_string();
+                let fetch_token = RecordId::package_record_for_log(&envelope.envelope).to_string();
                 PublishedRecord {
                     envelope: envelope.into(),
                     fetch_token,
@@ -174,6 +182,29 @@ async fn fetch_checkpoint(
     ))
 }
 
+#[debug_handler]
+async fn fetch_checkpoint_note(
+    State(config): State<Config>,
+    RegistryHeader(_registry_header): RegistryHeader,
+) -> Result<impl IntoResponse, FetchApiError> {
+    let checkpoint = config.core_service.store().get_latest_checkpoint().await?;
+    let note = config
+        .core_service
+        .sign_checkpoint_note(&config.checkpoint_origin, &checkpoint.as_ref().checkpoint)
+        .map_err(|e| {
+            tracing::error!("failed to sign checkpoint note: {e}");
+            FetchApiError(FetchError::Message {
+                status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                message: "an error occurred while processing the request".into(),
+            })
+        })?;
+
+    Ok((
+        [(header::CONTENT_TYPE, "text/plain; charset=utf-8")],
+        note.to_string(),
+    ))
+}
+
 #[debug_handler]
 async fn fetch_package_names(
     State(config): State<Config>,
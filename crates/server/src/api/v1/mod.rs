@@ -1,4 +1,8 @@
 use crate::{
+    auth::TokenStore,
+    contentstore::ContentStore,
+    limits::Limits,
+    metrics::Metrics,
     policy::{content::ContentPolicy, record::RecordPolicy},
     services::CoreService,
 };
@@ -7,23 +11,26 @@ use axum::{
     async_trait,
     extract::{
         rejection::{JsonRejection, PathRejection},
-        FromRequest, FromRequestParts,
+        FromRequest, FromRequestParts, MatchedPath, Request, State,
     },
-    http::{request::Parts, StatusCode},
-    response::IntoResponse,
+    http::{header::AUTHORIZATION, request::Parts, StatusCode},
+    middleware::{self, Next},
+    response::{IntoResponse, Response},
     Router,
 };
 use serde::{Serialize, Serializer};
-use std::{path::PathBuf, str::FromStr, sync::Arc};
-use url::Url;
+use std::{path::PathBuf, str::FromStr, sync::Arc, time::Instant};
 use warg_api::v1::REGISTRY_HEADER_NAME;
+use warg_crypto::signing::PublicKey;
 
 pub mod content;
 pub mod fetch;
 pub mod ledger;
 pub mod monitor;
+pub mod operator;
 pub mod package;
 pub mod proof;
+pub mod witness;
 
 /// An extractor that wraps the JSON extractor of Axum.
 ///
@@ -124,33 +131,97 @@ impl FromStr for RegistryHeader {
     }
 }
 
+/// An extractor for the bearer token in the `Authorization` header.
+///
+/// The token is `None` if the header is missing or does not use the
+/// `Bearer` scheme.
+pub struct BearerToken(Option<String>);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for BearerToken
+where
+    S: Send + Sync,
+{
+    type Rejection = std::convert::Infallible;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split_once(' '))
+            .filter(|(scheme, _)| scheme.eq_ignore_ascii_case("bearer"))
+            .map(|(_, token)| token.trim().to_string());
+        Ok(BearerToken(token))
+    }
+}
+
+/// Records the count and duration of requests by route in the server metrics.
+async fn record_request_metrics(
+    State(metrics): State<Arc<Metrics>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_default();
+
+    let start = Instant::now();
+    let response = next.run(request).await;
+    metrics.observe_request(
+        method.as_str(),
+        &route,
+        response.status().as_u16(),
+        start.elapsed(),
+    );
+    response
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn create_router(
-    content_base_url: Url,
+    checkpoint_origin: String,
+    witness_keys: Vec<PublicKey>,
     core: CoreService,
+    content_store: Arc<dyn ContentStore>,
     temp_dir: PathBuf,
-    files_dir: PathBuf,
     content_policy: Option<Arc<dyn ContentPolicy>>,
     record_policy: Option<Arc<dyn RecordPolicy>>,
+    token_store: Option<Arc<dyn TokenStore>>,
+    limits: Limits,
 ) -> Router {
+    let metrics = core.metrics().clone();
     let proof_config = proof::Config::new(core.clone());
     let package_config = package::Config::new(
         core.clone(),
-        files_dir.clone(),
+        content_store.clone(),
         temp_dir,
         content_policy,
         record_policy,
+        token_store,
+        limits,
     );
-    let fetch_config = fetch::Config::new(core.clone());
-    let content_config = content::Config::new(content_base_url, files_dir);
+    let fetch_config = fetch::Config::new(core.clone(), checkpoint_origin);
+    let content_config = content::Config::new(content_store);
     let monitor_config = monitor::Config::new(core.clone());
+    let operator_config = operator::Config::new(core.clone());
+    let witness_config = witness::Config::new(core.clone(), witness_keys);
     let ledger_config = ledger::Config::new(core);
 
     Router::new()
         .nest("/content", content_config.into_router())
         .nest("/fetch", fetch_config.into_router())
         .nest("/ledger", ledger_config.into_router())
+        .nest("/operator", operator_config.into_router())
         .nest("/package", package_config.into_router())
         .nest("/proof", proof_config.into_router())
         .nest("/verify", monitor_config.into_router())
+        .nest("/witness", witness_config.into_router())
+        .route_layer(middleware::from_fn_with_state(
+            metrics,
+            record_request_metrics,
+        ))
         .fallback(not_found)
 }
@@ -4,7 +4,6 @@ use crate::services::CoreService;
 use axum::http::StatusCode;
 use axum::{debug_handler, extract::State, response::IntoResponse, routing::post, Router};
 use warg_api::v1::monitor::{CheckpointVerificationResponse, MonitorError, VerificationState};
-use warg_crypto::hash::Sha256;
 use warg_protocol::registry::{LogId, TimestampedCheckpoint};
 use warg_protocol::SerdeEnvelope;
 
@@ -76,7 +75,10 @@ async fn verify_checkpoint(
         match config
             .core_service
             .store()
-            .verify_timestamped_checkpoint_signature(&LogId::operator_log::<Sha256>(), &body)
+            .verify_timestamped_checkpoint_signature(
+                &LogId::operator_log_with(config.core_service.hash_algorithm()),
+                &body,
+            )
             .await
         {
             Ok(_) => VerificationState::Verified,
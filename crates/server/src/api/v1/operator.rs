@@ -0,0 +1,161 @@
+use super::{Json, Path, RegistryHeader};
+use crate::{
+    datastore::{DataStoreError, RecordStatus},
+    services::CoreService,
+};
+use axum::{
+    debug_handler,
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Router,
+};
+use warg_api::v1::operator::{
+    OperatorError, OperatorRecord, OperatorRecordState, PublishOperatorRecordRequest,
+};
+use warg_protocol::{
+    operator,
+    registry::{LogId, RecordId},
+    ProtoEnvelope,
+};
+
+#[derive(Clone)]
+pub struct Config {
+    core_service: CoreService,
+}
+
+impl Config {
+    pub fn new(core_service: CoreService) -> Self {
+        Self { core_service }
+    }
+
+    pub fn into_router(self) -> Router {
+        Router::new()
+            .route("/record", post(publish_record))
+            .route("/record/:record_id", get(get_record))
+            .with_state(self)
+    }
+}
+
+struct OperatorApiError(OperatorError);
+
+impl OperatorApiError {
+    fn bad_request(message: impl ToString) -> Self {
+        Self(OperatorError::Message {
+            status: StatusCode::BAD_REQUEST.as_u16(),
+            message: message.to_string(),
+        })
+    }
+}
+
+impl From<DataStoreError> for OperatorApiError {
+    fn from(e: DataStoreError) -> Self {
+        Self(match e {
+            DataStoreError::OperatorValidationFailed(e) => {
+                return Self::bad_request(e);
+            }
+            DataStoreError::RecordNotFound(id) => OperatorError::RecordNotFound(id),
+            DataStoreError::UnknownKey(_) | DataStoreError::SignatureVerificationFailed(_) => {
+                OperatorError::Unauthorized(e.to_string())
+            }
+            // Other errors are internal server errors
+            e => {
+                tracing::error!("unexpected data store error: {e}");
+                OperatorError::Message {
+                    status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                    message: "an error occurred while processing the request".into(),
+                }
+            }
+        })
+    }
+}
+
+impl IntoResponse for OperatorApiError {
+    fn into_response(self) -> axum::response::Response {
+        (StatusCode::from_u16(self.0.status()).unwrap(), Json(self.0)).into_response()
+    }
+}
+
+#[debug_handler]
+async fn publish_record(
+    State(config): State<Config>,
+    RegistryHeader(_registry_header): RegistryHeader,
+    Json(body): Json<PublishOperatorRecordRequest<'static>>,
+) -> Result<impl IntoResponse, OperatorApiError> {
+    let log_id = LogId::operator_log_with(config.core_service.hash_algorithm());
+    let record: ProtoEnvelope<operator::OperatorRecord> = body
+        .record
+        .into_owned()
+        .try_into()
+        .map_err(OperatorApiError::bad_request)?;
+
+    // Verify the signature on the record itself before storing it; the
+    // record is validated against the operator log when it is committed
+    config
+        .core_service
+        .store()
+        .verify_operator_record_signature(&log_id, &record)
+        .await?;
+
+    let record_id = RecordId::operator_record_with(config.core_service.hash_algorithm(), &record);
+    config
+        .core_service
+        .store()
+        .store_operator_record(&log_id, &record_id, &record)
+        .await?;
+
+    config
+        .core_service
+        .submit_operator_record(record_id.clone())
+        .await;
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(OperatorRecord {
+            record_id,
+            state: OperatorRecordState::Processing,
+        }),
+    ))
+}
+
+#[debug_handler]
+async fn get_record(
+    State(config): State<Config>,
+    Path(record_id): Path<RecordId>,
+    RegistryHeader(_registry_header): RegistryHeader,
+) -> Result<Json<OperatorRecord>, OperatorApiError> {
+    let record = config
+        .core_service
+        .store()
+        .get_operator_record(
+            &LogId::operator_log_with(config.core_service.hash_algorithm()),
+            &record_id,
+        )
+        .await?;
+
+    match record.status {
+        // Operator records never source content
+        RecordStatus::MissingContent(_) => Err(OperatorApiError(OperatorError::Message {
+            status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+            message: "an error occurred while processing the request".into(),
+        })),
+        // Validated is considered still processing until included in a checkpoint
+        RecordStatus::Pending | RecordStatus::Validated => Ok(Json(OperatorRecord {
+            record_id,
+            state: OperatorRecordState::Processing,
+        })),
+        RecordStatus::Rejected(reason) => Ok(Json(OperatorRecord {
+            record_id,
+            state: OperatorRecordState::Rejected { reason },
+        })),
+        RecordStatus::Published => {
+            let registry_index = record.registry_index.unwrap();
+
+            Ok(Json(OperatorRecord {
+                record_id,
+                state: OperatorRecordState::Published { registry_index },
+            }))
+        }
+    }
+}
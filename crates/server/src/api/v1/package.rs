@@ -1,6 +1,9 @@
-use super::{Json, Path, RegistryHeader};
+use super::{BearerToken, Json, Path, RegistryHeader};
 use crate::{
+    auth::{Identity, TokenStore},
+    contentstore::{ContentStore, ContentStoreError},
     datastore::{DataStoreError, RecordStatus},
+    limits::{Limits, PublishRateLimiter},
     policy::{
         content::{ContentPolicy, ContentPolicyError},
         record::{RecordPolicy, RecordPolicyError},
@@ -17,7 +20,7 @@ use axum::{
     Router,
 };
 use futures::StreamExt;
-use indexmap::IndexMap;
+use indexmap::{IndexMap, IndexSet};
 use std::path::PathBuf;
 use std::sync::Arc;
 use tempfile::NamedTempFile;
@@ -26,39 +29,92 @@ use warg_api::v1::package::{
     MissingContent, PackageError, PackageRecord, PackageRecordState, PublishRecordRequest,
     UploadEndpoint,
 };
-use warg_crypto::hash::{AnyHash, Sha256};
+use warg_crypto::hash::AnyHash;
 use warg_protocol::{
     package,
-    registry::{LogId, RecordId},
+    registry::{LogId, PackageName, RecordId},
     ProtoEnvelope, Record as _,
 };
 
 #[derive(Clone)]
 pub struct Config {
     core_service: CoreService,
-    files_dir: PathBuf,
+    content_store: Arc<dyn ContentStore>,
     temp_dir: PathBuf,
     content_policy: Option<Arc<dyn ContentPolicy>>,
     record_policy: Option<Arc<dyn RecordPolicy>>,
+    token_store: Option<Arc<dyn TokenStore>>,
+    limits: Limits,
+    rate_limiter: Arc<PublishRateLimiter>,
 }
 
 impl Config {
     pub fn new(
         core_service: CoreService,
-        files_dir: PathBuf,
+        content_store: Arc<dyn ContentStore>,
         temp_dir: PathBuf,
         content_policy: Option<Arc<dyn ContentPolicy>>,
         record_policy: Option<Arc<dyn RecordPolicy>>,
+        token_store: Option<Arc<dyn TokenStore>>,
+        limits: Limits,
     ) -> Self {
         Self {
             core_service,
-            files_dir,
+            content_store,
             temp_dir,
             content_policy,
             record_policy,
+            token_store,
+            limits,
+            rate_limiter: Arc::new(PublishRateLimiter::new(&limits)),
         }
     }
 
+    /// Authenticates a publish or content upload request with the given
+    /// bearer token.
+    ///
+    /// Returns `None` if the server does not require authentication.
+    fn authenticate(&self, token: Option<&str>) -> Result<Option<&Identity>, PackageApiError> {
+        let Some(store) = &self.token_store else {
+            return Ok(None);
+        };
+
+        let token = token.ok_or_else(|| {
+            PackageApiError::unauthorized("an authentication token is required to publish")
+        })?;
+
+        store
+            .identity(token)
+            .map(Some)
+            .ok_or_else(|| PackageApiError::unauthorized("the authentication token is not valid"))
+    }
+
+    /// Authorizes an authenticated identity to publish a record to a package.
+    ///
+    /// The identity must hold the key the record is signed with and the record
+    /// policy must authorize the identity for the package; the record policy
+    /// separately decides what the key may publish to.
+    fn authorize(
+        &self,
+        identity: &Identity,
+        name: &PackageName,
+        record: &ProtoEnvelope<package::PackageRecord>,
+    ) -> Result<(), PackageApiError> {
+        let key = record.key_id();
+        if !identity.key_authorized(key) {
+            return Err(PackageApiError::unauthorized(format!(
+                "identity `{identity}` is not authorized to publish records signed by key id `{key}`",
+                identity = identity.name()
+            )));
+        }
+
+        if let Some(policy) = &self.record_policy {
+            policy.check_identity(identity, name)?;
+        }
+
+        Ok(())
+    }
+
     pub fn into_router(self) -> Router {
         Router::new()
             .route("/:log_id/record", post(publish_record))
@@ -70,18 +126,6 @@ impl Config {
             .with_state(self)
     }
 
-    fn content_present(&self, digest: &AnyHash) -> bool {
-        self.content_path(digest).is_file()
-    }
-
-    fn content_file_name(&self, digest: &AnyHash) -> String {
-        digest.to_string().replace(':', "-")
-    }
-
-    fn content_path(&self, digest: &AnyHash) -> PathBuf {
-        self.files_dir.join(self.content_file_name(digest))
-    }
-
     fn build_missing_content<'a>(
         &self,
         log_id: &LogId,
@@ -117,6 +161,10 @@ impl PackageApiError {
         })
     }
 
+    fn unauthorized(message: impl ToString) -> Self {
+        Self(PackageError::Unauthorized(message.to_string()))
+    }
+
     fn internal_error(e: impl std::fmt::Display) -> Self {
         tracing::error!("unexpected error: {e}");
         Self(PackageError::Message {
@@ -146,6 +194,9 @@ impl From<DataStoreError> for PackageApiError {
             }
             DataStoreError::PackageNamespaceNotDefined(id) => PackageError::NamespaceNotDefined(id),
             DataStoreError::PackageNamespaceImported(id) => PackageError::NamespaceImported(id),
+            DataStoreError::PackageNamespaceQuotaExceeded(namespace) => {
+                PackageError::NamespaceQuotaExceeded(namespace)
+            }
             // Other errors are internal server errors
             e => {
                 tracing::error!("unexpected data store error: {e}");
@@ -158,6 +209,12 @@ impl From<DataStoreError> for PackageApiError {
     }
 }
 
+impl From<ContentStoreError> for PackageApiError {
+    fn from(e: ContentStoreError) -> Self {
+        Self::internal_error(e)
+    }
+}
+
 impl From<ContentPolicyError> for PackageApiError {
     fn from(e: ContentPolicyError) -> Self {
         match e {
@@ -186,9 +243,13 @@ async fn publish_record(
     State(config): State<Config>,
     Path(log_id): Path<LogId>,
     RegistryHeader(_registry_header): RegistryHeader,
+    BearerToken(token): BearerToken,
     Json(body): Json<PublishRecordRequest<'static>>,
 ) -> Result<impl IntoResponse, PackageApiError> {
-    let expected_log_id = LogId::package_log::<Sha256>(&body.package_name);
+    let identity = config.authenticate(token.as_deref())?;
+
+    let expected_log_id =
+        LogId::package_log_with(config.core_service.hash_algorithm(), &body.package_name);
     if expected_log_id != log_id {
         return Err(PackageApiError::bad_request(format!(
             "package log identifier `{expected_log_id}` derived from `{name}` does not match provided log identifier `{log_id}`",
@@ -202,6 +263,16 @@ async fn publish_record(
         .try_into()
         .map_err(PackageApiError::bad_request)?;
 
+    if let Some(identity) = identity {
+        config.authorize(identity, &body.package_name, &record)?;
+
+        tracing::debug!(
+            "identity `{name}` is publishing to package `{package}`",
+            name = identity.name(),
+            package = body.package_name
+        );
+    }
+
     // Specifying content sources is not allowed in this implementation
     if !body.content_sources.is_empty() {
         return Err(PackageApiError::unsupported(
@@ -212,10 +283,11 @@ async fn publish_record(
     // Verify the package name is unique in a case insensitive way and
     // the namespace is defined in the operator log and not imported
     // from another registry.
+    let operator_log_id = LogId::operator_log_with(config.core_service.hash_algorithm());
     config
         .core_service
         .store()
-        .verify_can_publish_package(&LogId::operator_log::<Sha256>(), &body.package_name)
+        .verify_can_publish_package(&operator_log_id, &body.package_name)
         .await?;
 
     // Preemptively perform the policy check on the record before storing it
@@ -231,15 +303,31 @@ async fn publish_record(
         .verify_package_record_signature(&log_id, &record)
         .await?;
 
-    let record_id = RecordId::package_record::<Sha256>(&record);
-    let mut missing = record.as_ref().contents();
-    missing.retain(|d| !config.content_present(d));
+    let record_id = RecordId::package_record_for_log(&record);
+    let mut missing = IndexSet::new();
+    for digest in record.as_ref().contents() {
+        if !config.content_store.content_present(digest).await? {
+            missing.insert(digest);
+        }
+    }
 
+    // Only count accepted publishes against the key's rate limit; the publish
+    // is released again if the record cannot be stored
+    let key = record.key_id();
     config
+        .rate_limiter
+        .check(key)
+        .map_err(|message| PackageApiError(PackageError::RateLimitExceeded(message)))?;
+
+    if let Err(e) = config
         .core_service
         .store()
         .store_package_record(&log_id, &body.package_name, &record_id, &record, &missing)
-        .await?;
+        .await
+    {
+        config.rate_limiter.release(key);
+        return Err(e.into());
+    }
 
     // If there's no missing content, submit the record for processing now
     if missing.is_empty() {
@@ -312,8 +400,11 @@ async fn upload_content(
     State(config): State<Config>,
     Path((log_id, record_id, digest)): Path<(LogId, RecordId, AnyHash)>,
     RegistryHeader(_registry_header): RegistryHeader,
+    BearerToken(token): BearerToken,
     body: Body,
 ) -> Result<impl IntoResponse, PackageApiError> {
+    let identity = config.authenticate(token.as_deref())?;
+
     match config
         .core_service
         .store()
@@ -332,6 +423,25 @@ async fn upload_content(
         Err(e) => return Err(e.into()),
     }
 
+    let name = config
+        .core_service
+        .store()
+        .get_package_names(std::slice::from_ref(&log_id))
+        .await?
+        .swap_remove(&log_id)
+        .flatten()
+        .ok_or_else(|| PackageApiError(PackageError::LogNotFound(log_id.clone())))?;
+
+    // Only an identity that may publish the record may upload its content
+    if let Some(identity) = identity {
+        let record = config
+            .core_service
+            .store()
+            .get_package_record(&log_id, &record_id)
+            .await?;
+        config.authorize(identity, &name, &record.envelope)?;
+    }
+
     let tmp_path = NamedTempFile::new_in(&config.temp_dir)
         .map_err(PackageApiError::internal_error)?
         .into_temp_path();
@@ -346,6 +456,7 @@ async fn upload_content(
         &digest,
         body.into_data_stream(),
         config.content_policy.as_deref(),
+        config.limits.max_content_size(),
     )
     .await;
 
@@ -363,19 +474,46 @@ async fn upload_content(
     }
 
     // Only persist the file if the content was successfully processed
-    res?;
+    let size = res?;
+
+    // Reserve the size of the content in the storage quota of the package's
+    // namespace; the reservation is released if the content is not stored
+    let namespace = name.namespace().to_ascii_lowercase();
+    let store = config.core_service.store();
+    store
+        .add_namespace_usage(&namespace, size, config.limits.namespace_quota())
+        .await?;
 
-    tmp_path
-        .persist(config.content_path(&digest))
-        .map_err(PackageApiError::internal_error)?;
+    let res = async {
+        config
+            .content_store
+            .store_content(&digest, tmp_path)
+            .await?;
+        config.core_service.metrics().content_stored(size);
+
+        Ok::<_, PackageApiError>(
+            store
+                .set_content_present(&log_id, &record_id, &digest)
+                .await?,
+        )
+    }
+    .await;
+
+    let complete = match res {
+        Ok(complete) => complete,
+        Err(e) => {
+            if let Err(release) = store.release_namespace_usage(&namespace, size).await {
+                tracing::error!(
+                    "failed to release {size} byte(s) of storage of namespace `{namespace}`: \
+                     {release}"
+                );
+            }
+            return Err(e);
+        }
+    };
 
     // If this is the last content needed, submit the record for processing now
-    if config
-        .core_service
-        .store()
-        .set_content_present(&log_id, &record_id, &digest)
-        .await?
-    {
+    if complete {
         config
             .core_service
             .submit_package_record(log_id, record_id.clone())
@@ -390,13 +528,15 @@ async fn process_content(
     digest: &AnyHash,
     mut stream: BodyDataStream,
     policy: Option<&dyn ContentPolicy>,
-) -> Result<(), PackageApiError> {
+    max_size: Option<u64>,
+) -> Result<u64, PackageApiError> {
     let mut tmp_file = tokio::fs::File::create(&path)
         .await
         .map_err(PackageApiError::internal_error)?;
 
     let mut hasher = digest.algorithm().hasher();
     let mut policy = policy.map(|p| p.new_stream_policy(digest)).transpose()?;
+    let mut size = 0u64;
 
     while let Some(chunk) = stream
         .next()
@@ -404,6 +544,11 @@ async fn process_content(
         .transpose()
         .map_err(PackageApiError::internal_error)?
     {
+        size += chunk.len() as u64;
+        if let Some(max_size) = max_size.filter(|max_size| size > *max_size) {
+            return Err(PackageApiError(PackageError::ContentTooLarge(max_size)));
+        }
+
         if let Some(policy) = policy.as_mut() {
             policy.check(&chunk)?;
         }
@@ -426,5 +571,5 @@ async fn process_content(
         policy.finalize()?;
     }
 
-    Ok(())
+    Ok(size)
 }
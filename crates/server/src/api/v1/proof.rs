@@ -4,7 +4,8 @@ use axum::{
     debug_handler, extract::State, http::StatusCode, response::IntoResponse, routing::post, Router,
 };
 use warg_api::v1::proof::{
-    ConsistencyRequest, ConsistencyResponse, InclusionRequest, InclusionResponse, ProofError,
+    ConsistencyRequest, ConsistencyResponse, InclusionRequest, InclusionResponse, MapProofFormat,
+    ProofError,
 };
 use warg_protocol::registry::{RegistryIndex, RegistryLen};
 
@@ -37,6 +38,7 @@ impl From<CoreServiceError> for ProofApiError {
             CoreServiceError::LeafNotFound(leaf) => ProofError::LeafNotFound(leaf),
             CoreServiceError::BundleFailure(e) => ProofError::BundleFailure(e.to_string()),
             CoreServiceError::PackageNotIncluded(id) => ProofError::PackageLogNotIncluded(id),
+            CoreServiceError::PackageIncluded(id) => ProofError::PackageLogIncluded(id),
             CoreServiceError::IncorrectProof { root, found } => {
                 ProofError::IncorrectProof { root, found }
             }
@@ -86,11 +88,39 @@ async fn prove_inclusion(
         .map(|index| index as RegistryIndex)
         .collect::<Vec<RegistryIndex>>();
 
-    let log_bundle = config.core.log_inclusion_proofs(log_length, &leafs).await?;
-    let map_bundle = config.core.map_inclusion_proofs(log_length, &leafs).await?;
+    // A request for only absence proofs has no log inclusion proofs to bundle
+    let log = if leafs.is_empty() && !body.absent.is_empty() {
+        Vec::new()
+    } else {
+        config
+            .core
+            .log_inclusion_proofs(log_length, &leafs)
+            .await?
+            .encode()
+    };
+
+    // Absence proofs can only be bundled
+    let map_format = if body.absent.is_empty() {
+        body.map_format
+    } else {
+        MapProofFormat::Bundle
+    };
+    let map = match map_format {
+        MapProofFormat::Bundle => config
+            .core
+            .map_inclusion_proofs(log_length, &leafs, &body.absent)
+            .await?
+            .encode(),
+        MapProofFormat::Multiproof => config
+            .core
+            .map_inclusion_multiproof(log_length, &leafs)
+            .await?
+            .encode(),
+    };
 
     Ok(Json(InclusionResponse {
-        log: log_bundle.encode(),
-        map: map_bundle.encode(),
+        log,
+        map,
+        map_format,
     }))
 }
@@ -0,0 +1,221 @@
+use super::{Json, Path, RegistryHeader};
+use crate::datastore::DataStoreError;
+use crate::services::{CoreService, CoreServiceError};
+use axum::{
+    debug_handler,
+    extract::State,
+    http::StatusCode,
+    response::IntoResponse,
+    routing::{get, post},
+    Router,
+};
+use std::sync::Arc;
+use warg_api::v1::witness::{
+    CheckpointCosignaturesResponse, CosignCheckpointRequest, CosignedCheckpoint,
+    CosignedCheckpointsResponse, WitnessCheckpointResponse, WitnessError,
+};
+use warg_crypto::signing::{KeyID, PublicKey};
+use warg_protocol::registry::RegistryLen;
+
+// The number of most recently cosigned checkpoints served to clients.
+const COSIGNED_CHECKPOINTS_LIMIT: usize = 10;
+
+#[derive(Clone)]
+pub struct Config {
+    core_service: CoreService,
+    witness_keys: Arc<Vec<PublicKey>>,
+}
+
+impl Config {
+    pub fn new(core_service: CoreService, witness_keys: Vec<PublicKey>) -> Self {
+        Self {
+            core_service,
+            witness_keys: Arc::new(witness_keys),
+        }
+    }
+
+    pub fn into_router(self) -> Router {
+        Router::new()
+            .route("/cosign", post(cosign_checkpoint))
+            .route(
+                "/checkpoint/:log_length/cosignatures",
+                get(get_checkpoint_cosignatures),
+            )
+            .route("/cosigned", get(get_cosigned_checkpoints))
+            .route("/key/:key_id/checkpoint", get(get_witness_checkpoint))
+            .with_state(self)
+    }
+}
+
+struct WitnessApiError(WitnessError);
+
+impl From<DataStoreError> for WitnessApiError {
+    fn from(e: DataStoreError) -> Self {
+        Self(match e {
+            DataStoreError::CheckpointNotFound(log_length) => {
+                WitnessError::CheckpointNotFound(log_length)
+            }
+            e => {
+                tracing::error!("unexpected data store error: {e}");
+                WitnessError::Message {
+                    status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                    message: "an error occurred while processing the request".into(),
+                }
+            }
+        })
+    }
+}
+
+impl IntoResponse for WitnessApiError {
+    fn into_response(self) -> axum::response::Response {
+        (StatusCode::from_u16(self.0.status()).unwrap(), Json(self.0)).into_response()
+    }
+}
+
+/// Stores a witness cosignature of a checkpoint.
+///
+/// The cosignature must be made by one of the configured witness keys over
+/// the checkpoint of the registry's timestamped checkpoint; the timestamp is
+/// not cosigned as the registry re-signs its latest checkpoint periodically.
+///
+/// The request names the log length of the last checkpoint cosigned by the
+/// witness, which must match the latest checkpoint stored with a cosignature
+/// of the witness. The cosigned checkpoint must be consistent with it, so a
+/// witness never cosigns a checkpoint that is older than or forks from one it
+/// cosigned before.
+#[debug_handler]
+async fn cosign_checkpoint(
+    State(config): State<Config>,
+    RegistryHeader(_registry_header): RegistryHeader,
+    Json(body): Json<CosignCheckpointRequest<'static>>,
+) -> Result<Json<CheckpointCosignaturesResponse>, WitnessApiError> {
+    let cosignature = body.cosignature.into_owned();
+    let key = config
+        .witness_keys
+        .iter()
+        .find(|k| k.fingerprint() == cosignature.key_id)
+        .ok_or_else(|| {
+            WitnessApiError(WitnessError::Unauthorized(format!(
+                "key id `{key_id}` is not a witness of the registry",
+                key_id = cosignature.key_id
+            )))
+        })?;
+
+    let store = config.core_service.store();
+    let ts_checkpoint = store.get_checkpoint(body.log_length).await?;
+    let checkpoint = &ts_checkpoint.as_ref().checkpoint;
+    checkpoint
+        .verify_cosignature(key, &cosignature)
+        .map_err(|_| {
+            WitnessApiError(WitnessError::Unauthorized(format!(
+                "cosignature by key id `{key_id}` is not valid for checkpoint log length `{log_length}`",
+                key_id = cosignature.key_id,
+                log_length = body.log_length
+            )))
+        })?;
+
+    let old_log_length = store
+        .get_latest_cosigned_log_length(&cosignature.key_id)
+        .await?;
+    if body.old_log_length != old_log_length {
+        return Err(WitnessApiError(WitnessError::Conflict(
+            match old_log_length {
+                Some(old_log_length) => format!(
+                    "the last checkpoint cosigned by key id `{key_id}` has log length `{old_log_length}`",
+                    key_id = cosignature.key_id
+                ),
+                None => format!(
+                    "key id `{key_id}` has not cosigned a checkpoint",
+                    key_id = cosignature.key_id
+                ),
+            },
+        )));
+    }
+
+    if let Some(old_log_length) = old_log_length {
+        let old = store.get_checkpoint(old_log_length).await?;
+        config
+            .core_service
+            .verify_log_consistency(&old.as_ref().checkpoint, checkpoint)
+            .await
+            .map_err(|e| match e {
+                CoreServiceError::InconsistentCheckpoints { .. } => {
+                    WitnessApiError(WitnessError::Conflict(e.to_string()))
+                }
+                e => {
+                    tracing::error!("failed to verify checkpoint consistency: {e}");
+                    WitnessApiError(WitnessError::Message {
+                        status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                        message: "an error occurred while processing the request".into(),
+                    })
+                }
+            })?;
+    }
+
+    store
+        .store_checkpoint_cosignature(body.log_length, &cosignature)
+        .await?;
+
+    Ok(Json(CheckpointCosignaturesResponse {
+        log_length: body.log_length,
+        cosignatures: store.get_checkpoint_cosignatures(body.log_length).await?,
+    }))
+}
+
+/// Gets the latest checkpoint cosigned by a witness key, so that the witness
+/// can verify the checkpoint it cosigns next is consistent with it.
+#[debug_handler]
+async fn get_witness_checkpoint(
+    State(config): State<Config>,
+    Path(key_id): Path<KeyID>,
+    RegistryHeader(_registry_header): RegistryHeader,
+) -> Result<Json<WitnessCheckpointResponse>, WitnessApiError> {
+    let store = config.core_service.store();
+    let checkpoint = match store.get_latest_cosigned_log_length(&key_id).await? {
+        Some(log_length) => Some(CosignedCheckpoint {
+            checkpoint: store.get_checkpoint(log_length).await?,
+            cosignatures: store.get_checkpoint_cosignatures(log_length).await?,
+        }),
+        None => None,
+    };
+
+    Ok(Json(WitnessCheckpointResponse { checkpoint }))
+}
+
+#[debug_handler]
+async fn get_checkpoint_cosignatures(
+    State(config): State<Config>,
+    Path(log_length): Path<RegistryLen>,
+    RegistryHeader(_registry_header): RegistryHeader,
+) -> Result<Json<CheckpointCosignaturesResponse>, WitnessApiError> {
+    Ok(Json(CheckpointCosignaturesResponse {
+        log_length,
+        cosignatures: config
+            .core_service
+            .store()
+            .get_checkpoint_cosignatures(log_length)
+            .await?,
+    }))
+}
+
+/// Gets the most recently cosigned checkpoints, so that clients can fall back
+/// to one when the latest checkpoint has not been cosigned yet.
+#[debug_handler]
+async fn get_cosigned_checkpoints(
+    State(config): State<Config>,
+    RegistryHeader(_registry_header): RegistryHeader,
+) -> Result<Json<CosignedCheckpointsResponse>, WitnessApiError> {
+    let store = config.core_service.store();
+    let mut checkpoints = Vec::new();
+    for log_length in store
+        .get_cosigned_checkpoint_log_lengths(COSIGNED_CHECKPOINTS_LIMIT)
+        .await?
+    {
+        checkpoints.push(CosignedCheckpoint {
+            checkpoint: store.get_checkpoint(log_length).await?,
+            cosignatures: store.get_checkpoint_cosignatures(log_length).await?,
+        });
+    }
+
+    Ok(Json(CosignedCheckpointsResponse { checkpoints }))
+}
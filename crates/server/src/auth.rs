@@ -0,0 +1,214 @@
+//! Module for server authentication.
+use anyhow::{bail, Result};
+use indexmap::{IndexMap, IndexSet};
+use serde::Deserialize;
+use warg_crypto::{
+    hash::{AnyHash, Hash, HashAlgorithm, Sha256},
+    signing::KeyID,
+};
+
+/// Represents an identity that bearer tokens authenticate as.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Identity {
+    name: String,
+    keys: IndexSet<KeyID>,
+}
+
+impl Identity {
+    /// Gets the name of the identity.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Determines if the identity may publish records signed with the given
+    /// key.
+    pub fn key_authorized(&self, key: &KeyID) -> bool {
+        self.keys.contains(key)
+    }
+}
+
+/// Implemented by token stores.
+///
+/// A token store maps the bearer tokens of API requests to the identities
+/// they authenticate as.
+pub trait TokenStore: Send + Sync {
+    /// Gets the identity the given bearer token authenticates as.
+    ///
+    /// Returns `None` if the token is not known.
+    fn identity(&self, token: &str) -> Option<&Identity>;
+}
+
+/// A token store with a fixed set of identities and tokens.
+///
+/// Only the SHA-256 digests of the tokens are kept, so the store may be
+/// loaded from a file without that file holding the tokens themselves.
+#[derive(Default, Deserialize)]
+#[serde(try_from = "StaticTokenStoreFile")]
+pub struct StaticTokenStore {
+    identities: IndexMap<String, Identity>,
+    tokens: IndexMap<AnyHash, String>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StaticTokenStoreFile {
+    #[serde(default, rename = "identity")]
+    identities: IndexMap<String, IdentityEntry>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IdentityEntry {
+    // SHA-256 digests of the bearer tokens of the identity.
+    #[serde(default)]
+    tokens: Vec<AnyHash>,
+    // Key IDs the identity may sign published records with.
+    #[serde(default)]
+    keys: IndexSet<KeyID>,
+}
+
+impl TryFrom<StaticTokenStoreFile> for StaticTokenStore {
+    type Error = anyhow::Error;
+
+    fn try_from(file: StaticTokenStoreFile) -> Result<Self> {
+        let mut store = Self::new();
+        for (name, entry) in file.identities {
+            for key in entry.keys {
+                store = store.with_key(&name, key);
+            }
+            for digest in entry.tokens {
+                store = store.with_token_digest(&name, digest)?;
+            }
+        }
+        Ok(store)
+    }
+}
+
+impl StaticTokenStore {
+    /// Creates a new static token store.
+    ///
+    /// By default, no tokens are known.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a bearer token that authenticates as the given identity.
+    pub fn with_token(self, identity: impl Into<String>, token: &str) -> Result<Self> {
+        self.with_token_digest(identity, token_digest(token))
+    }
+
+    /// Adds the SHA-256 digest of a bearer token that authenticates as the
+    /// given identity.
+    pub fn with_token_digest(
+        mut self,
+        identity: impl Into<String>,
+        digest: AnyHash,
+    ) -> Result<Self> {
+        if digest.algorithm() != HashAlgorithm::Sha256 {
+            bail!("token digest `{digest}` is not a SHA-256 digest");
+        }
+
+        let identity = identity.into();
+        if let Some(existing) = self.tokens.get(&digest) {
+            if *existing != identity {
+                bail!(
+                    "token digest `{digest}` is used by both identity `{existing}` and identity \
+                     `{identity}`"
+                );
+            }
+        }
+
+        self.identity_or_default_mut(&identity);
+        self.tokens.insert(digest, identity);
+        Ok(self)
+    }
+
+    /// Authorizes the given identity to publish records signed with a key.
+    pub fn with_key(mut self, identity: impl Into<String>, key: KeyID) -> Self {
+        self.identity_or_default_mut(&identity.into())
+            .keys
+            .insert(key);
+        self
+    }
+
+    fn identity_or_default_mut(&mut self, identity: &str) -> &mut Identity {
+        self.identities
+            .entry(identity.to_string())
+            .or_insert_with(|| Identity {
+                name: identity.to_string(),
+                keys: IndexSet::new(),
+            })
+    }
+}
+
+impl TokenStore for StaticTokenStore {
+    fn identity(&self, token: &str) -> Option<&Identity> {
+        let name = self.tokens.get(&token_digest(token))?;
+        self.identities.get(name)
+    }
+}
+
+/// Gets the SHA-256 digest a token store keeps for the given token.
+fn token_digest(token: &str) -> AnyHash {
+    Hash::<Sha256>::of(token.as_bytes()).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warg_crypto::signing::generate_p256_pair;
+
+    #[test]
+    fn it_authenticates_known_tokens() -> Result<()> {
+        let (alice_key, _) = generate_p256_pair();
+        let (bob_key, _) = generate_p256_pair();
+        let store = StaticTokenStore::new()
+            .with_token("alice", "alice-token")?
+            .with_key("alice", alice_key.fingerprint())
+            .with_token("bob", "bob-token")?
+            .with_key("bob", bob_key.fingerprint());
+
+        let alice = store.identity("alice-token").unwrap();
+        assert_eq!(alice.name(), "alice");
+        assert!(alice.key_authorized(&alice_key.fingerprint()));
+        assert!(!alice.key_authorized(&bob_key.fingerprint()));
+
+        let bob = store.identity("bob-token").unwrap();
+        assert_eq!(bob.name(), "bob");
+        assert!(bob.key_authorized(&bob_key.fingerprint()));
+
+        assert!(store.identity("unknown-token").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn it_rejects_a_token_shared_by_identities() -> Result<()> {
+        let err = StaticTokenStore::new()
+            .with_token("alice", "token")?
+            .with_token("bob", "token")
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("identity `alice`"), "{err}");
+        Ok(())
+    }
+
+    #[test]
+    fn it_loads_from_toml() -> Result<()> {
+        let (key, _) = generate_p256_pair();
+        let store: StaticTokenStore = toml::from_str(&format!(
+            r#"
+            [identity.alice]
+            tokens = ["{digest}"]
+            keys = ["{key}"]
+            "#,
+            digest = token_digest("alice-token"),
+            key = key.fingerprint(),
+        ))?;
+
+        let alice = store.identity("alice-token").unwrap();
+        assert_eq!(alice.name(), "alice");
+        assert!(alice.key_authorized(&key.fingerprint()));
+        assert!(store.identity("bob-token").is_none());
+        Ok(())
+    }
+}
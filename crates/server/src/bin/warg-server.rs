@@ -1,22 +1,35 @@
 use anyhow::{Context, Result};
 use clap::{Parser, ValueEnum};
 use secrecy::SecretString;
-use std::{net::SocketAddr, path::PathBuf};
+use std::{net::SocketAddr, path::PathBuf, time::Duration};
 use tokio::signal;
 use tracing_subscriber::filter::LevelFilter;
 use url::Url;
-use warg_crypto::signing::PrivateKey;
-use warg_protocol::operator;
-use warg_server::{args::get_opt_secret, policy::record::AuthorizedKeyPolicy, Config, Server};
+use warg_crypto::signing::{PrivateKey, PublicKey};
+use warg_protocol::{operator, registry::RegistryLen};
+use warg_server::{
+    args::get_opt_secret, auth::StaticTokenStore, limits::Limits,
+    policy::record::AuthorizedKeyPolicy, Config, Server,
+};
 
 #[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
 enum DataStoreKind {
     #[cfg(feature = "postgres")]
     Postgres,
+    #[cfg(feature = "sqlite")]
+    Sqlite,
     #[default]
     Memory,
 }
 
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum ContentStoreKind {
+    #[default]
+    Filesystem,
+    #[cfg(feature = "s3")]
+    S3,
+}
+
 #[derive(Parser, Debug)]
 struct Args {
     /// Use verbose output
@@ -27,6 +40,11 @@ struct Args {
     #[arg(short, long, env = "WARG_LISTEN", default_value = "0.0.0.0:8090")]
     listen: SocketAddr,
 
+    /// Address to serve the metrics endpoint on; defaults to serving it at
+    /// `/metrics` on the listen address.
+    #[arg(long, env = "WARG_METRICS_LISTEN")]
+    metrics_listen: Option<SocketAddr>,
+
     /// The content storage directory to use.
     #[arg(long, env = "WARG_CONTENT_DIR")]
     content_dir: PathBuf,
@@ -35,28 +53,108 @@ struct Args {
     #[arg(long, env = "WARG_CONTENT_BASE_URL")]
     content_base_url: Option<Url>,
 
+    /// The origin of the registry log in checkpoint notes; defaults to the
+    /// content base URL without its scheme.
+    #[arg(long, env = "WARG_CHECKPOINT_ORIGIN")]
+    checkpoint_origin: Option<String>,
+
+    /// The public keys of witnesses allowed to cosign checkpoints.
+    #[arg(long = "witness-key", env = "WARG_WITNESS_KEYS", value_delimiter = ',')]
+    witness_keys: Vec<PublicKey>,
+
+    /// The content store to use for the server.
+    #[arg(long, env = "WARG_CONTENT_STORE", default_value = "filesystem")]
+    content_store: ContentStoreKind,
+
+    /// The S3 endpoint URL if content-store is set to s3.
+    #[cfg(feature = "s3")]
+    #[arg(long, env = "WARG_S3_ENDPOINT", required_if_eq("content_store", "s3"))]
+    s3_endpoint: Option<Url>,
+
+    /// The S3 bucket to store content in if content-store is set to s3.
+    #[cfg(feature = "s3")]
+    #[arg(long, env = "WARG_S3_BUCKET", required_if_eq("content_store", "s3"))]
+    s3_bucket: Option<String>,
+
+    /// The region of the S3 bucket.
+    #[cfg(feature = "s3")]
+    #[arg(long, env = "WARG_S3_REGION", default_value = "us-east-1")]
+    s3_region: String,
+
+    /// The prefix of the keys of objects stored in the S3 bucket.
+    #[cfg(feature = "s3")]
+    #[arg(long, env = "WARG_S3_KEY_PREFIX")]
+    s3_key_prefix: Option<String>,
+
+    /// The S3 access key ID.
+    #[cfg(feature = "s3")]
+    #[arg(
+        long,
+        env = "WARG_S3_ACCESS_KEY_ID",
+        required_if_eq("content_store", "s3")
+    )]
+    s3_access_key_id: Option<String>,
+
+    /// The S3 secret access key.
+    ///
+    /// Prefer using `s3-secret-access-key-file`, or environment variable
+    /// variation, to avoid exposing sensitive information.
+    #[cfg(feature = "s3")]
+    #[arg(long, env = "WARG_S3_SECRET_ACCESS_KEY")]
+    s3_secret_access_key: Option<SecretString>,
+
+    /// The path to the S3 secret access key file.
+    #[cfg(feature = "s3")]
+    #[arg(
+        long,
+        env = "WARG_S3_SECRET_ACCESS_KEY_FILE",
+        conflicts_with = "s3_secret_access_key"
+    )]
+    s3_secret_access_key_file: Option<PathBuf>,
+
     /// The data store to use for the server.
     #[arg(long, env = "WARG_DATA_STORE", default_value = "memory")]
     data_store: DataStoreKind,
 
-    /// The database connection URL if data-store is set to postgres.
+    /// The path to a journal file to persist the memory data store to;
+    /// defaults to not persisting the memory data store.
+    #[arg(long, env = "WARG_JOURNAL_FILE")]
+    journal_file: Option<PathBuf>,
+
+    /// The database connection URL if data-store is set to postgres, or the
+    /// path to the database file if data-store is set to sqlite.
     ///
     /// Prefer using `database-url-file`, or environment variable variation,
     /// to avoid exposing sensitive information.
-    #[cfg(feature = "postgres")]
+    #[cfg(any(feature = "postgres", feature = "sqlite"))]
     #[arg(long, env = "WARG_DATABASE_URL")]
     database_url: Option<SecretString>,
 
     /// The path to the database connection URL file.
-    #[cfg(feature = "postgres")]
+    #[cfg(any(feature = "postgres", feature = "sqlite"))]
     #[arg(long, env = "WARG_DATABASE_URL_FILE", conflicts_with = "database_url")]
     database_url_file: Option<PathBuf>,
 
     /// Run database migrations
-    #[cfg(feature = "postgres")]
+    #[cfg(any(feature = "postgres", feature = "sqlite"))]
     #[arg(long)]
     database_run_migrations: bool,
 
+    /// The number of log entries between stored snapshots of the
+    /// transparency state, which are used to speed up startup.
+    #[arg(long, env = "WARG_SNAPSHOT_INTERVAL")]
+    snapshot_interval: Option<RegistryLen>,
+
+    /// The number of checkpoint intervals the latest checkpoint may be older
+    /// than before `/readyz` reports that the server is not ready.
+    #[arg(long, env = "WARG_MAX_CHECKPOINT_STALENESS")]
+    max_checkpoint_staleness: Option<u32>,
+
+    /// The directory to store the registry log in; defaults to keeping the
+    /// log in memory.
+    #[arg(long, env = "WARG_LOG_STORAGE_DIR")]
+    log_storage_dir: Option<PathBuf>,
+
     /// The operator key.
     ///
     /// Prefer using `operator-key-file`, or environment variable variation.
@@ -71,6 +169,32 @@ struct Args {
     #[arg(long, env = "WARG_AUTHORIZED_KEYS_FILE")]
     authorized_keys_file: Option<PathBuf>,
 
+    /// The path to the auth tokens file.
+    ///
+    /// If set, publishing requires a bearer token of an identity in the file.
+    #[arg(long, env = "WARG_AUTH_TOKENS_FILE")]
+    auth_tokens_file: Option<PathBuf>,
+
+    /// The number of records each signing key may publish per publish rate
+    /// window; defaults to no limit.
+    ///
+    /// Publishes are counted by each server replica separately.
+    #[arg(long, env = "WARG_PUBLISH_RATE_LIMIT")]
+    publish_rate_limit: Option<usize>,
+
+    /// The duration of the publish rate window, in seconds.
+    #[arg(long, env = "WARG_PUBLISH_RATE_WINDOW", default_value = "60")]
+    publish_rate_window: u64,
+
+    /// The maximum size of uploaded content, in bytes; defaults to no limit.
+    #[arg(long, env = "WARG_MAX_CONTENT_SIZE")]
+    max_content_size: Option<u64>,
+
+    /// The total size of content that may be uploaded to each namespace, in
+    /// bytes; defaults to no limit.
+    #[arg(long, env = "WARG_NAMESPACE_QUOTA")]
+    namespace_quota: Option<u64>,
+
     /// The initial namespace defined for this registry.
     #[arg(long, env = "WARG_NAMESPACE")]
     namespace: Option<String>,
@@ -108,10 +232,34 @@ async fn main() -> Result<()> {
         .with_addr(args.listen)
         .with_shutdown(shutdown_signal());
 
+    if let Some(addr) = args.metrics_listen {
+        config = config.with_metrics_addr(addr);
+    }
+
     if let Some(url) = args.content_base_url {
         config = config.with_content_base_url(url);
     }
 
+    if let Some(origin) = args.checkpoint_origin {
+        config = config.with_checkpoint_origin(origin);
+    }
+
+    if !args.witness_keys.is_empty() {
+        config = config.with_witness_keys(args.witness_keys);
+    }
+
+    if let Some(interval) = args.snapshot_interval {
+        config = config.with_snapshot_interval(interval);
+    }
+
+    if let Some(intervals) = args.max_checkpoint_staleness {
+        config = config.with_max_checkpoint_staleness(intervals);
+    }
+
+    if let Some(dir) = args.log_storage_dir {
+        config = config.with_log_storage_dir(dir);
+    }
+
     if let Some(path) = args.authorized_keys_file {
         let authorized_keys_data = std::fs::read_to_string(&path)
             .with_context(|| format!("failed to read authorized keys from {path:?}"))?;
@@ -120,6 +268,53 @@ async fn main() -> Result<()> {
         config = config.with_record_policy(authorized_key_policy);
     }
 
+    if let Some(path) = args.auth_tokens_file {
+        let auth_tokens_data = std::fs::read_to_string(&path)
+            .with_context(|| format!("failed to read auth tokens from {path:?}"))?;
+        let token_store: StaticTokenStore = toml::from_str(&auth_tokens_data)
+            .with_context(|| format!("failed to decode auth tokens from {path:?}"))?;
+        config = config.with_token_store(token_store);
+    }
+
+    let mut limits = Limits::new();
+    if let Some(records) = args.publish_rate_limit {
+        limits = limits.with_publish_rate(records, Duration::from_secs(args.publish_rate_window));
+    }
+    if let Some(size) = args.max_content_size {
+        limits = limits.with_max_content_size(size);
+    }
+    if let Some(size) = args.namespace_quota {
+        limits = limits.with_namespace_quota(size);
+    }
+    config = config.with_limits(limits);
+
+    let config = match args.content_store {
+        #[cfg(feature = "s3")]
+        ContentStoreKind::S3 => {
+            use secrecy::ExposeSecret;
+            use warg_server::contentstore::S3ContentStore;
+            tracing::info!("using s3 content store");
+            let secret_access_key = get_opt_secret(
+                "s3-secret-access-key",
+                args.s3_secret_access_key_file,
+                args.s3_secret_access_key,
+            )?;
+            let mut store = S3ContentStore::new(
+                args.s3_endpoint.context("an S3 endpoint is required")?,
+                args.s3_bucket.context("an S3 bucket is required")?,
+                args.s3_access_key_id
+                    .context("an S3 access key ID is required")?,
+                secret_access_key.expose_secret().trim().to_string().into(),
+            )
+            .with_region(args.s3_region);
+            if let Some(prefix) = args.s3_key_prefix {
+                store = store.with_key_prefix(prefix);
+            }
+            config.with_content_store(store)
+        }
+        ContentStoreKind::Filesystem => config,
+    };
+
     let config = match args.data_store {
         #[cfg(feature = "postgres")]
         DataStoreKind::Postgres => {
@@ -134,10 +329,34 @@ async fn main() -> Result<()> {
             }
             config.with_data_store(pg_store)
         }
-        DataStoreKind::Memory => {
-            tracing::info!("using memory data store");
-            config
+        #[cfg(feature = "sqlite")]
+        DataStoreKind::Sqlite => {
+            use secrecy::ExposeSecret;
+            use warg_server::datastore::SqliteDataStore;
+            tracing::info!("using sqlite data store");
+            let database_url =
+                get_opt_secret("database-url", args.database_url_file, args.database_url)?;
+            let sqlite_store = SqliteDataStore::new(database_url.expose_secret())?;
+            if args.database_run_migrations {
+                tracing::info!("running any pending database migration(s)");
+                sqlite_store.run_pending_migrations().await?;
+            }
+            config.with_data_store(sqlite_store)
         }
+        DataStoreKind::Memory => match args.journal_file {
+            Some(path) => {
+                use warg_server::datastore::MemoryDataStore;
+                tracing::info!("using memory data store with journal file {path:?}");
+                let store = MemoryDataStore::with_journal(&path)
+                    .await
+                    .with_context(|| format!("failed to open journal file {path:?}"))?;
+                config.with_data_store(store)
+            }
+            None => {
+                tracing::info!("using memory data store");
+                config
+            }
+        },
     };
 
     Server::new(config).run().await
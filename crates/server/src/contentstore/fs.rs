@@ -0,0 +1,72 @@
+use super::{content_name, ContentStore, ContentStoreError};
+use std::{io, path::PathBuf};
+use tempfile::TempPath;
+use url::Url;
+use warg_api::v1::content::ContentSource;
+use warg_crypto::hash::AnyHash;
+
+/// Represents a content store that keeps content in a local directory.
+///
+/// The directory is expected to be served at the given base URL, as the
+/// server does for its `files` content directory.
+pub struct FileSystemContentStore {
+    files_dir: PathBuf,
+    base_url: Url,
+}
+
+impl FileSystemContentStore {
+    /// Creates a new file system content store for the given directory and
+    /// the URL it is served at.
+    pub fn new(files_dir: impl Into<PathBuf>, base_url: Url) -> Self {
+        Self {
+            files_dir: files_dir.into(),
+            base_url,
+        }
+    }
+
+    fn content_path(&self, digest: &AnyHash) -> PathBuf {
+        self.files_dir.join(content_name(digest))
+    }
+
+    fn content_url(&self, digest: &AnyHash) -> String {
+        self.base_url
+            .join(&content_name(digest))
+            .unwrap()
+            .to_string()
+    }
+}
+
+#[axum::async_trait]
+impl ContentStore for FileSystemContentStore {
+    async fn content_present(&self, digest: &AnyHash) -> Result<bool, ContentStoreError> {
+        match tokio::fs::metadata(self.content_path(digest)).await {
+            Ok(metadata) => Ok(metadata.is_file()),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    async fn store_content(
+        &self,
+        digest: &AnyHash,
+        file: TempPath,
+    ) -> Result<(), ContentStoreError> {
+        file.persist(self.content_path(digest))
+            .map_err(|e| ContentStoreError::Io(e.error))
+    }
+
+    async fn content_sources(
+        &self,
+        digest: &AnyHash,
+    ) -> Result<Vec<ContentSource>, ContentStoreError> {
+        if !self.content_present(digest).await? {
+            return Err(ContentStoreError::ContentNotFound(digest.clone()));
+        }
+
+        Ok(vec![ContentSource::HttpGet {
+            url: self.content_url(digest),
+            accept_ranges: false,
+            size: None,
+        }])
+    }
+}
@@ -0,0 +1,69 @@
+//! Module for server content store implementations.
+use tempfile::TempPath;
+use thiserror::Error;
+use warg_api::v1::content::ContentSource;
+use warg_crypto::hash::AnyHash;
+
+mod fs;
+#[cfg(feature = "s3")]
+mod s3;
+
+pub use fs::*;
+#[cfg(feature = "s3")]
+pub use s3::*;
+
+/// Represents a content store error.
+#[derive(Debug, Error)]
+pub enum ContentStoreError {
+    #[error("content with digest `{0}` was not found")]
+    ContentNotFound(AnyHash),
+
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[cfg(feature = "s3")]
+    #[error("failed to send a request to the object store: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[cfg(feature = "s3")]
+    #[error("failed to sign a request to the object store: {0}")]
+    Signing(Box<dyn std::error::Error + Send + Sync>),
+
+    #[cfg(feature = "s3")]
+    #[error("the object store responded with status {status}: {message}")]
+    ObjectStore { status: u16, message: String },
+}
+
+/// Implemented by content stores.
+///
+/// A content store holds the content of package records, keyed by digest,
+/// and provides the sources clients download it from.
+#[axum::async_trait]
+pub trait ContentStore: Send + Sync {
+    /// Determines if the content with the given digest is present.
+    async fn content_present(&self, digest: &AnyHash) -> Result<bool, ContentStoreError>;
+
+    /// Stores the content with the given digest from a temporary file.
+    ///
+    /// The content of the file has already been validated against the
+    /// digest and the content policy.
+    async fn store_content(
+        &self,
+        digest: &AnyHash,
+        file: TempPath,
+    ) -> Result<(), ContentStoreError>;
+
+    /// Gets the sources to download the content with the given digest from.
+    ///
+    /// Returns [`ContentStoreError::ContentNotFound`] if the content is not
+    /// present.
+    async fn content_sources(
+        &self,
+        digest: &AnyHash,
+    ) -> Result<Vec<ContentSource>, ContentStoreError>;
+}
+
+/// Gets the name content is stored under for the given digest.
+fn content_name(digest: &AnyHash) -> String {
+    digest.to_string().replace(':', "-")
+}
@@ -0,0 +1,422 @@
+use super::{DataStore, DataStoreError, Record, RecordCounts, Snapshot};
+use crate::metrics::Metrics;
+use futures::{Future, Stream};
+use indexmap::{IndexMap, IndexSet};
+use std::{pin::Pin, sync::Arc, time::Instant};
+use warg_crypto::{hash::AnyHash, signing::KeyID};
+use warg_protocol::{
+    operator, package,
+    registry::{
+        LogId, LogLeaf, PackageName, RecordId, RegistryIndex, RegistryLen, TimestampedCheckpoint,
+    },
+    Cosignature, ProtoEnvelope, PublishedProtoEnvelope, SerdeEnvelope,
+};
+
+/// A data store that records the duration of each operation of another data
+/// store in the server metrics.
+pub struct InstrumentedDataStore {
+    inner: Box<dyn DataStore>,
+    metrics: Arc<Metrics>,
+}
+
+impl InstrumentedDataStore {
+    /// Creates a new instrumented data store wrapping the given data store.
+    pub fn new(inner: Box<dyn DataStore>, metrics: Arc<Metrics>) -> Self {
+        Self { inner, metrics }
+    }
+
+    async fn time<T>(&self, operation: &'static str, fut: impl Future<Output = T>) -> T {
+        let start = Instant::now();
+        let result = fut.await;
+        self.metrics
+            .observe_datastore_operation(operation, start.elapsed());
+        result
+    }
+}
+
+#[axum::async_trait]
+impl DataStore for InstrumentedDataStore {
+    async fn get_all_checkpoints(
+        &self,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<TimestampedCheckpoint, DataStoreError>> + Send>>,
+        DataStoreError,
+    > {
+        self.inner.get_all_checkpoints().await
+    }
+
+    async fn get_all_validated_records(
+        &self,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>
+    {
+        self.inner.get_all_validated_records().await
+    }
+
+    async fn get_log_leafs_with_registry_index(
+        &self,
+        entries: &[RegistryIndex],
+    ) -> Result<Vec<LogLeaf>, DataStoreError> {
+        self.time(
+            "get_log_leafs_with_registry_index",
+            self.inner.get_log_leafs_with_registry_index(entries),
+        )
+        .await
+    }
+
+    async fn get_queued_records(&self) -> Result<Vec<LogLeaf>, DataStoreError> {
+        self.time("get_queued_records", self.inner.get_queued_records())
+            .await
+    }
+
+    async fn store_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+    ) -> Result<(), DataStoreError> {
+        self.time(
+            "store_operator_record",
+            self.inner.store_operator_record(log_id, record_id, record),
+        )
+        .await
+    }
+
+    async fn reject_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        reason: &str,
+    ) -> Result<(), DataStoreError> {
+        self.time(
+            "reject_operator_record",
+            self.inner.reject_operator_record(log_id, record_id, reason),
+        )
+        .await
+    }
+
+    async fn commit_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        registry_index: RegistryIndex,
+    ) -> Result<(), DataStoreError> {
+        self.time(
+            "commit_operator_record",
+            self.inner
+                .commit_operator_record(log_id, record_id, registry_index),
+        )
+        .await
+    }
+
+    async fn store_package_record(
+        &self,
+        log_id: &LogId,
+        package_name: &PackageName,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<package::PackageRecord>,
+        missing: &IndexSet<&AnyHash>,
+    ) -> Result<(), DataStoreError> {
+        self.time(
+            "store_package_record",
+            self.inner
+                .store_package_record(log_id, package_name, record_id, record, missing),
+        )
+        .await
+    }
+
+    async fn reject_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        reason: &str,
+    ) -> Result<(), DataStoreError> {
+        self.time(
+            "reject_package_record",
+            self.inner.reject_package_record(log_id, record_id, reason),
+        )
+        .await
+    }
+
+    async fn commit_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        registry_index: RegistryIndex,
+    ) -> Result<(), DataStoreError> {
+        self.time(
+            "commit_package_record",
+            self.inner
+                .commit_package_record(log_id, record_id, registry_index),
+        )
+        .await
+    }
+
+    async fn is_content_missing(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+    ) -> Result<bool, DataStoreError> {
+        self.time(
+            "is_content_missing",
+            self.inner.is_content_missing(log_id, record_id, digest),
+        )
+        .await
+    }
+
+    async fn set_content_present(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+    ) -> Result<bool, DataStoreError> {
+        self.time(
+            "set_content_present",
+            self.inner.set_content_present(log_id, record_id, digest),
+        )
+        .await
+    }
+
+    async fn store_checkpoint(
+        &self,
+        checkpoint_id: &AnyHash,
+        ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<(), DataStoreError> {
+        self.time(
+            "store_checkpoint",
+            self.inner.store_checkpoint(checkpoint_id, ts_checkpoint),
+        )
+        .await
+    }
+
+    async fn get_latest_checkpoint(
+        &self,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+        self.time("get_latest_checkpoint", self.inner.get_latest_checkpoint())
+            .await
+    }
+
+    async fn get_checkpoint(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+        self.time("get_checkpoint", self.inner.get_checkpoint(log_length))
+            .await
+    }
+
+    async fn store_checkpoint_cosignature(
+        &self,
+        log_length: RegistryLen,
+        cosignature: &Cosignature,
+    ) -> Result<(), DataStoreError> {
+        self.time(
+            "store_checkpoint_cosignature",
+            self.inner
+                .store_checkpoint_cosignature(log_length, cosignature),
+        )
+        .await
+    }
+
+    async fn get_checkpoint_cosignatures(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<Vec<Cosignature>, DataStoreError> {
+        self.time(
+            "get_checkpoint_cosignatures",
+            self.inner.get_checkpoint_cosignatures(log_length),
+        )
+        .await
+    }
+
+    async fn get_cosigned_checkpoint_log_lengths(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<RegistryLen>, DataStoreError> {
+        self.time(
+            "get_cosigned_checkpoint_log_lengths",
+            self.inner.get_cosigned_checkpoint_log_lengths(limit),
+        )
+        .await
+    }
+
+    async fn get_latest_cosigned_log_length(
+        &self,
+        key_id: &KeyID,
+    ) -> Result<Option<RegistryLen>, DataStoreError> {
+        self.time(
+            "get_latest_cosigned_log_length",
+            self.inner.get_latest_cosigned_log_length(key_id),
+        )
+        .await
+    }
+
+    async fn store_snapshot(&self, snapshot: Snapshot) -> Result<(), DataStoreError> {
+        self.time("store_snapshot", self.inner.store_snapshot(snapshot))
+            .await
+    }
+
+    async fn get_latest_snapshot(&self) -> Result<Option<Snapshot>, DataStoreError> {
+        self.time("get_latest_snapshot", self.inner.get_latest_snapshot())
+            .await
+    }
+
+    async fn add_namespace_usage(
+        &self,
+        namespace: &str,
+        size: u64,
+        quota: Option<u64>,
+    ) -> Result<(), DataStoreError> {
+        self.time(
+            "add_namespace_usage",
+            self.inner.add_namespace_usage(namespace, size, quota),
+        )
+        .await
+    }
+
+    async fn release_namespace_usage(
+        &self,
+        namespace: &str,
+        size: u64,
+    ) -> Result<(), DataStoreError> {
+        self.time(
+            "release_namespace_usage",
+            self.inner.release_namespace_usage(namespace, size),
+        )
+        .await
+    }
+
+    async fn get_record_counts(&self) -> Result<RecordCounts, DataStoreError> {
+        self.time("get_record_counts", self.inner.get_record_counts())
+            .await
+    }
+
+    async fn get_package_names(
+        &self,
+        log_ids: &[LogId],
+    ) -> Result<IndexMap<LogId, Option<PackageName>>, DataStoreError> {
+        self.time("get_package_names", self.inner.get_package_names(log_ids))
+            .await
+    }
+
+    async fn get_log_leafs_starting_with_registry_index(
+        &self,
+        starting_index: RegistryIndex,
+        limit: usize,
+    ) -> Result<Vec<(RegistryIndex, LogLeaf)>, DataStoreError> {
+        self.time(
+            "get_log_leafs_starting_with_registry_index",
+            self.inner
+                .get_log_leafs_starting_with_registry_index(starting_index, limit),
+        )
+        .await
+    }
+
+    async fn get_operator_records(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+        since: Option<&RecordId>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<operator::OperatorRecord>>, DataStoreError> {
+        self.time(
+            "get_operator_records",
+            self.inner
+                .get_operator_records(log_id, registry_log_length, since, limit),
+        )
+        .await
+    }
+
+    async fn get_package_records(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+        since: Option<&RecordId>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError> {
+        self.time(
+            "get_package_records",
+            self.inner
+                .get_package_records(log_id, registry_log_length, since, limit),
+        )
+        .await
+    }
+
+    async fn get_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Record<operator::OperatorRecord>, DataStoreError> {
+        self.time(
+            "get_operator_record",
+            self.inner.get_operator_record(log_id, record_id),
+        )
+        .await
+    }
+
+    async fn get_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Record<package::PackageRecord>, DataStoreError> {
+        self.time(
+            "get_package_record",
+            self.inner.get_package_record(log_id, record_id),
+        )
+        .await
+    }
+
+    async fn verify_operator_record_signature(
+        &self,
+        log_id: &LogId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+    ) -> Result<(), DataStoreError> {
+        self.time(
+            "verify_operator_record_signature",
+            self.inner.verify_operator_record_signature(log_id, record),
+        )
+        .await
+    }
+
+    async fn verify_package_record_signature(
+        &self,
+        log_id: &LogId,
+        record: &ProtoEnvelope<package::PackageRecord>,
+    ) -> Result<(), DataStoreError> {
+        self.time(
+            "verify_package_record_signature",
+            self.inner.verify_package_record_signature(log_id, record),
+        )
+        .await
+    }
+
+    async fn verify_can_publish_package(
+        &self,
+        operator_log_id: &LogId,
+        package_name: &PackageName,
+    ) -> Result<(), DataStoreError> {
+        self.time(
+            "verify_can_publish_package",
+            self.inner
+                .verify_can_publish_package(operator_log_id, package_name),
+        )
+        .await
+    }
+
+    async fn verify_timestamped_checkpoint_signature(
+        &self,
+        operator_log_id: &LogId,
+        ts_checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<(), DataStoreError> {
+        self.time(
+            "verify_timestamped_checkpoint_signature",
+            self.inner
+                .verify_timestamped_checkpoint_signature(operator_log_id, ts_checkpoint),
+        )
+        .await
+    }
+
+    #[cfg(feature = "debug")]
+    async fn debug_list_package_names(&self) -> anyhow::Result<Vec<PackageName>> {
+        self.inner.debug_list_package_names().await
+    }
+}
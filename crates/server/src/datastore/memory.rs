@@ -1,18 +1,21 @@
-use super::{DataStore, DataStoreError};
+use super::{DataStore, DataStoreError, RecordCounts, Snapshot};
 use futures::Stream;
 use indexmap::{IndexMap, IndexSet};
-use std::{pin::Pin, sync::Arc};
+use journal::{Journal, JournalEntry};
+use std::{path::Path, pin::Pin, sync::Arc};
 use tokio::sync::RwLock;
-use warg_crypto::{hash::AnyHash, Encode, Signable};
+use warg_crypto::{hash::AnyHash, signing::KeyID, Encode, Signable};
 use warg_protocol::{
     operator,
     package::{self, PackageEntry},
     registry::{
         LogId, LogLeaf, PackageName, RecordId, RegistryIndex, RegistryLen, TimestampedCheckpoint,
     },
-    ProtoEnvelope, PublishedProtoEnvelope, SerdeEnvelope,
+    Cosignature, ProtoEnvelope, PublishedProtoEnvelope, SerdeEnvelope,
 };
 
+mod journal;
+
 struct Entry<R> {
     registry_index: RegistryIndex,
     record_content: ProtoEnvelope<R>,
@@ -75,13 +78,33 @@ struct State {
     packages: IndexMap<LogId, Log<package::LogState, package::PackageRecord>>,
     package_names: IndexMap<LogId, Option<PackageName>>,
     checkpoints: IndexMap<RegistryLen, SerdeEnvelope<TimestampedCheckpoint>>,
+    checkpoint_cosignatures: IndexMap<RegistryLen, IndexMap<KeyID, Cosignature>>,
     records: IndexMap<LogId, IndexMap<RecordId, RecordStatus>>,
     log_leafs: IndexMap<RegistryIndex, LogLeaf>,
+    // The pending records that are ready to be committed, in order
+    queue: IndexSet<LogLeaf>,
+    snapshot: Option<Snapshot>,
+    // The content storage used by each namespace, in bytes
+    namespace_usage: IndexMap<String, u64>,
+    journal: Option<Journal>,
+}
+
+// Appends an entry to the journal of the data store, if it has one.
+fn append(
+    journal: &mut Option<Journal>,
+    entry: impl FnOnce() -> JournalEntry,
+) -> Result<(), DataStoreError> {
+    if let Some(journal) = journal {
+        journal.append(&entry()).map_err(DataStoreError::Journal)?;
+    }
+
+    Ok(())
 }
 
 /// Represents an in-memory data store.
 ///
-/// Data is not persisted between restarts of the server.
+/// Data is not persisted between restarts of the server unless the data
+/// store is created with a journal (see [`MemoryDataStore::with_journal`]).
 ///
 /// Note: this is mainly used for testing, so it is not very efficient as
 /// it shares a single RwLock for all operations.
@@ -91,6 +114,132 @@ impl MemoryDataStore {
     pub fn new() -> Self {
         Self(Arc::new(RwLock::new(State::default())))
     }
+
+    /// Creates a new in-memory data store that is persisted to a journal
+    /// file at the given path.
+    ///
+    /// Every record, record status change and checkpoint of a new log length
+    /// is appended to the journal, and an existing journal is replayed to
+    /// restore the data store. Snapshots are not journaled; the records are
+    /// replayed instead when the server restarts.
+    ///
+    /// Unlike a data store without a journal, the stored checkpoints and
+    /// validated records are returned to restore the transparency state.
+    pub async fn with_journal(path: impl AsRef<Path>) -> Result<Self, DataStoreError> {
+        let (journal, entries) = Journal::open(path.as_ref()).map_err(DataStoreError::Journal)?;
+        let store = Self::new();
+        for entry in entries {
+            match store.replay(entry).await {
+                // Commits of invalid records are journaled as they reject the record
+                Ok(())
+                | Err(DataStoreError::OperatorValidationFailed(_))
+                | Err(DataStoreError::PackageValidationFailed(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        store.0.write().await.journal = Some(journal);
+        Ok(store)
+    }
+
+    // Replays a journal entry by making the change it records.
+    async fn replay(&self, entry: JournalEntry) -> Result<(), DataStoreError> {
+        match entry {
+            JournalEntry::StoreOperatorRecord {
+                log_id,
+                record_id,
+                record,
+            } => {
+                let record = ProtoEnvelope::try_from(record).map_err(|e| {
+                    DataStoreError::InvalidRecordContents {
+                        record_id: record_id.clone(),
+                        message: e.to_string(),
+                    }
+                })?;
+                self.store_operator_record(&log_id, &record_id, &record)
+                    .await
+            }
+            JournalEntry::RejectOperatorRecord {
+                log_id,
+                record_id,
+                reason,
+            } => {
+                self.reject_operator_record(&log_id, &record_id, &reason)
+                    .await
+            }
+            JournalEntry::CommitOperatorRecord {
+                log_id,
+                record_id,
+                registry_index,
+            } => {
+                self.commit_operator_record(&log_id, &record_id, registry_index)
+                    .await
+            }
+            JournalEntry::StorePackageRecord {
+                log_id,
+                package_name,
+                record_id,
+                record,
+                missing,
+            } => {
+                let record = ProtoEnvelope::try_from(record).map_err(|e| {
+                    DataStoreError::InvalidRecordContents {
+                        record_id: record_id.clone(),
+                        message: e.to_string(),
+                    }
+                })?;
+                self.store_package_record(
+                    &log_id,
+                    &package_name,
+                    &record_id,
+                    &record,
+                    &missing.iter().collect(),
+                )
+                .await
+            }
+            JournalEntry::RejectPackageRecord {
+                log_id,
+                record_id,
+                reason,
+            } => {
+                self.reject_package_record(&log_id, &record_id, &reason)
+                    .await
+            }
+            JournalEntry::CommitPackageRecord {
+                log_id,
+                record_id,
+                registry_index,
+            } => {
+                self.commit_package_record(&log_id, &record_id, registry_index)
+                    .await
+            }
+            JournalEntry::SetContentPresent {
+                log_id,
+                record_id,
+                digest,
+            } => self
+                .set_content_present(&log_id, &record_id, &digest)
+                .await
+                .map(|_| ()),
+            JournalEntry::StoreCheckpoint {
+                checkpoint_id,
+                checkpoint,
+            } => self.store_checkpoint(&checkpoint_id, checkpoint).await,
+            JournalEntry::StoreCheckpointCosignature {
+                log_length,
+                cosignature,
+            } => {
+                self.store_checkpoint_cosignature(log_length, &cosignature)
+                    .await
+            }
+            JournalEntry::AddNamespaceUsage { namespace, size } => {
+                self.add_namespace_usage(&namespace, size, None).await
+            }
+            JournalEntry::ReleaseNamespaceUsage { namespace, size } => {
+                self.release_namespace_usage(&namespace, size).await
+            }
+        }
+    }
 }
 
 impl Default for MemoryDataStore {
@@ -107,14 +256,37 @@ impl DataStore for MemoryDataStore {
         Pin<Box<dyn Stream<Item = Result<TimestampedCheckpoint, DataStoreError>> + Send>>,
         DataStoreError,
     > {
-        Ok(Box::pin(futures::stream::empty()))
+        // Only a journaled data store outlives the server, so the transparency
+        // state starts empty for a data store without a journal
+        let state = self.0.read().await;
+        if state.journal.is_none() {
+            return Ok(Box::pin(futures::stream::empty()));
+        }
+
+        let checkpoints = state
+            .checkpoints
+            .values()
+            .map(|checkpoint| Ok(checkpoint.as_ref().clone()))
+            .collect::<Vec<_>>();
+        Ok(Box::pin(futures::stream::iter(checkpoints)))
     }
 
     async fn get_all_validated_records(
         &self,
     ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>
     {
-        Ok(Box::pin(futures::stream::empty()))
+        let state = self.0.read().await;
+        if state.journal.is_none() {
+            return Ok(Box::pin(futures::stream::empty()));
+        }
+
+        // Log leafs are inserted in registry log order as records are committed
+        let leafs = state
+            .log_leafs
+            .values()
+            .map(|leaf| Ok(leaf.clone()))
+            .collect::<Vec<_>>();
+        Ok(Box::pin(futures::stream::iter(leafs)))
     }
 
     async fn get_log_leafs_starting_with_registry_index(
@@ -158,6 +330,95 @@ impl DataStore for MemoryDataStore {
         Ok(leafs)
     }
 
+    async fn get_queued_records(&self) -> Result<Vec<LogLeaf>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state.queue.iter().cloned().collect())
+    }
+
+    async fn store_snapshot(&self, snapshot: Snapshot) -> Result<(), DataStoreError> {
+        let mut state = self.0.write().await;
+        state.snapshot = Some(snapshot);
+        Ok(())
+    }
+
+    async fn get_latest_snapshot(&self) -> Result<Option<Snapshot>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state.snapshot.clone())
+    }
+
+    async fn add_namespace_usage(
+        &self,
+        namespace: &str,
+        size: u64,
+        quota: Option<u64>,
+    ) -> Result<(), DataStoreError> {
+        let mut state = self.0.write().await;
+        let usage = state
+            .namespace_usage
+            .get(namespace)
+            .copied()
+            .unwrap_or_default()
+            .saturating_add(size);
+        if quota.is_some_and(|quota| usage > quota) {
+            return Err(DataStoreError::PackageNamespaceQuotaExceeded(
+                namespace.to_string(),
+            ));
+        }
+
+        append(&mut state.journal, || JournalEntry::AddNamespaceUsage {
+            namespace: namespace.to_string(),
+            size,
+        })?;
+
+        state.namespace_usage.insert(namespace.to_string(), usage);
+        Ok(())
+    }
+
+    async fn release_namespace_usage(
+        &self,
+        namespace: &str,
+        size: u64,
+    ) -> Result<(), DataStoreError> {
+        let mut state = self.0.write().await;
+        append(&mut state.journal, || JournalEntry::ReleaseNamespaceUsage {
+            namespace: namespace.to_string(),
+            size,
+        })?;
+
+        if let Some(usage) = state.namespace_usage.get_mut(namespace) {
+            *usage = usage.saturating_sub(size);
+        }
+        Ok(())
+    }
+
+    async fn get_record_counts(&self) -> Result<RecordCounts, DataStoreError> {
+        let state = self.0.read().await;
+        let published_length = state
+            .checkpoints
+            .last()
+            .map(|(_, c)| c.as_ref().checkpoint.log_length)
+            .unwrap_or_default();
+
+        let mut counts = RecordCounts::default();
+        for status in state.records.values().flat_map(IndexMap::values) {
+            match status {
+                RecordStatus::Pending(PendingRecord::Package { missing, .. })
+                    if !missing.is_empty() =>
+                {
+                    counts.missing_content += 1
+                }
+                RecordStatus::Pending(_) => counts.pending += 1,
+                RecordStatus::Rejected(_) => counts.rejected += 1,
+                RecordStatus::Validated(r) if r.registry_index < published_length => {
+                    counts.published += 1
+                }
+                RecordStatus::Validated(_) => counts.validated += 1,
+            }
+        }
+
+        Ok(counts)
+    }
+
     async fn get_package_names(
         &self,
         log_ids: &[LogId],
@@ -183,14 +444,28 @@ impl DataStore for MemoryDataStore {
         record: &ProtoEnvelope<operator::OperatorRecord>,
     ) -> Result<(), DataStoreError> {
         let mut state = self.0.write().await;
-        let prev = state.records.entry(log_id.clone()).or_default().insert(
+        let State {
+            records, journal, ..
+        } = &mut *state;
+
+        let log = records.entry(log_id.clone()).or_default();
+        assert!(!log.contains_key(record_id));
+        append(journal, || JournalEntry::StoreOperatorRecord {
+            log_id: log_id.clone(),
+            record_id: record_id.clone(),
+            record: record.clone().into(),
+        })?;
+
+        log.insert(
             record_id.clone(),
             RecordStatus::Pending(PendingRecord::Operator {
                 record: Some(record.clone()),
             }),
         );
-
-        assert!(prev.is_none());
+        state.queue.insert(LogLeaf {
+            log_id: log_id.clone(),
+            record_id: record_id.clone(),
+        });
         Ok(())
     }
 
@@ -201,23 +476,38 @@ impl DataStore for MemoryDataStore {
         reason: &str,
     ) -> Result<(), DataStoreError> {
         let mut state = self.0.write().await;
+        let State {
+            records,
+            queue,
+            journal,
+            ..
+        } = &mut *state;
 
-        let status = state
-            .records
+        let status = records
             .get_mut(log_id)
             .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
             .get_mut(record_id)
             .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
 
         let record = match status {
-            RecordStatus::Pending(PendingRecord::Operator { record }) => record.take().unwrap(),
+            RecordStatus::Pending(PendingRecord::Operator { record }) => record,
             _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
         };
 
+        append(journal, || JournalEntry::RejectOperatorRecord {
+            log_id: log_id.clone(),
+            record_id: record_id.clone(),
+            reason: reason.to_string(),
+        })?;
+
         *status = RecordStatus::Rejected(RejectedRecord::Operator {
-            record,
+            record: record.take().unwrap(),
             reason: reason.to_string(),
         });
+        queue.shift_remove(&LogLeaf {
+            log_id: log_id.clone(),
+            record_id: record_id.clone(),
+        });
 
         Ok(())
     }
@@ -234,6 +524,8 @@ impl DataStore for MemoryDataStore {
             operators,
             records,
             log_leafs,
+            queue,
+            journal,
             ..
         } = &mut *state;
 
@@ -245,7 +537,18 @@ impl DataStore for MemoryDataStore {
 
         match status {
             RecordStatus::Pending(PendingRecord::Operator { record }) => {
+                append(journal, || JournalEntry::CommitOperatorRecord {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                    registry_index,
+                })?;
+
                 let record = record.take().unwrap();
+                let leaf = LogLeaf {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                };
+                queue.shift_remove(&leaf);
                 let log = operators.entry(log_id.clone()).or_default();
                 match log
                     .state
@@ -264,13 +567,7 @@ impl DataStore for MemoryDataStore {
                             index,
                             registry_index,
                         });
-                        log_leafs.insert(
-                            registry_index,
-                            LogLeaf {
-                                log_id: log_id.clone(),
-                                record_id: record_id.clone(),
-                            },
-                        );
+                        log_leafs.insert(registry_index, leaf);
                         Ok(())
                     }
                     Err(e) => {
@@ -302,7 +599,21 @@ impl DataStore for MemoryDataStore {
         });
 
         let mut state = self.0.write().await;
-        let prev = state.records.entry(log_id.clone()).or_default().insert(
+        let State {
+            records, journal, ..
+        } = &mut *state;
+
+        let log = records.entry(log_id.clone()).or_default();
+        assert!(!log.contains_key(record_id));
+        append(journal, || JournalEntry::StorePackageRecord {
+            log_id: log_id.clone(),
+            package_name: package_name.clone(),
+            record_id: record_id.clone(),
+            record: record.clone().into(),
+            missing: missing.iter().map(|&d| d.clone()).collect(),
+        })?;
+
+        log.insert(
             record_id.clone(),
             RecordStatus::Pending(PendingRecord::Package {
                 record: Some(record.clone()),
@@ -313,7 +624,12 @@ impl DataStore for MemoryDataStore {
             .package_names
             .insert(log_id.clone(), Some(package_name.clone()));
 
-        assert!(prev.is_none());
+        if missing.is_empty() {
+            state.queue.insert(LogLeaf {
+                log_id: log_id.clone(),
+                record_id: record_id.clone(),
+            });
+        }
         Ok(())
     }
 
@@ -324,23 +640,38 @@ impl DataStore for MemoryDataStore {
         reason: &str,
     ) -> Result<(), DataStoreError> {
         let mut state = self.0.write().await;
+        let State {
+            records,
+            queue,
+            journal,
+            ..
+        } = &mut *state;
 
-        let status = state
-            .records
+        let status = records
             .get_mut(log_id)
             .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
             .get_mut(record_id)
             .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
 
         let record = match status {
-            RecordStatus::Pending(PendingRecord::Package { record, .. }) => record.take().unwrap(),
+            RecordStatus::Pending(PendingRecord::Package { record, .. }) => record,
             _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
         };
 
+        append(journal, || JournalEntry::RejectPackageRecord {
+            log_id: log_id.clone(),
+            record_id: record_id.clone(),
+            reason: reason.to_string(),
+        })?;
+
         *status = RecordStatus::Rejected(RejectedRecord::Package {
-            record,
+            record: record.take().unwrap(),
             reason: reason.to_string(),
         });
+        queue.shift_remove(&LogLeaf {
+            log_id: log_id.clone(),
+            record_id: record_id.clone(),
+        });
 
         Ok(())
     }
@@ -357,6 +688,8 @@ impl DataStore for MemoryDataStore {
             packages,
             records,
             log_leafs,
+            queue,
+            journal,
             ..
         } = &mut *state;
 
@@ -368,7 +701,18 @@ impl DataStore for MemoryDataStore {
 
         match status {
             RecordStatus::Pending(PendingRecord::Package { record, .. }) => {
+                append(journal, || JournalEntry::CommitPackageRecord {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                    registry_index,
+                })?;
+
                 let record = record.take().unwrap();
+                let leaf = LogLeaf {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                };
+                queue.shift_remove(&leaf);
                 let log = packages.entry(log_id.clone()).or_default();
                 match log
                     .state
@@ -387,13 +731,7 @@ impl DataStore for MemoryDataStore {
                             index,
                             registry_index,
                         });
-                        log_leafs.insert(
-                            registry_index,
-                            LogLeaf {
-                                log_id: log_id.clone(),
-                                record_id: record_id.clone(),
-                            },
-                        );
+                        log_leafs.insert(registry_index, leaf);
                         Ok(())
                     }
                     Err(e) => {
@@ -444,8 +782,14 @@ impl DataStore for MemoryDataStore {
         digest: &AnyHash,
     ) -> Result<bool, DataStoreError> {
         let mut state = self.0.write().await;
-        let log = state
-            .records
+        let State {
+            records,
+            queue,
+            journal,
+            ..
+        } = &mut *state;
+
+        let log = records
             .get_mut(log_id)
             .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
 
@@ -459,13 +803,27 @@ impl DataStore for MemoryDataStore {
                 Ok(false)
             }
             RecordStatus::Pending(PendingRecord::Package { missing, .. }) => {
-                if missing.is_empty() {
+                if !missing.contains(digest) {
                     return Ok(false);
                 }
 
+                append(journal, || JournalEntry::SetContentPresent {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                    digest: digest.clone(),
+                })?;
+
                 // Return true if this was the last missing content
                 missing.swap_remove(digest);
-                Ok(missing.is_empty())
+                if !missing.is_empty() {
+                    return Ok(false);
+                }
+
+                queue.insert(LogLeaf {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                });
+                Ok(true)
             }
             _ => return Err(DataStoreError::RecordNotPending(record_id.clone())),
         }
@@ -473,14 +831,23 @@ impl DataStore for MemoryDataStore {
 
     async fn store_checkpoint(
         &self,
-        _checkpoint_id: &AnyHash,
+        checkpoint_id: &AnyHash,
         ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
     ) -> Result<(), DataStoreError> {
         let mut state = self.0.write().await;
+        let log_length = ts_checkpoint.as_ref().checkpoint.log_length;
+
+        // A checkpoint is signed on every interval even when the log has not
+        // grown; the journaled checkpoint of a log length is still valid after
+        // a restart, so an idle registry does not append to the journal
+        if !state.checkpoints.contains_key(&log_length) {
+            append(&mut state.journal, || JournalEntry::StoreCheckpoint {
+                checkpoint_id: checkpoint_id.clone(),
+                checkpoint: ts_checkpoint.clone(),
+            })?;
+        }
 
-        state
-            .checkpoints
-            .insert(ts_checkpoint.as_ref().checkpoint.log_length, ts_checkpoint);
+        state.checkpoints.insert(log_length, ts_checkpoint);
 
         Ok(())
     }
@@ -505,6 +872,77 @@ impl DataStore for MemoryDataStore {
         Ok(checkpoint.clone())
     }
 
+    async fn store_checkpoint_cosignature(
+        &self,
+        log_length: RegistryLen,
+        cosignature: &Cosignature,
+    ) -> Result<(), DataStoreError> {
+        let mut state = self.0.write().await;
+        if !state.checkpoints.contains_key(&log_length) {
+            return Err(DataStoreError::CheckpointNotFound(log_length));
+        }
+
+        append(&mut state.journal, || {
+            JournalEntry::StoreCheckpointCosignature {
+                log_length,
+                cosignature: cosignature.clone(),
+            }
+        })?;
+
+        state
+            .checkpoint_cosignatures
+            .entry(log_length)
+            .or_default()
+            .insert(cosignature.key_id.clone(), cosignature.clone());
+
+        Ok(())
+    }
+
+    async fn get_checkpoint_cosignatures(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<Vec<Cosignature>, DataStoreError> {
+        let state = self.0.read().await;
+        if !state.checkpoints.contains_key(&log_length) {
+            return Err(DataStoreError::CheckpointNotFound(log_length));
+        }
+
+        Ok(state
+            .checkpoint_cosignatures
+            .get(&log_length)
+            .map(|c| c.values().cloned().collect())
+            .unwrap_or_default())
+    }
+
+    async fn get_cosigned_checkpoint_log_lengths(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<RegistryLen>, DataStoreError> {
+        let state = self.0.read().await;
+        let mut log_lengths = state
+            .checkpoint_cosignatures
+            .iter()
+            .filter(|(_, cosignatures)| !cosignatures.is_empty())
+            .map(|(log_length, _)| *log_length)
+            .collect::<Vec<_>>();
+        log_lengths.sort_unstable_by(|a, b| b.cmp(a));
+        log_lengths.truncate(limit);
+        Ok(log_lengths)
+    }
+
+    async fn get_latest_cosigned_log_length(
+        &self,
+        key_id: &KeyID,
+    ) -> Result<Option<RegistryLen>, DataStoreError> {
+        let state = self.0.read().await;
+        Ok(state
+            .checkpoint_cosignatures
+            .iter()
+            .filter(|(_, cosignatures)| cosignatures.contains_key(key_id))
+            .map(|(log_length, _)| *log_length)
+            .max())
+    }
+
     async fn get_operator_records(
         &self,
         log_id: &LogId,
@@ -691,6 +1129,24 @@ impl DataStore for MemoryDataStore {
         })
     }
 
+    async fn verify_operator_record_signature(
+        &self,
+        log_id: &LogId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+    ) -> Result<(), DataStoreError> {
+        let state = self.0.read().await;
+        let key = state
+            .operators
+            .get(log_id)
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?
+            .state
+            .public_key(record.key_id())
+            .ok_or_else(|| DataStoreError::UnknownKey(record.key_id().clone()))?;
+
+        operator::OperatorRecord::verify(key, record.content_bytes(), record.signature())
+            .map_err(|_| DataStoreError::SignatureVerificationFailed(record.signature().clone()))
+    }
+
     async fn verify_package_record_signature(
         &self,
         log_id: &LogId,
@@ -0,0 +1,212 @@
+use serde::{Deserialize, Serialize};
+use std::{
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::Path,
+};
+use warg_crypto::hash::AnyHash;
+use warg_protocol::{
+    registry::{LogId, PackageName, RecordId, RegistryIndex, RegistryLen, TimestampedCheckpoint},
+    Cosignature, ProtoEnvelopeBody, SerdeEnvelope,
+};
+
+/// Represents a change made to a memory data store.
+///
+/// Replaying the entries of a journal in order restores the state of the
+/// data store that wrote it.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub(super) enum JournalEntry {
+    StoreOperatorRecord {
+        log_id: LogId,
+        record_id: RecordId,
+        record: ProtoEnvelopeBody,
+    },
+    RejectOperatorRecord {
+        log_id: LogId,
+        record_id: RecordId,
+        reason: String,
+    },
+    CommitOperatorRecord {
+        log_id: LogId,
+        record_id: RecordId,
+        registry_index: RegistryIndex,
+    },
+    StorePackageRecord {
+        log_id: LogId,
+        package_name: PackageName,
+        record_id: RecordId,
+        record: ProtoEnvelopeBody,
+        missing: Vec<AnyHash>,
+    },
+    RejectPackageRecord {
+        log_id: LogId,
+        record_id: RecordId,
+        reason: String,
+    },
+    CommitPackageRecord {
+        log_id: LogId,
+        record_id: RecordId,
+        registry_index: RegistryIndex,
+    },
+    SetContentPresent {
+        log_id: LogId,
+        record_id: RecordId,
+        digest: AnyHash,
+    },
+    StoreCheckpoint {
+        checkpoint_id: AnyHash,
+        checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+    },
+    StoreCheckpointCosignature {
+        log_length: RegistryLen,
+        cosignature: Cosignature,
+    },
+    AddNamespaceUsage {
+        namespace: String,
+        size: u64,
+    },
+    ReleaseNamespaceUsage {
+        namespace: String,
+        size: u64,
+    },
+}
+
+/// An append-only journal file of a memory data store.
+///
+/// Each entry is written as a line of JSON and synced to disk before the
+/// change it records is made.
+pub(super) struct Journal {
+    file: File,
+}
+
+impl Journal {
+    /// Opens the journal at the given path, creating it if it does not exist.
+    ///
+    /// Returns the journal along with the entries already in it. An
+    /// incomplete entry at the end of the file, as left by a crash while it
+    /// was being written, is discarded.
+    pub fn open(path: &Path) -> io::Result<(Self, Vec<JournalEntry>)> {
+        let file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(path)?;
+
+        let mut reader = BufReader::new(&file);
+        let mut entries = Vec::new();
+        let mut line = Vec::new();
+        let mut len = 0;
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line)?;
+            if read == 0 || !line.ends_with(b"\n") {
+                break;
+            }
+
+            let entry = serde_json::from_slice(&line).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid journal entry at offset {len}: {e}"),
+                )
+            })?;
+            entries.push(entry);
+            len += read as u64;
+        }
+
+        if file.metadata()?.len() > len {
+            tracing::warn!("discarding incomplete entry at the end of the journal");
+            file.set_len(len)?;
+        }
+
+        Ok((Self { file }, entries))
+    }
+
+    /// Appends an entry to the journal.
+    pub fn append(&mut self, entry: &JournalEntry) -> io::Result<()> {
+        let mut line = serde_json::to_vec(entry)?;
+        line.push(b'\n');
+        self.file.write_all(&line)?;
+        self.file.sync_data()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use warg_crypto::hash::{Hash, Sha256};
+
+    fn entry(index: RegistryIndex) -> JournalEntry {
+        let hash = AnyHash::from(Hash::<Sha256>::of(index.to_le_bytes().as_slice()));
+        JournalEntry::CommitOperatorRecord {
+            log_id: LogId::operator_log::<Sha256>(),
+            record_id: hash.into(),
+            registry_index: index,
+        }
+    }
+
+    fn registry_indexes(entries: &[JournalEntry]) -> Vec<RegistryIndex> {
+        entries
+            .iter()
+            .map(|entry| match entry {
+                JournalEntry::CommitOperatorRecord { registry_index, .. } => *registry_index,
+                _ => unreachable!(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn it_replays_appended_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal");
+
+        let (mut journal, entries) = Journal::open(&path).unwrap();
+        assert!(entries.is_empty());
+        journal.append(&entry(0)).unwrap();
+        journal.append(&entry(1)).unwrap();
+        drop(journal);
+
+        let (mut journal, entries) = Journal::open(&path).unwrap();
+        assert_eq!(registry_indexes(&entries), [0, 1]);
+        journal.append(&entry(2)).unwrap();
+        drop(journal);
+
+        let (_, entries) = Journal::open(&path).unwrap();
+        assert_eq!(registry_indexes(&entries), [0, 1, 2]);
+    }
+
+    #[test]
+    fn it_discards_an_incomplete_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal");
+
+        let (mut journal, _) = Journal::open(&path).unwrap();
+        journal.append(&entry(0)).unwrap();
+        drop(journal);
+
+        let len = fs::metadata(&path).unwrap().len();
+        let mut contents = fs::read(&path).unwrap();
+        contents.extend_from_slice(br#"{"type":"commitOperatorRecord","log_id""#);
+        fs::write(&path, contents).unwrap();
+
+        let (mut journal, entries) = Journal::open(&path).unwrap();
+        assert_eq!(registry_indexes(&entries), [0]);
+        assert_eq!(fs::metadata(&path).unwrap().len(), len);
+        journal.append(&entry(1)).unwrap();
+        drop(journal);
+
+        let (_, entries) = Journal::open(&path).unwrap();
+        assert_eq!(registry_indexes(&entries), [0, 1]);
+    }
+
+    #[test]
+    fn it_fails_on_an_invalid_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal");
+        fs::write(&path, "not json\n").unwrap();
+
+        let err = Journal::open(&path).err().unwrap();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
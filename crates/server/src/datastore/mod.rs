@@ -11,16 +11,24 @@ use warg_protocol::{
     registry::{
         LogId, LogLeaf, PackageName, RecordId, RegistryIndex, RegistryLen, TimestampedCheckpoint,
     },
-    ProtoEnvelope, PublishedProtoEnvelope, SerdeEnvelope,
+    Cosignature, ProtoEnvelope, PublishedProtoEnvelope, SerdeEnvelope,
 };
 
+mod instrumented;
 mod memory;
 #[cfg(feature = "postgres")]
 mod postgres;
+#[cfg(any(feature = "postgres", feature = "sqlite"))]
+mod sql;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 
+pub(crate) use instrumented::InstrumentedDataStore;
 pub use memory::*;
 #[cfg(feature = "postgres")]
 pub use postgres::*;
+#[cfg(feature = "sqlite")]
+pub use sqlite::*;
 
 #[derive(Debug, Error)]
 pub enum DataStoreError {
@@ -62,6 +70,9 @@ pub enum DataStoreError {
     )]
     PackageNamespaceImported(String),
 
+    #[error("the package namespace `{0}` has exceeded its storage quota")]
+    PackageNamespaceQuotaExceeded(String),
+
     #[error("key id `{0}` does not have permission")]
     KeyUnauthorized(KeyID),
 
@@ -74,11 +85,18 @@ pub enum DataStoreError {
     #[error("the record was rejected: {0}")]
     Rejection(String),
 
+    #[error("failed to access the data store journal: {0}")]
+    Journal(std::io::Error),
+
     #[cfg(feature = "postgres")]
     #[error("a connection could not be established to the PostgreSQL server: {0}")]
     ConnectionPool(#[from] diesel_async::pooled_connection::deadpool::PoolError),
 
-    #[cfg(feature = "postgres")]
+    #[cfg(feature = "sqlite")]
+    #[error("the SQLite database operation failed to complete: {0}")]
+    TaskFailed(#[from] tokio::task::JoinError),
+
+    #[cfg(any(feature = "postgres", feature = "sqlite"))]
     #[error(transparent)]
     Diesel(#[from] diesel::result::Error),
 }
@@ -113,6 +131,32 @@ where
     pub registry_index: Option<RegistryIndex>,
 }
 
+/// Represents the number of records in each status.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RecordCounts {
+    /// The number of pending records with missing content.
+    pub missing_content: u64,
+    /// The number of pending records with all content present.
+    pub pending: u64,
+    /// The number of rejected records.
+    pub rejected: u64,
+    /// The number of validated records not yet included in a checkpoint.
+    pub validated: u64,
+    /// The number of published records.
+    pub published: u64,
+}
+
+/// Represents a snapshot of the registry's transparency state at a checkpoint.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    /// The log length of the checkpoint the snapshot was taken at.
+    pub log_length: RegistryLen,
+    /// The protobuf encoding of the verifiable log.
+    pub log: Vec<u8>,
+    /// The protobuf encoding of the verifiable map.
+    pub map: Vec<u8>,
+}
+
 /// Implemented by data stores.
 #[axum::async_trait]
 pub trait DataStore: Send + Sync {
@@ -139,6 +183,17 @@ pub trait DataStore: Send + Sync {
         entries: &[RegistryIndex],
     ) -> Result<Vec<LogLeaf>, DataStoreError>;
 
+    /// Gets the records that are ready to be committed but have not yet
+    /// been validated or rejected, in the order they became ready.
+    ///
+    /// A record is queued when it is stored without missing content or when
+    /// its last missing content is marked as present; it is removed from
+    /// the queue when it is committed or rejected.
+    ///
+    /// This is used on startup to resubmit the records that were accepted
+    /// before the server stopped.
+    async fn get_queued_records(&self) -> Result<Vec<LogLeaf>, DataStoreError>;
+
     /// Stores the given operator record.
     async fn store_operator_record(
         &self,
@@ -247,6 +302,65 @@ pub trait DataStore: Send + Sync {
         log_length: RegistryLen,
     ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError>;
 
+    /// Stores a witness cosignature of the checkpoint with the given log length.
+    ///
+    /// Any previous cosignature of the checkpoint by the same key is replaced.
+    async fn store_checkpoint_cosignature(
+        &self,
+        log_length: RegistryLen,
+        cosignature: &Cosignature,
+    ) -> Result<(), DataStoreError>;
+
+    /// Gets the witness cosignatures of the checkpoint with the given log length.
+    async fn get_checkpoint_cosignatures(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<Vec<Cosignature>, DataStoreError>;
+
+    /// Gets the log lengths of the most recent checkpoints with witness
+    /// cosignatures, newest first.
+    async fn get_cosigned_checkpoint_log_lengths(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<RegistryLen>, DataStoreError>;
+
+    /// Gets the log length of the latest checkpoint cosigned by the given
+    /// witness key, if it has cosigned one.
+    async fn get_latest_cosigned_log_length(
+        &self,
+        key_id: &KeyID,
+    ) -> Result<Option<RegistryLen>, DataStoreError>;
+
+    /// Stores a snapshot of the transparency state, replacing any older snapshot.
+    async fn store_snapshot(&self, snapshot: Snapshot) -> Result<(), DataStoreError>;
+
+    /// Gets the most recently stored snapshot, if there is one.
+    async fn get_latest_snapshot(&self) -> Result<Option<Snapshot>, DataStoreError>;
+
+    /// Adds the size of uploaded content to the storage used by a namespace.
+    ///
+    /// If a quota is given and the new usage would exceed it, the usage is
+    /// left unchanged and [`DataStoreError::PackageNamespaceQuotaExceeded`]
+    /// is returned.
+    async fn add_namespace_usage(
+        &self,
+        namespace: &str,
+        size: u64,
+        quota: Option<u64>,
+    ) -> Result<(), DataStoreError>;
+
+    /// Releases storage previously added to the usage of a namespace.
+    ///
+    /// This is used when the content the storage was added for is not stored.
+    async fn release_namespace_usage(
+        &self,
+        namespace: &str,
+        size: u64,
+    ) -> Result<(), DataStoreError>;
+
+    /// Gets the number of operator and package records in each status.
+    async fn get_record_counts(&self) -> Result<RecordCounts, DataStoreError>;
+
     /// Gets package names from log IDs. If package name is unavailable, a corresponding `None` is returned.
     async fn get_package_names(
         &self,
@@ -292,6 +406,16 @@ pub trait DataStore: Send + Sync {
         record_id: &RecordId,
     ) -> Result<Record<package::PackageRecord>, DataStoreError>;
 
+    /// Verifies the signature of an operator record.
+    ///
+    /// Only the signature on the envelope is verified; the record is
+    /// validated against the operator log when it is committed.
+    async fn verify_operator_record_signature(
+        &self,
+        log_id: &LogId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+    ) -> Result<(), DataStoreError>;
+
     /// Verifies the signature of a package record.
     ///
     /// This is different from `validate_package_record` in that
@@ -0,0 +1,8 @@
+-- Stores encoded snapshots of the registry log and map at a checkpoint so
+-- that the transparency state can be restored without replaying every record.
+CREATE TABLE snapshots (
+  log_length BIGINT PRIMARY KEY,
+  log BYTEA NOT NULL,
+  map BYTEA NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,13 @@
+-- Stores the cosignatures of checkpoints submitted by witnesses.
+-- Checkpoints are re-signed with new timestamps, so cosignatures are
+-- associated with a checkpoint by its log length.
+CREATE TABLE checkpoint_cosignatures (
+  log_length BIGINT NOT NULL,
+  key_id TEXT NOT NULL,
+  signature TEXT NOT NULL,
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+  PRIMARY KEY (log_length, key_id)
+);
+
+SELECT diesel_manage_updated_at('checkpoint_cosignatures');
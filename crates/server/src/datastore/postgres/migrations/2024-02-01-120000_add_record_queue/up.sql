@@ -0,0 +1,16 @@
+-- Stores the pending records that are ready to be committed to the registry
+-- log, in the order they became ready. Records are removed from the queue
+-- when they are validated or rejected, so the queue can be resubmitted when
+-- the server restarts.
+CREATE TABLE record_queue (
+  id SERIAL PRIMARY KEY,
+  record_id INTEGER NOT NULL UNIQUE REFERENCES records(id),
+  created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Queue the existing pending records that have all of their content
+INSERT INTO record_queue (record_id)
+SELECT r.id FROM records r
+WHERE r.status = 'pending'
+  AND NOT EXISTS (SELECT 1 FROM contents c WHERE c.record_id = r.id AND c.missing)
+ORDER BY r.id;
@@ -0,0 +1,9 @@
+-- Stores the size of the content uploaded to each namespace so that storage
+-- quotas can be enforced.
+CREATE TABLE namespace_usage (
+  namespace TEXT PRIMARY KEY,
+  size BIGINT NOT NULL DEFAULT 0,
+  updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+SELECT diesel_manage_updated_at('namespace_usage');
@@ -1,8 +1,9 @@
 use self::models::{
-    CheckpointData, NewCheckpoint, NewContent, NewLog, NewRecord, ParsedText, RecordContent,
-    RecordStatus, TextRef,
+    CheckpointCosignatureData, CheckpointData, NewCheckpoint, NewCheckpointCosignature, NewContent,
+    NewLog, NewQueuedRecord, NewRecord, NewSnapshot, ParsedText, RecordContent, RecordStatus,
+    SnapshotData, TextRef,
 };
-use super::{DataStore, DataStoreError, Record};
+use super::{DataStore, DataStoreError, Record, RecordCounts, Snapshot};
 use anyhow::{anyhow, Result};
 use diesel::sql_types::{Nullable, Text};
 use diesel::{prelude::*, result::DatabaseErrorKind};
@@ -19,7 +20,7 @@ use futures::{Stream, StreamExt};
 use indexmap::{IndexMap, IndexSet};
 use secrecy::{ExposeSecret, SecretString};
 use std::pin::Pin;
-use warg_crypto::{hash::AnyHash, Decode, Encode, Signable};
+use warg_crypto::{hash::AnyHash, signing::KeyID, Decode, Encode, Signable};
 use warg_protocol::{
     operator,
     package::{self, PackageEntry},
@@ -27,7 +28,7 @@ use warg_protocol::{
         Checkpoint, LogId, LogLeaf, PackageName, RecordId, RegistryIndex, RegistryLen,
         TimestampedCheckpoint,
     },
-    ProtoEnvelope, PublishedProtoEnvelope, Record as _, SerdeEnvelope, Validator,
+    Cosignature, ProtoEnvelope, PublishedProtoEnvelope, Record as _, SerdeEnvelope, Validator,
 };
 
 mod models;
@@ -173,6 +174,11 @@ where
                     .await?;
             }
 
+            // A record without missing content is ready to be committed
+            if !contents.iter().any(|s| missing.contains(s)) {
+                enqueue_record(conn, record_id).await?;
+            }
+
             Ok(())
         }
         .scope_boxed()
@@ -180,31 +186,55 @@ where
     .await
 }
 
+async fn enqueue_record(conn: &mut AsyncPgConnection, id: i32) -> Result<(), DataStoreError> {
+    diesel::insert_into(schema::record_queue::table)
+        .values(NewQueuedRecord { record_id: id })
+        .on_conflict_do_nothing()
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
+async fn dequeue_record(conn: &mut AsyncPgConnection, id: i32) -> Result<(), DataStoreError> {
+    diesel::delete(schema::record_queue::table)
+        .filter(schema::record_queue::record_id.eq(id))
+        .execute(conn)
+        .await?;
+
+    Ok(())
+}
+
 async fn reject_record(
     conn: &mut AsyncPgConnection,
     log_id: i32,
     record_id: &RecordId,
     reason: &str,
 ) -> Result<(), DataStoreError> {
-    let count = diesel::update(schema::records::table)
-        .filter(
-            schema::records::record_id
-                .eq(TextRef(record_id))
-                .and(schema::records::log_id.eq(log_id))
-                .and(schema::records::status.eq(RecordStatus::Pending)),
-        )
-        .set((
-            schema::records::status.eq(RecordStatus::Rejected),
-            schema::records::reason.eq(reason),
-        ))
-        .execute(conn)
-        .await?;
-
-    if count != 1 {
-        return Err(DataStoreError::RecordNotFound(record_id.clone()));
-    }
+    conn.transaction::<_, DataStoreError, _>(|conn| {
+        async move {
+            let id = diesel::update(schema::records::table)
+                .filter(
+                    schema::records::record_id
+                        .eq(TextRef(record_id))
+                        .and(schema::records::log_id.eq(log_id))
+                        .and(schema::records::status.eq(RecordStatus::Pending)),
+                )
+                .set((
+                    schema::records::status.eq(RecordStatus::Rejected),
+                    schema::records::reason.eq(reason),
+                ))
+                .returning(schema::records::id)
+                .get_result::<i32>(conn)
+                .await
+                .optional()?
+                .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
 
-    Ok(())
+            dequeue_record(conn, id).await
+        }
+        .scope_boxed()
+    })
+    .await
 }
 
 async fn commit_record<V>(
@@ -249,7 +279,7 @@ where
             })?;
 
             // Validate the record
-            let validator = validator.0.validate(&record).map_err(Into::into)?;
+            let validator = validator.0.validate(&record)?;
 
             // Store the updated validation state
             diesel::update(schema::logs::table)
@@ -268,13 +298,27 @@ where
                 .execute(conn)
                 .await?;
 
-            Ok(())
+            dequeue_record(conn, id).await
         }
         .scope_boxed()
     })
     .await
 }
 
+async fn checkpoint_exists(
+    conn: &mut AsyncPgConnection,
+    log_length: RegistryLen,
+) -> Result<(), DataStoreError> {
+    schema::checkpoints::table
+        .select(schema::checkpoints::id)
+        .filter(schema::checkpoints::log_length.eq(log_length as i64))
+        .first::<i32>(conn)
+        .await
+        .optional()?
+        .ok_or_else(|| DataStoreError::CheckpointNotFound(log_length))?;
+    Ok(())
+}
+
 async fn get_record<V>(
     conn: &mut AsyncPgConnection,
     log_id: &LogId,
@@ -482,6 +526,23 @@ impl DataStore for PostgresDataStore {
             .collect::<Vec<(RegistryIndex, LogLeaf)>>())
     }
 
+    async fn get_queued_records(&self) -> Result<Vec<LogLeaf>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        Ok(schema::record_queue::table
+            .inner_join(schema::records::table.inner_join(schema::logs::table))
+            .select((schema::logs::log_id, schema::records::record_id))
+            .order(schema::record_queue::id.asc())
+            .load::<(ParsedText<AnyHash>, ParsedText<AnyHash>)>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|(log_id, record_id)| LogLeaf {
+                log_id: log_id.0.into(),
+                record_id: record_id.0.into(),
+            })
+            .collect())
+    }
+
     // Note: order of the entries is expected to match to the corresponding returned log leafs.
     async fn get_log_leafs_with_registry_index(
         &self,
@@ -524,6 +585,172 @@ impl DataStore for PostgresDataStore {
             .collect::<Result<Vec<_>, _>>()?)
     }
 
+    async fn store_snapshot(&self, snapshot: Snapshot) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        conn.transaction::<_, DataStoreError, _>(|conn| {
+            async move {
+                // Only the latest snapshot is needed to restore state
+                diesel::delete(schema::snapshots::table)
+                    .execute(conn)
+                    .await?;
+
+                diesel::insert_into(schema::snapshots::table)
+                    .values(NewSnapshot {
+                        log_length: snapshot.log_length as i64,
+                        log: &snapshot.log,
+                        map: &snapshot.map,
+                    })
+                    .execute(conn)
+                    .await?;
+
+                Ok(())
+            }
+            .scope_boxed()
+        })
+        .await
+    }
+
+    async fn get_latest_snapshot(&self) -> Result<Option<Snapshot>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        Ok(schema::snapshots::table
+            .select(SnapshotData::as_select())
+            .order_by(schema::snapshots::log_length.desc())
+            .first::<SnapshotData>(&mut conn)
+            .await
+            .optional()?
+            .map(|snapshot| Snapshot {
+                log_length: snapshot.log_length as RegistryLen,
+                log: snapshot.log,
+                map: snapshot.map,
+            }))
+    }
+
+    async fn add_namespace_usage(
+        &self,
+        namespace: &str,
+        size: u64,
+        quota: Option<u64>,
+    ) -> Result<(), DataStoreError> {
+        use schema::namespace_usage::dsl;
+
+        let mut conn = self.pool.get().await?;
+        let size = i64::try_from(size).unwrap_or(i64::MAX);
+
+        diesel::insert_into(dsl::namespace_usage)
+            .values(dsl::namespace.eq(namespace))
+            .on_conflict_do_nothing()
+            .execute(&mut conn)
+            .await?;
+
+        // The quota is checked by the update itself so that concurrent
+        // uploads cannot exceed it together
+        let update = diesel::update(dsl::namespace_usage.find(namespace));
+        let updated = match quota {
+            Some(quota) => {
+                update
+                    .filter((dsl::size + size).le(i64::try_from(quota).unwrap_or(i64::MAX)))
+                    .set(dsl::size.eq(dsl::size + size))
+                    .execute(&mut conn)
+                    .await?
+            }
+            None => {
+                update
+                    .set(dsl::size.eq(dsl::size + size))
+                    .execute(&mut conn)
+                    .await?
+            }
+        };
+
+        if updated == 0 {
+            return Err(DataStoreError::PackageNamespaceQuotaExceeded(
+                namespace.to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn release_namespace_usage(
+        &self,
+        namespace: &str,
+        size: u64,
+    ) -> Result<(), DataStoreError> {
+        use schema::namespace_usage::dsl;
+
+        let mut conn = self.pool.get().await?;
+        let size = i64::try_from(size).unwrap_or(i64::MAX);
+
+        // Never let the usage go below zero
+        let updated = diesel::update(dsl::namespace_usage.find(namespace))
+            .filter(dsl::size.ge(size))
+            .set(dsl::size.eq(dsl::size - size))
+            .execute(&mut conn)
+            .await?;
+        if updated == 0 {
+            diesel::update(dsl::namespace_usage.find(namespace))
+                .set(dsl::size.eq(0))
+                .execute(&mut conn)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn get_record_counts(&self) -> Result<RecordCounts, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        let count = |status| {
+            schema::records::table
+                .filter(schema::records::status.eq(status))
+                .count()
+        };
+        let pending = count(RecordStatus::Pending)
+            .get_result::<i64>(&mut conn)
+            .await?;
+        let rejected = count(RecordStatus::Rejected)
+            .get_result::<i64>(&mut conn)
+            .await?;
+        let validated = count(RecordStatus::Validated)
+            .get_result::<i64>(&mut conn)
+            .await?;
+        let missing_content = schema::contents::table
+            .inner_join(schema::records::table)
+            .filter(
+                schema::contents::missing
+                    .eq(true)
+                    .and(schema::records::status.eq(RecordStatus::Pending)),
+            )
+            .select(diesel::dsl::count_distinct(schema::contents::record_id))
+            .get_result::<i64>(&mut conn)
+            .await?;
+
+        // Validated records are published once included in a checkpoint
+        let published_length = schema::checkpoints::table
+            .select(diesel::dsl::max(schema::checkpoints::log_length))
+            .get_result::<Option<i64>>(&mut conn)
+            .await?
+            .unwrap_or_default();
+        let published = schema::records::table
+            .filter(
+                schema::records::status
+                    .eq(RecordStatus::Validated)
+                    .and(schema::records::registry_log_index.lt(published_length)),
+            )
+            .count()
+            .get_result::<i64>(&mut conn)
+            .await?;
+
+        Ok(RecordCounts {
+            missing_content: missing_content as u64,
+            pending: (pending - missing_content) as u64,
+            rejected: rejected as u64,
+            validated: (validated - published) as u64,
+            published: published as u64,
+        })
+    }
+
     async fn get_package_names(
         &self,
         log_ids: &[LogId],
@@ -612,10 +839,17 @@ impl DataStore for PostgresDataStore {
             .await
         {
             Ok(()) => Ok(()),
-            Err(e) => {
+            Err(
+                e @ (DataStoreError::InvalidRecordContents { .. }
+                | DataStoreError::OperatorValidationFailed(_)
+                | DataStoreError::PackageValidationFailed(_)),
+            ) => {
                 reject_record(conn.as_mut(), log_id, record_id, &e.to_string()).await?;
                 Err(e)
             }
+            // Other errors leave the record pending so that it is committed
+            // again when the queue is resubmitted
+            Err(e) => Err(e),
         }
     }
 
@@ -676,10 +910,17 @@ impl DataStore for PostgresDataStore {
             .await
         {
             Ok(()) => Ok(()),
-            Err(e) => {
+            Err(
+                e @ (DataStoreError::InvalidRecordContents { .. }
+                | DataStoreError::OperatorValidationFailed(_)
+                | DataStoreError::PackageValidationFailed(_)),
+            ) => {
                 reject_record(conn.as_mut(), log_id, record_id, &e.to_string()).await?;
                 Err(e)
             }
+            // Other errors leave the record pending so that it is committed
+            // again when the queue is resubmitted
+            Err(e) => Err(e),
         }
     }
 
@@ -762,7 +1003,12 @@ impl DataStore for PostgresDataStore {
                     .await
                     .optional()?;
 
-                Ok(missing.is_none())
+                if missing.is_some() {
+                    return Ok(false);
+                }
+
+                enqueue_record(conn, record_id).await?;
+                Ok(true)
             }
             .scope_boxed()
         })
@@ -846,6 +1092,87 @@ impl DataStore for PostgresDataStore {
         ))
     }
 
+    async fn store_checkpoint_cosignature(
+        &self,
+        log_length: RegistryLen,
+        cosignature: &Cosignature,
+    ) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        checkpoint_exists(&mut conn, log_length).await?;
+
+        diesel::insert_into(schema::checkpoint_cosignatures::table)
+            .values(NewCheckpointCosignature {
+                log_length: log_length as i64,
+                key_id: TextRef(&cosignature.key_id),
+                signature: TextRef(&cosignature.signature),
+            })
+            .on_conflict((
+                schema::checkpoint_cosignatures::log_length,
+                schema::checkpoint_cosignatures::key_id,
+            ))
+            .do_update()
+            .set(schema::checkpoint_cosignatures::signature.eq(TextRef(&cosignature.signature)))
+            .execute(&mut conn)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn get_checkpoint_cosignatures(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<Vec<Cosignature>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+        checkpoint_exists(&mut conn, log_length).await?;
+
+        Ok(schema::checkpoint_cosignatures::table
+            .select(CheckpointCosignatureData::as_select())
+            .filter(schema::checkpoint_cosignatures::log_length.eq(log_length as i64))
+            .order_by(schema::checkpoint_cosignatures::created_at)
+            .load::<CheckpointCosignatureData>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|c| Cosignature {
+                key_id: c.key_id.0,
+                signature: c.signature.0,
+            })
+            .collect())
+    }
+
+    async fn get_cosigned_checkpoint_log_lengths(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<RegistryLen>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        Ok(schema::checkpoint_cosignatures::table
+            .select(schema::checkpoint_cosignatures::log_length)
+            .distinct()
+            .order_by(schema::checkpoint_cosignatures::log_length.desc())
+            .limit(limit as i64)
+            .load::<i64>(&mut conn)
+            .await?
+            .into_iter()
+            .map(|log_length| log_length as RegistryLen)
+            .collect())
+    }
+
+    async fn get_latest_cosigned_log_length(
+        &self,
+        key_id: &KeyID,
+    ) -> Result<Option<RegistryLen>, DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        Ok(schema::checkpoint_cosignatures::table
+            .select(diesel::dsl::max(
+                schema::checkpoint_cosignatures::log_length,
+            ))
+            .filter(schema::checkpoint_cosignatures::key_id.eq(TextRef(key_id)))
+            .first::<Option<i64>>(&mut conn)
+            .await?
+            .map(|log_length| log_length as RegistryLen))
+    }
+
     async fn get_checkpoint(
         &self,
         log_length: RegistryLen,
@@ -929,6 +1256,29 @@ impl DataStore for PostgresDataStore {
         get_record::<package::LogState>(conn.as_mut(), log_id, record_id).await
     }
 
+    async fn verify_operator_record_signature(
+        &self,
+        log_id: &LogId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+    ) -> Result<(), DataStoreError> {
+        let mut conn = self.pool.get().await?;
+
+        let validator = schema::logs::table
+            .select(schema::logs::validator)
+            .filter(schema::logs::log_id.eq(TextRef(log_id)))
+            .first::<Json<operator::LogState>>(&mut conn)
+            .await
+            .optional()?
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        let key = validator
+            .public_key(record.key_id())
+            .ok_or_else(|| DataStoreError::UnknownKey(record.key_id().clone()))?;
+
+        operator::OperatorRecord::verify(key, record.content_bytes(), record.signature())
+            .map_err(|_| DataStoreError::SignatureVerificationFailed(record.signature().clone()))
+    }
+
     async fn verify_package_record_signature(
         &self,
         log_id: &LogId,
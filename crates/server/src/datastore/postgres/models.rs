@@ -1,21 +1,24 @@
-use super::schema::{checkpoints, contents, logs, records};
+use super::schema::{
+    checkpoint_cosignatures, checkpoints, contents, logs, record_queue, records, snapshots,
+};
 use chrono::{DateTime, Utc};
 use diesel::{
-    deserialize::{self, FromSql},
-    pg::{Pg, PgValue},
+    pg::Pg,
     prelude::*,
     serialize::{self, IsNull, ToSql},
     sql_types, AsExpression, FromSqlRow, Insertable,
 };
 use diesel_json::Json;
 use serde::Serialize;
-use std::{fmt::Display, io::Write, str::FromStr};
+use std::{fmt::Display, io::Write};
 use warg_crypto::{
     hash::AnyHash,
     signing::{KeyID, Signature},
 };
 use warg_protocol::registry::{LogId, RecordId};
 
+pub use crate::datastore::sql::{ParsedText, Text};
+
 #[derive(Debug, Copy, Clone, Eq, PartialEq, diesel_derive_enum::DbEnum)]
 #[ExistingTypePath = "crate::datastore::postgres::schema::sql_types::RecordStatus"]
 pub enum RecordStatus {
@@ -24,29 +27,6 @@ pub enum RecordStatus {
     Validated,
 }
 
-#[derive(FromSqlRow, AsExpression, Debug)]
-#[diesel(sql_type = sql_types::Text)]
-pub struct Text<T>(pub T);
-
-impl<T: From<String>> FromSql<sql_types::Text, Pg> for Text<T> {
-    fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
-        Ok(Self(T::from(String::from_sql(bytes)?)))
-    }
-}
-
-#[derive(FromSqlRow, AsExpression, Debug)]
-#[diesel(sql_type = sql_types::Text)]
-pub struct ParsedText<T>(pub T);
-
-impl<T: FromStr> FromSql<sql_types::Text, Pg> for ParsedText<T>
-where
-    <T as std::str::FromStr>::Err: std::error::Error + Send + Sync + 'static,
-{
-    fn from_sql(bytes: PgValue) -> deserialize::Result<Self> {
-        Ok(Self(T::from_str(&String::from_sql(bytes)?)?))
-    }
-}
-
 #[derive(FromSqlRow, AsExpression, Debug)]
 #[diesel(sql_type = sql_types::Text)]
 pub struct TextRef<'a, T>(pub &'a T);
@@ -105,6 +85,37 @@ pub struct CheckpointData {
     pub timestamp: i64,
 }
 
+#[derive(Insertable)]
+#[diesel(table_name = checkpoint_cosignatures)]
+pub struct NewCheckpointCosignature<'a> {
+    pub log_length: i64,
+    pub key_id: TextRef<'a, KeyID>,
+    pub signature: TextRef<'a, Signature>,
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = checkpoint_cosignatures)]
+pub struct CheckpointCosignatureData {
+    pub key_id: Text<KeyID>,
+    pub signature: ParsedText<Signature>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = snapshots)]
+pub struct NewSnapshot<'a> {
+    pub log_length: i64,
+    pub log: &'a [u8],
+    pub map: &'a [u8],
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = snapshots)]
+pub struct SnapshotData {
+    pub log_length: i64,
+    pub log: Vec<u8>,
+    pub map: Vec<u8>,
+}
+
 /// Selects only the record content and status
 #[derive(Queryable, Selectable)]
 #[diesel(table_name = records)]
@@ -122,3 +133,9 @@ pub struct NewContent<'a> {
     pub digest: TextRef<'a, AnyHash>,
     pub missing: bool,
 }
+
+#[derive(Insertable)]
+#[diesel(table_name = record_queue)]
+pub struct NewQueuedRecord {
+    pub record_id: i32,
+}
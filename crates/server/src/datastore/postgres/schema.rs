@@ -6,6 +6,16 @@ pub mod sql_types {
     pub struct RecordStatus;
 }
 
+diesel::table! {
+    checkpoint_cosignatures (log_length, key_id) {
+        log_length -> Int8,
+        key_id -> Text,
+        signature -> Text,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     checkpoints (id) {
         id -> Int4,
@@ -43,6 +53,22 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    namespace_usage (namespace) {
+        namespace -> Text,
+        size -> Int8,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    record_queue (id) {
+        id -> Int4,
+        record_id -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use super::sql_types::RecordStatus;
@@ -60,7 +86,26 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    snapshots (log_length) {
+        log_length -> Int8,
+        log -> Bytea,
+        map -> Bytea,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::joinable!(contents -> records (record_id));
+diesel::joinable!(record_queue -> records (record_id));
 diesel::joinable!(records -> logs (log_id));
 
-diesel::allow_tables_to_appear_in_same_query!(checkpoints, contents, logs, records,);
+diesel::allow_tables_to_appear_in_same_query!(
+    checkpoint_cosignatures,
+    checkpoints,
+    contents,
+    logs,
+    namespace_usage,
+    record_queue,
+    records,
+    snapshots,
+);
@@ -0,0 +1,37 @@
+//! Models shared by the PostgreSQL and SQLite data stores.
+
+use diesel::{
+    backend::Backend,
+    deserialize::{self, FromSql},
+    sql_types, AsExpression, FromSqlRow,
+};
+use std::str::FromStr;
+
+/// A text column converted into a value.
+#[derive(FromSqlRow, AsExpression, Debug)]
+#[diesel(sql_type = sql_types::Text)]
+pub struct Text<T>(pub T);
+
+impl<T: From<String>, DB: Backend> FromSql<sql_types::Text, DB> for Text<T>
+where
+    String: FromSql<sql_types::Text, DB>,
+{
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        Ok(Self(T::from(String::from_sql(bytes)?)))
+    }
+}
+
+/// A text column parsed into a value.
+#[derive(FromSqlRow, AsExpression, Debug)]
+#[diesel(sql_type = sql_types::Text)]
+pub struct ParsedText<T>(pub T);
+
+impl<T: FromStr, DB: Backend> FromSql<sql_types::Text, DB> for ParsedText<T>
+where
+    <T as std::str::FromStr>::Err: std::error::Error + Send + Sync + 'static,
+    String: FromSql<sql_types::Text, DB>,
+{
+    fn from_sql(bytes: DB::RawValue<'_>) -> deserialize::Result<Self> {
+        Ok(Self(T::from_str(&String::from_sql(bytes)?)?))
+    }
+}
@@ -0,0 +1,7 @@
+DROP TABLE record_queue;
+DROP TABLE checkpoint_cosignatures;
+DROP TABLE snapshots;
+DROP TABLE contents;
+DROP TABLE records;
+DROP TABLE logs;
+DROP TABLE checkpoints;
@@ -0,0 +1,117 @@
+-- The SQLite schema matches the schema of the PostgreSQL migrations, with
+-- JSON stored as text and the record status stored as a constrained text
+-- column.
+
+-- Stores every checkpoint performed by the registry
+CREATE TABLE checkpoints (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  checkpoint_id TEXT NOT NULL UNIQUE,
+  log_root TEXT NOT NULL,
+  log_length BIGINT NOT NULL,
+  map_root TEXT NOT NULL,
+  key_id TEXT NOT NULL,
+  signature TEXT NOT NULL,
+  timestamp BIGINT NOT NULL DEFAULT 0,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TRIGGER checkpoints_set_updated_at AFTER UPDATE ON checkpoints
+FOR EACH ROW WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+  UPDATE checkpoints SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+-- Unified table for both package and operator logs.
+-- The `name` column is NULL for the operator log.
+CREATE TABLE logs (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  log_id TEXT NOT NULL UNIQUE,
+  name TEXT, -- implied UNIQUE constraint as log_id is derived from name
+  validator TEXT NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX logs_package_name_lowercase ON logs (LOWER(name));
+
+CREATE TRIGGER logs_set_updated_at AFTER UPDATE ON logs
+FOR EACH ROW WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+  UPDATE logs SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+-- Unified table for both package and operator log records.
+CREATE TABLE records (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  log_id INTEGER NOT NULL REFERENCES logs(id),
+  record_id TEXT NOT NULL UNIQUE,
+  registry_log_index BIGINT UNIQUE,
+  content BLOB NOT NULL,
+  status TEXT NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'rejected', 'validated')),
+  reason TEXT,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TRIGGER records_set_updated_at AFTER UPDATE ON records
+FOR EACH ROW WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+  UPDATE records SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+-- Represents record contents.
+-- Note that while digests may be repeated here (as these are per-record),
+-- only one copy of the content matching the digest is ever stored.
+CREATE TABLE contents (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  record_id INTEGER NOT NULL REFERENCES records(id),
+  digest TEXT NOT NULL,
+  missing BOOLEAN NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE UNIQUE INDEX contents_digest_record_id_idx ON contents (record_id, digest);
+
+CREATE TRIGGER contents_set_updated_at AFTER UPDATE ON contents
+FOR EACH ROW WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+  UPDATE contents SET updated_at = CURRENT_TIMESTAMP WHERE id = NEW.id;
+END;
+
+-- Stores encoded snapshots of the registry log and map at a checkpoint so
+-- that the transparency state can be restored without replaying every record.
+CREATE TABLE snapshots (
+  log_length BIGINT PRIMARY KEY,
+  log BLOB NOT NULL,
+  map BLOB NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- Stores the cosignatures of checkpoints submitted by witnesses.
+-- Checkpoints are re-signed with new timestamps, so cosignatures are
+-- associated with a checkpoint by its log length.
+CREATE TABLE checkpoint_cosignatures (
+  log_length BIGINT NOT NULL,
+  key_id TEXT NOT NULL,
+  signature TEXT NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (log_length, key_id)
+);
+
+CREATE TRIGGER checkpoint_cosignatures_set_updated_at AFTER UPDATE ON checkpoint_cosignatures
+FOR EACH ROW WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+  UPDATE checkpoint_cosignatures SET updated_at = CURRENT_TIMESTAMP
+  WHERE log_length = NEW.log_length AND key_id = NEW.key_id;
+END;
+
+-- Stores the pending records that are ready to be committed to the registry
+-- log, in the order they became ready.
+CREATE TABLE record_queue (
+  id INTEGER PRIMARY KEY AUTOINCREMENT,
+  record_id INTEGER NOT NULL UNIQUE REFERENCES records(id),
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
@@ -0,0 +1,13 @@
+-- Stores the size of the content uploaded to each namespace so that storage
+-- quotas can be enforced.
+CREATE TABLE namespace_usage (
+  namespace TEXT PRIMARY KEY,
+  size BIGINT NOT NULL DEFAULT 0,
+  updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TRIGGER namespace_usage_set_updated_at AFTER UPDATE ON namespace_usage
+FOR EACH ROW WHEN NEW.updated_at = OLD.updated_at
+BEGIN
+  UPDATE namespace_usage SET updated_at = CURRENT_TIMESTAMP WHERE namespace = NEW.namespace;
+END;
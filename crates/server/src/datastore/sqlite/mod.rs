@@ -0,0 +1,1351 @@
+use self::models::{
+    CheckpointCosignatureData, CheckpointData, Json, NewCheckpoint, NewCheckpointCosignature,
+    NewContent, NewLog, NewQueuedRecord, NewRecord, NewSnapshot, ParsedText, RecordContent,
+    RecordStatus, SnapshotData, TextRef,
+};
+use super::{DataStore, DataStoreError, Record, RecordCounts, Snapshot};
+use anyhow::{anyhow, Result};
+use diesel::{prelude::*, result::DatabaseErrorKind, sqlite::SqliteConnection};
+use diesel_migrations::{
+    embed_migrations, EmbeddedMigrations, HarnessWithOutput, MigrationHarness,
+};
+use futures::Stream;
+use indexmap::{IndexMap, IndexSet};
+use std::{
+    pin::Pin,
+    sync::{Arc, Mutex, PoisonError},
+};
+use warg_crypto::{hash::AnyHash, signing::KeyID, Decode, Encode, Signable};
+use warg_protocol::{
+    operator,
+    package::{self, PackageEntry},
+    registry::{
+        Checkpoint, LogId, LogLeaf, PackageName, RecordId, RegistryIndex, RegistryLen,
+        TimestampedCheckpoint,
+    },
+    Cosignature, ProtoEnvelope, PublishedProtoEnvelope, Record as _, SerdeEnvelope, Validator,
+};
+
+mod models;
+mod schema;
+
+fn get_log_id(conn: &mut SqliteConnection, log_id: &LogId) -> Result<i32, DataStoreError> {
+    schema::logs::table
+        .select(schema::logs::id)
+        .filter(schema::logs::log_id.eq(TextRef(log_id)))
+        .first::<i32>(conn)
+        .optional()?
+        .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))
+}
+
+fn get_records<R: Decode>(
+    conn: &mut SqliteConnection,
+    log_id: i32,
+    registry_log_length: RegistryLen,
+    since: Option<&RecordId>,
+    limit: i64,
+) -> Result<Vec<PublishedProtoEnvelope<R>>, DataStoreError> {
+    checkpoint_exists(conn, registry_log_length)?;
+
+    let mut query = schema::records::table
+        .into_boxed()
+        .select((
+            schema::records::record_id,
+            schema::records::content,
+            schema::records::registry_log_index,
+        ))
+        .order_by(schema::records::id.asc())
+        .limit(limit)
+        .filter(
+            schema::records::log_id
+                .eq(log_id)
+                .and(schema::records::registry_log_index.lt(registry_log_length as i64))
+                .and(schema::records::status.eq(RecordStatus::Validated)),
+        );
+
+    if let Some(since) = since {
+        let record_id = schema::records::table
+            .select(schema::records::id)
+            .filter(schema::records::record_id.eq(TextRef(since)))
+            .first::<i32>(conn)
+            .optional()?
+            .ok_or_else(|| DataStoreError::RecordNotFound(since.clone()))?;
+
+        query = query.filter(schema::records::id.gt(record_id));
+    }
+
+    query
+        .load::<(ParsedText<AnyHash>, Vec<u8>, Option<i64>)>(conn)?
+        .into_iter()
+        .map(
+            |(record_id, c, index)| match ProtoEnvelope::from_protobuf(&c) {
+                Ok(envelope) => Ok(PublishedProtoEnvelope {
+                    envelope,
+                    registry_index: index.unwrap() as RegistryIndex,
+                }),
+                Err(e) => Err(DataStoreError::InvalidRecordContents {
+                    record_id: record_id.0.into(),
+                    message: e.to_string(),
+                }),
+            },
+        )
+        .collect::<Result<_, _>>()
+}
+
+fn insert_record<V>(
+    conn: &mut SqliteConnection,
+    log_id: &LogId,
+    name: Option<&str>,
+    record_id: &RecordId,
+    record: &ProtoEnvelope<V::Record>,
+    missing: &IndexSet<AnyHash>,
+) -> Result<(), DataStoreError>
+where
+    V: Validator + 'static,
+{
+    let contents = record.as_ref().contents();
+    conn.transaction::<_, DataStoreError, _>(|conn| {
+        let log_id = match schema::logs::table
+            .select(schema::logs::id)
+            .filter(schema::logs::log_id.eq(TextRef(log_id)))
+            .first::<i32>(conn)
+            .optional()?
+        {
+            Some(id) => id,
+            None => {
+                diesel::insert_into(schema::logs::table)
+                    .values(NewLog {
+                        log_id: TextRef(log_id),
+                        name,
+                        validator: &Json(V::default()),
+                    })
+                    .execute(conn)
+                    .map_err(conflict)?;
+
+                get_log_id(conn, log_id)?
+            }
+        };
+
+        diesel::insert_into(schema::records::table)
+            .values(NewRecord {
+                log_id,
+                record_id: TextRef(record_id),
+                content: &record.to_protobuf(),
+            })
+            .execute(conn)
+            .map_err(conflict)?;
+
+        let record_id = schema::records::table
+            .select(schema::records::id)
+            .filter(schema::records::record_id.eq(TextRef(record_id)))
+            .first::<i32>(conn)?;
+
+        if !contents.is_empty() {
+            diesel::insert_into(schema::contents::table)
+                .values(
+                    &contents
+                        .iter()
+                        .map(|s| NewContent {
+                            record_id,
+                            digest: TextRef(s),
+                            missing: missing.contains(*s),
+                        })
+                        .collect::<Vec<_>>(),
+                )
+                .execute(conn)?;
+        }
+
+        // A record without missing content is ready to be committed
+        if !contents.iter().any(|s| missing.contains(*s)) {
+            enqueue_record(conn, record_id)?;
+        }
+
+        Ok(())
+    })
+}
+
+fn conflict(e: diesel::result::Error) -> DataStoreError {
+    match e {
+        diesel::result::Error::DatabaseError(DatabaseErrorKind::UniqueViolation, _) => {
+            DataStoreError::Conflict
+        }
+        e => e.into(),
+    }
+}
+
+fn enqueue_record(conn: &mut SqliteConnection, id: i32) -> Result<(), DataStoreError> {
+    diesel::insert_into(schema::record_queue::table)
+        .values(NewQueuedRecord { record_id: id })
+        .on_conflict_do_nothing()
+        .execute(conn)?;
+
+    Ok(())
+}
+
+fn dequeue_record(conn: &mut SqliteConnection, id: i32) -> Result<(), DataStoreError> {
+    diesel::delete(schema::record_queue::table)
+        .filter(schema::record_queue::record_id.eq(id))
+        .execute(conn)?;
+
+    Ok(())
+}
+
+fn reject_record(
+    conn: &mut SqliteConnection,
+    log_id: i32,
+    record_id: &RecordId,
+    reason: &str,
+) -> Result<(), DataStoreError> {
+    conn.transaction::<_, DataStoreError, _>(|conn| {
+        let id = schema::records::table
+            .select(schema::records::id)
+            .filter(
+                schema::records::record_id
+                    .eq(TextRef(record_id))
+                    .and(schema::records::log_id.eq(log_id))
+                    .and(schema::records::status.eq(RecordStatus::Pending)),
+            )
+            .first::<i32>(conn)
+            .optional()?
+            .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+        diesel::update(schema::records::table)
+            .filter(schema::records::id.eq(id))
+            .set((
+                schema::records::status.eq(RecordStatus::Rejected),
+                schema::records::reason.eq(reason),
+            ))
+            .execute(conn)?;
+
+        dequeue_record(conn, id)
+    })
+}
+
+fn commit_record<V>(
+    conn: &mut SqliteConnection,
+    log_id: i32,
+    record_id: &RecordId,
+    registry_index: RegistryIndex,
+) -> Result<(), DataStoreError>
+where
+    V: Validator + 'static,
+    DataStoreError: From<<V as Validator>::Error>,
+{
+    let registry_index: i64 = registry_index.try_into().unwrap();
+    conn.transaction::<_, DataStoreError, _>(|conn| {
+        // Get the record content and validator
+        let (id, content, validator) = schema::records::table
+            .inner_join(schema::logs::table)
+            .select((
+                schema::records::id,
+                schema::records::content,
+                schema::logs::validator,
+            ))
+            .filter(
+                schema::records::record_id
+                    .eq(TextRef(record_id))
+                    .and(schema::records::log_id.eq(log_id))
+                    .and(schema::records::status.eq(RecordStatus::Pending)),
+            )
+            .first::<(i32, Vec<u8>, Json<V>)>(conn)
+            .optional()?
+            .ok_or_else(|| DataStoreError::RecordNotPending(record_id.clone()))?;
+
+        let record = ProtoEnvelope::<V::Record>::from_protobuf(&content).map_err(|e| {
+            DataStoreError::InvalidRecordContents {
+                record_id: record_id.clone(),
+                message: e.to_string(),
+            }
+        })?;
+
+        // Validate the record
+        let validator = validator.0.validate(&record)?;
+
+        // Store the updated validation state
+        diesel::update(schema::logs::table)
+            .filter(schema::logs::id.eq(log_id))
+            .set(schema::logs::validator.eq(Json(validator)))
+            .execute(conn)?;
+
+        // Finally, mark the record as validated
+        diesel::update(schema::records::table)
+            .filter(schema::records::id.eq(id))
+            .set((
+                schema::records::status.eq(RecordStatus::Validated),
+                schema::records::registry_log_index.eq(Some(registry_index)),
+            ))
+            .execute(conn)?;
+
+        dequeue_record(conn, id)
+    })
+}
+
+fn commit_and_reject_invalid<V>(
+    conn: &mut SqliteConnection,
+    log_id: &LogId,
+    record_id: &RecordId,
+    registry_index: RegistryIndex,
+) -> Result<(), DataStoreError>
+where
+    V: Validator + 'static,
+    DataStoreError: From<<V as Validator>::Error>,
+{
+    let log_id = get_log_id(conn, log_id)?;
+    match commit_record::<V>(conn, log_id, record_id, registry_index) {
+        Ok(()) => Ok(()),
+        Err(
+            e @ (DataStoreError::InvalidRecordContents { .. }
+            | DataStoreError::OperatorValidationFailed(_)
+            | DataStoreError::PackageValidationFailed(_)),
+        ) => {
+            reject_record(conn, log_id, record_id, &e.to_string())?;
+            Err(e)
+        }
+        // Other errors leave the record pending so that it is committed
+        // again when the queue is resubmitted
+        Err(e) => Err(e),
+    }
+}
+
+fn checkpoint_exists(
+    conn: &mut SqliteConnection,
+    log_length: RegistryLen,
+) -> Result<(), DataStoreError> {
+    schema::checkpoints::table
+        .select(schema::checkpoints::log_length)
+        .filter(schema::checkpoints::log_length.eq(log_length as i64))
+        .first::<i64>(conn)
+        .optional()?
+        .ok_or_else(|| DataStoreError::CheckpointNotFound(log_length))?;
+
+    Ok(())
+}
+
+fn get_record<V>(
+    conn: &mut SqliteConnection,
+    log_id: &LogId,
+    record_id: &RecordId,
+) -> Result<Record<V::Record>, DataStoreError>
+where
+    V: Validator + 'static,
+{
+    let checkpoint = schema::checkpoints::table
+        .select(CheckpointData::as_select())
+        .order_by(schema::checkpoints::id.desc())
+        .first::<CheckpointData>(conn)?;
+
+    let log_id = get_log_id(conn, log_id)?;
+
+    let record = schema::records::table
+        .select(RecordContent::as_select())
+        .filter(
+            schema::records::record_id
+                .eq(TextRef(record_id))
+                .and(schema::records::log_id.eq(log_id)),
+        )
+        .first::<RecordContent>(conn)
+        .optional()?
+        .ok_or_else(|| DataStoreError::RecordNotFound(record_id.clone()))?;
+
+    Ok(Record {
+        status: match record.status {
+            RecordStatus::Pending => {
+                // Get the missing content
+                let missing = schema::contents::table
+                    .inner_join(schema::records::table)
+                    .select(schema::contents::digest)
+                    .filter(
+                        schema::records::record_id
+                            .eq(TextRef(record_id))
+                            .and(schema::contents::missing.eq(true)),
+                    )
+                    .load::<ParsedText<AnyHash>>(conn)?;
+
+                if missing.is_empty() {
+                    super::RecordStatus::Pending
+                } else {
+                    super::RecordStatus::MissingContent(missing.into_iter().map(|d| d.0).collect())
+                }
+            }
+            RecordStatus::Validated => {
+                if record.registry_log_index.unwrap() < checkpoint.log_length {
+                    super::RecordStatus::Published
+                } else {
+                    super::RecordStatus::Validated
+                }
+            }
+            RecordStatus::Rejected => {
+                super::RecordStatus::Rejected(record.reason.unwrap_or_default())
+            }
+        },
+        envelope: ProtoEnvelope::from_protobuf(&record.content).map_err(|e| {
+            DataStoreError::InvalidRecordContents {
+                record_id: record_id.clone(),
+                message: e.to_string(),
+            }
+        })?,
+        registry_index: record.registry_log_index.map(|idx| idx.try_into().unwrap()),
+    })
+}
+
+fn get_validator<V: Validator + 'static>(
+    conn: &mut SqliteConnection,
+    log_id: &LogId,
+) -> Result<Option<V>, DataStoreError> {
+    Ok(schema::logs::table
+        .select(schema::logs::validator)
+        .filter(schema::logs::log_id.eq(TextRef(log_id)))
+        .first::<Json<V>>(conn)
+        .optional()?
+        .map(|v| v.0))
+}
+
+fn to_timestamped_checkpoint(checkpoint: CheckpointData) -> SerdeEnvelope<TimestampedCheckpoint> {
+    SerdeEnvelope::from_parts_unchecked(
+        TimestampedCheckpoint {
+            checkpoint: Checkpoint {
+                log_root: checkpoint.log_root.0,
+                log_length: checkpoint.log_length.try_into().unwrap(),
+                map_root: checkpoint.map_root.0,
+            },
+            timestamp: checkpoint.timestamp.try_into().unwrap(),
+        },
+        checkpoint.key_id.0,
+        checkpoint.signature.0,
+    )
+}
+
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!("src/datastore/sqlite/migrations");
+
+/// Represents a data store backed by a SQLite database.
+///
+/// A single connection to the database is shared by all operations, which
+/// run on the blocking thread pool.
+pub struct SqliteDataStore {
+    conn: Arc<Mutex<SqliteConnection>>,
+}
+
+impl SqliteDataStore {
+    /// Opens the SQLite database at the given path, creating it if it does
+    /// not exist.
+    pub fn new(path: &str) -> Result<Self> {
+        let mut conn = SqliteConnection::establish(path)?;
+        diesel::sql_query("PRAGMA journal_mode = WAL").execute(&mut conn)?;
+        diesel::sql_query("PRAGMA foreign_keys = ON").execute(&mut conn)?;
+        diesel::sql_query("PRAGMA busy_timeout = 5000").execute(&mut conn)?;
+
+        Ok(Self {
+            conn: Arc::new(Mutex::new(conn)),
+        })
+    }
+
+    pub async fn run_pending_migrations(&self) -> Result<()> {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.lock().unwrap_or_else(PoisonError::into_inner);
+
+            // Send migration output to tracing::info
+            struct TracingWriter;
+            impl std::io::Write for TracingWriter {
+                fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                    tracing::info!("{}", String::from_utf8_lossy(buf).trim_end());
+                    Ok(buf.len())
+                }
+                fn flush(&mut self) -> std::io::Result<()> {
+                    Ok(())
+                }
+            }
+            let mut harness =
+                HarnessWithOutput::new(&mut *conn, std::io::LineWriter::new(TracingWriter));
+
+            harness
+                .run_pending_migrations(MIGRATIONS)
+                .map_err(|err| anyhow!("migrations failed: {err:?}"))?;
+
+            Ok(())
+        })
+        .await?
+    }
+
+    // Runs the given function with the connection on the blocking thread pool
+    async fn with_conn<T, F>(&self, f: F) -> Result<T, DataStoreError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut SqliteConnection) -> Result<T, DataStoreError> + Send + 'static,
+    {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            f(&mut conn.lock().unwrap_or_else(PoisonError::into_inner))
+        })
+        .await?
+    }
+}
+
+#[axum::async_trait]
+impl DataStore for SqliteDataStore {
+    async fn get_all_checkpoints(
+        &self,
+    ) -> Result<
+        Pin<Box<dyn Stream<Item = Result<TimestampedCheckpoint, DataStoreError>> + Send>>,
+        DataStoreError,
+    > {
+        let checkpoints = self
+            .with_conn(|conn| {
+                Ok(schema::checkpoints::table
+                    .select(CheckpointData::as_select())
+                    .order_by(schema::checkpoints::id.desc())
+                    .load::<CheckpointData>(conn)?)
+            })
+            .await?;
+
+        Ok(Box::pin(futures::stream::iter(
+            checkpoints.into_iter().map(|checkpoint| {
+                Ok(TimestampedCheckpoint {
+                    checkpoint: Checkpoint {
+                        log_root: checkpoint.log_root.0,
+                        log_length: checkpoint.log_length as RegistryIndex,
+                        map_root: checkpoint.map_root.0,
+                    },
+                    timestamp: checkpoint.timestamp.try_into().unwrap(),
+                })
+            }),
+        )))
+    }
+
+    async fn get_all_validated_records(
+        &self,
+    ) -> Result<Pin<Box<dyn Stream<Item = Result<LogLeaf, DataStoreError>> + Send>>, DataStoreError>
+    {
+        // SQLite connections cannot stream results across threads, so the
+        // leafs are loaded up front; this is only done on startup
+        let leafs = self
+            .with_conn(|conn| {
+                Ok(schema::records::table
+                    .inner_join(schema::logs::table)
+                    .select((schema::logs::log_id, schema::records::record_id))
+                    .filter(schema::records::status.eq(RecordStatus::Validated))
+                    .order(schema::records::registry_log_index.asc())
+                    .load::<(ParsedText<AnyHash>, ParsedText<AnyHash>)>(conn)?)
+            })
+            .await?;
+
+        Ok(Box::pin(futures::stream::iter(leafs.into_iter().map(
+            |(log_id, record_id)| {
+                Ok(LogLeaf {
+                    log_id: log_id.0.into(),
+                    record_id: record_id.0.into(),
+                })
+            },
+        ))))
+    }
+
+    async fn get_log_leafs_starting_with_registry_index(
+        &self,
+        starting_index: RegistryIndex,
+        limit: usize,
+    ) -> Result<Vec<(RegistryIndex, LogLeaf)>, DataStoreError> {
+        self.with_conn(move |conn| {
+            Ok(schema::records::table
+                .inner_join(schema::logs::table)
+                .select((
+                    schema::records::registry_log_index,
+                    schema::logs::log_id,
+                    schema::records::record_id,
+                ))
+                .filter(schema::records::registry_log_index.ge(starting_index as i64))
+                .order(schema::records::registry_log_index.asc())
+                .limit(limit as i64)
+                .load::<(Option<i64>, ParsedText<AnyHash>, ParsedText<AnyHash>)>(conn)?
+                .into_iter()
+                .map(|(registry_index, log_id, record_id)| {
+                    (
+                        registry_index.unwrap() as RegistryIndex,
+                        LogLeaf {
+                            log_id: log_id.0.into(),
+                            record_id: record_id.0.into(),
+                        },
+                    )
+                })
+                .collect())
+        })
+        .await
+    }
+
+    async fn get_queued_records(&self) -> Result<Vec<LogLeaf>, DataStoreError> {
+        self.with_conn(|conn| {
+            Ok(schema::record_queue::table
+                .inner_join(schema::records::table.inner_join(schema::logs::table))
+                .select((schema::logs::log_id, schema::records::record_id))
+                .order(schema::record_queue::id.asc())
+                .load::<(ParsedText<AnyHash>, ParsedText<AnyHash>)>(conn)?
+                .into_iter()
+                .map(|(log_id, record_id)| LogLeaf {
+                    log_id: log_id.0.into(),
+                    record_id: record_id.0.into(),
+                })
+                .collect())
+        })
+        .await
+    }
+
+    // Note: order of the entries is expected to match to the corresponding returned log leafs.
+    async fn get_log_leafs_with_registry_index(
+        &self,
+        entries: &[RegistryIndex],
+    ) -> Result<Vec<LogLeaf>, DataStoreError> {
+        let entries = entries.to_vec();
+        self.with_conn(move |conn| {
+            let mut leafs_map = schema::records::table
+                .inner_join(schema::logs::table)
+                .select((
+                    schema::logs::log_id,
+                    schema::records::record_id,
+                    schema::records::registry_log_index,
+                ))
+                .filter(
+                    schema::records::registry_log_index
+                        .eq_any(entries.iter().map(|i| *i as i64).collect::<Vec<i64>>()),
+                )
+                .load::<(ParsedText<AnyHash>, ParsedText<AnyHash>, Option<i64>)>(conn)?
+                .into_iter()
+                .map(|(log_id, record_id, index)| {
+                    (
+                        index.unwrap() as RegistryIndex,
+                        LogLeaf {
+                            log_id: log_id.0.into(),
+                            record_id: record_id.0.into(),
+                        },
+                    )
+                })
+                .collect::<IndexMap<RegistryIndex, LogLeaf>>();
+
+            entries
+                .iter()
+                .map(|registry_index| {
+                    leafs_map
+                        .swap_remove(registry_index)
+                        .ok_or(DataStoreError::LogLeafNotFound(*registry_index))
+                })
+                .collect()
+        })
+        .await
+    }
+
+    async fn store_snapshot(&self, snapshot: Snapshot) -> Result<(), DataStoreError> {
+        self.with_conn(move |conn| {
+            conn.transaction::<_, DataStoreError, _>(|conn| {
+                // Only the latest snapshot is needed to restore state
+                diesel::delete(schema::snapshots::table).execute(conn)?;
+
+                diesel::insert_into(schema::snapshots::table)
+                    .values(NewSnapshot {
+                        log_length: snapshot.log_length as i64,
+                        log: &snapshot.log,
+                        map: &snapshot.map,
+                    })
+                    .execute(conn)?;
+
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn get_latest_snapshot(&self) -> Result<Option<Snapshot>, DataStoreError> {
+        self.with_conn(|conn| {
+            Ok(schema::snapshots::table
+                .select(SnapshotData::as_select())
+                .order_by(schema::snapshots::log_length.desc())
+                .first::<SnapshotData>(conn)
+                .optional()?
+                .map(|snapshot| Snapshot {
+                    log_length: snapshot.log_length as RegistryLen,
+                    log: snapshot.log,
+                    map: snapshot.map,
+                }))
+        })
+        .await
+    }
+
+    async fn add_namespace_usage(
+        &self,
+        namespace: &str,
+        size: u64,
+        quota: Option<u64>,
+    ) -> Result<(), DataStoreError> {
+        use schema::namespace_usage::dsl;
+
+        let namespace = namespace.to_string();
+        let size = i64::try_from(size).unwrap_or(i64::MAX);
+        self.with_conn(move |conn| {
+            diesel::insert_or_ignore_into(dsl::namespace_usage)
+                .values(dsl::namespace.eq(&namespace))
+                .execute(conn)?;
+
+            let update = diesel::update(dsl::namespace_usage.find(&namespace));
+            let updated = match quota {
+                Some(quota) => update
+                    .filter((dsl::size + size).le(i64::try_from(quota).unwrap_or(i64::MAX)))
+                    .set(dsl::size.eq(dsl::size + size))
+                    .execute(conn)?,
+                None => update.set(dsl::size.eq(dsl::size + size)).execute(conn)?,
+            };
+
+            if updated == 0 {
+                return Err(DataStoreError::PackageNamespaceQuotaExceeded(namespace));
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn release_namespace_usage(
+        &self,
+        namespace: &str,
+        size: u64,
+    ) -> Result<(), DataStoreError> {
+        use schema::namespace_usage::dsl;
+
+        let namespace = namespace.to_string();
+        let size = i64::try_from(size).unwrap_or(i64::MAX);
+        self.with_conn(move |conn| {
+            // Never let the usage go below zero
+            let updated = diesel::update(dsl::namespace_usage.find(&namespace))
+                .filter(dsl::size.ge(size))
+                .set(dsl::size.eq(dsl::size - size))
+                .execute(conn)?;
+            if updated == 0 {
+                diesel::update(dsl::namespace_usage.find(&namespace))
+                    .set(dsl::size.eq(0))
+                    .execute(conn)?;
+            }
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_record_counts(&self) -> Result<RecordCounts, DataStoreError> {
+        self.with_conn(|conn| {
+            let mut count = |status| {
+                schema::records::table
+                    .filter(schema::records::status.eq(status))
+                    .count()
+                    .get_result::<i64>(conn)
+            };
+            let pending = count(RecordStatus::Pending)?;
+            let rejected = count(RecordStatus::Rejected)?;
+            let validated = count(RecordStatus::Validated)?;
+            let missing_content = schema::contents::table
+                .inner_join(schema::records::table)
+                .filter(
+                    schema::contents::missing
+                        .eq(true)
+                        .and(schema::records::status.eq(RecordStatus::Pending)),
+                )
+                .select(diesel::dsl::count_distinct(schema::contents::record_id))
+                .get_result::<i64>(conn)?;
+
+            // Validated records are published once included in a checkpoint
+            let published_length = schema::checkpoints::table
+                .select(diesel::dsl::max(schema::checkpoints::log_length))
+                .get_result::<Option<i64>>(conn)?
+                .unwrap_or_default();
+            let published = schema::records::table
+                .filter(
+                    schema::records::status
+                        .eq(RecordStatus::Validated)
+                        .and(schema::records::registry_log_index.lt(published_length)),
+                )
+                .count()
+                .get_result::<i64>(conn)?;
+
+            Ok(RecordCounts {
+                missing_content: missing_content as u64,
+                pending: (pending - missing_content) as u64,
+                rejected: rejected as u64,
+                validated: (validated - published) as u64,
+                published: published as u64,
+            })
+        })
+        .await
+    }
+
+    async fn get_package_names(
+        &self,
+        log_ids: &[LogId],
+    ) -> Result<IndexMap<LogId, Option<PackageName>>, DataStoreError> {
+        let log_ids = log_ids.to_vec();
+        self.with_conn(move |conn| {
+            let map = schema::logs::table
+                .select((schema::logs::log_id, schema::logs::name))
+                .filter(
+                    schema::logs::log_id
+                        .eq_any(log_ids.iter().map(TextRef).collect::<Vec<TextRef<LogId>>>()),
+                )
+                .load::<(ParsedText<AnyHash>, Option<String>)>(conn)?
+                .into_iter()
+                .map(|(log_id, opt_package_name)| {
+                    (
+                        log_id.0.into(),
+                        opt_package_name.map(|name| PackageName::new(name).unwrap()),
+                    )
+                })
+                .collect::<IndexMap<LogId, Option<PackageName>>>();
+
+            // check if any log IDs were not found
+            for log_id in &log_ids {
+                if !map.contains_key(log_id) {
+                    return Err(DataStoreError::LogNotFound(log_id.clone()));
+                }
+            }
+
+            Ok(map)
+        })
+        .await
+    }
+
+    async fn store_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+    ) -> Result<(), DataStoreError> {
+        let (log_id, record_id, record) = (log_id.clone(), record_id.clone(), record.clone());
+        self.with_conn(move |conn| {
+            insert_record::<operator::LogState>(
+                conn,
+                &log_id,
+                None,
+                &record_id,
+                &record,
+                &Default::default(),
+            )
+        })
+        .await
+    }
+
+    async fn reject_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        reason: &str,
+    ) -> Result<(), DataStoreError> {
+        let (log_id, record_id, reason) = (log_id.clone(), record_id.clone(), reason.to_string());
+        self.with_conn(move |conn| {
+            let log_id = get_log_id(conn, &log_id)?;
+            reject_record(conn, log_id, &record_id, &reason)
+        })
+        .await
+    }
+
+    async fn commit_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        registry_index: RegistryIndex,
+    ) -> Result<(), DataStoreError> {
+        let (log_id, record_id) = (log_id.clone(), record_id.clone());
+        self.with_conn(move |conn| {
+            commit_and_reject_invalid::<operator::LogState>(
+                conn,
+                &log_id,
+                &record_id,
+                registry_index,
+            )
+        })
+        .await
+    }
+
+    async fn store_package_record(
+        &self,
+        log_id: &LogId,
+        package_name: &PackageName,
+        record_id: &RecordId,
+        record: &ProtoEnvelope<package::PackageRecord>,
+        missing: &IndexSet<&AnyHash>,
+    ) -> Result<(), DataStoreError> {
+        let (log_id, package_name, record_id, record) = (
+            log_id.clone(),
+            package_name.clone(),
+            record_id.clone(),
+            record.clone(),
+        );
+        let missing = missing.iter().map(|&d| d.clone()).collect();
+        self.with_conn(move |conn| {
+            insert_record::<package::LogState>(
+                conn,
+                &log_id,
+                Some(package_name.as_ref()),
+                &record_id,
+                &record,
+                &missing,
+            )
+        })
+        .await
+    }
+
+    async fn reject_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        reason: &str,
+    ) -> Result<(), DataStoreError> {
+        let (log_id, record_id, reason) = (log_id.clone(), record_id.clone(), reason.to_string());
+        self.with_conn(move |conn| {
+            let log_id = get_log_id(conn, &log_id)?;
+            reject_record(conn, log_id, &record_id, &reason)
+        })
+        .await
+    }
+
+    async fn commit_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        registry_index: RegistryIndex,
+    ) -> Result<(), DataStoreError> {
+        let (log_id, record_id) = (log_id.clone(), record_id.clone());
+        self.with_conn(move |conn| {
+            commit_and_reject_invalid::<package::LogState>(
+                conn,
+                &log_id,
+                &record_id,
+                registry_index,
+            )
+        })
+        .await
+    }
+
+    async fn is_content_missing(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+    ) -> Result<bool, DataStoreError> {
+        let (log_id, record_id, digest) = (log_id.clone(), record_id.clone(), digest.clone());
+        self.with_conn(move |conn| {
+            schema::contents::table
+                .inner_join(schema::records::table.inner_join(schema::logs::table))
+                .select(schema::contents::missing)
+                .filter(
+                    schema::records::status
+                        .eq(RecordStatus::Pending)
+                        .and(schema::logs::log_id.eq(TextRef(&log_id)))
+                        .and(schema::records::record_id.eq(TextRef(&record_id)))
+                        .and(schema::contents::digest.eq(TextRef(&digest))),
+                )
+                .first::<bool>(conn)
+                .optional()?
+                .ok_or_else(|| DataStoreError::RecordNotPending(record_id.clone()))
+        })
+        .await
+    }
+
+    async fn set_content_present(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+        digest: &AnyHash,
+    ) -> Result<bool, DataStoreError> {
+        let (log_id, record_id, digest) = (log_id.clone(), record_id.clone(), digest.clone());
+        self.with_conn(move |conn| {
+            conn.transaction::<_, DataStoreError, _>(|conn| {
+                let id = schema::records::table
+                    .inner_join(schema::logs::table)
+                    .select(schema::records::id)
+                    .filter(
+                        schema::records::status
+                            .eq(RecordStatus::Pending)
+                            .and(schema::logs::log_id.eq(TextRef(&log_id)))
+                            .and(schema::records::record_id.eq(TextRef(&record_id))),
+                    )
+                    .first::<i32>(conn)
+                    .optional()?
+                    .ok_or_else(|| DataStoreError::RecordNotPending(record_id.clone()))?;
+
+                // If the content was already present, return false since this
+                // update didn't change anything
+                if diesel::update(schema::contents::table)
+                    .filter(
+                        schema::contents::record_id
+                            .eq(id)
+                            .and(schema::contents::digest.eq(TextRef(&digest)))
+                            .and(schema::contents::missing.eq(true)),
+                    )
+                    .set(schema::contents::missing.eq(false))
+                    .execute(conn)?
+                    == 0
+                {
+                    return Ok(false);
+                }
+
+                // Finally, check if all contents are present; if so, return true
+                // to indicate that this record is ready to be processed
+                let missing = schema::contents::table
+                    .select(schema::contents::id)
+                    .filter(
+                        schema::contents::record_id
+                            .eq(id)
+                            .and(schema::contents::missing.eq(true)),
+                    )
+                    .first::<i32>(conn)
+                    .optional()?;
+
+                if missing.is_some() {
+                    return Ok(false);
+                }
+
+                enqueue_record(conn, id)?;
+                Ok(true)
+            })
+        })
+        .await
+    }
+
+    async fn store_checkpoint(
+        &self,
+        checkpoint_id: &AnyHash,
+        ts_checkpoint: SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<(), DataStoreError> {
+        let checkpoint_id = checkpoint_id.clone();
+        self.with_conn(move |conn| {
+            conn.transaction::<_, DataStoreError, _>(|conn| {
+                let TimestampedCheckpoint {
+                    checkpoint:
+                        Checkpoint {
+                            log_root,
+                            log_length,
+                            map_root,
+                        },
+                    timestamp,
+                } = ts_checkpoint.as_ref();
+
+                // Replacing any existing checkpoint with the same checkpoint_id
+                diesel::delete(
+                    schema::checkpoints::table
+                        .filter(schema::checkpoints::checkpoint_id.eq(TextRef(&checkpoint_id))),
+                )
+                .execute(conn)?;
+
+                // Insert the checkpoint
+                diesel::insert_into(schema::checkpoints::table)
+                    .values(NewCheckpoint {
+                        checkpoint_id: TextRef(&checkpoint_id),
+                        log_root: TextRef(log_root),
+                        map_root: TextRef(map_root),
+                        log_length: *log_length as i64,
+                        key_id: TextRef(ts_checkpoint.key_id()),
+                        signature: TextRef(ts_checkpoint.signature()),
+                        timestamp: (*timestamp).try_into().unwrap(),
+                    })
+                    .execute(conn)?;
+
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    async fn get_latest_checkpoint(
+        &self,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+        self.with_conn(|conn| {
+            let checkpoint = schema::checkpoints::table
+                .select(CheckpointData::as_select())
+                .order_by(schema::checkpoints::id.desc())
+                .first::<CheckpointData>(conn)?;
+
+            Ok(to_timestamped_checkpoint(checkpoint))
+        })
+        .await
+    }
+
+    async fn store_checkpoint_cosignature(
+        &self,
+        log_length: RegistryLen,
+        cosignature: &Cosignature,
+    ) -> Result<(), DataStoreError> {
+        let cosignature = cosignature.clone();
+        self.with_conn(move |conn| {
+            checkpoint_exists(conn, log_length)?;
+
+            diesel::insert_into(schema::checkpoint_cosignatures::table)
+                .values(NewCheckpointCosignature {
+                    log_length: log_length as i64,
+                    key_id: TextRef(&cosignature.key_id),
+                    signature: TextRef(&cosignature.signature),
+                })
+                .on_conflict((
+                    schema::checkpoint_cosignatures::log_length,
+                    schema::checkpoint_cosignatures::key_id,
+                ))
+                .do_update()
+                .set(schema::checkpoint_cosignatures::signature.eq(TextRef(&cosignature.signature)))
+                .execute(conn)?;
+
+            Ok(())
+        })
+        .await
+    }
+
+    async fn get_checkpoint_cosignatures(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<Vec<Cosignature>, DataStoreError> {
+        self.with_conn(move |conn| {
+            checkpoint_exists(conn, log_length)?;
+
+            Ok(schema::checkpoint_cosignatures::table
+                .select(CheckpointCosignatureData::as_select())
+                .filter(schema::checkpoint_cosignatures::log_length.eq(log_length as i64))
+                .order_by(schema::checkpoint_cosignatures::created_at)
+                .load::<CheckpointCosignatureData>(conn)?
+                .into_iter()
+                .map(|c| Cosignature {
+                    key_id: c.key_id.0,
+                    signature: c.signature.0,
+                })
+                .collect())
+        })
+        .await
+    }
+
+    async fn get_cosigned_checkpoint_log_lengths(
+        &self,
+        limit: usize,
+    ) -> Result<Vec<RegistryLen>, DataStoreError> {
+        self.with_conn(move |conn| {
+            Ok(schema::checkpoint_cosignatures::table
+                .select(schema::checkpoint_cosignatures::log_length)
+                .distinct()
+                .order_by(schema::checkpoint_cosignatures::log_length.desc())
+                .limit(limit as i64)
+                .load::<i64>(conn)?
+                .into_iter()
+                .map(|log_length| log_length as RegistryLen)
+                .collect())
+        })
+        .await
+    }
+
+    async fn get_latest_cosigned_log_length(
+        &self,
+        key_id: &KeyID,
+    ) -> Result<Option<RegistryLen>, DataStoreError> {
+        let key_id = key_id.clone();
+        self.with_conn(move |conn| {
+            Ok(schema::checkpoint_cosignatures::table
+                .select(diesel::dsl::max(
+                    schema::checkpoint_cosignatures::log_length,
+                ))
+                .filter(schema::checkpoint_cosignatures::key_id.eq(TextRef(&key_id)))
+                .first::<Option<i64>>(conn)?
+                .map(|log_length| log_length as RegistryLen))
+        })
+        .await
+    }
+
+    async fn get_checkpoint(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<SerdeEnvelope<TimestampedCheckpoint>, DataStoreError> {
+        self.with_conn(move |conn| {
+            let checkpoint = schema::checkpoints::table
+                .select(CheckpointData::as_select())
+                .filter(schema::checkpoints::log_length.eq(log_length as i64))
+                .first::<CheckpointData>(conn)
+                .optional()?
+                .ok_or_else(|| DataStoreError::CheckpointNotFound(log_length))?;
+
+            Ok(to_timestamped_checkpoint(checkpoint))
+        })
+        .await
+    }
+
+    async fn get_operator_records(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+        since: Option<&RecordId>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<operator::OperatorRecord>>, DataStoreError> {
+        let (log_id, since) = (log_id.clone(), since.cloned());
+        self.with_conn(move |conn| {
+            let log_id = get_log_id(conn, &log_id)?;
+            get_records(
+                conn,
+                log_id,
+                registry_log_length,
+                since.as_ref(),
+                limit as i64,
+            )
+        })
+        .await
+    }
+
+    async fn get_package_records(
+        &self,
+        log_id: &LogId,
+        registry_log_length: RegistryLen,
+        since: Option<&RecordId>,
+        limit: u16,
+    ) -> Result<Vec<PublishedProtoEnvelope<package::PackageRecord>>, DataStoreError> {
+        let (log_id, since) = (log_id.clone(), since.cloned());
+        self.with_conn(move |conn| {
+            let log_id = get_log_id(conn, &log_id)?;
+            get_records(
+                conn,
+                log_id,
+                registry_log_length,
+                since.as_ref(),
+                limit as i64,
+            )
+        })
+        .await
+    }
+
+    async fn get_operator_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Record<operator::OperatorRecord>, DataStoreError> {
+        let (log_id, record_id) = (log_id.clone(), record_id.clone());
+        self.with_conn(move |conn| get_record::<operator::LogState>(conn, &log_id, &record_id))
+            .await
+    }
+
+    async fn get_package_record(
+        &self,
+        log_id: &LogId,
+        record_id: &RecordId,
+    ) -> Result<Record<package::PackageRecord>, DataStoreError> {
+        let (log_id, record_id) = (log_id.clone(), record_id.clone());
+        self.with_conn(move |conn| get_record::<package::LogState>(conn, &log_id, &record_id))
+            .await
+    }
+
+    async fn verify_operator_record_signature(
+        &self,
+        log_id: &LogId,
+        record: &ProtoEnvelope<operator::OperatorRecord>,
+    ) -> Result<(), DataStoreError> {
+        let id = log_id.clone();
+        let validator = self
+            .with_conn(move |conn| get_validator::<operator::LogState>(conn, &id))
+            .await?
+            .ok_or_else(|| DataStoreError::LogNotFound(log_id.clone()))?;
+
+        let key = validator
+            .public_key(record.key_id())
+            .ok_or_else(|| DataStoreError::UnknownKey(record.key_id().clone()))?;
+
+        operator::OperatorRecord::verify(key, record.content_bytes(), record.signature())
+            .map_err(|_| DataStoreError::SignatureVerificationFailed(record.signature().clone()))
+    }
+
+    async fn verify_package_record_signature(
+        &self,
+        log_id: &LogId,
+        record: &ProtoEnvelope<package::PackageRecord>,
+    ) -> Result<(), DataStoreError> {
+        let id = log_id.clone();
+        let validator = self
+            .with_conn(move |conn| get_validator::<package::LogState>(conn, &id))
+            .await?;
+
+        #[allow(clippy::get_first)] // Vec::first() conflicts with diesel's RunQueryDsl
+        let key = match validator
+            .as_ref()
+            .and_then(|v| v.public_key(record.key_id()))
+        {
+            Some(key) => key,
+            None => match record.as_ref().entries.get(0) {
+                Some(PackageEntry::Init { key, .. }) => key,
+                _ => return Err(DataStoreError::UnknownKey(record.key_id().clone())),
+            },
+        };
+
+        package::PackageRecord::verify(key, record.content_bytes(), record.signature())
+            .map_err(|_| DataStoreError::SignatureVerificationFailed(record.signature().clone()))
+    }
+
+    async fn verify_can_publish_package(
+        &self,
+        operator_log_id: &LogId,
+        package_name: &PackageName,
+    ) -> Result<(), DataStoreError> {
+        let id = operator_log_id.clone();
+        let validator = self
+            .with_conn(move |conn| get_validator::<operator::LogState>(conn, &id))
+            .await?
+            .ok_or_else(|| DataStoreError::LogNotFound(operator_log_id.clone()))?;
+
+        // verify namespace is defined and not imported
+        match validator.namespace_state(package_name.namespace()) {
+            Some(state) => match state {
+                operator::NamespaceState::Defined => {}
+                operator::NamespaceState::Imported { .. } => {
+                    return Err(DataStoreError::PackageNamespaceImported(
+                        package_name.namespace().to_string(),
+                    ))
+                }
+            },
+            None => {
+                return Err(DataStoreError::PackageNamespaceNotDefined(
+                    package_name.namespace().to_string(),
+                ))
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn verify_timestamped_checkpoint_signature(
+        &self,
+        operator_log_id: &LogId,
+        ts_checkpoint: &SerdeEnvelope<TimestampedCheckpoint>,
+    ) -> Result<(), DataStoreError> {
+        let id = operator_log_id.clone();
+        let validator = self
+            .with_conn(move |conn| get_validator::<operator::LogState>(conn, &id))
+            .await?
+            .ok_or_else(|| DataStoreError::LogNotFound(operator_log_id.clone()))?;
+
+        TimestampedCheckpoint::verify(
+            validator
+                .public_key(ts_checkpoint.key_id())
+                .ok_or(DataStoreError::UnknownKey(ts_checkpoint.key_id().clone()))?,
+            &ts_checkpoint.as_ref().encode(),
+            ts_checkpoint.signature(),
+        )
+        .or(Err(DataStoreError::SignatureVerificationFailed(
+            ts_checkpoint.signature().clone(),
+        )))?;
+
+        if !validator.key_has_permission_to_sign_checkpoints(ts_checkpoint.key_id()) {
+            return Err(DataStoreError::KeyUnauthorized(
+                ts_checkpoint.key_id().clone(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    #[cfg(feature = "debug")]
+    async fn debug_list_package_names(&self) -> anyhow::Result<Vec<PackageName>> {
+        let names = self
+            .with_conn(|conn| {
+                Ok(schema::logs::table
+                    .select(schema::logs::name)
+                    .load::<Option<String>>(conn)?)
+            })
+            .await?
+            .into_iter()
+            .flatten()
+            .filter_map(|name| name.parse().ok())
+            .collect();
+        Ok(names)
+    }
+}
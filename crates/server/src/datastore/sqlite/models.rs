@@ -0,0 +1,181 @@
+use super::schema::{
+    checkpoint_cosignatures, checkpoints, contents, logs, record_queue, records, snapshots,
+};
+use diesel::{
+    backend::Backend,
+    deserialize::{self, FromSql},
+    prelude::*,
+    serialize::{self, IsNull, ToSql},
+    sql_types,
+    sqlite::Sqlite,
+    AsExpression, FromSqlRow, Insertable,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use std::fmt::Display;
+use warg_crypto::{
+    hash::AnyHash,
+    signing::{KeyID, Signature},
+};
+use warg_protocol::registry::{LogId, RecordId};
+
+pub use crate::datastore::sql::{ParsedText, Text};
+
+fn text(bytes: <Sqlite as Backend>::RawValue<'_>) -> deserialize::Result<String> {
+    <String as FromSql<sql_types::Text, Sqlite>>::from_sql(bytes)
+}
+
+#[derive(FromSqlRow, AsExpression, Debug, Copy, Clone, Eq, PartialEq)]
+#[diesel(sql_type = sql_types::Text)]
+pub enum RecordStatus {
+    Pending,
+    Rejected,
+    Validated,
+}
+
+impl ToSql<sql_types::Text, Sqlite> for RecordStatus {
+    fn to_sql<'b>(&'b self, out: &mut serialize::Output<'b, '_, Sqlite>) -> serialize::Result {
+        out.set_value(match self {
+            Self::Pending => "pending",
+            Self::Rejected => "rejected",
+            Self::Validated => "validated",
+        });
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<sql_types::Text, Sqlite> for RecordStatus {
+    fn from_sql(bytes: <Sqlite as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        match text(bytes)?.as_str() {
+            "pending" => Ok(Self::Pending),
+            "rejected" => Ok(Self::Rejected),
+            "validated" => Ok(Self::Validated),
+            status => Err(format!("unknown record status `{status}`").into()),
+        }
+    }
+}
+
+#[derive(FromSqlRow, AsExpression, Debug)]
+#[diesel(sql_type = sql_types::Text)]
+pub struct TextRef<'a, T>(pub &'a T);
+
+impl<'a, T: std::fmt::Debug + Display> ToSql<sql_types::Text, Sqlite> for TextRef<'a, T> {
+    fn to_sql<'b>(&'b self, out: &mut serialize::Output<'b, '_, Sqlite>) -> serialize::Result {
+        out.set_value(self.0.to_string());
+        Ok(IsNull::No)
+    }
+}
+
+/// A value stored as JSON text, as SQLite has no JSON column type.
+#[derive(FromSqlRow, AsExpression, Debug)]
+#[diesel(sql_type = sql_types::Text)]
+pub struct Json<T>(pub T);
+
+impl<T: std::fmt::Debug + Serialize> ToSql<sql_types::Text, Sqlite> for Json<T> {
+    fn to_sql<'b>(&'b self, out: &mut serialize::Output<'b, '_, Sqlite>) -> serialize::Result {
+        out.set_value(serde_json::to_string(&self.0)?);
+        Ok(IsNull::No)
+    }
+}
+
+impl<T: DeserializeOwned> FromSql<sql_types::Text, Sqlite> for Json<T> {
+    fn from_sql(bytes: <Sqlite as Backend>::RawValue<'_>) -> deserialize::Result<Self> {
+        Ok(Self(serde_json::from_str(&text(bytes)?)?))
+    }
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = logs)]
+pub struct NewLog<'a, V>
+where
+    V: std::fmt::Debug + Serialize,
+{
+    pub log_id: TextRef<'a, LogId>,
+    pub name: Option<&'a str>,
+    pub validator: &'a Json<V>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = records)]
+pub struct NewRecord<'a> {
+    pub log_id: i32,
+    pub record_id: TextRef<'a, RecordId>,
+    pub content: &'a [u8],
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = checkpoints)]
+pub struct NewCheckpoint<'a> {
+    pub checkpoint_id: TextRef<'a, AnyHash>,
+    pub log_root: TextRef<'a, AnyHash>,
+    pub log_length: i64,
+    pub map_root: TextRef<'a, AnyHash>,
+    pub key_id: TextRef<'a, KeyID>,
+    pub signature: TextRef<'a, Signature>,
+    pub timestamp: i64,
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = checkpoints)]
+pub struct CheckpointData {
+    pub log_root: ParsedText<AnyHash>,
+    pub log_length: i64,
+    pub map_root: ParsedText<AnyHash>,
+    pub key_id: Text<KeyID>,
+    pub signature: ParsedText<Signature>,
+    pub timestamp: i64,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = checkpoint_cosignatures)]
+pub struct NewCheckpointCosignature<'a> {
+    pub log_length: i64,
+    pub key_id: TextRef<'a, KeyID>,
+    pub signature: TextRef<'a, Signature>,
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = checkpoint_cosignatures)]
+pub struct CheckpointCosignatureData {
+    pub key_id: Text<KeyID>,
+    pub signature: ParsedText<Signature>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = snapshots)]
+pub struct NewSnapshot<'a> {
+    pub log_length: i64,
+    pub log: &'a [u8],
+    pub map: &'a [u8],
+}
+
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = snapshots)]
+pub struct SnapshotData {
+    pub log_length: i64,
+    pub log: Vec<u8>,
+    pub map: Vec<u8>,
+}
+
+/// Selects only the record content and status
+#[derive(Queryable, Selectable)]
+#[diesel(table_name = records)]
+pub struct RecordContent {
+    pub status: RecordStatus,
+    pub registry_log_index: Option<i64>,
+    pub reason: Option<String>,
+    pub content: Vec<u8>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = contents)]
+pub struct NewContent<'a> {
+    pub record_id: i32,
+    pub digest: TextRef<'a, AnyHash>,
+    pub missing: bool,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = record_queue)]
+pub struct NewQueuedRecord {
+    pub record_id: i32,
+}
@@ -1,24 +1,42 @@
 use crate::{api::create_router, datastore::MemoryDataStore};
-use anyhow::{Context, Result};
-use axum::Router;
-use datastore::DataStore;
+use anyhow::{bail, Context, Result};
+use auth::TokenStore;
+use axum::{extract::Request, http::StatusCode, response::IntoResponse, Router};
+use contentstore::{ContentStore, FileSystemContentStore};
+use datastore::{DataStore, InstrumentedDataStore};
 use futures::Future;
+use limits::Limits;
+use metrics::Metrics;
 use policy::{content::ContentPolicy, record::RecordPolicy};
-use services::CoreService;
-use std::{fs, net::SocketAddr, path::PathBuf, pin::Pin, sync::Arc, time::Duration};
+use services::{CoreService, TileWriter};
+use std::{
+    fs,
+    net::SocketAddr,
+    path::PathBuf,
+    pin::Pin,
+    sync::{Arc, OnceLock},
+    time::Duration,
+};
 use tokio::{net::TcpListener, task::JoinHandle};
+use tower::ServiceExt;
 use url::Url;
-use warg_crypto::signing::PrivateKey;
-use warg_protocol::operator;
+use warg_crypto::signing::{note, PrivateKey, PublicKey};
+use warg_protocol::{operator, registry::RegistryLen};
 
 pub mod api;
 pub mod args;
+pub mod auth;
+pub mod contentstore;
 pub mod datastore;
+pub mod limits;
+pub mod metrics;
 pub mod policy;
 pub mod services;
 
 const DEFAULT_BIND_ADDRESS: &str = "0.0.0.0:8090";
 const DEFAULT_CHECKPOINT_INTERVAL: Duration = Duration::from_secs(5);
+const DEFAULT_SNAPSHOT_INTERVAL: RegistryLen = 10_000;
+const DEFAULT_MAX_CHECKPOINT_STALENESS: u32 = 3;
 
 type ShutdownFut = Pin<Box<dyn Future<Output = ()> + Send + Sync>>;
 
@@ -27,13 +45,22 @@ pub struct Config {
     operator_key: PrivateKey,
     namespaces: Option<Vec<(String, operator::NamespaceState)>>,
     addr: Option<SocketAddr>,
+    metrics_addr: Option<SocketAddr>,
     data_store: Option<Box<dyn DataStore>>,
+    content_store: Option<Arc<dyn ContentStore>>,
     content_dir: PathBuf,
     content_base_url: Option<Url>,
+    checkpoint_origin: Option<String>,
+    witness_keys: Vec<PublicKey>,
     shutdown: Option<ShutdownFut>,
     checkpoint_interval: Option<Duration>,
+    max_checkpoint_staleness: Option<u32>,
+    snapshot_interval: Option<RegistryLen>,
+    log_storage_dir: Option<PathBuf>,
     content_policy: Option<Arc<dyn ContentPolicy>>,
     record_policy: Option<Arc<dyn RecordPolicy>>,
+    token_store: Option<Arc<dyn TokenStore>>,
+    limits: Limits,
 }
 
 impl std::fmt::Debug for Config {
@@ -42,13 +69,23 @@ impl std::fmt::Debug for Config {
             .field("operator_key", &"<redacted>")
             .field("namespaces", &self.namespaces)
             .field("addr", &self.addr)
+            .field("metrics_addr", &self.metrics_addr)
             .field(
                 "data_store",
                 &self.data_store.as_ref().map(|_| "dyn DataStore"),
             )
+            .field(
+                "content_store",
+                &self.content_store.as_ref().map(|_| "dyn ContentStore"),
+            )
             .field("content_dir", &self.content_dir)
+            .field("checkpoint_origin", &self.checkpoint_origin)
+            .field("witness_keys", &self.witness_keys)
             .field("shutdown", &self.shutdown.as_ref().map(|_| "dyn Future"))
             .field("checkpoint_interval", &self.checkpoint_interval)
+            .field("max_checkpoint_staleness", &self.max_checkpoint_staleness)
+            .field("snapshot_interval", &self.snapshot_interval)
+            .field("log_storage_dir", &self.log_storage_dir)
             .field(
                 "content_policy",
                 &self.content_policy.as_ref().map(|_| "dyn ContentPolicy"),
@@ -57,6 +94,11 @@ impl std::fmt::Debug for Config {
                 "record_policy",
                 &self.record_policy.as_ref().map(|_| "dyn RecordPolicy"),
             )
+            .field(
+                "token_store",
+                &self.token_store.as_ref().map(|_| "dyn TokenStore"),
+            )
+            .field("limits", &self.limits)
             .finish()
     }
 }
@@ -72,13 +114,22 @@ impl Config {
             operator_key,
             namespaces,
             addr: None,
+            metrics_addr: None,
             data_store: None,
+            content_store: None,
             content_dir,
             content_base_url: None,
+            checkpoint_origin: None,
+            witness_keys: Vec::new(),
             shutdown: None,
             checkpoint_interval: None,
+            max_checkpoint_staleness: None,
+            snapshot_interval: None,
+            log_storage_dir: None,
             content_policy: None,
             record_policy: None,
+            token_store: None,
+            limits: Limits::default(),
         }
     }
 
@@ -88,6 +139,14 @@ impl Config {
         self
     }
 
+    /// Specify a separate address to serve the metrics endpoint on.
+    ///
+    /// If not set, the metrics are served at `/metrics` on the server address.
+    pub fn with_metrics_addr(mut self, addr: impl Into<SocketAddr>) -> Self {
+        self.metrics_addr = Some(addr.into());
+        self
+    }
+
     /// Specify the content base URL to use.
     ///
     /// If not set, the content base URL will be derived from the server address.
@@ -96,6 +155,21 @@ impl Config {
         self
     }
 
+    /// Specify the origin of the registry log in checkpoint notes.
+    ///
+    /// The origin is also used as the name of the operator key signing the
+    /// notes. If not set, it will be derived from the content base URL.
+    pub fn with_checkpoint_origin(mut self, origin: impl Into<String>) -> Self {
+        self.checkpoint_origin = Some(origin.into());
+        self
+    }
+
+    /// Specify the keys of witnesses allowed to cosign checkpoints.
+    pub fn with_witness_keys(mut self, keys: impl IntoIterator<Item = PublicKey>) -> Self {
+        self.witness_keys = keys.into_iter().collect();
+        self
+    }
+
     /// Specify the data store to use.
     ///
     /// If this is not specified, the server will use an in-memory data store.
@@ -112,6 +186,19 @@ impl Config {
         self
     }
 
+    /// Specify the content store to use.
+    ///
+    /// If this is not specified, the server will store content in the
+    /// `files` subdirectory of the content directory and serve it at
+    /// `/content`.
+    ///
+    /// The content store only holds record content; log tiles are always
+    /// written to the `tiles` subdirectory of the content directory.
+    pub fn with_content_store(mut self, store: impl ContentStore + 'static) -> Self {
+        self.content_store = Some(Arc::new(store));
+        self
+    }
+
     /// Specifies the future to wait on to shutdown the server.
     ///
     /// If the future completes, the server will initiate a graceful shutdown.
@@ -129,6 +216,33 @@ impl Config {
         self
     }
 
+    /// Sets how many checkpoint intervals may pass since the latest
+    /// checkpoint was stored before the server reports that it is not ready.
+    ///
+    /// Defaults to three checkpoint intervals.
+    pub fn with_max_checkpoint_staleness(mut self, intervals: u32) -> Self {
+        self.max_checkpoint_staleness = Some(intervals);
+        self
+    }
+
+    /// Sets how many log entries must be added since the last snapshot of
+    /// the transparency state before a new snapshot is stored.
+    ///
+    /// On startup, the latest snapshot is loaded and only the records after
+    /// it are replayed.
+    pub fn with_snapshot_interval(mut self, interval: RegistryLen) -> Self {
+        self.snapshot_interval = Some(interval);
+        self
+    }
+
+    /// Specify a directory to store the node hashes of the registry log in.
+    ///
+    /// If not set, the log is kept in memory.
+    pub fn with_log_storage_dir(mut self, dir: impl Into<PathBuf>) -> Self {
+        self.log_storage_dir = Some(dir.into());
+        self
+    }
+
     /// Sets the content policy to use for the server.
     pub fn with_content_policy(mut self, policy: impl ContentPolicy + 'static) -> Self {
         self.content_policy = Some(Arc::new(policy));
@@ -140,6 +254,33 @@ impl Config {
         self.record_policy = Some(Arc::new(policy));
         self
     }
+
+    /// Sets the token store used to authenticate publish requests.
+    ///
+    /// If set, publishing a record requires a bearer token of an identity
+    /// that is authorized to sign with the record's key.
+    pub fn with_token_store(mut self, store: impl TokenStore + 'static) -> Self {
+        self.token_store = Some(Arc::new(store));
+        self
+    }
+
+    /// Sets the limits on publishing to the server.
+    ///
+    /// By default, nothing is limited.
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+}
+
+/// Derives a checkpoint origin from a URL by removing its scheme.
+fn checkpoint_origin(url: &Url) -> String {
+    let mut origin = url.host_str().unwrap_or_default().to_string();
+    if let Some(port) = url.port() {
+        origin.push_str(&format!(":{port}"));
+    }
+    origin.push_str(url.path().trim_end_matches('/'));
+    origin
 }
 
 /// Represents the warg registry server.
@@ -162,11 +303,15 @@ impl Server {
     }
 
     /// Initializes the server's internal state, background task(s), and
-    /// listening socket, returning an [`InitializedServer`]. To actually begin
-    /// serving, call [`InitializedServer::serve`].
+    /// listening socket, returning an [`InitializedServer`]. To wait for the
+    /// server to stop, call [`InitializedServer::serve`].
+    ///
+    /// The health endpoints are served as soon as the listening socket is
+    /// bound, so that the server may be probed while it initializes; the
+    /// other endpoints respond with a `503` until initialization completes.
     ///
     /// Useful for tests that need full initialization before running.
-    pub async fn initialize(self) -> Result<InitializedServer> {
+    pub async fn initialize(mut self) -> Result<InitializedServer> {
         let addr = self
             .config
             .addr
@@ -178,22 +323,148 @@ impl Server {
             .with_context(|| format!("failed to bind to address `{addr}`"))?;
         let addr = listener.local_addr()?;
 
+        let metrics_listener = match self.config.metrics_addr {
+            Some(addr) => {
+                tracing::debug!("binding metrics server to address `{addr}`");
+                Some(
+                    TcpListener::bind(addr)
+                        .await
+                        .with_context(|| format!("failed to bind to address `{addr}`"))?,
+                )
+            }
+            None => None,
+        };
+
+        let checkpoint_interval = self
+            .config
+            .checkpoint_interval
+            .unwrap_or(DEFAULT_CHECKPOINT_INTERVAL);
+        let health = api::health::Config::new(
+            checkpoint_interval
+                * self
+                    .config
+                    .max_checkpoint_staleness
+                    .unwrap_or(DEFAULT_MAX_CHECKPOINT_STALENESS),
+        );
+
+        // The API router is set once the server has been initialized
+        let api_router = Arc::new(OnceLock::<Router>::new());
+        let router = health.clone().into_router().fallback({
+            let api_router = api_router.clone();
+            move |request: Request| async move {
+                match api_router.get() {
+                    Some(router) => router.clone().oneshot(request).await.into_response(),
+                    None => (
+                        StatusCode::SERVICE_UNAVAILABLE,
+                        "the server is initializing",
+                    )
+                        .into_response(),
+                }
+            }
+        });
+
+        let shutdown = self.config.shutdown.take();
+        let server = tokio::spawn(async move {
+            let server = axum::serve::serve(listener, router.into_make_service());
+            if let Some(shutdown) = shutdown {
+                tracing::debug!("server is running with a shutdown signal");
+                server.with_graceful_shutdown(shutdown).await
+            } else {
+                tracing::debug!("server is running without a shutdown signal");
+                server.await
+            }
+        });
+
+        tracing::info!("listening on {addr}");
+
+        let (router, core, core_handle) = match self.start(addr, checkpoint_interval).await {
+            Ok(started) => started,
+            Err(e) => {
+                server.abort();
+                return Err(e);
+            }
+        };
+
+        // Serve the metrics with the API unless they have an address of their own
+        let metrics_router = api::metrics::Config::new(core.clone()).into_router();
+        let (router, metrics) = match metrics_listener {
+            Some(listener) => (router, Some((listener, metrics_router))),
+            None => (router.merge(metrics_router), None),
+        };
+
+        if api_router.set(router).is_err() {
+            unreachable!("the API router is only set once");
+        }
+        health.set_initialized(core);
+        tracing::info!("server initialized");
+
+        Ok(InitializedServer {
+            addr,
+            server,
+            metrics,
+            core_handle,
+        })
+    }
+
+    /// Starts the core service and creates the router for the API.
+    async fn start(
+        self,
+        addr: SocketAddr,
+        checkpoint_interval: Duration,
+    ) -> Result<(Router, CoreService, JoinHandle<()>)> {
         tracing::debug!(
             "using server configuration: {config:?}",
             config = self.config
         );
 
-        let store = self
+        let content_base_url = self
             .config
-            .data_store
-            .unwrap_or_else(|| Box::<MemoryDataStore>::default());
+            .content_base_url
+            .unwrap_or_else(|| Url::parse(&format!("http://{addr}")).unwrap());
+
+        let checkpoint_origin = match self.config.checkpoint_origin {
+            Some(origin) => origin,
+            None => checkpoint_origin(&content_base_url),
+        };
+        if checkpoint_origin.is_empty()
+            || checkpoint_origin.contains(|c: char| c.is_whitespace() || c == '+')
+        {
+            bail!(
+                "checkpoint origin `{checkpoint_origin}` must be non-empty and contain no \
+                 whitespace or `+`"
+            );
+        }
+        tracing::info!(
+            "checkpoint notes are signed with verifier key `{key}`",
+            key = note::verifier_key(&checkpoint_origin, &self.config.operator_key.public_key())
+        );
+
+        let tiles_dir = self.config.content_dir.join("tiles");
+        let tile_writer = TileWriter::open(&tiles_dir, &checkpoint_origin).with_context(|| {
+            format!(
+                "failed to open tiles directory `{path}`",
+                path = tiles_dir.display()
+            )
+        })?;
+
+        let metrics = Arc::new(Metrics::new());
+        let store = Box::new(InstrumentedDataStore::new(
+            self.config
+                .data_store
+                .unwrap_or_else(|| Box::<MemoryDataStore>::default()),
+            metrics.clone(),
+        ));
         let (core, core_handle) = CoreService::start(
             self.config.operator_key,
             self.config.namespaces,
             store,
+            checkpoint_interval,
             self.config
-                .checkpoint_interval
-                .unwrap_or(DEFAULT_CHECKPOINT_INTERVAL),
+                .snapshot_interval
+                .unwrap_or(DEFAULT_SNAPSHOT_INTERVAL),
+            self.config.log_storage_dir,
+            Some(tile_writer),
+            metrics,
         )
         .await?;
 
@@ -213,59 +484,76 @@ impl Server {
             )
         })?;
 
-        let content_base_url = self
-            .config
-            .content_base_url
-            .unwrap_or_else(|| Url::parse(&format!("http://{addr}")).unwrap());
+        let content_store = self.config.content_store.unwrap_or_else(|| {
+            Arc::new(FileSystemContentStore::new(
+                &files_dir,
+                content_base_url.join("content/").unwrap(),
+            ))
+        });
 
         let router = create_router(
-            content_base_url,
-            core,
+            checkpoint_origin,
+            self.config.witness_keys,
+            core.clone(),
+            content_store,
             temp_dir,
             files_dir,
+            tiles_dir,
             self.config.content_policy,
             self.config.record_policy,
+            self.config.token_store,
+            self.config.limits,
         );
 
-        Ok(InitializedServer {
-            listener,
-            router,
-            core_handle,
-            shutdown: self.config.shutdown,
-        })
+        Ok((router, core, core_handle))
     }
 }
 
 /// Represents an initialized warg registry server.
 pub struct InitializedServer {
-    listener: TcpListener,
-    router: Router,
+    addr: SocketAddr,
+    server: JoinHandle<std::io::Result<()>>,
+    metrics: Option<(TcpListener, Router)>,
     core_handle: JoinHandle<()>,
-    shutdown: Option<ShutdownFut>,
 }
 
 impl InitializedServer {
     /// Returns the listening address of the server. If a random listening
     /// port was requested (i.e. `:0`), this returns the actual bound port.
     pub fn local_addr(&self) -> std::io::Result<SocketAddr> {
-        self.listener.local_addr()
+        Ok(self.addr)
+    }
+
+    /// Returns the listening address of the metrics endpoint, if it is served
+    /// on an address of its own.
+    pub fn metrics_addr(&self) -> Option<std::io::Result<SocketAddr>> {
+        self.metrics
+            .as_ref()
+            .map(|(listener, _)| listener.local_addr())
     }
 
-    /// Serves the server's services. On server shutdown, awaits completion of
+    /// Serves the metrics endpoint, if it has an address of its own, and waits
+    /// for the server to shut down. On server shutdown, awaits completion of
     /// background task(s) before returning.
     pub async fn serve(self) -> Result<()> {
-        let addr = self.local_addr()?;
-
-        let server = axum::serve::serve(self.listener, self.router.into_make_service());
-
-        tracing::info!("listening on {addr}");
-
-        if let Some(shutdown) = self.shutdown {
-            tracing::debug!("server is running with a shutdown signal");
-            server.with_graceful_shutdown(shutdown).await?;
-        } else {
-            tracing::debug!("server is running without a shutdown signal");
-            server.await?;
+        // The metrics server has no state to shut down gracefully, so it is
+        // stopped once the API server has stopped
+        let metrics_task = match self.metrics {
+            Some((listener, router)) => {
+                tracing::info!("serving metrics on {addr}", addr = listener.local_addr()?);
+                Some(tokio::spawn(async move {
+                    if let Err(e) = axum::serve::serve(listener, router.into_make_service()).await {
+                        tracing::error!("metrics server failed: {e}");
+                    }
+                }))
+            }
+            None => None,
+        };
+
+        self.server.await??;
+
+        if let Some(task) = metrics_task {
+            task.abort();
         }
 
         tracing::info!("waiting for core service to stop");
@@ -0,0 +1,194 @@
+//! Module for server publishing limits.
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+use warg_crypto::signing::KeyID;
+
+/// The limits on publishing to the registry.
+///
+/// By default, nothing is limited.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Limits {
+    publish_rate: Option<PublishRate>,
+    max_content_size: Option<u64>,
+    namespace_quota: Option<u64>,
+}
+
+/// A number of records that may be published in a time window.
+#[derive(Debug, Clone, Copy)]
+struct PublishRate {
+    records: usize,
+    window: Duration,
+}
+
+impl Limits {
+    /// Creates a new set of limits with nothing limited.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Limits each signing key to publishing the given number of records in
+    /// any time window of the given duration.
+    ///
+    /// Publishes are counted by each server instance, so when several
+    /// replicas serve the registry a key may publish up to the limit to each.
+    pub fn with_publish_rate(mut self, records: usize, window: Duration) -> Self {
+        self.publish_rate = Some(PublishRate { records, window });
+        self
+    }
+
+    /// Limits the size of each uploaded content, in bytes.
+    pub fn with_max_content_size(mut self, size: u64) -> Self {
+        self.max_content_size = Some(size);
+        self
+    }
+
+    /// Limits the total size of the content uploaded to each namespace, in
+    /// bytes.
+    pub fn with_namespace_quota(mut self, size: u64) -> Self {
+        self.namespace_quota = Some(size);
+        self
+    }
+
+    /// Gets the maximum size of each uploaded content, in bytes.
+    pub fn max_content_size(&self) -> Option<u64> {
+        self.max_content_size
+    }
+
+    /// Gets the storage quota of each namespace, in bytes.
+    pub fn namespace_quota(&self) -> Option<u64> {
+        self.namespace_quota
+    }
+}
+
+/// Enforces the publish rate limit of signing keys.
+///
+/// Publishes are tracked in memory, so the limit applies to each server
+/// instance separately and is reset when the server restarts.
+pub(crate) struct PublishRateLimiter {
+    rate: Option<PublishRate>,
+    published: Mutex<HashMap<KeyID, VecDeque<Instant>>>,
+}
+
+impl PublishRateLimiter {
+    /// Creates a new rate limiter for the publish rate of the given limits.
+    pub fn new(limits: &Limits) -> Self {
+        Self {
+            rate: limits.publish_rate,
+            published: Default::default(),
+        }
+    }
+
+    /// Records a publish of a record signed with the given key.
+    ///
+    /// If the key has already published as many records as allowed in the
+    /// current window, the publish is not recorded and an error describing
+    /// the limit is returned.
+    pub fn check(&self, key: &KeyID) -> Result<(), String> {
+        let Some(rate) = self.rate else {
+            return Ok(());
+        };
+
+        let now = Instant::now();
+        let mut published = self.published.lock().unwrap();
+
+        // Forget publishes that are no longer in the window of any key
+        published.retain(|_, times| {
+            while times
+                .front()
+                .is_some_and(|time| now.duration_since(*time) >= rate.window)
+            {
+                times.pop_front();
+            }
+            !times.is_empty()
+        });
+
+        let times = published.entry(key.clone()).or_default();
+        if times.len() >= rate.records {
+            let retry_after = times
+                .front()
+                .map(|time| rate.window.saturating_sub(now.duration_since(*time)))
+                .unwrap_or_default();
+            return Err(format!(
+                "key id `{key}` may publish at most {records} record(s) every {window} second(s); \
+                 try again in {retry_after} second(s)",
+                records = rate.records,
+                window = rate.window.as_secs(),
+                retry_after = retry_after.as_secs().max(1),
+            ));
+        }
+
+        times.push_back(now);
+        Ok(())
+    }
+
+    /// Releases the most recent publish recorded for the given key.
+    ///
+    /// This is used when a publish that passed the check was not accepted.
+    pub fn release(&self, key: &KeyID) {
+        if let Some(times) = self.published.lock().unwrap().get_mut(key) {
+            times.pop_back();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use warg_crypto::signing::generate_p256_pair;
+
+    #[test]
+    fn it_limits_the_publish_rate_of_each_key() {
+        let limiter =
+            PublishRateLimiter::new(&Limits::new().with_publish_rate(2, Duration::from_secs(60)));
+        let (first, _) = generate_p256_pair();
+        let (second, _) = generate_p256_pair();
+
+        assert!(limiter.check(&first.fingerprint()).is_ok());
+        assert!(limiter.check(&first.fingerprint()).is_ok());
+        let message = limiter.check(&first.fingerprint()).unwrap_err();
+        assert!(
+            message.contains("may publish at most 2 record(s) every 60 second(s)"),
+            "{message}"
+        );
+
+        // Other keys have their own limit
+        assert!(limiter.check(&second.fingerprint()).is_ok());
+    }
+
+    #[test]
+    fn it_allows_publishes_once_the_window_passes() {
+        let limiter =
+            PublishRateLimiter::new(&Limits::new().with_publish_rate(1, Duration::from_millis(50)));
+        let (key, _) = generate_p256_pair();
+
+        assert!(limiter.check(&key.fingerprint()).is_ok());
+        assert!(limiter.check(&key.fingerprint()).is_err());
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(limiter.check(&key.fingerprint()).is_ok());
+    }
+
+    #[test]
+    fn it_does_not_count_released_publishes() {
+        let limiter =
+            PublishRateLimiter::new(&Limits::new().with_publish_rate(1, Duration::from_secs(60)));
+        let (key, _) = generate_p256_pair();
+
+        assert!(limiter.check(&key.fingerprint()).is_ok());
+        limiter.release(&key.fingerprint());
+        assert!(limiter.check(&key.fingerprint()).is_ok());
+        assert!(limiter.check(&key.fingerprint()).is_err());
+    }
+
+    #[test]
+    fn it_does_not_limit_by_default() {
+        let limiter = PublishRateLimiter::new(&Limits::new());
+        let (key, _) = generate_p256_pair();
+
+        for _ in 0..100 {
+            assert!(limiter.check(&key.fingerprint()).is_ok());
+        }
+    }
+}
@@ -0,0 +1,249 @@
+//! Module for server metrics.
+use crate::datastore::RecordCounts;
+use prometheus_client::{
+    encoding::{text::encode, EncodeLabelSet},
+    metrics::{
+        counter::Counter,
+        family::Family,
+        gauge::Gauge,
+        histogram::{exponential_buckets, Histogram},
+    },
+    registry::{Registry, Unit},
+};
+use std::{
+    sync::{atomic::AtomicU64, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct RequestLabels {
+    method: String,
+    route: String,
+    status: u16,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct RouteLabels {
+    method: String,
+    route: String,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct StatusLabels {
+    status: &'static str,
+}
+
+#[derive(Clone, Debug, Hash, PartialEq, Eq, EncodeLabelSet)]
+struct OperationLabels {
+    operation: &'static str,
+}
+
+type HistogramFamily<L> = Family<L, Histogram, fn() -> Histogram>;
+
+// Buckets from 5ms to ~10s for request and checkpoint durations.
+fn duration_histogram() -> Histogram {
+    Histogram::new(exponential_buckets(0.005, 2.0, 12))
+}
+
+// Buckets from 0.5ms to ~4s for data store operations.
+fn operation_histogram() -> Histogram {
+    Histogram::new(exponential_buckets(0.0005, 2.0, 14))
+}
+
+/// The operational metrics of the server.
+///
+/// The metrics are served in the OpenMetrics text format at `/metrics`.
+pub struct Metrics {
+    registry: Registry,
+    requests: Family<RequestLabels, Counter>,
+    request_duration: HistogramFamily<RouteLabels>,
+    records: Family<StatusLabels, Gauge>,
+    checkpoint_duration: Histogram,
+    checkpoint_interval: Histogram,
+    checkpoint_timestamp: Gauge<f64, AtomicU64>,
+    checkpoint_failures: Counter,
+    log_length: Gauge,
+    content_stored: Counter,
+    datastore_duration: HistogramFamily<OperationLabels>,
+    last_checkpoint: Mutex<Option<Instant>>,
+}
+
+impl Metrics {
+    /// Creates a new set of metrics.
+    pub fn new() -> Self {
+        let mut registry = Registry::with_prefix("warg");
+
+        let requests = Family::<RequestLabels, Counter>::default();
+        registry.register(
+            "http_requests",
+            "Number of API requests by route and response status",
+            requests.clone(),
+        );
+
+        let request_duration = HistogramFamily::new_with_constructor(duration_histogram as _);
+        registry.register_with_unit(
+            "http_request_duration",
+            "Duration of API requests by route",
+            Unit::Seconds,
+            request_duration.clone(),
+        );
+
+        let records = Family::<StatusLabels, Gauge>::default();
+        registry.register("records", "Number of records by status", records.clone());
+
+        let checkpoint_duration = duration_histogram();
+        registry.register_with_unit(
+            "checkpoint_duration",
+            "Duration of signing and storing a checkpoint",
+            Unit::Seconds,
+            checkpoint_duration.clone(),
+        );
+
+        let checkpoint_interval = Histogram::new(exponential_buckets(1.0, 2.0, 10));
+        registry.register_with_unit(
+            "checkpoint_interval",
+            "Time between stored checkpoints",
+            Unit::Seconds,
+            checkpoint_interval.clone(),
+        );
+
+        let checkpoint_timestamp = Gauge::<f64, AtomicU64>::default();
+        registry.register_with_unit(
+            "checkpoint_last_stored_timestamp",
+            "Time the latest checkpoint was stored, since the Unix epoch",
+            Unit::Seconds,
+            checkpoint_timestamp.clone(),
+        );
+
+        let checkpoint_failures = Counter::default();
+        registry.register(
+            "checkpoint_failures",
+            "Number of checkpoints that failed to be stored",
+            checkpoint_failures.clone(),
+        );
+
+        let log_length = Gauge::default();
+        registry.register(
+            "log_length",
+            "Length of the registry log at the latest checkpoint",
+            log_length.clone(),
+        );
+
+        let content_stored = Counter::default();
+        registry.register_with_unit(
+            "content_stored",
+            "Size of the content uploaded to the content store",
+            Unit::Bytes,
+            content_stored.clone(),
+        );
+
+        let datastore_duration = HistogramFamily::new_with_constructor(operation_histogram as _);
+        registry.register_with_unit(
+            "datastore_operation_duration",
+            "Duration of data store operations",
+            Unit::Seconds,
+            datastore_duration.clone(),
+        );
+
+        Self {
+            registry,
+            requests,
+            request_duration,
+            records,
+            checkpoint_duration,
+            checkpoint_interval,
+            checkpoint_timestamp,
+            checkpoint_failures,
+            log_length,
+            content_stored,
+            datastore_duration,
+            last_checkpoint: Mutex::new(None),
+        }
+    }
+
+    /// Records a completed API request for the given route pattern.
+    pub fn observe_request(&self, method: &str, route: &str, status: u16, duration: Duration) {
+        self.requests
+            .get_or_create(&RequestLabels {
+                method: method.to_string(),
+                route: route.to_string(),
+                status,
+            })
+            .inc();
+        self.request_duration
+            .get_or_create(&RouteLabels {
+                method: method.to_string(),
+                route: route.to_string(),
+            })
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Records a stored checkpoint and how long it took to store.
+    pub fn observe_checkpoint(&self, log_length: u64, duration: Duration) {
+        let now = Instant::now();
+        if let Some(last) = self.last_checkpoint.lock().unwrap().replace(now) {
+            self.checkpoint_interval
+                .observe(now.duration_since(last).as_secs_f64());
+        }
+
+        self.checkpoint_duration.observe(duration.as_secs_f64());
+        self.checkpoint_timestamp.set(
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs_f64(),
+        );
+        self.log_length.set(log_length as i64);
+    }
+
+    /// Records a checkpoint that failed to be stored.
+    pub fn checkpoint_failed(&self) {
+        self.checkpoint_failures.inc();
+    }
+
+    /// Records the size of content stored in the content store.
+    pub fn content_stored(&self, size: u64) {
+        self.content_stored.inc_by(size);
+    }
+
+    /// Records how long a data store operation took.
+    pub fn observe_datastore_operation(&self, operation: &'static str, duration: Duration) {
+        self.datastore_duration
+            .get_or_create(&OperationLabels { operation })
+            .observe(duration.as_secs_f64());
+    }
+
+    /// Sets the number of records in each status.
+    pub fn set_record_counts(&self, counts: &RecordCounts) {
+        for (status, count) in [
+            ("missing_content", counts.missing_content),
+            ("pending", counts.pending),
+            ("rejected", counts.rejected),
+            ("validated", counts.validated),
+            ("published", counts.published),
+        ] {
+            self.records
+                .get_or_create(&StatusLabels { status })
+                .set(count as i64);
+        }
+    }
+
+    /// Encodes the metrics in the OpenMetrics text format.
+    pub fn encode(&self) -> String {
+        let mut buffer = String::new();
+        encode(&mut buffer, &self.registry).expect("writing to a string cannot fail");
+        buffer
+    }
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for Metrics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Metrics").finish_non_exhaustive()
+    }
+}
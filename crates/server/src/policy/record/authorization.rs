@@ -1,4 +1,5 @@
 use super::{RecordPolicy, RecordPolicyError, RecordPolicyResult};
+use crate::auth::Identity;
 use anyhow::{bail, Result};
 use indexmap::{IndexMap, IndexSet};
 use serde::Deserialize;
@@ -10,6 +11,10 @@ use warg_protocol::{
 };
 
 /// A policy that ensures a published record is signed by an authorized key.
+///
+/// The policy may also restrict which authenticated identities may publish to
+/// a namespace or package; if no identities are listed for either, any
+/// identity may publish there.
 #[derive(Default, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct AuthorizedKeyPolicy {
@@ -26,6 +31,9 @@ pub struct AuthorizedKeyPolicy {
 struct LogPolicy {
     // Authorized key IDs
     keys: IndexSet<KeyID>,
+    // Authorized identity names; if empty, identities are not restricted
+    #[serde(default)]
+    identities: IndexSet<String>,
     // If true, permission grants are permitted.
     #[serde(default)]
     delegation: bool,
@@ -57,6 +65,18 @@ impl AuthorizedKeyPolicy {
         Ok(self)
     }
 
+    /// Sets an authorized identity for publishing to a particular namespace.
+    pub fn with_namespace_identity(
+        mut self,
+        namespace: impl Into<String>,
+        identity: impl Into<String>,
+    ) -> Result<Self> {
+        self.namespace_or_default_mut(namespace)?
+            .identities
+            .insert(identity.into());
+        Ok(self)
+    }
+
     /// Enables delegation for a particular namespace.
     pub fn with_namespace_delegation(mut self, namespace: impl Into<String>) -> Result<Self> {
         self.namespace_or_default_mut(namespace)?.delegation = true;
@@ -78,6 +98,18 @@ impl AuthorizedKeyPolicy {
         Ok(self)
     }
 
+    /// Sets an authorized identity for publishing to a particular package.
+    pub fn with_package_identity(
+        mut self,
+        package_name: impl Into<String>,
+        identity: impl Into<String>,
+    ) -> Result<Self> {
+        self.package_or_default_mut(package_name)?
+            .identities
+            .insert(identity.into());
+        Ok(self)
+    }
+
     /// Enables delegation for a particular package.
     pub fn with_package_delegation(mut self, package_name: impl Into<String>) -> Result<Self> {
         self.package_or_default_mut(package_name)?.delegation = true;
@@ -116,6 +148,27 @@ impl AuthorizedKeyPolicy {
 
         false
     }
+
+    /// Determines if the named identity may publish to the given package.
+    ///
+    /// An identity is authorized if it is listed for the package or its
+    /// namespace, or if neither lists any identities.
+    pub fn identity_authorized_for_package(&self, identity: &str, package: &PackageName) -> bool {
+        let policies = [
+            self.namespaces.get(package.namespace()),
+            self.packages.get(package),
+        ];
+
+        let mut restricted = false;
+        for policy in policies.into_iter().flatten() {
+            if policy.identities.contains(identity) {
+                return true;
+            }
+            restricted |= !policy.identities.is_empty();
+        }
+
+        !restricted
+    }
 }
 
 impl RecordPolicy for AuthorizedKeyPolicy {
@@ -135,6 +188,16 @@ impl RecordPolicy for AuthorizedKeyPolicy {
         }
         Ok(())
     }
+
+    fn check_identity(&self, identity: &Identity, name: &PackageName) -> RecordPolicyResult<()> {
+        if !self.identity_authorized_for_package(identity.name(), name) {
+            return Err(RecordPolicyError::Unauthorized(format!(
+                "identity `{identity}` is not authorized to publish to package `{name}`",
+                identity = identity.name()
+            )));
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -201,4 +264,26 @@ mod tests {
         assert!(policy.key_authorized_for_entry(&other_key, &ns2_pkg, false));
         Ok(())
     }
+
+    #[test]
+    fn test_identity_authorized_for_package() -> Result<()> {
+        let policy = AuthorizedKeyPolicy::new()
+            .with_namespace_identity("ns1", "alice")?
+            .with_package_identity("ns1:pkg", "bob")?
+            .with_namespace_key("ns2", KeyID::from("key".to_string()))?;
+
+        let ns1_pkg: PackageName = "ns1:pkg".parse()?;
+        let ns1_other: PackageName = "ns1:other".parse()?;
+        let ns2_pkg: PackageName = "ns2:pkg".parse()?;
+
+        assert!(policy.identity_authorized_for_package("alice", &ns1_pkg));
+        assert!(policy.identity_authorized_for_package("alice", &ns1_other));
+        assert!(policy.identity_authorized_for_package("bob", &ns1_pkg));
+        assert!(!policy.identity_authorized_for_package("bob", &ns1_other));
+        assert!(!policy.identity_authorized_for_package("eve", &ns1_pkg));
+
+        // Namespaces without listed identities are not restricted
+        assert!(policy.identity_authorized_for_package("eve", &ns2_pkg));
+        Ok(())
+    }
 }
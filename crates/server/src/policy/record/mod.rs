@@ -1,4 +1,5 @@
 //! Module for server record policy implementations.
+use crate::auth::Identity;
 use thiserror::Error;
 use warg_protocol::{package::PackageRecord, registry::PackageName, ProtoEnvelope};
 
@@ -30,6 +31,14 @@ pub trait RecordPolicy: Send + Sync {
         name: &PackageName,
         record: &ProtoEnvelope<PackageRecord>,
     ) -> RecordPolicyResult<()>;
+
+    /// Checks that an authenticated identity may publish to the given package.
+    ///
+    /// The default implementation authorizes every identity.
+    fn check_identity(&self, identity: &Identity, name: &PackageName) -> RecordPolicyResult<()> {
+        let _ = (identity, name);
+        Ok(())
+    }
 }
 
 /// Represents a collection of record policies.
@@ -65,4 +74,12 @@ impl RecordPolicy for RecordPolicyCollection {
 
         Ok(())
     }
+
+    fn check_identity(&self, identity: &Identity, name: &PackageName) -> RecordPolicyResult<()> {
+        for policy in &self.policies {
+            policy.check_identity(identity, name)?;
+        }
+
+        Ok(())
+    }
 }
@@ -1,6 +1,9 @@
 use std::{
+    collections::VecDeque,
+    io, mem,
+    path::PathBuf,
     sync::Arc,
-    time::{Duration, SystemTime},
+    time::{Duration, Instant, SystemTime},
 };
 
 use futures::{pin_mut, StreamExt};
@@ -12,8 +15,8 @@ use tokio::{
     time::MissedTickBehavior,
 };
 use warg_crypto::{
-    hash::{AnyHash, Hash, Sha256, SupportedDigest},
-    signing::PrivateKey,
+    hash::{AnyHash, Hash, HashAlgorithm, Sha256, SupportedDigest},
+    signing::{note::Note, note::NoteError, PrivateKey},
 };
 use warg_protocol::{
     operator,
@@ -24,17 +27,40 @@ use warg_protocol::{
     ProtoEnvelope, SerdeEnvelope,
 };
 use warg_transparency::{
-    log::{LogBuilder, LogData, LogProofBundle, Node, VecLog},
-    map::{Map, MapProofBundle},
+    log::{self, DiskLog, LogBuilder, LogData, LogProofBundle, Node, VecLog},
+    map::{
+        AbsenceProof as MapAbsenceProof, Map, MapProofBundle, MultiProof as MapMultiProof,
+        Proof as MapProof,
+    },
+};
+
+use super::TileWriter;
+use crate::{
+    datastore::{DataStore, DataStoreError, Snapshot},
+    metrics::Metrics,
 };
 
-use crate::datastore::{DataStore, DataStoreError};
+// The number of log leafs to fetch at a time when replaying records.
+const REPLAY_BATCH_SIZE: usize = 1000;
+
+// The number of checkpoint maps kept in memory; older maps are rebuilt on demand.
+const CACHED_CHECKPOINT_MAPS: usize = 64;
+
+// The name of the file storing the log's node hashes in the log storage directory.
+const LOG_FILE_NAME: &str = "log";
+
+// The delay before retrying to commit a record, doubled after each attempt.
+const COMMIT_RETRY_DELAY: Duration = Duration::from_millis(100);
+
+// The maximum delay before retrying to commit a record.
+const MAX_COMMIT_RETRY_DELAY: Duration = Duration::from_secs(30);
 
 #[derive(Clone)]
 pub struct CoreService<Digest: SupportedDigest = Sha256> {
     inner: Arc<Inner<Digest>>,
 
-    // Channel sender used by `submit_package_record` to serialize submissions.
+    // Channel sender used by `submit_operator_record` and `submit_package_record`
+    // to serialize submissions.
     submit_entry_tx: mpsc::Sender<LogLeaf>,
 }
 
@@ -42,19 +68,50 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
     /// Starts the `CoreService`, returning a `clone`able handle to the
     /// service and a [`JoinHandle`] which should be awaited after dropping all
     /// copies of the service handle to allow for graceful shutdown.
+    ///
+    /// A snapshot of the transparency state is stored whenever a checkpoint
+    /// is at least `snapshot_interval` log entries past the last snapshot; a
+    /// `snapshot_interval` of zero disables snapshots.
+    ///
+    /// If `log_storage_dir` is given, the node hashes of the log are stored
+    /// on disk in that directory instead of in memory.
+    ///
+    /// If `tiles` is given, the log is written as tiles whenever it grows,
+    /// before the new checkpoint is stored.
+    ///
+    /// Stored checkpoints and checkpoint failures are recorded in `metrics`.
+    #[allow(clippy::too_many_arguments)]
     pub async fn start(
         operator_key: PrivateKey,
         namespaces: Option<Vec<(String, operator::NamespaceState)>>,
         store: Box<dyn DataStore>,
         checkpoint_interval: Duration,
+        snapshot_interval: RegistryLen,
+        log_storage_dir: Option<PathBuf>,
+        tiles: Option<TileWriter>,
+        metrics: Arc<Metrics>,
     ) -> Result<(Self, JoinHandle<()>), CoreServiceError> {
+        let log = match log_storage_dir {
+            Some(dir) => {
+                std::fs::create_dir_all(&dir)?;
+                StateLog::Disk(DiskLog::open(dir.join(LOG_FILE_NAME))?)
+            }
+            None => StateLog::Memory(Default::default()),
+        };
+
         // Build service
         let mut inner = Inner {
             operator_key,
             store,
-            state: Default::default(),
+            snapshot_interval,
+            tiles,
+            metrics,
+            state: RwLock::new(State::new(log)),
         };
         inner.initialize(namespaces).await?;
+        if let Some(tiles) = &mut inner.tiles {
+            tiles.resume(inner.store.as_ref()).await?;
+        }
 
         // Spawn state update task
         let inner = Arc::new(inner);
@@ -65,6 +122,16 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
                 .process_state_updates(submit_entry_rx, checkpoint_interval),
         );
 
+        // Resubmit the records that were accepted before the service stopped
+        let queued = inner.store.get_queued_records().await?;
+        if !queued.is_empty() {
+            tracing::info!("resubmitting {len} queued record(s)", len = queued.len());
+        }
+
+        for entry in queued {
+            submit_entry_tx.send(entry).await.unwrap();
+        }
+
         let svc = Self {
             inner,
             submit_entry_tx,
@@ -85,6 +152,42 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
             .map_err(CoreServiceError::BundleFailure)
     }
 
+    /// Verifies that a checkpoint is consistent with an older checkpoint.
+    pub async fn verify_log_consistency(
+        &self,
+        from: &Checkpoint,
+        to: &Checkpoint,
+    ) -> Result<(), CoreServiceError> {
+        let inconsistent = || CoreServiceError::InconsistentCheckpoints {
+            from: from.log_length,
+            to: to.log_length,
+        };
+
+        if from.log_length >= to.log_length {
+            return if from == to {
+                Ok(())
+            } else {
+                Err(inconsistent())
+            };
+        }
+
+        let state = self.inner.state.read().await;
+        if to.log_length > state.log.length() {
+            return Err(CoreServiceError::CheckpointNotFound(to.log_length));
+        }
+
+        let (from_root, to_root) = state
+            .log
+            .prove_consistency(from.log_length, to.log_length)
+            .evaluate(&state.log)
+            .map_err(|_| inconsistent())?;
+        if AnyHash::from(from_root) != from.log_root || AnyHash::from(to_root) != to.log_root {
+            return Err(inconsistent());
+        }
+
+        Ok(())
+    }
+
     /// Constructs log inclusion proofs for the given entries at the given log tree root.
     pub async fn log_inclusion_proofs(
         &self,
@@ -96,11 +199,12 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
         let proofs = entries
             .iter()
             .map(|&index| {
-                let node = if index < state.leaf_index.len() as RegistryIndex {
-                    state.leaf_index[index]
-                } else {
+                if index >= state.log.length() as RegistryIndex {
                     return Err(CoreServiceError::LeafNotFound(index));
-                };
+                }
+
+                // Leafs are stored at every other node of the log tree
+                let node = Node(index * 2);
                 Ok(state.log.prove_inclusion(node, log_length))
             })
             .collect::<Result<Vec<_>, CoreServiceError>>()?;
@@ -108,18 +212,40 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
         LogProofBundle::bundle(vec![], proofs, &state.log).map_err(CoreServiceError::BundleFailure)
     }
 
-    /// Constructs map inclusion proofs for the given entries at the given map tree root.
+    /// Constructs map inclusion proofs for the given entries and map absence
+    /// proofs for the given package logs at the given map tree root.
     pub async fn map_inclusion_proofs(
         &self,
         log_length: RegistryLen,
         entries: &[RegistryIndex],
+        absent: &[LogId],
     ) -> Result<MapProofBundle<Digest, LogId, MapLeaf>, CoreServiceError> {
-        let state = self.inner.state.read().await;
+        let proofs = self.prove_map_inclusion(log_length, entries).await?;
+        let absence_proofs = self.prove_map_absence(log_length, absent).await?;
+        Ok(MapProofBundle::bundle_with_absence(
+            proofs.into_iter().map(|(_, proof)| proof).collect(),
+            absence_proofs,
+        ))
+    }
 
-        let (map_root, map) = state
-            .map_index
-            .get(&log_length)
-            .ok_or_else(|| CoreServiceError::CheckpointNotFound(log_length))?;
+    /// Constructs a map inclusion multiproof for the given entries at the given map tree root.
+    pub async fn map_inclusion_multiproof(
+        &self,
+        log_length: RegistryLen,
+        entries: &[RegistryIndex],
+    ) -> Result<MapMultiProof<Digest, LogId, MapLeaf>, CoreServiceError> {
+        let proofs = self.prove_map_inclusion(log_length, entries).await?;
+        Ok(MapMultiProof::from_proofs(
+            proofs.iter().map(|(log_id, proof)| (log_id, proof)),
+        ))
+    }
+
+    async fn prove_map_inclusion(
+        &self,
+        log_length: RegistryLen,
+        entries: &[RegistryIndex],
+    ) -> Result<Vec<(LogId, MapProof<Digest, LogId, MapLeaf>)>, CoreServiceError> {
+        let (map_root, map) = self.inner.checkpoint_map(log_length).await?;
 
         let indexes = self
             .inner
@@ -128,8 +254,8 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
             .await
             .map_err(CoreServiceError::DataStore)?;
 
-        let proofs = indexes
-            .iter()
+        indexes
+            .into_iter()
             .map(|log_leaf| {
                 let LogLeaf { log_id, record_id } = log_leaf;
 
@@ -137,22 +263,59 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
                     .prove(log_id.clone())
                     .ok_or_else(|| CoreServiceError::PackageNotIncluded(log_id.clone()))?;
 
-                let map_leaf = MapLeaf {
-                    record_id: record_id.clone(),
-                };
-                let found_root = proof.evaluate(log_id, &map_leaf);
-                if &found_root != map_root {
+                let map_leaf = MapLeaf { record_id };
+                let found_root = proof.evaluate(&log_id, &map_leaf);
+                if found_root != map_root {
                     return Err(CoreServiceError::IncorrectProof {
-                        root: map_root.into(),
+                        root: (&map_root).into(),
+                        found: found_root.into(),
+                    });
+                }
+
+                Ok((log_id, proof))
+            })
+            .collect()
+    }
+
+    async fn prove_map_absence(
+        &self,
+        log_length: RegistryLen,
+        log_ids: &[LogId],
+    ) -> Result<Vec<MapAbsenceProof<Digest, LogId>>, CoreServiceError> {
+        if log_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let (map_root, map) = self.inner.checkpoint_map(log_length).await?;
+
+        log_ids
+            .iter()
+            .map(|log_id| {
+                let proof = map
+                    .prove_absence(log_id.clone())
+                    .ok_or_else(|| CoreServiceError::PackageIncluded(log_id.clone()))?;
+
+                let found_root = proof.evaluate(log_id);
+                if found_root != map_root {
+                    return Err(CoreServiceError::IncorrectProof {
+                        root: (&map_root).into(),
                         found: found_root.into(),
                     });
                 }
 
                 Ok(proof)
             })
-            .collect::<Result<Vec<_>, CoreServiceError>>()?;
+            .collect()
+    }
 
-        Ok(MapProofBundle::bundle(proofs))
+    /// Signs the given checkpoint as a tlog-checkpoint note of the registry
+    /// log with the given origin, using the origin as the key name.
+    pub fn sign_checkpoint_note(
+        &self,
+        origin: &str,
+        checkpoint: &Checkpoint,
+    ) -> Result<Note, CoreServiceError> {
+        self.inner.sign_checkpoint_note(origin, checkpoint)
     }
 
     /// Gets the data store associated with the transparency service.
@@ -160,6 +323,28 @@ impl<Digest: SupportedDigest> CoreService<Digest> {
         self.inner.store.as_ref()
     }
 
+    /// Gets the hash algorithm of the registry's log and map, which is also
+    /// used to derive log and operator record identifiers.
+    pub fn hash_algorithm(&self) -> HashAlgorithm {
+        Digest::ALGORITHM
+    }
+
+    /// Gets the server metrics the service records to.
+    pub fn metrics(&self) -> &Arc<Metrics> {
+        &self.inner.metrics
+    }
+
+    /// Submits an operator record to be processed.
+    pub async fn submit_operator_record(&self, record_id: RecordId) {
+        self.submit_entry_tx
+            .send(LogLeaf {
+                log_id: LogId::operator_log::<Digest>(),
+                record_id,
+            })
+            .await
+            .unwrap()
+    }
+
     /// Submits a package record to be processed.
     pub async fn submit_package_record(&self, log_id: LogId, record_id: RecordId) {
         self.submit_entry_tx
@@ -176,6 +361,15 @@ struct Inner<Digest: SupportedDigest> {
     // DataStore persists transparency state.
     store: Box<dyn DataStore>,
 
+    // Number of log entries between stored snapshots (zero disables snapshots).
+    snapshot_interval: RegistryLen,
+
+    // Writes the log as tiles, if enabled.
+    tiles: Option<TileWriter>,
+
+    // Server metrics.
+    metrics: Arc<Metrics>,
+
     // In-memory transparency state.
     state: RwLock<State<Digest>>,
 }
@@ -189,6 +383,30 @@ impl<Digest: SupportedDigest> Inner<Digest> {
     ) -> Result<(), CoreServiceError> {
         tracing::debug!("Initializing CoreService");
 
+        let mut checkpoints = self.store.get_all_checkpoints().await?;
+        let mut checkpoints_by_len: IndexMap<RegistryLen, Checkpoint> = Default::default();
+        while let Some(checkpoint) = checkpoints.next().await {
+            let checkpoint = checkpoint?.checkpoint;
+            checkpoints_by_len.insert(checkpoint.log_length, checkpoint);
+        }
+
+        // Restore from the latest snapshot and replay only the records after it
+        if let Some(snapshot) = self.store.get_latest_snapshot().await? {
+            let log_length = snapshot.log_length;
+            match self.state.get_mut().restore(snapshot, &checkpoints_by_len) {
+                Ok(()) => {
+                    tracing::debug!("Restored snapshot at log length {log_length}");
+                    return self.replay(&checkpoints_by_len).await;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to restore snapshot at log length {log_length}: {e}");
+                }
+            }
+        }
+
+        // Start over from an empty state
+        self.state.get_mut().reset()?;
+
         let published = self.store.get_all_validated_records().await?.peekable();
         pin_mut!(published);
 
@@ -199,28 +417,102 @@ impl<Digest: SupportedDigest> Inner<Digest> {
         }
 
         // Reconstruct internal state from previously-stored data
-        let mut checkpoints = self.store.get_all_checkpoints().await?;
-        let mut checkpoints_by_len: IndexMap<RegistryLen, Checkpoint> = Default::default();
-        while let Some(checkpoint) = checkpoints.next().await {
-            let checkpoint = checkpoint?.checkpoint;
-            checkpoints_by_len.insert(checkpoint.log_length, checkpoint);
-        }
-
         let state = self.state.get_mut();
         while let Some(entry) = published.next().await {
             state.push_entry(entry?);
-            if let Some(stored_checkpoint) =
-                checkpoints_by_len.get(&(state.log.length() as RegistryLen))
-            {
-                // Validate stored checkpoint (and update internal state as a side-effect)
-                let computed_checkpoint = state.checkpoint();
-                assert!(stored_checkpoint == &computed_checkpoint);
-            }
+            state.validate_checkpoint(&checkpoints_by_len);
         }
 
         Ok(())
     }
 
+    // Replays the validated records following the current state of the log.
+    async fn replay(
+        &mut self,
+        checkpoints_by_len: &IndexMap<RegistryLen, Checkpoint>,
+    ) -> Result<(), CoreServiceError> {
+        let state = self.state.get_mut();
+        loop {
+            let leafs = self
+                .store
+                .get_log_leafs_starting_with_registry_index(state.log.length(), REPLAY_BATCH_SIZE)
+                .await?;
+            if leafs.is_empty() {
+                return Ok(());
+            }
+
+            for (index, leaf) in leafs {
+                if index != state.log.length() {
+                    return Err(CoreServiceError::LeafNotFound(state.log.length()));
+                }
+                state.push_entry(leaf);
+                state.validate_checkpoint(checkpoints_by_len);
+            }
+        }
+    }
+
+    // Gets the map of the checkpoint at the given log length, rebuilding it if
+    // it is no longer in memory.
+    async fn checkpoint_map(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<CheckpointMap<Digest>, CoreServiceError> {
+        let cached = self.state.read().await.map_index.get(&log_length).cloned();
+        match cached {
+            Some(Some(entry)) => Ok(entry),
+            Some(None) => self.rebuild_map(log_length).await,
+            None => Err(CoreServiceError::CheckpointNotFound(log_length)),
+        }
+    }
+
+    // Rebuilds the map of a checkpoint that precedes the restored snapshot
+    // from the stored log leafs, caching it for subsequent proofs.
+    async fn rebuild_map(
+        &self,
+        log_length: RegistryLen,
+    ) -> Result<CheckpointMap<Digest>, CoreServiceError> {
+        // Start from the closest earlier map still in memory
+        let (mut index, mut map) = self
+            .state
+            .read()
+            .await
+            .map_index
+            .iter()
+            .filter_map(|(len, entry)| Some((*len, entry.as_ref()?.1.clone())))
+            .filter(|(len, _)| *len < log_length)
+            .max_by_key(|(len, _)| *len)
+            .unwrap_or_default();
+        while index < log_length {
+            let leafs = self
+                .store
+                .get_log_leafs_starting_with_registry_index(
+                    index,
+                    REPLAY_BATCH_SIZE.min(log_length - index),
+                )
+                .await?;
+            if leafs.is_empty() {
+                return Err(CoreServiceError::LeafNotFound(index));
+            }
+
+            let mut entries = Vec::with_capacity(leafs.len());
+            for (registry_index, LogLeaf { log_id, record_id }) in leafs {
+                if registry_index != index {
+                    return Err(CoreServiceError::LeafNotFound(index));
+                }
+                entries.push((log_id, MapLeaf { record_id }));
+                index += 1;
+            }
+            map = map.par_extend(entries);
+        }
+
+        let entry = (map.root().clone(), map);
+        self.state
+            .write()
+            .await
+            .cache_map(log_length, entry.clone());
+        Ok(entry)
+    }
+
     async fn initialize_new(
         &mut self,
         namespaces: Option<Vec<(String, operator::NamespaceState)>>,
@@ -303,67 +595,182 @@ impl<Digest: SupportedDigest> Inner<Digest> {
         let mut checkpoint_interval = tokio::time::interval(checkpoint_interval);
         checkpoint_interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
 
+        // An entry that failed to commit is retried with backoff until it is
+        // committed; later entries are not received in the meantime so that
+        // they are validated against state that includes it
+        let mut retry: Option<(LogLeaf, Duration)> = None;
+        let retry_timer = tokio::time::sleep(Duration::ZERO);
+        tokio::pin!(retry_timer);
+
         loop {
-            tokio::select! {
-                entry = submit_entry_rx.recv() => match entry {
-                    Some(entry) => self.process_package_entry(&entry).await,
+            let (entry, delay) = tokio::select! {
+                entry = submit_entry_rx.recv(), if retry.is_none() => match entry {
+                    Some(entry) => (entry, COMMIT_RETRY_DELAY),
                     None => break, // Channel closed
                 },
-                _ = checkpoint_interval.tick() => self.update_checkpoint(&mut checkpoint).await,
+                _ = &mut retry_timer, if retry.is_some() => retry.take().unwrap(),
+                _ = checkpoint_interval.tick() => {
+                    self.update_checkpoint(&mut checkpoint).await;
+                    continue;
+                }
+            };
+
+            if let Err(e) = self.process_entry(&entry).await {
+                if submit_entry_rx.is_closed() {
+                    // The record remains queued in the store and will be
+                    // submitted again when the service is restarted
+                    tracing::error!(
+                        "failed to validate record `{record_id}` before shutdown: {e}",
+                        record_id = entry.record_id
+                    );
+                    break;
+                }
+
+                tracing::warn!(
+                    "failed to validate record `{record_id}`, retrying in {delay:?}: {e}",
+                    record_id = entry.record_id
+                );
+                retry_timer
+                    .as_mut()
+                    .reset(tokio::time::Instant::now() + delay);
+                retry = Some((entry, (delay * 2).min(MAX_COMMIT_RETRY_DELAY)));
             }
         }
     }
 
-    // Processes a submitted package entry
-    async fn process_package_entry(&self, entry: &LogLeaf) {
+    // Processes a submitted operator or package entry, committing it to the
+    // store and pushing it to the log
+    //
+    // Returns datastore errors that may succeed when retried.
+    async fn process_entry(&self, entry: &LogLeaf) -> Result<(), DataStoreError> {
         tracing::debug!("Processing entry {entry:?}");
 
         let mut state = self.state.write().await;
         let LogLeaf { log_id, record_id } = entry;
 
-        // Validate and commit the package entry to the store
+        // Validate and commit the entry to the store
         let registry_index = state.log.length() as RegistryIndex;
-        let commit_res = self
-            .store
-            .commit_package_record(log_id, record_id, registry_index)
-            .await;
+        let commit_res = if log_id == &LogId::operator_log::<Digest>() {
+            self.store
+                .commit_operator_record(log_id, record_id, registry_index)
+                .await
+        } else {
+            self.store
+                .commit_package_record(log_id, record_id, registry_index)
+                .await
+        };
 
         if let Err(err) = commit_res {
             match err {
                 DataStoreError::Rejection(_)
+                | DataStoreError::InvalidRecordContents { .. }
                 | DataStoreError::OperatorValidationFailed(_)
                 | DataStoreError::PackageValidationFailed(_) => {
                     // The record failed to validate and was rejected; do not include it in the next checkpoint
                     tracing::debug!("record `{record_id}` rejected: {err:?}");
                 }
-                e => {
-                    // TODO: this should be made more robust with a proper reliable message
-                    // queue with retry logic
-                    tracing::error!("failed to validate package record `{record_id}`: {e}");
+                DataStoreError::RecordNotPending(_) => {
+                    // The record was already committed or rejected
+                    tracing::debug!("record `{record_id}` was already processed");
                 }
+                e => return Err(e),
             }
-            return;
+            return Ok(());
         }
 
         state.push_entry(entry.clone());
+        Ok(())
     }
 
     // Store a checkpoint including the given new entries
     async fn update_checkpoint(&self, checkpoint: &mut Checkpoint) {
+        let mut snapshot = None;
         {
             // Recalculate the checkpoint if necessary
             let mut state = self.state.write().await;
             if state.log.length() as RegistryLen != checkpoint.log_length {
                 *checkpoint = state.checkpoint();
                 tracing::debug!("Updating to checkpoint {checkpoint:?}");
+
+                if let Err(err) = state.log.flush() {
+                    tracing::error!("Error writing log to storage: {err:?}");
+                } else if self.snapshot_interval > 0
+                    && checkpoint.log_length >= state.snapshot_length + self.snapshot_interval
+                {
+                    // A disk log is restored from storage rather than the snapshot
+                    let log = match &state.log {
+                        StateLog::Memory(log) => Some(log.clone()),
+                        StateLog::Disk(_) => None,
+                    };
+                    state.snapshot_length = checkpoint.log_length;
+                    snapshot = Some((log, state.map.clone()));
+                }
             }
         }
 
+        // Tiles are written first so that they are available to clients
+        // verifying proofs against the stored checkpoint
+        if let Some(tiles) = &self.tiles {
+            if tiles.length() != checkpoint.log_length {
+                if let Err(err) = self.write_tiles(tiles, checkpoint).await {
+                    tracing::error!("Error writing tiles for checkpoint {checkpoint:?}: {err:?}");
+                }
+            }
+        }
+
+        let start = Instant::now();
         if let Err(err) = self.sign_and_store_checkpoint(checkpoint.clone()).await {
             tracing::error!("Error storing checkpoint {checkpoint:?}: {err:?}");
+            self.metrics.checkpoint_failed();
+            return;
+        }
+        self.metrics
+            .observe_checkpoint(checkpoint.log_length as u64, start.elapsed());
+
+        // Snapshots are only stored once their checkpoint has been stored
+        if let Some((log, map)) = snapshot {
+            let snapshot = Snapshot {
+                log_length: checkpoint.log_length,
+                log: log.map(VecLog::to_protobuf).unwrap_or_default(),
+                map: map.to_protobuf(),
+            };
+            if let Err(err) = self.store.store_snapshot(snapshot).await {
+                tracing::error!("Error storing snapshot for checkpoint {checkpoint:?}: {err:?}");
+            }
         }
     }
 
+    async fn write_tiles(
+        &self,
+        tiles: &TileWriter,
+        checkpoint: &Checkpoint,
+    ) -> Result<(), CoreServiceError> {
+        let note = self.sign_checkpoint_note(tiles.origin(), checkpoint)?;
+        // Release the state before fetching the data tile leafs
+        let hash_tiles = {
+            let state = self.state.read().await;
+            tiles.hash_tiles(&state.log, checkpoint.log_length)?
+        };
+        tiles
+            .write(
+                hash_tiles,
+                self.store.as_ref(),
+                checkpoint.log_length,
+                &note,
+            )
+            .await
+    }
+
+    fn sign_checkpoint_note(
+        &self,
+        origin: &str,
+        checkpoint: &Checkpoint,
+    ) -> Result<Note, CoreServiceError> {
+        let mut note = Note::new(checkpoint.to_note_text(origin))?;
+        note.sign(origin, &self.operator_key)?;
+        Ok(note)
+    }
+
     async fn sign_and_store_checkpoint(&self, checkpoint: Checkpoint) -> anyhow::Result<()> {
         let checkpoint_id = Hash::<Digest>::of(&checkpoint).into();
         let timestamped = TimestampedCheckpoint::now(checkpoint.clone())?;
@@ -374,46 +781,217 @@ impl<Digest: SupportedDigest> Inner<Digest> {
 }
 
 type VerifiableMap<Digest> = Map<Digest, LogId, MapLeaf>;
+type CheckpointMap<Digest> = (Hash<Digest>, VerifiableMap<Digest>);
+
+// The verifiable log, with its node hashes kept in memory or on disk
+enum StateLog<Digest: SupportedDigest> {
+    Memory(VecLog<Digest, LogLeaf>),
+    Disk(DiskLog<Digest, LogLeaf>),
+}
+
+impl<Digest: SupportedDigest> StateLog<Digest> {
+    fn length(&self) -> usize {
+        match self {
+            Self::Memory(log) => log.length(),
+            Self::Disk(log) => log.length(),
+        }
+    }
+
+    // Writes any buffered node hashes to disk
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Memory(_) => Ok(()),
+            Self::Disk(log) => log.flush(),
+        }
+    }
+}
+
+impl<Digest: SupportedDigest> LogBuilder<Digest, LogLeaf> for StateLog<Digest> {
+    fn checkpoint(&self) -> log::Checkpoint<Digest> {
+        match self {
+            Self::Memory(log) => log.checkpoint(),
+            Self::Disk(log) => log.checkpoint(),
+        }
+    }
+
+    fn push(&mut self, entry: &LogLeaf) -> Node {
+        match self {
+            Self::Memory(log) => log.push(entry),
+            Self::Disk(log) => log.push(entry),
+        }
+    }
+}
+
+impl<Digest: SupportedDigest> LogData<Digest, LogLeaf> for StateLog<Digest> {
+    fn has_hash(&self, node: Node) -> bool {
+        match self {
+            Self::Memory(log) => log.has_hash(node),
+            Self::Disk(log) => log.has_hash(node),
+        }
+    }
+
+    fn hash_for(&self, node: Node) -> Option<Hash<Digest>> {
+        match self {
+            Self::Memory(log) => log.hash_for(node),
+            Self::Disk(log) => log.hash_for(node),
+        }
+    }
+
+    fn try_hash_for(&self, node: Node) -> anyhow::Result<Option<Hash<Digest>>> {
+        match self {
+            Self::Memory(log) => log.try_hash_for(node),
+            Self::Disk(log) => log.try_hash_for(node),
+        }
+    }
+}
 
-#[derive(Default)]
 struct State<Digest: SupportedDigest> {
     // The verifiable log of all package log entries
-    log: VecLog<Digest, LogLeaf>,
-    // Index log tree nodes by registry log index of the record
-    leaf_index: Vec<Node>,
+    log: StateLog<Digest>,
 
     // The verifiable map of package logs' latest entries (log_id -> record_id)
     map: VerifiableMap<Digest>,
-    // Index verifiable map snapshots by log length (at checkpoints only)
-    map_index: IndexMap<RegistryLen, (Hash<Digest>, VerifiableMap<Digest>)>,
+    // The map entries pushed since the map was last updated; they are
+    // inserted together at the next checkpoint
+    pending_map_entries: Vec<(LogId, MapLeaf)>,
+    // Index verifiable map snapshots by log length (at checkpoints only);
+    // maps that are not in memory are rebuilt on demand
+    map_index: IndexMap<RegistryLen, Option<CheckpointMap<Digest>>>,
+    // The log lengths of the maps in memory, in the order they were cached
+    cached_maps: VecDeque<RegistryLen>,
+
+    // The log length of the last stored or restored snapshot
+    snapshot_length: RegistryLen,
 }
 
 impl<Digest: SupportedDigest> State<Digest> {
+    fn new(log: StateLog<Digest>) -> Self {
+        Self {
+            log,
+            map: Default::default(),
+            pending_map_entries: Default::default(),
+            map_index: Default::default(),
+            cached_maps: Default::default(),
+            snapshot_length: 0,
+        }
+    }
+
+    // Resets to an empty state, discarding any stored log
+    fn reset(&mut self) -> io::Result<()> {
+        match &mut self.log {
+            StateLog::Memory(log) => *log = Default::default(),
+            StateLog::Disk(log) => log.truncate(0)?,
+        }
+        self.map = Default::default();
+        self.pending_map_entries.clear();
+        self.map_index.clear();
+        self.cached_maps.clear();
+        self.snapshot_length = 0;
+        Ok(())
+    }
+
     fn push_entry(&mut self, log_leaf: LogLeaf) {
-        let node = self.log.push(&log_leaf);
-        self.leaf_index.push(node);
+        self.log.push(&log_leaf);
 
         let LogLeaf { log_id, record_id } = log_leaf;
-        self.map = self.map.insert(log_id, MapLeaf { record_id });
+        self.pending_map_entries
+            .push((log_id, MapLeaf { record_id }));
+    }
+
+    // Caches the map of a checkpoint, evicting the earliest cached map once
+    // too many are held in memory.
+    fn cache_map(&mut self, log_length: RegistryLen, entry: CheckpointMap<Digest>) {
+        if let Some(Some(_)) = self.map_index.insert(log_length, Some(entry)) {
+            return;
+        }
+
+        self.cached_maps.push_back(log_length);
+        if self.cached_maps.len() > CACHED_CHECKPOINT_MAPS {
+            if let Some(evicted) = self.cached_maps.pop_front() {
+                self.map_index.insert(evicted, None);
+            }
+        }
     }
 
     fn checkpoint(&mut self) -> Checkpoint {
         let log_checkpoint = self.log.checkpoint();
-        let map_root = self.map.root();
+        if !self.pending_map_entries.is_empty() {
+            self.map = self
+                .map
+                .par_extend(mem::take(&mut self.pending_map_entries));
+        }
+        let map_root = self.map.root().clone();
         let log_length = log_checkpoint.length() as RegistryLen;
 
         // Update map snapshot
         if log_length > 0 {
-            self.map_index
-                .insert(log_length, (map_root.clone(), self.map.clone()));
+            self.cache_map(log_length, (map_root.clone(), self.map.clone()));
         }
 
         Checkpoint {
             log_length,
             log_root: log_checkpoint.root().into(),
-            map_root: map_root.into(),
+            map_root: (&map_root).into(),
         }
     }
+
+    // Validates the stored checkpoint at the current log length, if any
+    // (updating the map index as a side-effect).
+    fn validate_checkpoint(&mut self, checkpoints_by_len: &IndexMap<RegistryLen, Checkpoint>) {
+        if let Some(stored_checkpoint) = checkpoints_by_len.get(&(self.log.length() as RegistryLen))
+        {
+            let computed_checkpoint = self.checkpoint();
+            assert!(stored_checkpoint == &computed_checkpoint);
+        }
+    }
+
+    // Restores the state from a snapshot, validating it against the stored
+    // checkpoint at the same log length.
+    fn restore(
+        &mut self,
+        snapshot: Snapshot,
+        checkpoints_by_len: &IndexMap<RegistryLen, Checkpoint>,
+    ) -> Result<(), CoreServiceError> {
+        let stored_checkpoint = checkpoints_by_len
+            .get(&snapshot.log_length)
+            .ok_or(CoreServiceError::CheckpointNotFound(snapshot.log_length))?;
+
+        let map = VerifiableMap::from_protobuf(&snapshot.map)
+            .map_err(|e| CoreServiceError::InitializationFailure(e.to_string()))?;
+        match &mut self.log {
+            StateLog::Memory(log) => {
+                *log = VecLog::from_protobuf(&snapshot.log)
+                    .map_err(|e| CoreServiceError::InitializationFailure(e.to_string()))?;
+            }
+            // Discard any entries stored after the snapshot; they are replayed
+            StateLog::Disk(log) => log.truncate(snapshot.log_length)?,
+        }
+        if self.log.length() != snapshot.log_length {
+            return Err(CoreServiceError::InitializationFailure(format!(
+                "snapshot log has length {found} but expected {expected}",
+                found = self.log.length(),
+                expected = snapshot.log_length
+            )));
+        }
+
+        self.map = map;
+        self.pending_map_entries.clear();
+        self.snapshot_length = snapshot.log_length;
+        for log_length in checkpoints_by_len.keys() {
+            if *log_length < snapshot.log_length {
+                self.map_index.insert(*log_length, None);
+            }
+        }
+
+        if &self.checkpoint() != stored_checkpoint {
+            return Err(CoreServiceError::InitializationFailure(format!(
+                "snapshot does not match the checkpoint at log length {log_length}",
+                log_length = snapshot.log_length
+            )));
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Debug, Error)]
@@ -426,10 +1004,220 @@ pub enum CoreServiceError {
     BundleFailure(anyhow::Error),
     #[error("failed to prove inclusion of package `{0}`")]
     PackageNotIncluded(LogId),
+    #[error("failed to prove absence of package `{0}`")]
+    PackageIncluded(LogId),
     #[error("failed to prove inclusion: found root `{found}` but was given root `{root}`")]
     IncorrectProof { root: AnyHash, found: AnyHash },
+    #[error("checkpoint log length `{to}` is not consistent with checkpoint log length `{from}`")]
+    InconsistentCheckpoints { from: RegistryLen, to: RegistryLen },
     #[error("data store error: {0}")]
     DataStore(#[from] DataStoreError),
+    #[error("log storage error: {0}")]
+    LogStorage(#[from] io::Error),
+    #[error("failed to sign checkpoint note: {0}")]
+    CheckpointNote(#[from] NoteError),
     #[error("initialization failed: {0}")]
     InitializationFailure(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datastore::MemoryDataStore;
+
+    // Creates a log leaf spread across a few package logs, so that later
+    // leafs replace the map entries of earlier ones
+    fn leaf(index: u8) -> LogLeaf {
+        LogLeaf {
+            log_id: AnyHash::from(Hash::<Sha256>::of((0u8, index % 3))).into(),
+            record_id: AnyHash::from(Hash::<Sha256>::of((1u8, index))).into(),
+        }
+    }
+
+    // Pushes the given leafs and returns the resulting checkpoint
+    fn push(state: &mut State<Sha256>, leafs: &[LogLeaf]) -> Checkpoint {
+        for leaf in leafs {
+            state.push_entry(leaf.clone());
+        }
+        state.checkpoint()
+    }
+
+    #[test]
+    fn restored_snapshot_matches_full_replay() {
+        let leafs = (0..20).map(leaf).collect::<Vec<_>>();
+
+        // Replay every leaf, taking a snapshot at the second checkpoint
+        let mut replayed = State::new(StateLog::Memory(Default::default()));
+        let mut checkpoints = IndexMap::new();
+        let mut snapshot = None;
+        for chunk in leafs.chunks(5) {
+            let checkpoint = push(&mut replayed, chunk);
+            if checkpoint.log_length == 10 {
+                let StateLog::Memory(log) = &replayed.log else {
+                    unreachable!("the log is in memory");
+                };
+                snapshot = Some(Snapshot {
+                    log_length: checkpoint.log_length,
+                    log: log.clone().to_protobuf(),
+                    map: replayed.map.clone().to_protobuf(),
+                });
+            }
+            checkpoints.insert(checkpoint.log_length, checkpoint);
+        }
+
+        // Restore from the snapshot and replay only the leafs after it
+        let mut restored = State::new(StateLog::Memory(Default::default()));
+        restored.restore(snapshot.unwrap(), &checkpoints).unwrap();
+        for chunk in leafs[10..].chunks(5) {
+            let checkpoint = push(&mut restored, chunk);
+            assert_eq!(&checkpoint, &checkpoints[&checkpoint.log_length]);
+        }
+
+        assert_eq!(restored.checkpoint(), replayed.checkpoint());
+        assert_eq!(restored.map.root(), replayed.map.root());
+    }
+
+    // Creates a service that stores a snapshot every 5 log entries
+    fn inner(store: Box<dyn DataStore>, log: StateLog<Sha256>) -> Inner<Sha256> {
+        Inner {
+            operator_key: PrivateKey::decode(
+                "ecdsa-p256:2CV1EpLaSYEn4In4OAEDAj5O4Hzu8AFAxgHXuG310Ew=".to_string(),
+            )
+            .unwrap(),
+            store,
+            snapshot_interval: 5,
+            tiles: None,
+            metrics: Default::default(),
+            state: RwLock::new(State::new(log)),
+        }
+    }
+
+    // Initializes the service, returning its latest checkpoint
+    async fn initialize(inner: &mut Inner<Sha256>) -> Checkpoint {
+        inner.initialize(None).await.unwrap();
+        inner
+            .store
+            .get_latest_checkpoint()
+            .await
+            .unwrap()
+            .into_contents()
+            .checkpoint
+    }
+
+    // Defines the given namespaces in the operator log, one checkpoint per
+    // namespace, returning the stored checkpoints
+    async fn define_namespaces(
+        inner: &Inner<Sha256>,
+        checkpoint: &mut Checkpoint,
+        namespaces: impl IntoIterator<Item = String>,
+    ) -> Vec<Checkpoint> {
+        let log_id = LogId::operator_log::<Sha256>();
+        let (_, LogLeaf { mut record_id, .. }) = inner
+            .store
+            .get_log_leafs_starting_with_registry_index(checkpoint.log_length - 1, 1)
+            .await
+            .unwrap()
+            .remove(0);
+
+        let mut checkpoints = Vec::new();
+        for namespace in namespaces {
+            let record = operator::OperatorRecord {
+                prev: Some(record_id),
+                version: 0,
+                timestamp: SystemTime::now(),
+                entries: vec![operator::OperatorEntry::DefineNamespace { namespace }],
+            };
+            let record = ProtoEnvelope::signed_contents(&inner.operator_key, record).unwrap();
+            record_id = RecordId::operator_record::<Sha256>(&record);
+            inner
+                .store
+                .store_operator_record(&log_id, &record_id, &record)
+                .await
+                .unwrap();
+
+            inner
+                .process_entry(&LogLeaf {
+                    log_id: log_id.clone(),
+                    record_id: record_id.clone(),
+                })
+                .await
+                .unwrap();
+            inner.update_checkpoint(checkpoint).await;
+            checkpoints.push(checkpoint.clone());
+        }
+        checkpoints
+    }
+
+    #[tokio::test]
+    async fn disk_snapshot_is_restored_from_the_stored_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(LOG_FILE_NAME);
+
+        // Only a journaled store restores the transparency state on restart
+        let store = MemoryDataStore::with_journal(dir.path().join("journal"))
+            .await
+            .unwrap();
+        let mut service = inner(
+            Box::new(store),
+            StateLog::Disk(DiskLog::open(&path).unwrap()),
+        );
+        let mut checkpoint = initialize(&mut service).await;
+        define_namespaces(&service, &mut checkpoint, (0..7).map(|i| format!("ns{i}"))).await;
+
+        // The snapshot leaves the log hashes to the log file
+        let snapshot = service.store.get_latest_snapshot().await.unwrap().unwrap();
+        assert_eq!(snapshot.log_length, 5);
+        assert!(snapshot.log.is_empty());
+        assert!(!snapshot.map.is_empty());
+
+        // Restarting restores the log from the file and replays the rest
+        let Inner { store, state, .. } = service;
+        let expected = state.into_inner().checkpoint();
+        let mut restarted = inner(store, StateLog::Disk(DiskLog::open(&path).unwrap()));
+        assert_eq!(initialize(&mut restarted).await, checkpoint);
+        assert_eq!(restarted.state.get_mut().checkpoint(), expected);
+        assert_eq!(restarted.state.get_mut().snapshot_length, 5);
+    }
+
+    #[tokio::test]
+    async fn evicted_checkpoint_maps_are_rebuilt() {
+        let mut service = inner(
+            Box::new(MemoryDataStore::new()),
+            StateLog::Memory(Default::default()),
+        );
+        let mut checkpoint = initialize(&mut service).await;
+        let checkpoints = define_namespaces(
+            &service,
+            &mut checkpoint,
+            (0..CACHED_CHECKPOINT_MAPS + 2).map(|i| format!("ns{i}")),
+        )
+        .await;
+
+        // Only the most recent maps are kept in memory
+        {
+            let state = service.state.read().await;
+            assert_eq!(state.cached_maps.len(), CACHED_CHECKPOINT_MAPS);
+            assert_eq!(state.map_index.len(), CACHED_CHECKPOINT_MAPS + 3);
+            for log_length in 1..=3 {
+                assert!(state.map_index[&log_length].is_none());
+            }
+            assert!(state.map_index[&4].is_some());
+        }
+
+        // Evicted maps are rebuilt from the stored log leafs
+        for checkpoint in &checkpoints[..2] {
+            let (root, _) = service.checkpoint_map(checkpoint.log_length).await.unwrap();
+            assert_eq!(AnyHash::from(root), checkpoint.map_root);
+            assert!(service.state.read().await.map_index[&checkpoint.log_length].is_some());
+        }
+        assert_eq!(
+            service.state.read().await.cached_maps.len(),
+            CACHED_CHECKPOINT_MAPS
+        );
+
+        assert!(matches!(
+            service.checkpoint_map(checkpoint.log_length + 1).await,
+            Err(CoreServiceError::CheckpointNotFound(_))
+        ));
+    }
+}
@@ -1,3 +1,5 @@
 mod core;
+mod tiles;
 
 pub use self::core::{CoreService, CoreServiceError};
+pub use self::tiles::TileWriter;
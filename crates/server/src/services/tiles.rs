@@ -0,0 +1,314 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use warg_crypto::{hash::SupportedDigest, signing::note::Note, Encode};
+use warg_protocol::registry::{Checkpoint, LogLeaf, RegistryLen};
+use warg_transparency::log::{tiles, LogData, Tile};
+
+use super::CoreServiceError;
+use crate::datastore::{DataStore, DataStoreError};
+
+// The name of the file holding the latest checkpoint note in the tiles directory.
+const CHECKPOINT_FILE_NAME: &str = "checkpoint";
+
+/// Writes the registry log to a directory as [C2SP tlog-tiles] as it grows.
+///
+/// Full tiles are never rewritten, so the directory can be served by a
+/// static file server or CDN with long-lived caching for everything but the
+/// `checkpoint` file.
+///
+/// [C2SP tlog-tiles]: https://c2sp.org/tlog-tiles
+pub struct TileWriter {
+    dir: PathBuf,
+    origin: String,
+    // The log length the tiles were last written for
+    length: AtomicUsize,
+    // The checkpoint of the tiles found in the directory, until resumed
+    existing: Option<Checkpoint>,
+}
+
+impl TileWriter {
+    /// Opens a tile writer for the given directory.
+    ///
+    /// Tiles already in the directory are kept if its checkpoint note has
+    /// the given origin; they are checked against the log by
+    /// [`TileWriter::resume`]. Otherwise the directory is cleared.
+    ///
+    /// The `origin` is the origin line of the checkpoint notes written
+    /// alongside the tiles.
+    pub fn open(dir: impl Into<PathBuf>, origin: impl Into<String>) -> io::Result<Self> {
+        let dir = dir.into();
+        let origin = origin.into();
+
+        let existing = match fs::read_to_string(dir.join(CHECKPOINT_FILE_NAME)) {
+            Ok(text) => text
+                .parse::<Note>()
+                .ok()
+                .and_then(|note| Checkpoint::from_note_text(note.text()).ok())
+                .filter(|(note_origin, _)| *note_origin == origin)
+                .map(|(_, checkpoint)| checkpoint),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+
+        if existing.is_none() {
+            clear_dir(&dir)?;
+        }
+
+        Ok(Self {
+            dir,
+            origin,
+            length: AtomicUsize::new(0),
+            existing,
+        })
+    }
+
+    /// Resumes writing from the tiles found in the directory when opened.
+    ///
+    /// The tiles are kept if the log has a stored checkpoint with the same
+    /// length and roots as their checkpoint note, in which case only tiles
+    /// past that length are written. Otherwise they were written for a
+    /// different log, so the directory is cleared and all tiles are written
+    /// again on the first call to [`TileWriter::write`].
+    pub(crate) async fn resume(&mut self, store: &dyn DataStore) -> Result<(), CoreServiceError> {
+        let Some(existing) = self.existing.take() else {
+            return Ok(());
+        };
+
+        let matches = match store.get_checkpoint(existing.log_length).await {
+            Ok(stored) => stored.as_ref().checkpoint == existing,
+            Err(DataStoreError::CheckpointNotFound(_)) => false,
+            Err(e) => return Err(e.into()),
+        };
+
+        if matches {
+            tracing::debug!(
+                "resuming tiles from log length {length}",
+                length = existing.log_length
+            );
+            *self.length.get_mut() = existing.log_length;
+        } else {
+            tracing::warn!(
+                "tiles in `{dir}` do not match the log; writing them again",
+                dir = self.dir.display()
+            );
+            clear_dir(&self.dir)?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets the origin of the checkpoint notes.
+    pub(crate) fn origin(&self) -> &str {
+        &self.origin
+    }
+
+    /// Gets the log length the tiles were last written for.
+    pub(crate) fn length(&self) -> RegistryLen {
+        self.length.load(Ordering::Acquire)
+    }
+
+    /// Encodes the hash tiles that changed since the last write.
+    ///
+    /// This only reads the log, so the datastore is not accessed while the
+    /// caller holds the log.
+    pub(crate) fn hash_tiles<Digest: SupportedDigest>(
+        &self,
+        log: &impl LogData<Digest, LogLeaf>,
+        log_length: RegistryLen,
+    ) -> Result<Vec<(Tile, Vec<u8>)>, CoreServiceError> {
+        Tile::tiles_between(self.length(), log_length)
+            .into_iter()
+            .map(|tile| {
+                let hashes = tile
+                    .encode_hashes(log)
+                    .map_err(io::Error::other)?
+                    .ok_or_else(|| {
+                        io::Error::other(format!(
+                            "log hashes of tile `{path}` are not available",
+                            path = tile.path()
+                        ))
+                    })?;
+                Ok((tile, hashes))
+            })
+            .collect()
+    }
+
+    /// Writes the given hash tiles from [`TileWriter::hash_tiles`] and their
+    /// data tiles, followed by the given checkpoint note.
+    pub(crate) async fn write(
+        &self,
+        hash_tiles: Vec<(Tile, Vec<u8>)>,
+        store: &dyn DataStore,
+        log_length: RegistryLen,
+        note: &Note,
+    ) -> Result<(), CoreServiceError> {
+        for (tile, hashes) in hash_tiles {
+            let path = self.dir.join(tile.path());
+            let data_path = self.dir.join(tile.data_path());
+            write_file(&path, &hashes)?;
+
+            if tile.level() == 0 {
+                let start = tile.index() * tiles::TILE_WIDTH;
+                let leafs = store
+                    .get_log_leafs_starting_with_registry_index(start, tile.width())
+                    .await?;
+                if leafs.len() != tile.width()
+                    || leafs
+                        .iter()
+                        .enumerate()
+                        .any(|(i, (index, _))| *index != start + i)
+                {
+                    return Err(CoreServiceError::LeafNotFound(start + leafs.len()));
+                }
+
+                let entries = leafs
+                    .iter()
+                    .map(|(_, leaf)| leaf.encode())
+                    .collect::<Vec<_>>();
+                let data = tiles::encode_entries(entries.iter().map(Vec::as_slice))
+                    .map_err(io::Error::other)?;
+                write_file(&data_path, &data)?;
+            }
+
+            // Partial tiles are superseded by the full tile
+            if !tile.is_partial() {
+                remove_partial_tiles(&path)?;
+                if tile.level() == 0 {
+                    remove_partial_tiles(&data_path)?;
+                }
+            }
+        }
+
+        write_file(
+            &self.dir.join(CHECKPOINT_FILE_NAME),
+            note.to_string().as_bytes(),
+        )?;
+        self.length.store(log_length, Ordering::Release);
+        Ok(())
+    }
+}
+
+// Removes the contents of a directory, creating it if needed.
+fn clear_dir(dir: &Path) -> io::Result<()> {
+    match fs::remove_dir_all(dir) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+        _ => {}
+    }
+    fs::create_dir_all(dir)
+}
+
+// Writes a file by renaming a temporary file so that partially written files
+// are never served.
+fn write_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let temp = path.with_extension("tmp");
+    fs::write(&temp, contents)?;
+    fs::rename(&temp, path)
+}
+
+// Removes the partial tiles of the given full tile.
+fn remove_partial_tiles(full: &Path) -> io::Result<()> {
+    match fs::remove_dir_all(full.with_extension("p")) {
+        Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::datastore::MemoryDataStore;
+    use warg_crypto::{
+        hash::{Hash, Sha256},
+        signing::generate_ed25519_pair,
+    };
+    use warg_protocol::{registry::TimestampedCheckpoint, SerdeEnvelope};
+
+    const ORIGIN: &str = "example.com/log";
+
+    fn checkpoint(log_length: RegistryLen, log_root: &str) -> Checkpoint {
+        Checkpoint {
+            log_root: Hash::<Sha256>::of(log_root).into(),
+            log_length,
+            map_root: Hash::<Sha256>::of("map").into(),
+        }
+    }
+
+    // Writes a full tile and a checkpoint note for it to the directory.
+    fn write_tiles(dir: &Path, origin: &str, checkpoint: &Checkpoint) {
+        let (_, key) = generate_ed25519_pair();
+        let mut note = Note::new(checkpoint.to_note_text(origin)).unwrap();
+        note.sign(origin, &key).unwrap();
+        write_file(&dir.join("tile/0/000"), b"tile").unwrap();
+        write_file(&dir.join(CHECKPOINT_FILE_NAME), note.to_string().as_bytes()).unwrap();
+    }
+
+    async fn store_checkpoint(store: &dyn DataStore, checkpoint: Checkpoint) {
+        let (_, key) = generate_ed25519_pair();
+        let checkpoint_id = Hash::<Sha256>::of(&checkpoint).into();
+        let timestamped = TimestampedCheckpoint::now(checkpoint).unwrap();
+        store
+            .store_checkpoint(
+                &checkpoint_id,
+                SerdeEnvelope::signed_contents(&key, timestamped).unwrap(),
+            )
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn open_removes_tiles_of_other_origins() {
+        let dir = tempfile::tempdir().unwrap();
+        let tiles_dir = dir.path().join("tiles");
+        write_tiles(&tiles_dir, "example.com/other", &checkpoint(256, "log"));
+
+        let writer = TileWriter::open(&tiles_dir, ORIGIN).unwrap();
+        assert_eq!(writer.length(), 0);
+        assert!(tiles_dir.is_dir());
+        assert_eq!(fs::read_dir(&tiles_dir).unwrap().count(), 0);
+
+        write_file(&tiles_dir.join("tile/0/000"), b"stale").unwrap();
+        write_file(&tiles_dir.join(CHECKPOINT_FILE_NAME), b"stale").unwrap();
+        TileWriter::open(&tiles_dir, ORIGIN).unwrap();
+        assert_eq!(fs::read_dir(&tiles_dir).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn resume_keeps_tiles_of_the_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let tiles_dir = dir.path().join("tiles");
+        let store = MemoryDataStore::new();
+        store_checkpoint(&store, checkpoint(256, "log")).await;
+        write_tiles(&tiles_dir, ORIGIN, &checkpoint(256, "log"));
+
+        let mut writer = TileWriter::open(&tiles_dir, ORIGIN).unwrap();
+        writer.resume(&store).await.unwrap();
+        assert_eq!(writer.length(), 256);
+        assert_eq!(fs::read(tiles_dir.join("tile/0/000")).unwrap(), b"tile");
+    }
+
+    #[tokio::test]
+    async fn resume_removes_tiles_of_other_logs() {
+        let store = MemoryDataStore::new();
+        store_checkpoint(&store, checkpoint(256, "log")).await;
+
+        // A different root at the same length, and a length the log does not have
+        for existing in [checkpoint(256, "other"), checkpoint(512, "log")] {
+            let dir = tempfile::tempdir().unwrap();
+            let tiles_dir = dir.path().join("tiles");
+            write_tiles(&tiles_dir, ORIGIN, &existing);
+
+            let mut writer = TileWriter::open(&tiles_dir, ORIGIN).unwrap();
+            writer.resume(&store).await.unwrap();
+            assert_eq!(writer.length(), 0);
+            assert_eq!(fs::read_dir(&tiles_dir).unwrap().count(), 0);
+        }
+    }
+}
@@ -1,14 +1,21 @@
 use std::{iter::repeat_with, time::Duration};
 
-use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion};
 use rand::Rng;
 use warg_crypto::hash::Sha256;
 use warg_transparency::map::Map;
 
-fn create(items: impl Iterator<Item = ([u8; 32], [u8; 32])>) -> Map<Sha256, [u8; 32], [u8; 32]> {
+type Item = ([u8; 32], [u8; 32]);
+
+fn create(items: impl Iterator<Item = Item>) -> Map<Sha256, [u8; 32], [u8; 32]> {
     Map::<Sha256, _, _>::default().extend(items)
 }
 
+fn items(size: usize) -> Vec<Item> {
+    let mut rng = rand::thread_rng();
+    repeat_with(|| (rng.gen(), rng.gen())).take(size).collect()
+}
+
 fn extend(c: &mut Criterion) {
     let mut rng = rand::thread_rng();
     let mut grp = c.benchmark_group("map");
@@ -24,5 +31,32 @@ fn extend(c: &mut Criterion) {
     }
 }
 
-criterion_group!(benches, extend);
+fn par_extend(c: &mut Criterion) {
+    let mut grp = c.benchmark_group("map");
+
+    grp.sample_size(20);
+    grp.warm_up_time(Duration::from_secs(1));
+
+    // Batches are inserted into an existing map, as with checkpoints
+    let map = create(items(10_000).into_iter());
+    for size in [1024, 16384] {
+        grp.throughput(criterion::Throughput::Elements(size as u64));
+        grp.bench_with_input(BenchmarkId::new("extend_batch", size), &size, |b, i| {
+            b.iter_batched(
+                || items(*i),
+                |items| map.extend(items),
+                BatchSize::LargeInput,
+            )
+        });
+        grp.bench_with_input(BenchmarkId::new("par_extend_batch", size), &size, |b, i| {
+            b.iter_batched(
+                || items(*i),
+                |items| map.par_extend(items),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+}
+
+criterion_group!(benches, extend, par_extend);
 criterion_main!(benches);
@@ -0,0 +1,311 @@
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::path::Path;
+use std::sync::Mutex;
+
+use warg_crypto::{
+    hash::{Digest, Hash, SupportedDigest},
+    VisitBytes,
+};
+
+use super::{hash_branch, hash_empty, hash_leaf, node::Node, Checkpoint, LogBuilder, LogData};
+
+/// A verifiable log where the node hashes are stored in a file.
+///
+/// Node hashes are laid out by index exactly like a [`VecLog`](super::VecLog),
+/// but only the balanced roots needed to extend the log are kept in memory.
+/// Newly computed hashes are buffered until [`DiskLog::flush`] is called.
+#[derive(Debug)]
+pub struct DiskLog<D, V>
+where
+    D: SupportedDigest,
+    V: VisitBytes,
+{
+    /// The file storing the node hashes
+    file: Mutex<File>,
+    /// The number of entries
+    length: usize,
+    /// The balanced roots of the log
+    roots: Vec<(Node, Hash<D>)>,
+    /// Node hashes that have not yet been written to the file
+    dirty: BTreeMap<usize, Hash<D>>,
+    /// Marker for value type
+    _value: PhantomData<V>,
+}
+
+impl<D, V> DiskLog<D, V>
+where
+    D: SupportedDigest,
+    V: VisitBytes,
+{
+    /// Opens the log stored in the given file, creating it if it doesn't exist.
+    ///
+    /// Any node hashes that were only partially written are discarded.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+
+        let mut log = Self {
+            file: Mutex::new(file),
+            length: 0,
+            roots: Vec::new(),
+            dirty: BTreeMap::new(),
+            _value: PhantomData,
+        };
+        let length = log.stored_length()?;
+        log.truncate(length)?;
+        Ok(log)
+    }
+
+    /// Returns the number of entries in the log.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Writes any buffered node hashes to the file.
+    pub fn flush(&mut self) -> io::Result<()> {
+        if self.dirty.is_empty() {
+            return Ok(());
+        }
+
+        let file = self.file.get_mut().map_err(|_| Self::poisoned())?;
+        for (index, hash) in &self.dirty {
+            file.seek(SeekFrom::Start((index * Self::hash_len()) as u64))?;
+            file.write_all(hash.bytes())?;
+        }
+        file.sync_data()?;
+
+        self.dirty.clear();
+        Ok(())
+    }
+
+    /// Truncates the log to the given number of entries.
+    ///
+    /// Buffered node hashes are written to the file first.
+    pub fn truncate(&mut self, length: usize) -> io::Result<()> {
+        self.flush()?;
+
+        let length = length.min(self.length.max(self.stored_length()?));
+        let file = self.file.get_mut().map_err(|_| Self::poisoned())?;
+        file.set_len((Self::node_count(length) * Self::hash_len()) as u64)?;
+
+        self.length = length;
+        self.roots = Node::broots_for_len(length)
+            .into_iter()
+            .map(|node| Ok((node, self.read_hash(node)?)))
+            .collect::<io::Result<_>>()?;
+        Ok(())
+    }
+
+    fn hash_len() -> usize {
+        <D as Digest>::output_size()
+    }
+
+    fn node_count(length: usize) -> usize {
+        (length * 2).saturating_sub(1)
+    }
+
+    fn stored_length(&mut self) -> io::Result<usize> {
+        let file = self.file.get_mut().map_err(|_| Self::poisoned())?;
+        let nodes = file.metadata()?.len() as usize / Self::hash_len();
+        Ok(nodes.div_ceil(2))
+    }
+
+    fn read_hash(&self, node: Node) -> io::Result<Hash<D>> {
+        let mut bytes = vec![0; Self::hash_len()];
+        let mut file = self.file.lock().map_err(|_| Self::poisoned())?;
+        file.seek(SeekFrom::Start((node.index() * Self::hash_len()) as u64))?;
+        file.read_exact(&mut bytes)?;
+        bytes
+            .try_into()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    fn poisoned() -> io::Error {
+        io::Error::other("log file lock was poisoned")
+    }
+}
+
+impl<D, V> LogBuilder<D, V> for DiskLog<D, V>
+where
+    D: SupportedDigest,
+    V: VisitBytes,
+{
+    fn checkpoint(&self) -> Checkpoint<D> {
+        let root = self
+            .roots
+            .iter()
+            .rev()
+            .map(|(_n, hash)| hash.clone())
+            .reduce(|new, old| hash_branch::<D>(old, new))
+            .unwrap_or_else(hash_empty::<D>);
+
+        Checkpoint {
+            root,
+            length: self.length,
+        }
+    }
+
+    fn push(&mut self, entry: &V) -> Node {
+        let leaf_node = Node(self.length * 2);
+
+        // Store spacer (if necessary) and entry digest
+        if self.length != 0 {
+            self.dirty.insert(leaf_node.index() - 1, hash_empty::<D>());
+        }
+        let mut current_digest = hash_leaf::<D>(entry);
+        let mut current_node = leaf_node;
+        self.dirty.insert(leaf_node.index(), current_digest.clone());
+        self.length += 1;
+
+        // Merge balanced roots of equal height, storing each new parent
+        while let Some((node, hash)) = self.roots.last() {
+            if node.height() != current_node.height() {
+                break;
+            }
+
+            current_digest = hash_branch::<D>(hash, current_digest);
+            current_node = current_node.parent();
+            self.dirty
+                .insert(current_node.index(), current_digest.clone());
+            self.roots.pop();
+        }
+        self.roots.push((current_node, current_digest));
+
+        leaf_node
+    }
+}
+
+impl<D, V> LogData<D, V> for DiskLog<D, V>
+where
+    D: SupportedDigest,
+    V: VisitBytes,
+{
+    fn has_hash(&self, node: Node) -> bool {
+        node.index() < Self::node_count(self.length)
+    }
+
+    /// Returns `None` if the node does not exist or could not be read; use
+    /// [`LogData::try_hash_for`] to get the error reading the node.
+    fn hash_for(&self, node: Node) -> Option<Hash<D>> {
+        self.try_hash_for(node).ok().flatten()
+    }
+
+    fn try_hash_for(&self, node: Node) -> Result<Option<Hash<D>>, anyhow::Error> {
+        if !self.has_hash(node) {
+            return Ok(None);
+        }
+
+        match self.dirty.get(&node.index()) {
+            Some(hash) => Ok(Some(hash.clone())),
+            None => {
+                let hash = self.read_hash(node).map_err(|e| {
+                    anyhow::Error::new(e).context(format!(
+                        "failed to read the hash of log node {index}",
+                        index = node.index()
+                    ))
+                })?;
+                Ok(Some(hash))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use warg_crypto::hash::Sha256;
+
+    use super::super::VecLog;
+    use super::*;
+
+    #[test]
+    fn test_matches_vec_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log");
+
+        let mut vec_log: VecLog<Sha256, u8> = VecLog::default();
+        let mut disk_log: DiskLog<Sha256, u8> = DiskLog::open(&path).unwrap();
+
+        for i in 0..100u8 {
+            assert_eq!(vec_log.push(&i), disk_log.push(&i));
+            assert_eq!(vec_log.checkpoint(), disk_log.checkpoint());
+
+            // Flush at irregular intervals so parents overwrite stored spacers
+            if i % 7 == 0 {
+                disk_log.flush().unwrap();
+            }
+        }
+
+        for index in 0..vec_log.as_ref().len() {
+            assert_eq!(
+                vec_log.hash_for(Node(index)),
+                disk_log.hash_for(Node(index)),
+                "node {index} differs"
+            );
+        }
+        assert_eq!(disk_log.hash_for(Node(vec_log.as_ref().len())), None);
+    }
+
+    #[test]
+    fn test_reopen_and_truncate() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log");
+
+        let mut vec_log: VecLog<Sha256, u8> = VecLog::default();
+        let mut checkpoints = Vec::new();
+        {
+            let mut disk_log: DiskLog<Sha256, u8> = DiskLog::open(&path).unwrap();
+            for i in 0..50u8 {
+                vec_log.push(&i);
+                disk_log.push(&i);
+                checkpoints.push(disk_log.checkpoint());
+            }
+            disk_log.flush().unwrap();
+        }
+
+        let mut disk_log: DiskLog<Sha256, u8> = DiskLog::open(&path).unwrap();
+        assert_eq!(disk_log.length(), 50);
+        assert_eq!(disk_log.checkpoint(), vec_log.checkpoint());
+
+        disk_log.truncate(23).unwrap();
+        assert_eq!(disk_log.length(), 23);
+        assert_eq!(disk_log.checkpoint(), checkpoints[22]);
+
+        // Extending a truncated log matches the original
+        for i in 23..50u8 {
+            disk_log.push(&i);
+        }
+        assert_eq!(disk_log.checkpoint(), vec_log.checkpoint());
+    }
+
+    #[test]
+    fn test_read_error_propagates() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log");
+
+        let mut disk_log: DiskLog<Sha256, u8> = DiskLog::open(&path).unwrap();
+        for i in 0..4u8 {
+            disk_log.push(&i);
+        }
+        disk_log.flush().unwrap();
+
+        // Lose the stored hashes behind the log's back
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_len(0)
+            .unwrap();
+
+        assert!(disk_log.has_hash(Node(0)));
+        assert!(disk_log.try_hash_for(Node(0)).is_err());
+        assert_eq!(disk_log.hash_for(Node(0)), None);
+        assert_eq!(disk_log.try_hash_for(Node(7)).unwrap(), None);
+    }
+}
@@ -12,12 +12,14 @@
 //! using binary in-order interval numbering as described in
 //! [Dat - Distributed Dataset Synchronization and Versioning][2].
 
+mod disk_log;
 mod node;
 /// Logic for constructing and validating proofs
 mod proof;
 mod proof_bundle;
 mod sparse_data;
 mod stack_log;
+pub mod tiles;
 mod vec_log;
 
 use warg_crypto::{
@@ -25,6 +27,7 @@ use warg_crypto::{
     VisitBytes,
 };
 
+pub use disk_log::DiskLog;
 pub use node::{Node, Side};
 pub use proof::{
     ConsistencyProof, ConsistencyProofError, InclusionProof, InclusionProofError,
@@ -33,6 +36,7 @@ pub use proof::{
 pub use proof_bundle::ProofBundle;
 pub use proof_bundle::ProofBundle as LogProofBundle;
 pub use stack_log::StackLog;
+pub use tiles::{Tile, TiledLog};
 pub use vec_log::VecLog;
 
 /// A [merkle tree][0] log data type based on [DAT][1].
@@ -128,6 +132,12 @@ where
     /// None if node does not yet exist
     fn hash_for(&self, node: Node) -> Option<Hash<D>>;
 
+    /// Get the hash for a given node, failing if it could not be read
+    /// Ok(None) if node does not yet exist
+    fn try_hash_for(&self, node: Node) -> Result<Option<Hash<D>>, anyhow::Error> {
+        Ok(self.hash_for(node))
+    }
+
     /// Construct an inclusion proof for this log
     fn prove_inclusion(&self, leaf: Node, log_length: usize) -> InclusionProof<D, V> {
         InclusionProof::new(leaf, log_length)
@@ -88,7 +88,7 @@ where
         let mut hashes = Vec::new();
         for node in nodes_needed {
             let hash = data
-                .hash_for(node)
+                .try_hash_for(node)?
                 .ok_or_else(|| Error::msg("Necessary hash not found"))?;
             hashes.push((node, hash));
         }
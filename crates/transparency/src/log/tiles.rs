@@ -0,0 +1,566 @@
+//! The log as [C2SP tlog-tiles].
+//!
+//! The log is split into immutable tiles that can be served by a static file
+//! server or CDN. A hash tile at level `L` holds up to [`TILE_WIDTH`]
+//! consecutive hashes of the complete subtrees of height `L * TILE_HEIGHT`;
+//! the hashes of the subtrees in between are computed from the tile below.
+//! Each level 0 tile has a corresponding data tile holding the log entries.
+//!
+//! As the log hashes leaves and branches as described in RFC 6962, the tiles
+//! are compatible with other tlog-tiles tooling.
+//!
+//! [C2SP tlog-tiles]: https://c2sp.org/tlog-tiles
+
+use std::collections::HashMap;
+use std::marker::PhantomData;
+
+use alloc::{format, string::String, vec::Vec};
+use thiserror::Error;
+use warg_crypto::{
+    hash::{Digest, Hash, SupportedDigest},
+    VisitBytes,
+};
+
+use super::{
+    hash_branch, node::Node, ConsistencyProof, ConsistencyProofError, InclusionProof,
+    InclusionProofError, LogData,
+};
+
+/// The number of tree levels covered by a tile.
+pub const TILE_HEIGHT: u32 = 8;
+
+/// The maximum number of hashes or entries in a tile.
+pub const TILE_WIDTH: usize = 1 << TILE_HEIGHT;
+
+/// An error occurring when reading a tile.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum TileError {
+    /// The tile does not have the expected size.
+    #[error("tile `{path}` has {found} bytes but expected {expected}")]
+    InvalidLength {
+        /// The path of the tile.
+        path: String,
+        /// The expected number of bytes.
+        expected: usize,
+        /// The number of bytes found.
+        found: usize,
+    },
+    /// A data tile entry is truncated.
+    #[error("data tile entry is truncated")]
+    TruncatedEntry,
+    /// A data tile entry is too long to be encoded.
+    #[error("data tile entry has {len} bytes but at most {max} are allowed", max = u16::MAX)]
+    EntryTooLong {
+        /// The number of bytes of the entry.
+        len: usize,
+    },
+}
+
+/// A hash tile of a log and its corresponding data tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Tile {
+    level: u32,
+    index: usize,
+    width: usize,
+}
+
+impl Tile {
+    /// Creates a tile at the given level and index holding `width` hashes.
+    ///
+    /// Returns `None` if the width is zero or more than [`TILE_WIDTH`].
+    pub fn new(level: u32, index: usize, width: usize) -> Option<Self> {
+        if width == 0 || width > TILE_WIDTH {
+            return None;
+        }
+
+        Some(Self {
+            level,
+            index,
+            width,
+        })
+    }
+
+    /// The level of the tile.
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// The index of the tile within its level.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The number of hashes (or entries) in the tile.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Whether the tile is partial, i.e. has fewer than [`TILE_WIDTH`] hashes.
+    pub fn is_partial(&self) -> bool {
+        self.width < TILE_WIDTH
+    }
+
+    /// Gets the full tile at the same level and index.
+    pub fn full(&self) -> Self {
+        Self {
+            width: TILE_WIDTH,
+            ..*self
+        }
+    }
+
+    /// The path of the hash tile, relative to the tiles root.
+    pub fn path(&self) -> String {
+        format!(
+            "tile/{level}/{index}",
+            level = self.level,
+            index = self.index_path()
+        )
+    }
+
+    /// The path of the data tile, relative to the tiles root.
+    ///
+    /// Only level 0 tiles have a data tile.
+    pub fn data_path(&self) -> String {
+        format!("tile/entries/{index}", index = self.index_path())
+    }
+
+    /// Formats the index as zero-padded groups of three digits, with all but
+    /// the last group prefixed with `x`, followed by the width if partial.
+    fn index_path(&self) -> String {
+        let mut groups = Vec::new();
+        let mut index = self.index;
+        loop {
+            groups.push(index % 1000);
+            index /= 1000;
+            if index == 0 {
+                break;
+            }
+        }
+
+        let mut path = groups
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(i, group)| {
+                if i + 1 == groups.len() {
+                    format!("{group:03}")
+                } else {
+                    format!("x{group:03}/")
+                }
+            })
+            .collect::<String>();
+        if self.is_partial() {
+            path.push_str(&format!(".p/{width}", width = self.width));
+        }
+        path
+    }
+
+    /// The height of the subtrees whose hashes are stored in the tile.
+    fn stored_height(level: u32) -> u32 {
+        level * TILE_HEIGHT
+    }
+
+    /// Gets the tile with the given index at the given level of a log with
+    /// the given length.
+    fn at_length(level: u32, index: usize, length: usize) -> Option<Self> {
+        let count = length.checked_shr(Self::stored_height(level)).unwrap_or(0);
+        let width = count.saturating_sub(index * TILE_WIDTH).min(TILE_WIDTH);
+        Self::new(level, index, width)
+    }
+
+    /// Gets the tiles that were added or grew when the log grew from
+    /// `from` to `to` entries, ordered by level and index.
+    pub fn tiles_between(from: usize, to: usize) -> Vec<Self> {
+        let mut tiles = Vec::new();
+        for level in 0.. {
+            let height = Self::stored_height(level);
+            let count_from = from.checked_shr(height).unwrap_or(0);
+            let count_to = to.checked_shr(height).unwrap_or(0);
+            if count_to <= count_from {
+                break;
+            }
+
+            let first = count_from / TILE_WIDTH;
+            let last = (count_to - 1) / TILE_WIDTH;
+            tiles.extend((first..=last).filter_map(|index| Self::at_length(level, index, to)));
+        }
+        tiles
+    }
+
+    /// Gets the tile of a log with the given length from which the hash of
+    /// the given node is computed.
+    ///
+    /// Returns `None` if the node does not exist at that length.
+    pub fn for_node(node: Node, length: usize) -> Option<Self> {
+        if !node.exists_at_length(length) {
+            return None;
+        }
+
+        let height = node.height();
+        let level = height / TILE_HEIGHT;
+        let (first, _) = Self::stored_range(node, level);
+        Self::at_length(level, first / TILE_WIDTH, length)
+    }
+
+    /// Gets the range of positions of the hashes stored at the given level
+    /// that the hash of the given node is computed from.
+    fn stored_range(node: Node, level: u32) -> (usize, usize) {
+        let height = node.height();
+        let position = node.index() >> (height + 1);
+        let below = height - Self::stored_height(level);
+        (position << below, (position + 1) << below)
+    }
+
+    /// The node whose hash is stored at the given position of the tile.
+    fn node(&self, position: usize) -> Node {
+        let height = Self::stored_height(self.level);
+        let position = self.index * TILE_WIDTH + position;
+        Node((position << (height + 1)) + (1 << height) - 1)
+    }
+
+    /// Encodes the hash tile from the node hashes of a log.
+    ///
+    /// Returns `Ok(None)` if a hash of the tile is not known, or an error if
+    /// a hash could not be read.
+    pub fn encode_hashes<D, V>(
+        &self,
+        log: &impl LogData<D, V>,
+    ) -> Result<Option<Vec<u8>>, anyhow::Error>
+    where
+        D: SupportedDigest,
+        V: VisitBytes,
+    {
+        let mut bytes = Vec::with_capacity(self.width * <D as Digest>::output_size());
+        for position in 0..self.width {
+            match log.try_hash_for(self.node(position))? {
+                Some(hash) => bytes.extend_from_slice(hash.bytes()),
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(bytes))
+    }
+}
+
+/// Encodes the entries of a data tile, each prefixed by its big-endian
+/// 16-bit length.
+///
+/// Returns an error if an entry is longer than [`u16::MAX`] bytes.
+pub fn encode_entries<'a>(
+    entries: impl IntoIterator<Item = &'a [u8]>,
+) -> Result<Vec<u8>, TileError> {
+    let mut bytes = Vec::new();
+    for entry in entries {
+        let len =
+            u16::try_from(entry.len()).map_err(|_| TileError::EntryTooLong { len: entry.len() })?;
+        bytes.extend_from_slice(&len.to_be_bytes());
+        bytes.extend_from_slice(entry);
+    }
+    Ok(bytes)
+}
+
+/// Decodes the entries of a data tile.
+pub fn decode_entries(mut bytes: &[u8]) -> Result<Vec<&[u8]>, TileError> {
+    let mut entries = Vec::new();
+    while !bytes.is_empty() {
+        if bytes.len() < 2 {
+            return Err(TileError::TruncatedEntry);
+        }
+
+        let (len, rest) = bytes.split_at(2);
+        let len = u16::from_be_bytes([len[0], len[1]]) as usize;
+        if rest.len() < len {
+            return Err(TileError::TruncatedEntry);
+        }
+
+        let (entry, rest) = rest.split_at(len);
+        entries.push(entry);
+        bytes = rest;
+    }
+    Ok(entries)
+}
+
+/// The node hashes of a log of a given length, computed from its hash tiles.
+///
+/// Tiles are not trusted: the roots found by evaluating proofs with a tiled
+/// log must still be checked against a trusted checkpoint.
+#[derive(Debug)]
+pub struct TiledLog<D, V>
+where
+    D: SupportedDigest,
+    V: VisitBytes,
+{
+    /// The number of entries
+    length: usize,
+    /// The hashes of each tile by level and index
+    tiles: HashMap<(u32, usize), Vec<Hash<D>>>,
+    /// Marker for value type
+    _value: PhantomData<V>,
+}
+
+impl<D, V> TiledLog<D, V>
+where
+    D: SupportedDigest,
+    V: VisitBytes,
+{
+    /// Creates a tiled log of the given length without any tiles.
+    pub fn new(length: usize) -> Self {
+        Self {
+            length,
+            tiles: HashMap::new(),
+            _value: PhantomData,
+        }
+    }
+
+    /// Returns the number of entries in the log.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    /// Adds a hash tile to the log.
+    ///
+    /// A tile that is wider than the log requires, such as a full tile in
+    /// place of a partial one, may be added.
+    pub fn add_tile(&mut self, tile: Tile, bytes: &[u8]) -> Result<(), TileError> {
+        let hash_len = <D as Digest>::output_size();
+        if bytes.len() != tile.width * hash_len {
+            return Err(TileError::InvalidLength {
+                path: tile.path(),
+                expected: tile.width * hash_len,
+                found: bytes.len(),
+            });
+        }
+
+        let hashes = bytes
+            .chunks_exact(hash_len)
+            .filter_map(|chunk| chunk.to_vec().try_into().ok())
+            .collect();
+        self.tiles.insert((tile.level, tile.index), hashes);
+        Ok(())
+    }
+
+    /// Gets the tiles that must be added to compute the hashes of the given
+    /// nodes, ignoring nodes that do not exist in the log.
+    pub fn missing_tiles(&self, nodes: impl IntoIterator<Item = Node>) -> Vec<Tile> {
+        let mut tiles: Vec<Tile> = nodes
+            .into_iter()
+            .filter_map(|node| Tile::for_node(node, self.length))
+            .filter(|tile| !self.has_tile(tile))
+            .collect();
+        tiles.sort();
+        tiles.dedup();
+        tiles
+    }
+
+    /// Gets the tiles that must be added to evaluate the inclusion proof.
+    pub fn missing_tiles_for_inclusion(
+        &self,
+        proof: &InclusionProof<D, V>,
+    ) -> Result<Vec<Tile>, InclusionProofError> {
+        Ok(self.missing_tiles(proof.walk()?.nodes))
+    }
+
+    /// Gets the tiles that must be added to evaluate the consistency proof.
+    pub fn missing_tiles_for_consistency(
+        &self,
+        proof: &ConsistencyProof<D, V>,
+    ) -> Result<Vec<Tile>, ConsistencyProofError> {
+        let mut nodes = Vec::new();
+        for inclusion in proof.inclusions()? {
+            nodes.push(inclusion.leaf());
+            nodes.extend(inclusion.walk()?.nodes);
+        }
+        Ok(self.missing_tiles(nodes))
+    }
+
+    fn has_tile(&self, tile: &Tile) -> bool {
+        self.tiles
+            .get(&(tile.level, tile.index))
+            .is_some_and(|hashes| hashes.len() >= tile.width)
+    }
+
+    fn stored_hash(&self, level: u32, position: usize) -> Option<&Hash<D>> {
+        self.tiles
+            .get(&(level, position / TILE_WIDTH))?
+            .get(position % TILE_WIDTH)
+    }
+}
+
+impl<D, V> LogData<D, V> for TiledLog<D, V>
+where
+    D: SupportedDigest,
+    V: VisitBytes,
+{
+    fn has_hash(&self, node: Node) -> bool {
+        Tile::for_node(node, self.length).is_some_and(|tile| self.has_tile(&tile))
+    }
+
+    fn hash_for(&self, node: Node) -> Option<Hash<D>> {
+        if !self.has_hash(node) {
+            return None;
+        }
+
+        // Hash the stored subtree hashes up to the height of the node
+        let level = node.height() / TILE_HEIGHT;
+        let (first, end) = Tile::stored_range(node, level);
+        let mut hashes = (first..end)
+            .map(|position| self.stored_hash(level, position).cloned())
+            .collect::<Option<Vec<_>>>()?;
+        while hashes.len() > 1 {
+            hashes = hashes
+                .chunks_exact(2)
+                .map(|pair| hash_branch::<D>(&pair[0], &pair[1]))
+                .collect();
+        }
+        hashes.pop()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use warg_crypto::hash::Sha256;
+
+    use super::super::{LogBuilder, VecLog};
+    use super::*;
+
+    #[test]
+    fn test_tile_paths() {
+        let tile = |level, index, width| Tile::new(level, index, width).unwrap();
+        assert_eq!(tile(0, 0, TILE_WIDTH).path(), "tile/0/000");
+        assert_eq!(tile(1, 5, 3).path(), "tile/1/005.p/3");
+        assert_eq!(tile(0, 1000, TILE_WIDTH).path(), "tile/0/x001/000");
+        assert_eq!(tile(2, 1234067, TILE_WIDTH).path(), "tile/2/x001/x234/067");
+        assert_eq!(
+            tile(0, 1234067, 7).data_path(),
+            "tile/entries/x001/x234/067.p/7"
+        );
+        assert!(Tile::new(0, 0, 0).is_none());
+        assert!(Tile::new(0, 0, TILE_WIDTH + 1).is_none());
+    }
+
+    #[test]
+    fn test_tiles_between() {
+        let tiles = |from, to| {
+            Tile::tiles_between(from, to)
+                .into_iter()
+                .map(|t| (t.level(), t.index(), t.width()))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(tiles(0, 0), vec![]);
+        assert_eq!(tiles(0, 3), vec![(0, 0, 3)]);
+        assert_eq!(tiles(3, 3), vec![]);
+        assert_eq!(tiles(3, 256), vec![(0, 0, 256), (1, 0, 1)]);
+        assert_eq!(tiles(256, 300), vec![(0, 1, 44)]);
+        assert_eq!(
+            tiles(300, 70000),
+            (1..273)
+                .map(|i| (0, i, 256))
+                .chain([(0, 273, 112), (1, 0, 256), (1, 1, 17), (2, 0, 1)])
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_encode_decode_entries() {
+        let entries: [&[u8]; 3] = [b"foo", b"", b"bar"];
+        let bytes = encode_entries(entries).unwrap();
+        assert_eq!(bytes, b"\0\x03foo\0\0\0\x03bar");
+        assert_eq!(decode_entries(&bytes).unwrap(), entries);
+        assert_eq!(
+            decode_entries(&bytes[..bytes.len() - 1]),
+            Err(TileError::TruncatedEntry)
+        );
+
+        let long = vec![0; u16::MAX as usize + 1];
+        assert_eq!(
+            encode_entries([long.as_slice()]),
+            Err(TileError::EntryTooLong { len: long.len() })
+        );
+    }
+
+    fn entry(i: u32) -> [u8; 32] {
+        let mut entry = [0; 32];
+        entry[..4].copy_from_slice(&i.to_be_bytes());
+        entry
+    }
+
+    fn tiled_log(log: &VecLog<Sha256, [u8; 32]>) -> TiledLog<Sha256, [u8; 32]> {
+        let mut tiled = TiledLog::new(log.length());
+        for tile in Tile::tiles_between(0, log.length()) {
+            tiled
+                .add_tile(tile, &tile.encode_hashes(log).unwrap().unwrap())
+                .unwrap();
+        }
+        tiled
+    }
+
+    #[test]
+    fn test_tiled_log_matches_vec_log() {
+        let mut log: VecLog<Sha256, [u8; 32]> = VecLog::default();
+        for i in 0..70000u32 {
+            log.push(&entry(i));
+        }
+
+        let tiled = tiled_log(&log);
+        for index in (0..log.as_ref().len()).step_by(7) {
+            let node = Node(index);
+            if node.exists_at_length(log.length()) {
+                assert_eq!(tiled.hash_for(node), log.hash_for(node), "node {index}");
+            } else {
+                assert_eq!(tiled.hash_for(node), None, "node {index}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_tiled_log_proofs() {
+        let mut log: VecLog<Sha256, [u8; 32]> = VecLog::default();
+        let mut checkpoints = Vec::new();
+        for i in 0..1000u32 {
+            log.push(&entry(i));
+            checkpoints.push(log.checkpoint());
+        }
+
+        let tiled = tiled_log(&log);
+        let root = log.checkpoint().root();
+
+        let empty = TiledLog::<Sha256, [u8; 32]>::new(log.length());
+        for i in [0u32, 1, 255, 256, 600, 999] {
+            let proof = log.prove_inclusion(Node(i as usize * 2), log.length());
+            assert!(!empty
+                .missing_tiles_for_inclusion(&proof)
+                .unwrap()
+                .is_empty());
+            assert!(tiled
+                .missing_tiles_for_inclusion(&proof)
+                .unwrap()
+                .is_empty());
+            assert_eq!(proof.evaluate_value(&tiled, &entry(i)).unwrap(), root);
+        }
+
+        for old_length in [1, 3, 256, 257, 511, 512, 999] {
+            let proof = log.prove_consistency(old_length, log.length());
+            assert!(tiled
+                .missing_tiles_for_consistency(&proof)
+                .unwrap()
+                .is_empty());
+            assert_eq!(
+                proof.evaluate(&tiled).unwrap(),
+                (checkpoints[old_length - 1].root(), root.clone())
+            );
+        }
+    }
+
+    #[test]
+    fn test_add_tile_invalid_length() {
+        let mut tiled = TiledLog::<Sha256, [u8; 32]>::new(3);
+        let tile = Tile::new(0, 0, 3).unwrap();
+        assert!(matches!(
+            tiled.add_tile(tile, &[0; 64]),
+            Err(TileError::InvalidLength {
+                expected: 96,
+                found: 64,
+                ..
+            })
+        ));
+    }
+}
@@ -14,7 +14,7 @@ use super::{hash_branch, hash_empty, hash_leaf, Checkpoint, LogBuilder, LogData}
 
 /// A verifiable log where the node hashes are stored
 /// contiguously in memory by index.
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub struct VecLog<D, V>
 where
     D: SupportedDigest,
@@ -85,6 +85,20 @@ where
     }
 }
 
+impl<D, V> Clone for VecLog<D, V>
+where
+    D: SupportedDigest,
+    V: VisitBytes,
+{
+    fn clone(&self) -> Self {
+        Self {
+            length: self.length,
+            tree: self.tree.clone(),
+            _value: PhantomData,
+        }
+    }
+}
+
 impl<D, V> Default for VecLog<D, V>
 where
     D: SupportedDigest,
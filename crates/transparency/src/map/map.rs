@@ -1,13 +1,17 @@
 use core::fmt::{Debug, Formatter};
 use std::marker::PhantomData;
 
-use warg_crypto::hash::{Hash, SupportedDigest};
+use anyhow::Error;
+use prost::Message;
+use rayon::prelude::*;
+use warg_crypto::hash::{Hash, Output, SupportedDigest};
 use warg_crypto::VisitBytes;
+use warg_protobuf::internal as protobuf;
 
 use super::link::Link;
 use super::node::Node;
 use super::path::Path;
-use super::proof::Proof;
+use super::proof::{AbsenceProof, Proof};
 
 /// Immutable Map w/ Inclusion Proofs
 ///
@@ -161,6 +165,8 @@ where
     }
 }
 
+// The `?Sized` bound is part of the public API, so it is kept as is.
+#[allow(clippy::needless_maybe_sized)]
 impl<D, K, V> Map<D, K, V>
 where
     D: SupportedDigest,
@@ -190,14 +196,18 @@ where {
         self.link.node().prove(Path::new(&Hash::of(key)))
     }
 
+    /// Gets a proof that the given key is absent from this map.
+    ///
+    /// Returns `None` if the map contains a value for the key.
+    pub fn prove_absence(&self, key: K) -> Option<AbsenceProof<D, K>> {
+        self.link.node().prove_absence(Path::new(&Hash::of(key)))
+    }
+
     /// Insert a value into the map, creating a new map.
     ///
     /// This replaces any existing items with the same key.
     pub fn insert(&self, key: K, val: V) -> Self {
-        let key_hash = Hash::<D>::of(&key);
-        let mut path: Path<'_, D> = Path::new(&key_hash);
-        let (node, new) = self.link.node().insert(&mut path, hash_leaf(val));
-        Self::new(Link::new(node), self.len + usize::from(new))
+        self.insert_hashed(&Hash::of(&key), hash_leaf(val))
     }
 
     /// Inserts all key/value pairs into the map, creating a new map.
@@ -205,14 +215,104 @@ where {
         let mut here = self.clone();
 
         for (key, val) in iter {
-            let key_hash = Hash::<D>::of(&key);
-            let mut path: Path<'_, D> = Path::new(&key_hash);
-            let (node, new) = here.link.node().insert(&mut path, hash_leaf(val));
-            here = Self::new(Link::new(node), here.len + usize::from(new));
+            here = here.insert_hashed(&Hash::of(&key), hash_leaf(val));
         }
 
         here
     }
+
+    /// Inserts all key/value pairs into the map in parallel, creating a new map.
+    ///
+    /// This creates the same map as [`Map::extend`], with later values
+    /// replacing earlier values of the same key. The pairs are hashed in
+    /// parallel and the disjoint subtrees they are inserted into are built
+    /// concurrently, which is faster than [`Map::extend`] for large batches.
+    pub fn par_extend(&self, iter: impl IntoParallelIterator<Item = (K, V)>) -> Self
+    where
+        K: Send,
+        V: Send,
+    {
+        let mut entries = iter
+            .into_par_iter()
+            .map(|(key, val)| (Hash::<D>::of(&key), hash_leaf(val)))
+            .collect::<Vec<_>>();
+
+        // The sort is stable, so the last value of a key is the latest
+        entries.par_sort_by(|(a, _), (b, _)| a.bytes().cmp(b.bytes()));
+        let mut unique: Vec<(Hash<D>, Hash<D>)> = Vec::with_capacity(entries.len());
+        for entry in entries {
+            match unique.last_mut() {
+                Some(last) if last.0 == entry.0 => *last = entry,
+                _ => unique.push(entry),
+            }
+        }
+
+        let (node, new) = self.link.node().extend(0, &unique);
+        Self::new(Link::new(node), self.len + new)
+    }
+
+    /// Turn a Map into bytes using protobuf
+    ///
+    /// Only the key hashes and leaf hashes are encoded, which is all that is
+    /// needed to rebuild the tree and its proofs.
+    pub fn to_protobuf(self) -> Vec<u8> {
+        let proto: protobuf::Map = self.into();
+        proto.encode_to_vec()
+    }
+
+    /// Parse a Map from bytes using protobuf
+    pub fn from_protobuf(bytes: &[u8]) -> Result<Self, Error> {
+        let proto = protobuf::Map::decode(bytes)?;
+        let value = proto.try_into()?;
+        Ok(value)
+    }
+
+    fn insert_hashed(&self, key_hash: &Hash<D>, leaf: Hash<D>) -> Self {
+        let mut path: Path<'_, D> = Path::new(key_hash);
+        let (node, new) = self.link.node().insert(&mut path, leaf);
+        Self::new(Link::new(node), self.len + usize::from(new))
+    }
+}
+
+impl<D, K, V> From<Map<D, K, V>> for protobuf::Map
+where
+    D: SupportedDigest,
+    K: VisitBytes + Clone,
+    V: VisitBytes + Clone,
+{
+    fn from(value: Map<D, K, V>) -> Self {
+        let mut entries = Vec::with_capacity(value.len);
+        value
+            .link
+            .node()
+            .entries(0, &mut Output::<D>::default(), &mut entries);
+        protobuf::Map {
+            entries: entries
+                .into_iter()
+                .map(|(key, value)| protobuf::MapEntry {
+                    key: key.bytes().to_vec(),
+                    value: value.bytes().to_vec(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<D, K, V> TryFrom<protobuf::Map> for Map<D, K, V>
+where
+    D: SupportedDigest,
+    K: VisitBytes + Clone,
+    V: VisitBytes + Clone,
+{
+    type Error = Error;
+
+    fn try_from(value: protobuf::Map) -> Result<Self, Self::Error> {
+        let mut map = Self::default();
+        for entry in value.entries {
+            map = map.insert_hashed(&entry.key.try_into()?, entry.value.try_into()?);
+        }
+        Ok(map)
+    }
 }
 
 // If updating this function, also update `hash_empty` in crypto crate
@@ -251,4 +351,19 @@ mod tests {
         let map: Map<Sha256, &str, &str> = Map::default();
         assert_eq!(Sha256::empty_tree_hash(256), map.link.hash());
     }
+
+    #[test]
+    fn protobuf_roundtrip() {
+        let map: Map<Sha256, (u8, u8), u8> =
+            Map::default().extend((0..=255).map(|i| ((i, i / 2), i)));
+
+        let decoded: Map<Sha256, (u8, u8), u8> =
+            Map::from_protobuf(&map.clone().to_protobuf()).unwrap();
+        assert_eq!(map.len(), 256);
+        assert_eq!(decoded.root(), map.root());
+        assert_eq!(decoded.len(), map.len());
+
+        let proof = decoded.prove((42, 21)).unwrap();
+        assert_eq!(decoded.root().clone(), proof.evaluate(&(42, 21), &42));
+    }
 }
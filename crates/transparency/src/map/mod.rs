@@ -13,6 +13,7 @@
 mod fork;
 mod link;
 mod map;
+mod multiproof;
 mod node;
 mod path;
 mod proof;
@@ -20,7 +21,8 @@ mod proof_bundle;
 mod singleton;
 
 pub use map::Map;
-pub use proof::Proof;
+pub use multiproof::MultiProof;
+pub use proof::{AbsenceProof, Proof};
 pub use proof_bundle::ProofBundle as MapProofBundle;
 
 #[cfg(test)]
@@ -30,7 +32,7 @@ mod test {
         VisitBytes,
     };
 
-    use super::Map;
+    use super::{Map, MapProofBundle};
 
     #[test]
     fn insert() {
@@ -63,6 +65,16 @@ mod test {
         assert_eq!(fourth.len(), 2);
     }
 
+    #[test]
+    fn len_with_many_keys() {
+        let first = Map::<Sha256, u8, u8>::default();
+        let second = first.extend((0..=255).map(|i| (i, i)));
+        assert_eq!(second.len(), 256);
+
+        let third = second.extend((0..=255).map(|i| (i, i / 2)));
+        assert_eq!(third.len(), 256);
+    }
+
     #[test]
     fn is_empty() {
         let first = Map::<Sha256, &'static str, &'static str>::default();
@@ -92,6 +104,47 @@ mod test {
         assert_eq!(extended, third);
     }
 
+    #[test]
+    fn par_extend() {
+        fn key(i: usize) -> (u8, u8) {
+            ((i / 256) as u8, (i % 256) as u8)
+        }
+
+        let items = (0..300).map(|i| (key(i), i as u8)).collect::<Vec<_>>();
+        let first = Map::<Sha256, (u8, u8), u8>::default();
+
+        // Large and small batches into an empty map
+        for len in [1, 10, 300] {
+            let extended = first.par_extend(items[..len].to_vec());
+            assert_eq!(extended, first.extend(items[..len].iter().cloned()));
+            assert_eq!(extended.len(), len);
+        }
+
+        // Extending an existing map, replacing some of its values with the
+        // last value given for a key
+        let existing = first.extend(items[..10].iter().cloned());
+        let updates = items[5..]
+            .iter()
+            .map(|(k, v)| (*k, v.wrapping_add(1)))
+            .chain([(key(5), 0), (key(250), 0)])
+            .collect::<Vec<_>>();
+        let extended = existing.par_extend(updates.clone());
+        let expected = existing.extend(updates);
+        assert_eq!(extended, expected);
+        assert_eq!(extended.len(), 300);
+
+        // The map built in parallel proves and accepts new entries like any other
+        let proof = extended.prove(key(250)).unwrap();
+        assert_eq!(extended.root().clone(), proof.evaluate(&key(250), &0));
+        let proof = extended.prove(key(42)).unwrap();
+        assert_eq!(extended.root().clone(), proof.evaluate(&key(42), &43));
+        assert_eq!(extended.insert(key(2000), 1), expected.insert(key(2000), 1));
+        assert_eq!(
+            extended.par_extend(vec![(key(3000), 1)]),
+            expected.insert(key(3000), 1)
+        );
+    }
+
     #[test]
     fn replace() {
         let first = Map::<Sha256, &'static str, &'static str>::default();
@@ -135,4 +188,47 @@ mod test {
         let fourth = third.insert("foo", "qux");
         check(&fourth, "foo", "qux");
     }
+
+    #[test]
+    fn prove_absence() {
+        fn check<D: SupportedDigest, K: VisitBytes + Clone, V: VisitBytes + Clone>(
+            tree: &Map<D, K, V>,
+            key: K,
+        ) {
+            let proof = tree.prove_absence(key.clone()).unwrap();
+            assert_eq!(tree.root().clone(), proof.evaluate(&key));
+        }
+
+        let first = Map::<Sha256, u8, u8>::default();
+        check(&first, 0);
+
+        // Absent keys are either beneath a singleton or an empty subtree
+        let mut map = first;
+        for i in 0..100u8 {
+            map = map.insert(i * 2, i);
+            assert!(map.prove_absence(i * 2).is_none());
+            check(&map, i * 2 + 1);
+            check(&map, i * 2 + 2);
+            check(&map, 255);
+        }
+    }
+
+    #[test]
+    fn bundle_with_absence_roundtrip() {
+        let map = Map::<Sha256, u8, u8>::default().insert(1, 10).insert(2, 20);
+        let bundle = MapProofBundle::bundle_with_absence(
+            vec![map.prove(1).unwrap()],
+            vec![map.prove_absence(3).unwrap(), map.prove_absence(4).unwrap()],
+        );
+
+        let bundle = MapProofBundle::<Sha256, u8, u8>::decode(&bundle.encode()).unwrap();
+        let absence_proofs = bundle.absence_proofs();
+        assert_eq!(absence_proofs.len(), 2);
+        assert_eq!(&absence_proofs[0].evaluate(&3), map.root());
+        assert_eq!(&absence_proofs[1].evaluate(&4), map.root());
+
+        let proofs = bundle.unbundle();
+        assert_eq!(proofs.len(), 1);
+        assert_eq!(&proofs[0].evaluate(&1, &10), map.root());
+    }
 }
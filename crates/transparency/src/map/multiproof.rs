@@ -0,0 +1,327 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::marker::PhantomData;
+
+use anyhow::{bail, Error};
+use prost::Message;
+use warg_crypto::{
+    hash::{Hash, SupportedDigest},
+    VisitBytes,
+};
+use warg_protobuf::transparency as protobuf;
+
+use super::{
+    map::{hash_branch, hash_leaf},
+    path::Side,
+    proof::Proof,
+};
+
+/// An inclusion proof of multiple values in a map
+///
+/// A multiproof proves the same values as a [`Proof`] per key while holding
+/// each sibling hash at most once.
+///
+/// # Compression
+///
+/// The paths of keys in the same map share their upper nodes, so individual
+/// proofs repeat the siblings of those nodes. A multiproof hashes all of the
+/// keys up to the root together, level by level, and only contains the
+/// siblings that are not themselves on the path of another key.
+///
+/// Siblings are stored in the order they are consumed when walking each
+/// level from the lowest to the highest key hash; as with [`Proof`], empty
+/// subtrees are represented by `None`.
+pub struct MultiProof<D, K, V>
+where
+    D: SupportedDigest,
+    K: VisitBytes,
+    V: VisitBytes,
+{
+    key: PhantomData<K>,
+    value: PhantomData<V>,
+    /// Sibling node hashes needed to construct the proof
+    pub peers: Vec<Option<Hash<D>>>,
+}
+
+impl<D, K, V> MultiProof<D, K, V>
+where
+    D: SupportedDigest,
+    K: VisitBytes,
+    V: VisitBytes,
+{
+    pub(crate) fn new(peers: Vec<Option<Hash<D>>>) -> Self {
+        Self {
+            key: PhantomData,
+            value: PhantomData,
+            peers,
+        }
+    }
+
+    /// Combines the inclusion proofs of the given keys into a multiproof
+    pub fn from_proofs<'a>(proofs: impl IntoIterator<Item = (&'a K, &'a Proof<D, K, V>)>) -> Self
+    where
+        K: 'a,
+        V: 'a,
+    {
+        let proofs = proofs.into_iter().collect::<Vec<_>>();
+        let nodes = proofs
+            .iter()
+            .enumerate()
+            .map(|(i, (key, _))| (Hash::<D>::of(key).bytes().to_vec(), i))
+            .collect();
+
+        let mut peers = Vec::new();
+        // The walk only ends early when there are no proofs
+        let _ = walk(nodes, |height, _, index: usize, sibling| {
+            if sibling.is_none() {
+                // Proofs omit the leading empty subtrees
+                let proof = &proofs[index].1.peers;
+                let peer = (height + proof.len())
+                    .checked_sub(256)
+                    .and_then(|i| proof[i].clone());
+                peers.push(peer);
+            }
+            Some(index)
+        });
+
+        Self::new(peers)
+    }
+
+    /// Computes the root obtained by evaluating this multiproof with the given leafs
+    ///
+    /// Returns `None` if the proof does not contain exactly the siblings
+    /// needed for the leafs or a key is given with different values.
+    pub fn evaluate(&self, leafs: &[(K, V)]) -> Option<Hash<D>> {
+        let mut nodes = BTreeMap::new();
+        for (key, value) in leafs {
+            let leaf = hash_leaf(value);
+            match nodes.insert(Hash::<D>::of(key).bytes().to_vec(), leaf.clone()) {
+                Some(existing) if existing != leaf => return None,
+                _ => {}
+            }
+        }
+
+        let mut peers = self.peers.iter();
+        let root = walk(nodes, |height, side, hash, sibling| {
+            let sibling = match sibling {
+                Some(sibling) => sibling,
+                None => peers
+                    .next()?
+                    .clone()
+                    .unwrap_or_else(|| D::empty_tree_hash(height).clone()),
+            };
+            Some(match side {
+                Side::Left => hash_branch(&hash, &sibling),
+                Side::Right => hash_branch(&sibling, &hash),
+            })
+        })?;
+
+        peers.next().is_none().then_some(root)
+    }
+
+    /// Turn a multiproof into bytes using protobuf
+    pub fn encode(self) -> Vec<u8> {
+        let proto: protobuf::MapMultiProof = self.into();
+        proto.encode_to_vec()
+    }
+
+    /// Parse a multiproof from bytes using protobuf
+    pub fn decode(bytes: &[u8]) -> Result<Self, Error> {
+        let proto = protobuf::MapMultiProof::decode(bytes)?;
+        let proof = proto.try_into()?;
+        Ok(proof)
+    }
+}
+
+impl<D, K, V> From<MultiProof<D, K, V>> for Vec<Option<Hash<D>>>
+where
+    D: SupportedDigest,
+    K: VisitBytes,
+    V: VisitBytes,
+{
+    fn from(value: MultiProof<D, K, V>) -> Self {
+        value.peers
+    }
+}
+
+impl<D, K, V> From<MultiProof<D, K, V>> for protobuf::MapMultiProof
+where
+    D: SupportedDigest,
+    K: VisitBytes,
+    V: VisitBytes,
+{
+    fn from(value: MultiProof<D, K, V>) -> Self {
+        let mut empty = alloc::vec![0u8; value.peers.len().div_ceil(8)];
+        let mut hashes = Vec::new();
+        for (i, peer) in value.peers.iter().enumerate() {
+            match peer {
+                Some(hash) => hashes.push(hash.bytes().to_vec()),
+                None => empty[i / 8] |= 1 << (i % 8),
+            }
+        }
+
+        protobuf::MapMultiProof {
+            hashes,
+            empty,
+            count: value.peers.len() as u32,
+        }
+    }
+}
+
+impl<D, K, V> TryFrom<protobuf::MapMultiProof> for MultiProof<D, K, V>
+where
+    D: SupportedDigest,
+    K: VisitBytes,
+    V: VisitBytes,
+{
+    type Error = Error;
+
+    fn try_from(value: protobuf::MapMultiProof) -> Result<Self, Self::Error> {
+        let count = value.count as usize;
+        if value.empty.len() != count.div_ceil(8) {
+            bail!("multiproof empty sibling bitmap does not match its sibling count");
+        }
+
+        let mut hashes = value.hashes.into_iter();
+        let mut peers = Vec::with_capacity(count);
+        for i in 0..count {
+            if value.empty[i / 8] & (1 << (i % 8)) != 0 {
+                peers.push(None);
+            } else {
+                match hashes.next() {
+                    Some(hash) => peers.push(Some(hash.try_into()?)),
+                    None => bail!("multiproof is missing sibling hashes"),
+                }
+            }
+        }
+
+        if hashes.next().is_some() {
+            bail!("multiproof has more sibling hashes than its sibling count");
+        }
+
+        Ok(Self::new(peers))
+    }
+}
+
+/// Walks the given nodes, keyed by key hash, level by level up to the root.
+///
+/// At each level, `merge` is called in key hash order with the height, side
+/// and value of a node that has no left sibling among the nodes, along with
+/// the value of its right sibling if that is also one of the nodes. It
+/// returns the value of the parent node.
+fn walk<T>(
+    mut nodes: BTreeMap<Vec<u8>, T>,
+    mut merge: impl FnMut(usize, Side, T, Option<T>) -> Option<T>,
+) -> Option<T> {
+    for height in 0..256 {
+        let bit = 255 - height;
+        let mut parents = BTreeMap::new();
+        let mut level = nodes.into_iter().peekable();
+        while let Some((mut id, value)) = level.next() {
+            let side = side(&id, bit);
+            clear(&mut id, bit);
+            let sibling = match side {
+                // A left sibling always precedes its right sibling
+                Side::Left => level
+                    .next_if(|(next, _)| is_child(&id, next, bit))
+                    .map(|(_, value)| value),
+                Side::Right => None,
+            };
+            parents.insert(id, merge(height, side, value, sibling)?);
+        }
+        nodes = parents;
+    }
+
+    nodes.into_values().next()
+}
+
+fn side(id: &[u8], bit: usize) -> Side {
+    if (id[bit / 8] >> (7 - bit % 8)) & 1 == 1 {
+        Side::Right
+    } else {
+        Side::Left
+    }
+}
+
+fn clear(id: &mut [u8], bit: usize) {
+    id[bit / 8] &= !(1 << (7 - bit % 8));
+}
+
+fn is_child(parent: &[u8], node: &[u8], bit: usize) -> bool {
+    let mut node = node.to_vec();
+    clear(&mut node, bit);
+    parent == node.as_slice()
+}
+
+#[cfg(test)]
+mod tests {
+    use warg_crypto::hash::Sha256;
+
+    use super::*;
+    use crate::map::Map;
+
+    fn map() -> Map<Sha256, u8, u8> {
+        Map::default().extend((0..100u8).map(|i| (i, i * 2)))
+    }
+
+    #[test]
+    fn test_multiproof_evaluate() {
+        let map = map();
+        let keys = [3u8, 17, 42, 43, 99];
+        let proofs = keys
+            .iter()
+            .map(|key| map.prove(*key).unwrap())
+            .collect::<Vec<_>>();
+        let multiproof = MultiProof::from_proofs(keys.iter().zip(proofs.iter()));
+
+        // Shared siblings are only included once
+        let total = proofs.iter().map(|p| p.peers.len()).sum::<usize>();
+        assert!(multiproof.peers.iter().flatten().count() < total);
+
+        let leafs = keys.iter().map(|key| (*key, key * 2)).collect::<Vec<_>>();
+        assert_eq!(multiproof.evaluate(&leafs).as_ref(), Some(map.root()));
+
+        // The order of the leafs doesn't matter
+        let reversed = leafs.iter().rev().cloned().collect::<Vec<_>>();
+        assert_eq!(multiproof.evaluate(&reversed).as_ref(), Some(map.root()));
+
+        // A wrong value doesn't evaluate to the root
+        let mut wrong = leafs.clone();
+        wrong[2].1 = 0;
+        assert_ne!(multiproof.evaluate(&wrong).as_ref(), Some(map.root()));
+
+        // A proof for different keys has the wrong siblings
+        assert!(multiproof.evaluate(&leafs[1..]).as_ref() != Some(map.root()));
+    }
+
+    #[test]
+    fn test_multiproof_single_key() {
+        let map = map();
+        let proof = map.prove(7).unwrap();
+        let multiproof = MultiProof::from_proofs([(&7u8, &proof)]);
+        assert_eq!(multiproof.evaluate(&[(7, 14)]).as_ref(), Some(map.root()));
+        assert_eq!(
+            multiproof.evaluate(&[(7, 14), (7, 14)]).as_ref(),
+            Some(map.root())
+        );
+        assert!(multiproof.evaluate(&[(7, 14), (7, 15)]).is_none());
+    }
+
+    #[test]
+    fn test_multiproof_encoding() {
+        let map = map();
+        let keys = [1u8, 2, 3];
+        let proofs = keys
+            .iter()
+            .map(|key| map.prove(*key).unwrap())
+            .collect::<Vec<_>>();
+        let multiproof = MultiProof::from_proofs(keys.iter().zip(proofs.iter()));
+        let peers = multiproof.peers.clone();
+
+        let decoded = MultiProof::<Sha256, u8, u8>::decode(&multiproof.encode()).unwrap();
+        assert_eq!(decoded.peers, peers);
+        assert_eq!(
+            decoded.evaluate(&[(1, 2), (2, 4), (3, 6)]).as_ref(),
+            Some(map.root())
+        );
+    }
+}
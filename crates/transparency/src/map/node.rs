@@ -2,15 +2,19 @@ use alloc::sync::Arc;
 use alloc::vec::Vec;
 use std::fmt::Debug;
 
-use warg_crypto::hash::{Hash, SupportedDigest};
+use warg_crypto::hash::{Hash, Output, SupportedDigest};
 use warg_crypto::VisitBytes;
 
 use super::fork::Fork;
 use super::link::Link;
 use super::path::{Path, Side};
-use super::proof::Proof;
+use super::proof::{AbsenceProof, Proof};
 use super::singleton::Singleton;
 
+// The number of entries below which a subtree is built by inserting its
+// entries one at a time rather than by building its subtrees in parallel.
+const PARALLEL_THRESHOLD: usize = 64;
+
 #[derive(Debug)]
 pub enum Node<D: SupportedDigest> {
     Leaf(Hash<D>),
@@ -66,6 +70,46 @@ impl<D: SupportedDigest> Node<D> {
         }
     }
 
+    /// Proves that the key of `path` has no value beneath this node.
+    ///
+    /// Returns `None` if the key is present.
+    pub fn prove_absence<K: VisitBytes>(
+        &self,
+        mut path: Path<'_, D>,
+    ) -> Option<AbsenceProof<D, K>> {
+        match (path.next(), self) {
+            (Some(_), Self::Singleton(singleton)) => {
+                if singleton.key() == path.hash() {
+                    return None;
+                }
+
+                // The only non-empty peer beneath this node is the subtree
+                // of the singleton where its key diverges from the path.
+                let depth = 256 - singleton.height;
+                let key = Path::new(singleton.key());
+                let diverges = (depth..256).find(|&i| key.get(i) != path.get(i))?;
+                let peer = Singleton::new(
+                    singleton.key.clone(),
+                    singleton.value.clone(),
+                    255 - diverges,
+                );
+
+                let mut peers = vec![Some(peer.hash())];
+                peers.resize(diverges - depth + 1, None);
+                Some(AbsenceProof::new(peers))
+            }
+            (Some(_), Self::Empty(_)) => Some(AbsenceProof::new(Vec::new())),
+            (Some(idx), Self::Fork(fork)) => {
+                let mut proof = fork[idx].as_ref().node().prove_absence(path)?;
+                let peer = fork[idx.opposite()].as_ref().hash();
+                proof.push(Some(peer.clone()));
+                Some(proof)
+            }
+
+            _ => None,
+        }
+    }
+
     /// A recursive function for setting the value in the tree.
     ///
     /// Returns:
@@ -125,4 +169,104 @@ impl<D: SupportedDigest> Node<D> {
             },
         }
     }
+
+    /// Inserts the given entries beneath this node at the given depth,
+    /// building the subtrees of large batches in parallel.
+    ///
+    /// The entries must be sorted by key hash with unique keys.
+    ///
+    /// Returns:
+    ///   * the new node that must replace the current node.
+    ///   * the number of new entries in the map.
+    pub fn extend(&self, depth: usize, entries: &[(Hash<D>, Hash<D>)]) -> (Self, usize) {
+        // A leaf is at the end of the path of its only key
+        if entries.len() < PARALLEL_THRESHOLD || matches!(self, Node::Leaf(_)) {
+            let mut node = self.clone();
+            let mut new = 0;
+            for (key, value) in entries {
+                let mut path = Path::new(key);
+                for _ in 0..depth {
+                    path.next();
+                }
+                let (inserted, is_new) = node.insert(&mut path, value.clone());
+                node = inserted;
+                new += usize::from(is_new);
+            }
+            return (node, new);
+        }
+
+        match self {
+            Node::Fork(fork) => {
+                Self::extend_fork(&fork[Side::Left], &fork[Side::Right], depth, entries)
+            }
+            Node::Empty(height) => {
+                let empty = Arc::new(Link::new(Node::Empty(height - 1)));
+                Self::extend_fork(&empty, &empty, depth, entries)
+            }
+            Node::Singleton(singleton) => {
+                // Build the subtree from scratch with the singleton's entry,
+                // unless it is replaced by one of the entries
+                let mut merged = entries.to_vec();
+                if let Err(i) =
+                    merged.binary_search_by(|(key, _)| key.bytes().cmp(singleton.key.bytes()))
+                {
+                    merged.insert(i, (singleton.key.clone(), singleton.value.clone()));
+                }
+                let (node, new) = Node::Empty(singleton.height).extend(depth, &merged);
+                (node, new - 1)
+            }
+            Node::Leaf(_) => (self.clone(), 0),
+        }
+    }
+
+    // Creates a fork at the given depth from the given children, extending
+    // them concurrently with the entries on their side.
+    fn extend_fork(
+        left: &Arc<Link<D>>,
+        right: &Arc<Link<D>>,
+        depth: usize,
+        entries: &[(Hash<D>, Hash<D>)],
+    ) -> (Self, usize) {
+        let split = entries.partition_point(|(key, _)| Path::new(key).get(depth) == Side::Left);
+        let (left_entries, right_entries) = entries.split_at(split);
+        let extend = |link: &Arc<Link<D>>, entries: &[(Hash<D>, Hash<D>)]| {
+            if entries.is_empty() {
+                return (link.clone(), 0);
+            }
+            let (node, new) = link.node().extend(depth + 1, entries);
+            (Arc::new(Link::new(node)), new)
+        };
+        let ((left, left_new), (right, right_new)) = rayon::join(
+            || extend(left, left_entries),
+            || extend(right, right_entries),
+        );
+        (Node::Fork(Fork::new(left, right)), left_new + right_new)
+    }
+
+    /// Collects the key and leaf hashes of every entry beneath this node.
+    ///
+    /// `key` holds the `depth` path bits taken to reach this node, which is
+    /// the only record of the key of a [`Node::Leaf`].
+    pub fn entries(
+        &self,
+        depth: usize,
+        key: &mut Output<D>,
+        entries: &mut Vec<(Hash<D>, Hash<D>)>,
+    ) {
+        match self {
+            Node::Leaf(value) => entries.push((Hash::from(key.clone()), value.clone())),
+            Node::Singleton(singleton) => {
+                entries.push((singleton.key.clone(), singleton.value.clone()))
+            }
+            Node::Empty(_) => {}
+            Node::Fork(fork) => {
+                let (byte, bit) = (depth / 8, 1 << (7 - depth % 8));
+                key[byte] &= !bit;
+                fork[Side::Left].node().entries(depth + 1, key, entries);
+                key[byte] |= bit;
+                fork[Side::Right].node().entries(depth + 1, key, entries);
+                key[byte] &= !bit;
+            }
+        }
+    }
 }
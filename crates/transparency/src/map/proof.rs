@@ -61,33 +61,7 @@ where
 
     /// Computes the root obtained by evaluating this inclusion proof with the given leaf
     pub fn evaluate(&self, key: &K, value: &V) -> Hash<D> {
-        // Get the path from bottom to top.
-        let path = ReversePath::<D>::new(Hash::of(key));
-
-        let fill = repeat(None).take(256 - self.peers.len());
-        // Calculate the leaf hash.
-        let mut hash = hash_leaf(value);
-
-        // Loop over each side and peer.
-        let peers = fill.chain(self.peers.iter().cloned());
-        for (i, (side, peer)) in path.zip(peers).enumerate() {
-            match &peer {
-                Some(_) => {
-                    hash = match side {
-                        Side::Left => hash_branch(&hash, &peer.unwrap()),
-                        Side::Right => hash_branch(&peer.unwrap(), &hash),
-                    };
-                }
-                None => match side {
-                    Side::Left => hash = hash_branch(&hash, D::empty_tree_hash(i)),
-                    Side::Right => {
-                        hash = hash_branch(D::empty_tree_hash(i), &hash);
-                    }
-                },
-            }
-        }
-
-        hash
+        evaluate_peers(Hash::of(key), hash_leaf(value), &self.peers)
     }
 }
 
@@ -102,6 +76,79 @@ where
     }
 }
 
+/// A proof that the specified key is absent from a map
+///
+/// The proof is the inclusion proof of an empty leaf at the position of the
+/// key; it shares the compression scheme of [`Proof`]. Since a leaf hash is
+/// domain separated from the hash of an empty leaf, a proof can only evaluate
+/// to the root of a map that has no value for the key.
+pub struct AbsenceProof<D, K>
+where
+    D: SupportedDigest,
+    K: VisitBytes,
+{
+    key: PhantomData<K>,
+    /// Sibling node hashes needed to construct a proof
+    pub peers: Vec<Option<Hash<D>>>,
+}
+
+impl<D, K> AbsenceProof<D, K>
+where
+    D: SupportedDigest,
+    K: VisitBytes,
+{
+    pub(crate) fn new(peers: Vec<Option<Hash<D>>>) -> Self {
+        Self {
+            key: PhantomData,
+            peers,
+        }
+    }
+
+    pub(crate) fn push(&mut self, peer: Option<Hash<D>>) {
+        self.peers.push(peer);
+    }
+
+    /// Computes the root obtained by evaluating this absence proof for the given key
+    pub fn evaluate(&self, key: &K) -> Hash<D> {
+        evaluate_peers(Hash::of(key), D::empty_tree_hash(0).clone(), &self.peers)
+    }
+}
+
+impl<D, K> From<AbsenceProof<D, K>> for Vec<Option<Hash<D>>>
+where
+    D: SupportedDigest,
+    K: VisitBytes,
+{
+    fn from(value: AbsenceProof<D, K>) -> Self {
+        value.peers
+    }
+}
+
+/// Hashes a leaf up to the root using the peers of a (compressed) proof.
+fn evaluate_peers<D: SupportedDigest>(
+    key_hash: Hash<D>,
+    leaf: Hash<D>,
+    peers: &[Option<Hash<D>>],
+) -> Hash<D> {
+    // Get the path from bottom to top.
+    let path = ReversePath::<D>::new(key_hash);
+
+    let fill = repeat(None).take(256 - peers.len());
+    let mut hash = leaf;
+
+    // Loop over each side and peer.
+    let peers = fill.chain(peers.iter().cloned());
+    for (i, (side, peer)) in path.zip(peers).enumerate() {
+        let peer = peer.as_ref().unwrap_or_else(|| D::empty_tree_hash(i));
+        hash = match side {
+            Side::Left => hash_branch(&hash, peer),
+            Side::Right => hash_branch(peer, &hash),
+        };
+    }
+
+    hash
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -119,4 +166,21 @@ mod tests {
         assert_eq!(root, p.evaluate(&"baz", &b"bat".as_slice()));
         assert_ne!(root, p.evaluate(&"other", &b"bar".as_slice()));
     }
+
+    #[test]
+    fn test_absence_proof_evaluate() {
+        use warg_crypto::hash::Sha256;
+
+        let a = crate::map::Map::<Sha256, &str, &[u8]>::default();
+        let b = a.insert("foo", b"bar");
+        let c = b.insert("baz", b"bat");
+
+        let root = c.root().clone();
+
+        let p = c.prove_absence("other").unwrap();
+
+        assert_eq!(root, p.evaluate(&"other"));
+        assert_ne!(root, p.evaluate(&"baz"));
+        assert_ne!(root, p.evaluate(&"foo"));
+    }
 }
@@ -7,9 +7,9 @@ use warg_crypto::{
 };
 use warg_protobuf::transparency as protobuf;
 
-use crate::map::proof::Proof;
+use crate::map::proof::{AbsenceProof, Proof};
 
-/// A collection of inclusion proof info
+/// A collection of inclusion and absence proof info
 pub struct ProofBundle<D, K, V>
 where
     D: SupportedDigest,
@@ -17,6 +17,7 @@ where
     V: VisitBytes,
 {
     proofs: Vec<Proof<D, K, V>>,
+    absence_proofs: Vec<AbsenceProof<D, K>>,
 }
 
 impl<D, K, V> ProofBundle<D, K, V>
@@ -27,7 +28,18 @@ where
 {
     /// Bundles inclusion proofs together
     pub fn bundle(proofs: Vec<Proof<D, K, V>>) -> Self {
-        ProofBundle { proofs }
+        Self::bundle_with_absence(proofs, Vec::new())
+    }
+
+    /// Bundles inclusion and absence proofs together
+    pub fn bundle_with_absence(
+        proofs: Vec<Proof<D, K, V>>,
+        absence_proofs: Vec<AbsenceProof<D, K>>,
+    ) -> Self {
+        ProofBundle {
+            proofs,
+            absence_proofs,
+        }
     }
 
     /// Splits a bundle into its constituent inclusion proofs
@@ -35,6 +47,11 @@ where
         self.proofs
     }
 
+    /// The absence proofs of the bundle
+    pub fn absence_proofs(&self) -> &[AbsenceProof<D, K>] {
+        &self.absence_proofs
+    }
+
     /// Turn a bundle into bytes using protobuf
     pub fn encode(self) -> Vec<u8> {
         let proto: protobuf::MapProofBundle = self.into();
@@ -57,7 +74,15 @@ where
 {
     fn from(value: ProofBundle<D, K, V>) -> Self {
         let proofs = value.proofs.into_iter().map(|proof| proof.into()).collect();
-        protobuf::MapProofBundle { proofs }
+        let absence_proofs = value
+            .absence_proofs
+            .into_iter()
+            .map(|proof| proof.into())
+            .collect();
+        protobuf::MapProofBundle {
+            proofs,
+            absence_proofs,
+        }
     }
 }
 
@@ -88,7 +113,14 @@ where
         for entry in value.proofs {
             proofs.push(entry.try_into()?);
         }
-        let bundle = ProofBundle { proofs };
+        let mut absence_proofs = Vec::new();
+        for entry in value.absence_proofs {
+            absence_proofs.push(entry.try_into()?);
+        }
+        let bundle = ProofBundle {
+            proofs,
+            absence_proofs,
+        };
         Ok(bundle)
     }
 }
@@ -108,3 +140,31 @@ where
         Ok(proof)
     }
 }
+
+impl<D, K> From<AbsenceProof<D, K>> for protobuf::MapAbsenceProof
+where
+    D: SupportedDigest,
+    K: VisitBytes,
+{
+    fn from(value: AbsenceProof<D, K>) -> Self {
+        let peers: Vec<Option<Hash<D>>> = value.into();
+        protobuf::MapAbsenceProof {
+            hashes: peers.into_iter().map(|h| h.into()).collect(),
+        }
+    }
+}
+
+impl<D, K> TryFrom<protobuf::MapAbsenceProof> for AbsenceProof<D, K>
+where
+    D: SupportedDigest,
+    K: VisitBytes,
+{
+    type Error = Error;
+
+    fn try_from(value: protobuf::MapAbsenceProof) -> Result<Self, Self::Error> {
+        let peers: Result<Vec<Option<Hash<D>>>, Error> =
+            value.hashes.into_iter().map(|h| h.try_into()).collect();
+        let proof = AbsenceProof::new(peers?);
+        Ok(proof)
+    }
+}
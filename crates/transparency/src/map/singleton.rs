@@ -62,7 +62,7 @@ impl<D: SupportedDigest> Singleton<D> {
                 Side::Left => Fork::new(Arc::new(Link::new(node)), Arc::new(Link::new(original))),
                 Side::Right => Fork::new(Arc::new(Link::new(original)), Arc::new(Link::new(node))),
             };
-            (Node::Fork(fork), false)
+            (Node::Fork(fork), true)
         } else {
             let cur_index = path.index();
             let pre_insert = Node::Singleton(Singleton::new(
@@ -70,7 +70,7 @@ impl<D: SupportedDigest> Singleton<D> {
                 self.value.clone(),
                 self.height - 1,
             ));
-            let (down_one, _) = pre_insert.insert(path, value);
+            let (down_one, new) = pre_insert.insert(path, value);
             let fork = match cur_side {
                 Side::Left => Fork::new(
                     Arc::new(Link::new(down_one)),
@@ -81,7 +81,7 @@ impl<D: SupportedDigest> Singleton<D> {
                     Arc::new(Link::new(down_one)),
                 ),
             };
-            (Node::Fork(fork), true)
+            (Node::Fork(fork), new)
         }
     }
 }
@@ -4,8 +4,8 @@ use std::process::exit;
 use tracing_subscriber::EnvFilter;
 use warg_cli::commands::{
     BundleCommand, ClearCommand, ConfigCommand, DependenciesCommand, DownloadCommand, InfoCommand,
-    KeyCommand, LockCommand, LoginCommand, LogoutCommand, PublishCommand, ResetCommand,
-    UpdateCommand,
+    KeyCommand, LockCommand, LoginCommand, LogoutCommand, OperatorCommand, PublishCommand,
+    ResetCommand, UpdateCommand, WitnessCommand,
 };
 use warg_client::ClientError;
 
@@ -33,6 +33,10 @@ enum WargCli {
     Update(UpdateCommand),
     #[clap(subcommand)]
     Publish(PublishCommand),
+    #[clap(subcommand)]
+    Operator(OperatorCommand),
+    #[clap(subcommand)]
+    Witness(WitnessCommand),
     Reset(ResetCommand),
     Clear(ClearCommand),
     Login(LoginCommand),
@@ -55,6 +59,8 @@ async fn main() -> Result<()> {
         WargCli::Download(cmd) => cmd.exec().await,
         WargCli::Update(cmd) => cmd.exec().await,
         WargCli::Publish(cmd) => cmd.exec().await,
+        WargCli::Operator(cmd) => cmd.exec().await,
+        WargCli::Witness(cmd) => cmd.exec().await,
         WargCli::Reset(cmd) => cmd.exec().await,
         WargCli::Clear(cmd) => cmd.exec().await,
         WargCli::Login(cmd) => cmd.exec().await,
@@ -146,6 +152,9 @@ This may be expected behavior for registries that offer key management."
         } => {
             eprintln!("Package `{name}` publish rejected due to conflict with pending publish of record `{pending_record_id}`")
         }
+        ClientError::OperatorPublishRejected { reason, .. } => {
+            eprintln!("Operator record publish rejected: {reason}")
+        }
         ClientError::Unauthorized(reason) => {
             eprintln!("Unauthorized: {reason}")
         }
@@ -18,9 +18,11 @@ mod key;
 mod lock;
 mod login;
 mod logout;
+mod operator;
 mod publish;
 mod reset;
 mod update;
+mod witness;
 
 pub use self::bundle::*;
 pub use self::clear::*;
@@ -32,9 +34,11 @@ pub use self::key::*;
 pub use self::lock::*;
 pub use self::login::*;
 pub use self::logout::*;
+pub use self::operator::*;
 pub use self::publish::*;
 pub use self::reset::*;
 pub use self::update::*;
+pub use self::witness::*;
 
 /// Common options for commands.
 #[derive(Args)]
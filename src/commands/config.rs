@@ -3,6 +3,7 @@ use anyhow::{bail, Context, Result};
 use clap::Args;
 use std::path::PathBuf;
 use warg_client::{keyring::Keyring, Config, RegistryUrl};
+use warg_crypto::signing::PublicKey;
 
 /// Creates a new warg configuration file.
 #[derive(Args)]
@@ -32,6 +33,18 @@ pub struct ConfigCommand {
     #[clap(long)]
     pub disable_auto_package_init: Option<bool>,
 
+    /// The public key(s) of witnesses trusted to cosign checkpoints.
+    #[clap(long = "witness-key", value_name = "PUBLIC_KEY", value_delimiter = ',')]
+    pub witness_keys: Vec<PublicKey>,
+
+    /// The number of witness cosignatures a checkpoint requires before it is accepted.
+    #[clap(long, value_name = "COUNT")]
+    pub witness_threshold: Option<usize>,
+
+    /// Verify log proofs using the home registry's log tiles.
+    #[clap(long)]
+    pub use_tiles: Option<bool>,
+
     /// Overwrite the existing configuration file.
     #[clap(long)]
     pub overwrite: bool,
@@ -97,6 +110,9 @@ impl ConfigCommand {
                 disable_auto_package_init: self.disable_auto_package_init.unwrap_or_default(),
                 disable_interactive: false,
                 keyring_backend: self.keyring_backend,
+                witness_keys: self.witness_keys,
+                witness_threshold: self.witness_threshold.unwrap_or_default(),
+                use_tiles: self.use_tiles.unwrap_or_default(),
             }
         } else {
             let mut config = self.common.read_config()?;
@@ -139,6 +155,15 @@ impl ConfigCommand {
             if self.keyring_backend.is_some() {
                 config.keyring_backend = self.keyring_backend;
             }
+            if !self.witness_keys.is_empty() {
+                config.witness_keys = self.witness_keys;
+            }
+            if let Some(witness_threshold) = self.witness_threshold {
+                config.witness_threshold = witness_threshold;
+            }
+            if let Some(use_tiles) = self.use_tiles {
+                config.use_tiles = use_tiles;
+            }
 
             config
         };
@@ -52,6 +52,18 @@ impl DownloadCommand {
             digest = download.digest
         );
 
+        if let Some(deprecation) = &download.deprecation {
+            println!(
+                "warning: version {version} is deprecated: {message}",
+                version = download.version,
+                message = deprecation.message
+            );
+            if let Some(successor) = &deprecation.successor {
+                println!("warning: use `{successor}` instead");
+            }
+            println!();
+        }
+
         // use the `output` path specified or ask the use if wants to save in the current working
         // directory
         let default_file_name = format!("{name}.wasm", name = self.name.name());
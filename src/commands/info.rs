@@ -7,7 +7,7 @@ use warg_client::{
     Client,
 };
 use warg_crypto::hash::AnyHash;
-use warg_protocol::{registry::PackageName, Version};
+use warg_protocol::{package::PackageDeprecation, registry::PackageName, Version};
 
 /// Display client storage information.
 #[derive(Args)]
@@ -83,10 +83,30 @@ impl InfoCommand {
 
     fn print_package_info(info: &PackageInfo) {
         println!("  Name: {name}", name = info.name);
+        if let Some(metadata) = info.state.metadata() {
+            if let Some(description) = &metadata.description {
+                println!("  Description: {description}");
+            }
+            if let Some(license) = &metadata.license {
+                println!("  License: {license}");
+            }
+            if let Some(repository) = &metadata.repository {
+                println!("  Repository: {repository}");
+            }
+            if !metadata.keywords.is_empty() {
+                println!(
+                    "  Keywords: {keywords}",
+                    keywords = metadata.keywords.join(", ")
+                );
+            }
+        }
         println!("  Versions:");
         info.state.releases().for_each(|r| {
             if let Some(content) = r.content() {
                 Self::print_release(&r.version, content);
+                if let Some(deprecation) = info.state.deprecation(&r.version) {
+                    Self::print_deprecation(deprecation);
+                }
             }
         });
     }
@@ -95,6 +115,13 @@ impl InfoCommand {
         println!("    {version} ({content})");
     }
 
+    fn print_deprecation(deprecation: &PackageDeprecation) {
+        println!("      Deprecated: {message}", message = deprecation.message);
+        if let Some(successor) = &deprecation.successor {
+            println!("      Successor: {successor}");
+        }
+    }
+
     async fn print_namespace_map<R: RegistryStorage, C: ContentStorage, N: NamespaceMapStorage>(
         client: &Client<R, C, N>,
     ) -> Result<()> {
@@ -1,14 +1,18 @@
 use anyhow::{Context, Result};
 use clap::{Args, Subcommand};
 use dialoguer::{theme::ColorfulTheme, Confirm, Password};
-use p256::ecdsa::SigningKey;
-use rand_core::OsRng;
+use itertools::Itertools;
+use std::time::Duration;
 use warg_client::keyring::Keyring;
-use warg_client::Config;
-use warg_crypto::signing::PrivateKey;
+use warg_client::storage::{PublishEntry, PublishInfo};
+use warg_client::{Config, FileSystemClient};
+use warg_crypto::signing::{generate_pair, PrivateKey, PublicKey, SignatureAlgorithm};
+use warg_protocol::registry::PackageName;
 
 use super::CommonOptions;
 
+const DEFAULT_WAIT_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Manage signing keys for interacting with a registry.
 #[derive(Args)]
 pub struct KeyCommand {
@@ -25,6 +29,7 @@ impl KeyCommand {
             KeySubcommand::Info(cmd) => cmd.exec().await,
             KeySubcommand::Set(cmd) => cmd.exec().await,
             KeySubcommand::Delete(cmd) => cmd.exec().await,
+            KeySubcommand::Rotate(cmd) => cmd.exec().await,
         }
     }
 }
@@ -40,6 +45,8 @@ pub enum KeySubcommand {
     Set(KeySetCommand),
     /// Deletes the signing key for a registry from the local keyring.
     Delete(KeyDeleteCommand),
+    /// Rotates the signing key for a registry, transferring package permissions to a new key.
+    Rotate(KeyRotateCommand),
 }
 
 /// Creates a new signing key for a registry in the local keyring.
@@ -48,13 +55,16 @@ pub struct KeyNewCommand {
     /// The common command options.
     #[clap(flatten)]
     pub common: CommonOptions,
+    /// The signature algorithm of the new key (`ecdsa-p256` or `ed25519`).
+    #[clap(long, value_name = "ALGORITHM", default_value = "ecdsa-p256")]
+    pub algorithm: SignatureAlgorithm,
 }
 
 impl KeyNewCommand {
     /// Executes the command.
     pub async fn exec(self) -> Result<()> {
         let config = &mut self.common.read_config()?;
-        let key = SigningKey::random(&mut OsRng).into();
+        let (_, key) = generate_pair(&self.algorithm);
         if let Some(ref reg) = self.common.registry {
             config.keys.insert(reg.to_string());
         } else {
@@ -173,3 +183,172 @@ impl KeyDeleteCommand {
         Ok(())
     }
 }
+
+/// Rotates the signing key for a registry, transferring package permissions to a new key.
+#[derive(Args)]
+pub struct KeyRotateCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The package(s) whose permissions are transferred to the new key.
+    #[clap(
+        long = "name",
+        short,
+        value_name = "PACKAGE",
+        value_delimiter = ',',
+        required = true
+    )]
+    pub names: Vec<PackageName>,
+    /// The signature algorithm of the new key (`ecdsa-p256` or `ed25519`).
+    #[clap(long, value_name = "ALGORITHM", default_value = "ecdsa-p256")]
+    pub algorithm: SignatureAlgorithm,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl KeyRotateCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = &mut self.common.read_config()?;
+        let client = self.common.create_client(config).await?;
+        let keyring = Keyring::from_config(config)?;
+        let registry = self.common.registry.as_deref();
+        let old_key =
+            keyring.get_signing_key(registry, &config.keys, config.home_url.as_deref())?;
+
+        // The new key is stored as pending before any package is rotated so
+        // that it is never lost; a previous incomplete rotation is resumed
+        let pending =
+            keyring.get_pending_signing_key(registry, &config.keys, config.home_url.as_deref())?;
+        let resuming = pending.is_some();
+        let new_key = match pending {
+            Some(key) => key,
+            None => {
+                let (_, key) = generate_pair(&self.algorithm);
+                keyring.set_pending_signing_key(
+                    registry,
+                    &key,
+                    &config.keys,
+                    config.home_url.as_deref(),
+                )?;
+                key
+            }
+        };
+        let new_public_key = new_key.public_key();
+
+        let mut result = Ok(());
+        let mut rotated = Vec::new();
+        let mut submitted = Vec::new();
+        for name in &self.names {
+            if resuming && Self::is_rotated(&client, &new_public_key, name).await? {
+                rotated.push(name);
+                continue;
+            }
+
+            match self.rotate(&client, &old_key, &new_public_key, name).await {
+                Ok(true) => rotated.push(name),
+                Ok(false) => submitted.push(name),
+                Err(e) => {
+                    result = Err(e.context(format!("failed to rotate key for package `{name}`")));
+                    break;
+                }
+            }
+        }
+
+        if rotated.len() < self.names.len() {
+            // Both keys are kept until every rotation is confirmed, as the
+            // rotated packages only accept the new key and the others only
+            // accept the old key
+            if !rotated.is_empty() {
+                println!(
+                    "rotated key for package(s): {names}",
+                    names = rotated.iter().join(", ")
+                );
+            }
+            if !submitted.is_empty() {
+                println!(
+                    "submitted key rotation for package(s): {names}",
+                    names = submitted.iter().join(", ")
+                );
+            }
+            println!(
+                "the new key `{key_id}` is pending; run this command again once the rotations are published to make it the signing key",
+                key_id = new_public_key.fingerprint()
+            );
+            return result;
+        }
+
+        // The pending key is deleted before the keys are updated, as they
+        // determine which keyring entry it was stored in
+        keyring.delete_pending_signing_key(registry, &config.keys, config.home_url.as_deref())?;
+        if let Some(reg) = registry {
+            config.keys.insert(reg.to_string());
+        } else {
+            config.keys.insert("default".to_string());
+        }
+        keyring.set_signing_key(
+            registry,
+            &new_key,
+            &mut config.keys,
+            config.home_url.as_deref(),
+        )?;
+        config.write_to_file(&Config::default_config_path()?)?;
+        println!("Key ID: {}", new_public_key.fingerprint());
+        println!("Public Key: {new_public_key}");
+
+        Ok(())
+    }
+
+    /// Determines if the key of a package has already been rotated to the
+    /// given key by a previous incomplete rotation.
+    async fn is_rotated(
+        client: &FileSystemClient,
+        new_public_key: &PublicKey,
+        name: &PackageName,
+    ) -> Result<bool> {
+        let info = client.fetch_package(name).await?;
+        Ok(info
+            .state
+            .public_key(&new_public_key.fingerprint())
+            .is_some())
+    }
+
+    async fn rotate(
+        &self,
+        client: &FileSystemClient,
+        old_key: &PrivateKey,
+        new_public_key: &PublicKey,
+        name: &PackageName,
+    ) -> Result<bool> {
+        let record_id = client
+            .publish_with_info(
+                old_key,
+                PublishInfo {
+                    name: name.clone(),
+                    head: None,
+                    entries: vec![PublishEntry::RotateKey {
+                        key_id: old_key.public_key().fingerprint(),
+                        key: new_public_key.clone(),
+                    }],
+                    cosigning: None,
+                },
+            )
+            .await?;
+
+        if self.no_wait {
+            println!("submitted record `{record_id}` for publishing");
+            return Ok(false);
+        }
+
+        client
+            .wait_for_publish(name, &record_id, DEFAULT_WAIT_INTERVAL)
+            .await?;
+
+        println!(
+            "rotated key `{key_id}` for package `{name}`",
+            key_id = old_key.public_key().fingerprint()
+        );
+        Ok(true)
+    }
+}
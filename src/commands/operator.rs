@@ -0,0 +1,215 @@
+use super::CommonOptions;
+use anyhow::Result;
+use clap::{Args, Subcommand};
+use itertools::Itertools;
+use std::time::Duration;
+use warg_crypto::signing::{KeyID, PublicKey};
+use warg_protocol::operator::{OperatorEntry, Permission};
+
+const DEFAULT_WAIT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Manage the operator log of a warg registry.
+#[derive(Subcommand)]
+pub enum OperatorCommand {
+    /// Define a namespace for packages published to the registry.
+    DefineNamespace(OperatorDefineNamespaceCommand),
+    /// Import a namespace from another registry.
+    ImportNamespace(OperatorImportNamespaceCommand),
+    /// Grant operator permissions to a key.
+    Grant(OperatorGrantCommand),
+    /// Revoke operator permissions from a key.
+    Revoke(OperatorRevokeCommand),
+}
+
+impl OperatorCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        match self {
+            Self::DefineNamespace(cmd) => cmd.exec().await,
+            Self::ImportNamespace(cmd) => cmd.exec().await,
+            Self::Grant(cmd) => cmd.exec().await,
+            Self::Revoke(cmd) => cmd.exec().await,
+        }
+    }
+}
+
+/// Signs and submits the given entry to the operator log.
+///
+/// Returns `true` if the record was published or `false` if it was only submitted.
+async fn publish(common: &CommonOptions, entry: OperatorEntry, no_wait: bool) -> Result<bool> {
+    let config = common.read_config()?;
+    let client = common.create_client(&config).await?;
+    let signing_key = common.signing_key(None).await?;
+
+    let record_id = client
+        .publish_operator_record(&signing_key, vec![entry])
+        .await?;
+
+    if no_wait {
+        println!("submitted operator record `{record_id}` for publishing");
+        return Ok(false);
+    }
+
+    client
+        .wait_for_operator_publish(&record_id, DEFAULT_WAIT_INTERVAL)
+        .await?;
+    Ok(true)
+}
+
+/// Define a namespace for packages published to the registry.
+#[derive(Args)]
+pub struct OperatorDefineNamespaceCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The namespace to define.
+    #[clap(value_name = "NAMESPACE")]
+    pub namespace: String,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl OperatorDefineNamespaceCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let entry = OperatorEntry::DefineNamespace {
+            namespace: self.namespace.clone(),
+        };
+
+        if publish(&self.common, entry, self.no_wait).await? {
+            println!(
+                "defined namespace `{namespace}`",
+                namespace = self.namespace
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Import a namespace from another registry.
+#[derive(Args)]
+pub struct OperatorImportNamespaceCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The namespace to import.
+    #[clap(value_name = "NAMESPACE")]
+    pub namespace: String,
+    /// The registry that the namespace is imported from.
+    #[clap(long = "from", value_name = "REGISTRY")]
+    pub registry: String,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl OperatorImportNamespaceCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let entry = OperatorEntry::ImportNamespace {
+            namespace: self.namespace.clone(),
+            registry: self.registry.clone(),
+        };
+
+        if publish(&self.common, entry, self.no_wait).await? {
+            println!(
+                "imported namespace `{namespace}` from registry `{registry}`",
+                namespace = self.namespace,
+                registry = self.registry
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Grant operator permissions to a key.
+#[derive(Args)]
+pub struct OperatorGrantCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The public key to grant permissions to.
+    #[clap(value_name = "PUBLIC_KEY")]
+    pub public_key: PublicKey,
+    /// The permission(s) to grant.
+    #[clap(
+        long = "permission",
+        value_delimiter = ',',
+        required = true,
+        value_parser = permission_parser
+    )]
+    pub permissions: Vec<Permission>,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl OperatorGrantCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let entry = OperatorEntry::GrantFlat {
+            key: self.public_key.clone(),
+            permissions: self.permissions.clone(),
+        };
+
+        if publish(&self.common, entry, self.no_wait).await? {
+            println!(
+                "granted ({permissions_str}) to key ID `{key_id}` for the registry operator",
+                permissions_str = self.permissions.iter().join(","),
+                key_id = self.public_key.fingerprint(),
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Revoke operator permissions from a key.
+#[derive(Args)]
+pub struct OperatorRevokeCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The key ID to revoke permissions from.
+    #[clap(value_name = "KEY_ID")]
+    pub key: KeyID,
+    /// The permission(s) to revoke.
+    #[clap(
+        long = "permission",
+        value_delimiter = ',',
+        required = true,
+        value_parser = permission_parser
+    )]
+    pub permissions: Vec<Permission>,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl OperatorRevokeCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let entry = OperatorEntry::RevokeFlat {
+            key_id: self.key.clone(),
+            permissions: self.permissions.clone(),
+        };
+
+        if publish(&self.common, entry, self.no_wait).await? {
+            println!(
+                "revoked ({permissions_str}) from key ID `{key_id}` for the registry operator",
+                permissions_str = self.permissions.iter().join(","),
+                key_id = self.key,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn permission_parser(s: &str) -> Result<Permission, String> {
+    s.parse()
+        .map_err(|_| format!("`{s}` is not a valid operator permission"))
+}
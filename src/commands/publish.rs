@@ -1,24 +1,29 @@
 use super::CommonOptions;
 use anyhow::{anyhow, bail, Context, Result};
-use clap::{Args, Subcommand};
+use clap::{Args, Subcommand, ValueEnum};
 use dialoguer::{theme::ColorfulTheme, Confirm};
 use futures::TryStreamExt;
 use itertools::Itertools;
-use std::{future::Future, path::PathBuf, time::Duration};
+use std::{
+    fs,
+    future::Future,
+    path::{Path, PathBuf},
+    time::Duration,
+};
 use tokio::io::BufReader;
 use tokio_util::io::ReaderStream;
 use warg_client::{
     storage::{ContentStorage as _, PublishEntry, PublishInfo, RegistryStorage as _},
-    FileSystemClient,
+    ClientError, FileSystemClient,
 };
 use warg_crypto::{
-    hash::AnyHash,
+    hash::{AnyHash, HashAlgorithm},
     signing::{KeyID, PublicKey},
 };
 use warg_protocol::{
-    package::Permission,
+    package::{PackageDeprecation, Permission, Role},
     registry::{PackageName, RecordId},
-    Version,
+    Version, VersionReq,
 };
 
 const DEFAULT_WAIT_INTERVAL: Duration = Duration::from_secs(1);
@@ -45,7 +50,7 @@ where
 
             let entry = entry(client).await?;
 
-            if matches!(entry, PublishEntry::Init) && info.initializing() {
+            if matches!(entry, PublishEntry::Init { .. }) && info.initializing() {
                 bail!("there is already a pending initializing for package `{name}`");
             }
 
@@ -57,6 +62,48 @@ where
     }
 }
 
+/// Describes the permissions or role being granted or revoked.
+fn describe_grant(
+    permissions: &[Permission],
+    versions: Option<&VersionReq>,
+    role: Option<Role>,
+) -> String {
+    match (role, versions) {
+        (Some(role), _) => format!("role `{role}`"),
+        (None, Some(versions)) => format!(
+            "({permissions_str}) for versions `{versions}`",
+            permissions_str = permissions.iter().join(",")
+        ),
+        (None, None) => format!("({})", permissions.iter().join(",")),
+    }
+}
+
+/// Reads a publish exported to a file.
+fn read_publish_file(path: &Path) -> Result<PublishInfo> {
+    let contents = fs::read_to_string(path).with_context(|| {
+        format!(
+            "failed to read publish file `{path}`",
+            path = path.display()
+        )
+    })?;
+    serde_json::from_str(&contents).with_context(|| {
+        format!(
+            "failed to deserialize publish file `{path}`",
+            path = path.display()
+        )
+    })
+}
+
+/// Writes a publish to a file.
+fn write_publish_file(path: &Path, info: &PublishInfo) -> Result<()> {
+    fs::write(path, serde_json::to_string_pretty(info)?).with_context(|| {
+        format!(
+            "failed to write publish file `{path}`",
+            path = path.display()
+        )
+    })
+}
+
 /// Publish a package to a warg registry.
 #[derive(Subcommand)]
 pub enum PublishCommand {
@@ -66,16 +113,30 @@ pub enum PublishCommand {
     Release(PublishReleaseCommand),
     /// Yank a package version.
     Yank(PublishYankCommand),
+    /// Unyank a previously yanked package version.
+    Unyank(PublishUnyankCommand),
+    /// Set the metadata of a package.
+    Metadata(PublishMetadataCommand),
+    /// Deprecate a package or a range of its versions.
+    Deprecate(PublishDeprecateCommand),
+    /// Retract the deprecation of a package or a range of its versions.
+    Undeprecate(PublishUndeprecateCommand),
     /// Grant permissions for the package.
     Grant(PublishGrantCommand),
     /// Revoke permissions for the package.
     Revoke(PublishRevokeCommand),
+    /// Set the number of signatures required on each record of the package.
+    Threshold(PublishThresholdCommand),
     /// Start a new pending publish.
     Start(PublishStartCommand),
     /// List the records in a pending publish.
     List(PublishListCommand),
     /// Abort a pending publish.
     Abort(PublishAbortCommand),
+    /// Export a pending publish to a file for cosigning.
+    Export(PublishExportCommand),
+    /// Cosign an exported publish.
+    Cosign(PublishCosignCommand),
     /// Submit a pending publish.
     Submit(PublishSubmitCommand),
     /// Wait for a pending publish to complete.
@@ -89,11 +150,18 @@ impl PublishCommand {
             Self::Init(cmd) => cmd.exec().await,
             Self::Release(cmd) => cmd.exec().await,
             Self::Yank(cmd) => cmd.exec().await,
+            Self::Unyank(cmd) => cmd.exec().await,
+            Self::Metadata(cmd) => cmd.exec().await,
+            Self::Deprecate(cmd) => cmd.exec().await,
+            Self::Undeprecate(cmd) => cmd.exec().await,
             Self::Grant(cmd) => cmd.exec().await,
             Self::Revoke(cmd) => cmd.exec().await,
+            Self::Threshold(cmd) => cmd.exec().await,
             Self::Start(cmd) => cmd.exec().await,
             Self::List(cmd) => cmd.exec().await,
             Self::Abort(cmd) => cmd.exec().await,
+            Self::Export(cmd) => cmd.exec().await,
+            Self::Cosign(cmd) => cmd.exec().await,
             Self::Submit(cmd) => cmd.exec().await,
             Self::Wait(cmd) => cmd.exec().await,
         }
@@ -110,6 +178,9 @@ pub struct PublishInitCommand {
     /// The package name being initialized.
     #[clap(value_name = "PACKAGE")]
     pub name: PackageName,
+    /// The hash algorithm used by the package log (`sha256`, `sha512` or `blake3`).
+    #[clap(long, value_name = "ALGORITHM", default_value = "sha256")]
+    pub hash_algorithm: HashAlgorithm,
     /// Whether to wait for the publish to complete.
     #[clap(long)]
     pub no_wait: bool,
@@ -123,8 +194,9 @@ impl PublishInitCommand {
         let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
 
         let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+        let hash_algorithm = self.hash_algorithm;
         match enqueue(&client, &self.name, |_| {
-            std::future::ready(Ok(PublishEntry::Init))
+            std::future::ready(Ok(PublishEntry::Init { hash_algorithm }))
         })
         .await?
         {
@@ -136,6 +208,7 @@ impl PublishInitCommand {
                             name: self.name.clone(),
                             head: None,
                             entries: vec![entry],
+                            cosigning: None,
                         },
                     )
                     .await?;
@@ -224,6 +297,7 @@ impl PublishReleaseCommand {
                             name: self.name.clone(),
                             head: None,
                             entries: vec![entry],
+                            cosigning: None,
                         },
                     )
                     .await?;
@@ -278,7 +352,7 @@ impl PublishYankCommand {
     pub async fn exec(self) -> Result<()> {
         if !Confirm::with_theme(&ColorfulTheme::default())
             .with_prompt(format!(
-                "`Yank` revokes a version, making it unavailable. It is permanent and cannot be reversed.
+                "`Yank` revokes a version, making it unavailable. It can be reversed with `warg publish unyank`.
 Yank `{version}` of `{package}`?",
                 version = &self.version,
                 package = &self.name,
@@ -309,6 +383,7 @@ Yank `{version}` of `{package}`?",
                             name: self.name.clone(),
                             head: None,
                             entries: vec![entry],
+                            cosigning: None,
                         },
                     )
                     .await?;
@@ -340,6 +415,365 @@ Yank `{version}` of `{package}`?",
     }
 }
 
+/// Unyank a previously yanked package release from a warg registry.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct PublishUnyankCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The package name being unyanked.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub name: PackageName,
+    /// The version of the package being unyanked.
+    #[clap(long, short, value_name = "VERSION")]
+    pub version: Version,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl PublishUnyankCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
+        let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+
+        let version = self.version.clone();
+        match enqueue(&client, &self.name, move |_| async move {
+            Ok(PublishEntry::Unyank { version })
+        })
+        .await?
+        {
+            Some(entry) => {
+                let record_id = client
+                    .publish_with_info(
+                        &signing_key,
+                        PublishInfo {
+                            name: self.name.clone(),
+                            head: None,
+                            entries: vec![entry],
+                            cosigning: None,
+                        },
+                    )
+                    .await?;
+
+                if self.no_wait {
+                    println!("submitted record `{record_id}` for publishing");
+                } else {
+                    client
+                        .wait_for_publish(&self.name, &record_id, DEFAULT_WAIT_INTERVAL)
+                        .await?;
+
+                    println!(
+                        "unyanked version {version} of package `{name}`",
+                        version = self.version,
+                        name = self.name
+                    );
+                }
+            }
+            None => {
+                println!(
+                    "added unyank of version {version} for package `{name}` to pending publish",
+                    version = self.version,
+                    name = self.name
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A package metadata field.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetadataField {
+    /// The description of the package.
+    Description,
+    /// The license of the package.
+    License,
+    /// The source repository of the package.
+    Repository,
+    /// The keywords of the package.
+    Keywords,
+}
+
+/// Set the metadata of a package in a warg registry.
+///
+/// Metadata not specified is kept from the package's current metadata,
+/// unless it is cleared with `--clear`.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct PublishMetadataCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The package name to set the metadata of.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub name: PackageName,
+    /// A short description of the package.
+    #[clap(long, value_name = "DESCRIPTION")]
+    pub description: Option<String>,
+    /// The SPDX license expression of the package.
+    #[clap(long, value_name = "LICENSE")]
+    pub license: Option<String>,
+    /// The URL of the package's source repository.
+    #[clap(long, value_name = "URL")]
+    pub repository: Option<String>,
+    /// The keyword(s) of the package, replacing any existing keywords.
+    #[clap(long = "keyword", value_name = "KEYWORD", value_delimiter = ',')]
+    pub keywords: Vec<String>,
+    /// The metadata field(s) to clear from the package's current metadata.
+    #[clap(long = "clear", value_name = "FIELD", value_delimiter = ',')]
+    pub clear: Vec<MetadataField>,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl PublishMetadataCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
+        let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+
+        let name = self.name.clone();
+        let description = self.description.clone();
+        let license = self.license.clone();
+        let repository = self.repository.clone();
+        let keywords = self.keywords.clone();
+        let clear = self.clear.clone();
+        match enqueue(&client, &self.name, move |c| async move {
+            let mut metadata = match c.package(&name).await {
+                Ok(info) => info.state.metadata().cloned().unwrap_or_default(),
+                Err(ClientError::PackageDoesNotExist { .. }) => Default::default(),
+                Err(e) => return Err(e.into()),
+            };
+
+            for field in clear {
+                match field {
+                    MetadataField::Description => metadata.description = None,
+                    MetadataField::License => metadata.license = None,
+                    MetadataField::Repository => metadata.repository = None,
+                    MetadataField::Keywords => metadata.keywords.clear(),
+                }
+            }
+            if description.is_some() {
+                metadata.description = description;
+            }
+            if license.is_some() {
+                metadata.license = license;
+            }
+            if repository.is_some() {
+                metadata.repository = repository;
+            }
+            if !keywords.is_empty() {
+                metadata.keywords = keywords;
+            }
+
+            Ok(PublishEntry::Metadata { metadata })
+        })
+        .await?
+        {
+            Some(entry) => {
+                let record_id = client
+                    .publish_with_info(
+                        &signing_key,
+                        PublishInfo {
+                            name: self.name.clone(),
+                            head: None,
+                            entries: vec![entry],
+                            cosigning: None,
+                        },
+                    )
+                    .await?;
+
+                if self.no_wait {
+                    println!("submitted record `{record_id}` for publishing");
+                } else {
+                    client
+                        .wait_for_publish(&self.name, &record_id, DEFAULT_WAIT_INTERVAL)
+                        .await?;
+
+                    println!("set metadata of package `{name}`", name = self.name);
+                }
+            }
+            None => {
+                println!(
+                    "added metadata for package `{name}` to pending publish",
+                    name = self.name
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Deprecate a package, or a range of its versions, in a warg registry.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct PublishDeprecateCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The package name being deprecated.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub name: PackageName,
+    /// The version requirement of the versions being deprecated; defaults to all versions.
+    #[clap(long, value_name = "VERSIONS")]
+    pub versions: Option<VersionReq>,
+    /// The message explaining the deprecation.
+    #[clap(long, short, value_name = "MESSAGE")]
+    pub message: String,
+    /// The package that should be used instead.
+    #[clap(long, value_name = "PACKAGE")]
+    pub successor: Option<PackageName>,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl PublishDeprecateCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
+        let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+
+        let deprecation = PackageDeprecation {
+            versions: self.versions.clone(),
+            message: self.message.clone(),
+            successor: self.successor.clone(),
+        };
+        let versions = match &self.versions {
+            Some(req) => format!("versions `{req}`"),
+            None => "all versions".to_string(),
+        };
+
+        match enqueue(&client, &self.name, move |_| async move {
+            Ok(PublishEntry::Deprecate { deprecation })
+        })
+        .await?
+        {
+            Some(entry) => {
+                let record_id = client
+                    .publish_with_info(
+                        &signing_key,
+                        PublishInfo {
+                            name: self.name.clone(),
+                            head: None,
+                            entries: vec![entry],
+                            cosigning: None,
+                        },
+                    )
+                    .await?;
+
+                if self.no_wait {
+                    println!("submitted record `{record_id}` for publishing");
+                } else {
+                    client
+                        .wait_for_publish(&self.name, &record_id, DEFAULT_WAIT_INTERVAL)
+                        .await?;
+
+                    println!(
+                        "deprecated {versions} of package `{name}`",
+                        name = self.name
+                    );
+                }
+            }
+            None => {
+                println!(
+                    "added deprecation of {versions} for package `{name}` to pending publish",
+                    name = self.name
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Retract the deprecation of a package, or a range of its versions, in a warg registry.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct PublishUndeprecateCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The package name being undeprecated.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub name: PackageName,
+    /// The version requirement of the deprecation being retracted; defaults to all versions.
+    #[clap(long, value_name = "VERSIONS")]
+    pub versions: Option<VersionReq>,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl PublishUndeprecateCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
+        let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+
+        let versions = match &self.versions {
+            Some(req) => format!("versions `{req}`"),
+            None => "all versions".to_string(),
+        };
+
+        let req = self.versions.clone();
+        match enqueue(&client, &self.name, move |_| async move {
+            Ok(PublishEntry::Undeprecate { versions: req })
+        })
+        .await?
+        {
+            Some(entry) => {
+                let record_id = client
+                    .publish_with_info(
+                        &signing_key,
+                        PublishInfo {
+                            name: self.name.clone(),
+                            head: None,
+                            entries: vec![entry],
+                            cosigning: None,
+                        },
+                    )
+                    .await?;
+
+                if self.no_wait {
+                    println!("submitted record `{record_id}` for publishing");
+                } else {
+                    client
+                        .wait_for_publish(&self.name, &record_id, DEFAULT_WAIT_INTERVAL)
+                        .await?;
+
+                    println!(
+                        "undeprecated {versions} of package `{name}`",
+                        name = self.name
+                    );
+                }
+            }
+            None => {
+                println!(
+                    "added undeprecation of {versions} for package `{name}` to pending publish",
+                    name = self.name
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// Publish a package to a warg registry.
 #[derive(Args)]
 #[clap(disable_version_flag = true)]
@@ -360,6 +794,12 @@ pub struct PublishGrantCommand {
         default_value = "release,yank"
     )]
     pub permissions: Vec<Permission>,
+    /// Limit the permission(s) to the versions matching this requirement.
+    #[clap(long, value_name = "VERSIONS")]
+    pub versions: Option<VersionReq>,
+    /// Grant a role (e.g. `admin`) instead of permissions.
+    #[clap(long, value_name = "ROLE", conflicts_with_all = ["permissions", "versions"])]
+    pub role: Option<Role>,
     /// Whether to wait for the publish to complete.
     #[clap(long)]
     pub no_wait: bool,
@@ -373,10 +813,22 @@ impl PublishGrantCommand {
         let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
         let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
 
+        let grant = describe_grant(&self.permissions, self.versions.as_ref(), self.role);
         match enqueue(&client, &self.name, |_| async {
-            Ok(PublishEntry::Grant {
-                key: self.public_key.clone(),
-                permissions: self.permissions.clone(),
+            Ok(match (self.role, &self.versions) {
+                (Some(role), _) => PublishEntry::GrantRole {
+                    key: self.public_key.clone(),
+                    role,
+                },
+                (None, Some(versions)) => PublishEntry::GrantVersions {
+                    key: self.public_key.clone(),
+                    permissions: self.permissions.clone(),
+                    versions: versions.clone(),
+                },
+                (None, None) => PublishEntry::Grant {
+                    key: self.public_key.clone(),
+                    permissions: self.permissions.clone(),
+                },
             })
         })
         .await?
@@ -389,6 +841,7 @@ impl PublishGrantCommand {
                             name: self.name.clone(),
                             head: None,
                             entries: vec![entry],
+                            cosigning: None,
                         },
                     )
                     .await?;
@@ -401,8 +854,7 @@ impl PublishGrantCommand {
                         .await?;
 
                     println!(
-                        "granted ({permissions_str}) to key ID `{key_id}` for package `{name}`",
-                        permissions_str = self.permissions.iter().join(","),
+                        "granted {grant} to key ID `{key_id}` for package `{name}`",
                         key_id = self.public_key.fingerprint(),
                         name = self.name
                     );
@@ -410,8 +862,7 @@ impl PublishGrantCommand {
             }
             None => {
                 println!(
-                    "added grant of ({permissions_str}) to key ID `{key_id}` for package `{name}` to pending publish",
-                    permissions_str = self.permissions.iter().join(","),
+                    "added grant of {grant} to key ID `{key_id}` for package `{name}` to pending publish",
                     key_id = self.public_key.fingerprint(),
                     name = self.name
                 );
@@ -442,6 +893,12 @@ pub struct PublishRevokeCommand {
         default_value = "release,yank"
     )]
     pub permissions: Vec<Permission>,
+    /// Revoke the permission(s) granted for this version requirement.
+    #[clap(long, value_name = "VERSIONS")]
+    pub versions: Option<VersionReq>,
+    /// Revoke a role (e.g. `admin`) instead of permissions.
+    #[clap(long, value_name = "ROLE", conflicts_with_all = ["permissions", "versions"])]
+    pub role: Option<Role>,
     /// Whether to wait for the publish to complete.
     #[clap(long)]
     pub no_wait: bool,
@@ -455,10 +912,22 @@ impl PublishRevokeCommand {
         let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
         let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
 
+        let grant = describe_grant(&self.permissions, self.versions.as_ref(), self.role);
         match enqueue(&client, &self.name, |_| async {
-            Ok(PublishEntry::Revoke {
-                key_id: self.key.clone(),
-                permissions: self.permissions.clone(),
+            Ok(match (self.role, &self.versions) {
+                (Some(role), _) => PublishEntry::RevokeRole {
+                    key_id: self.key.clone(),
+                    role,
+                },
+                (None, Some(versions)) => PublishEntry::RevokeVersions {
+                    key_id: self.key.clone(),
+                    permissions: self.permissions.clone(),
+                    versions: versions.clone(),
+                },
+                (None, None) => PublishEntry::Revoke {
+                    key_id: self.key.clone(),
+                    permissions: self.permissions.clone(),
+                },
             })
         })
         .await?
@@ -471,6 +940,7 @@ impl PublishRevokeCommand {
                             name: self.name.clone(),
                             head: None,
                             entries: vec![entry],
+                            cosigning: None,
                         },
                     )
                     .await?;
@@ -483,8 +953,7 @@ impl PublishRevokeCommand {
                         .await?;
 
                     println!(
-                        "revoked ({permissions_str}) from key ID `{key_id}` for package `{name}`",
-                        permissions_str = self.permissions.iter().join(","),
+                        "revoked {grant} from key ID `{key_id}` for package `{name}`",
                         key_id = self.key,
                         name = self.name
                     );
@@ -492,8 +961,7 @@ impl PublishRevokeCommand {
             }
             None => {
                 println!(
-                    "added revoke of ({permissions_str}) from key ID `{key_id}` for package `{name}` to pending publish",
-                    permissions_str = self.permissions.iter().join(","),
+                    "added revoke of {grant} from key ID `{key_id}` for package `{name}` to pending publish",
                     key_id = self.key,
                     name = self.name
                 );
@@ -504,6 +972,78 @@ impl PublishRevokeCommand {
     }
 }
 
+/// Set the number of signatures required on each record of a package.
+#[derive(Args)]
+#[clap(disable_version_flag = true)]
+pub struct PublishThresholdCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The package name.
+    #[clap(long, short, value_name = "PACKAGE")]
+    pub name: PackageName,
+    /// The number of signatures from keys with permissions required on each record.
+    #[clap(value_name = "THRESHOLD")]
+    pub threshold: u32,
+    /// Whether to wait for the publish to complete.
+    #[clap(long)]
+    pub no_wait: bool,
+}
+
+impl PublishThresholdCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let registry_domain = client.get_warg_registry(self.name.namespace()).await?;
+        let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+
+        let threshold = self.threshold;
+        match enqueue(&client, &self.name, move |_| async move {
+            Ok(PublishEntry::SignatureThreshold { threshold })
+        })
+        .await?
+        {
+            Some(entry) => {
+                let record_id = client
+                    .publish_with_info(
+                        &signing_key,
+                        PublishInfo {
+                            name: self.name.clone(),
+                            head: None,
+                            entries: vec![entry],
+                            cosigning: None,
+                        },
+                    )
+                    .await?;
+
+                if self.no_wait {
+                    println!("submitted record `{record_id}` for publishing");
+                } else {
+                    client
+                        .wait_for_publish(&self.name, &record_id, DEFAULT_WAIT_INTERVAL)
+                        .await?;
+
+                    println!(
+                        "set signature threshold of package `{name}` to {threshold}",
+                        name = self.name,
+                        threshold = self.threshold
+                    );
+                }
+            }
+            None => {
+                println!(
+                    "added signature threshold of {threshold} for package `{name}` to pending publish",
+                    threshold = self.threshold,
+                    name = self.name
+                );
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// Start a new pending publish.
 #[derive(Args)]
 #[clap(disable_version_flag = true)]
@@ -529,6 +1069,7 @@ impl PublishStartCommand {
                     name: self.name.clone(),
                     head: None,
                     entries: Default::default(),
+                    cosigning: None,
                 }))
                 .await?;
 
@@ -567,8 +1108,8 @@ impl PublishListCommand {
                 for (i, entry) in info.entries.iter().enumerate() {
                     print!("record {i}: ");
                     match entry {
-                        PublishEntry::Init => {
-                            println!("initialize package");
+                        PublishEntry::Init { hash_algorithm } => {
+                            println!("initialize package using {hash_algorithm}");
                         }
                         PublishEntry::Release { version, content } => {
                             println!("release {version} with content digest `{content}`")
@@ -576,6 +1117,20 @@ impl PublishListCommand {
                         PublishEntry::Yank { version } => {
                             println!("yank {version}")
                         }
+                        PublishEntry::Unyank { version } => {
+                            println!("unyank {version}")
+                        }
+                        PublishEntry::Metadata { .. } => {
+                            println!("set package metadata")
+                        }
+                        PublishEntry::Deprecate { deprecation } => match &deprecation.versions {
+                            Some(req) => println!("deprecate versions `{req}`"),
+                            None => println!("deprecate all versions"),
+                        },
+                        PublishEntry::Undeprecate { versions } => match versions {
+                            Some(req) => println!("undeprecate versions `{req}`"),
+                            None => println!("undeprecate all versions"),
+                        },
                         PublishEntry::Grant { key, permissions } => println!(
                             "grant ({permissions_str}) to `{key_id}`",
                             permissions_str = permissions.iter().join(","),
@@ -588,8 +1143,46 @@ impl PublishListCommand {
                             "revoke ({permissions_str}) from `{key_id}`",
                             permissions_str = permissions.iter().join(","),
                         ),
+                        PublishEntry::GrantVersions {
+                            key,
+                            permissions,
+                            versions,
+                        } => println!(
+                            "grant {grant} to `{key_id}`",
+                            grant = describe_grant(permissions, Some(versions), None),
+                            key_id = key.fingerprint(),
+                        ),
+                        PublishEntry::RevokeVersions {
+                            key_id,
+                            permissions,
+                            versions,
+                        } => println!(
+                            "revoke {grant} from `{key_id}`",
+                            grant = describe_grant(permissions, Some(versions), None),
+                        ),
+                        PublishEntry::GrantRole { key, role } => println!(
+                            "grant role `{role}` to `{key_id}`",
+                            key_id = key.fingerprint(),
+                        ),
+                        PublishEntry::RevokeRole { key_id, role } => {
+                            println!("revoke role `{role}` from `{key_id}`")
+                        }
+                        PublishEntry::RotateKey { key_id, key } => println!(
+                            "rotate `{key_id}` to `{new_key_id}`",
+                            new_key_id = key.fingerprint(),
+                        ),
+                        PublishEntry::SignatureThreshold { threshold } => {
+                            println!("set signature threshold to {threshold}")
+                        }
                     }
                 }
+
+                if let Some(cosigning) = &info.cosigning {
+                    println!(
+                        "\ncosigned by {count} key(s)",
+                        count = cosigning.cosignatures.len()
+                    );
+                }
             }
             None => bail!("no pending publish to list"),
         }
@@ -627,12 +1220,90 @@ impl PublishAbortCommand {
     }
 }
 
+/// Export a pending publish to a file for cosigning.
+///
+/// The pending publish is removed from client storage once exported.
+#[derive(Args)]
+pub struct PublishExportCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The path of the file to export the pending publish to.
+    #[clap(long, short, value_name = "FILE")]
+    pub file: PathBuf,
+}
+
+impl PublishExportCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+
+        match client.registry().load_publish().await? {
+            Some(mut info) => {
+                if info.cosigning.is_none() {
+                    client.prepare_cosigning(&mut info).await?;
+                }
+
+                write_publish_file(&self.file, &info)?;
+                client.registry().store_publish(None).await?;
+
+                println!(
+                    "exported the pending publish for package `{name}` to `{path}`",
+                    name = info.name,
+                    path = self.file.display()
+                );
+            }
+            None => bail!("no pending publish to export"),
+        }
+
+        Ok(())
+    }
+}
+
+/// Cosign an exported publish.
+#[derive(Args)]
+pub struct PublishCosignCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+    /// The path of the exported publish to cosign.
+    #[clap(long, short, value_name = "FILE")]
+    pub file: PathBuf,
+}
+
+impl PublishCosignCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+
+        let mut info = read_publish_file(&self.file)?;
+        let registry_domain = client.get_warg_registry(info.name.namespace()).await?;
+        let signing_key = self.common.signing_key(registry_domain.as_ref()).await?;
+
+        info.cosign(&signing_key)?;
+        write_publish_file(&self.file, &info)?;
+
+        println!(
+            "cosigned the publish for package `{name}` with key `{key_id}`",
+            name = info.name,
+            key_id = signing_key.public_key().fingerprint()
+        );
+
+        Ok(())
+    }
+}
+
 /// Submit a pending publish.
 #[derive(Args)]
 pub struct PublishSubmitCommand {
     /// The common command options.
     #[clap(flatten)]
     pub common: CommonOptions,
+    /// The path of an exported publish to submit instead of the pending publish.
+    #[clap(long, short, value_name = "FILE")]
+    pub file: Option<PathBuf>,
     /// Whether to wait for the publish to complete.
     #[clap(long)]
     pub no_wait: bool,
@@ -644,7 +1315,12 @@ impl PublishSubmitCommand {
         let config = self.common.read_config()?;
         let client = self.common.create_client(&config).await?;
 
-        match client.registry().load_publish().await? {
+        let info = match &self.file {
+            Some(path) => Some(read_publish_file(path)?),
+            None => client.registry().load_publish().await?,
+        };
+
+        match info {
             Some(info) => {
                 println!(
                     "submitting publish for package `{name}`...",
@@ -654,7 +1330,9 @@ impl PublishSubmitCommand {
                 let signing_key = self.common.signing_key(None).await?;
                 let record_id = client.publish_with_info(&signing_key, info.clone()).await?;
 
-                client.registry().store_publish(None).await?;
+                if self.file.is_none() {
+                    client.registry().store_publish(None).await?;
+                }
 
                 if self.no_wait {
                     println!("submitted record `{record_id}` for publishing");
@@ -666,7 +1344,7 @@ impl PublishSubmitCommand {
                     for entry in &info.entries {
                         let name = &info.name;
                         match entry {
-                            PublishEntry::Init => {
+                            PublishEntry::Init { .. } => {
                                 println!("published initialization of package `{name}`");
                             }
                             PublishEntry::Release { version, .. } => {
@@ -675,6 +1353,18 @@ impl PublishSubmitCommand {
                             PublishEntry::Yank { version } => {
                                 println!("yanked version {version} of package `{name}`")
                             }
+                            PublishEntry::Unyank { version } => {
+                                println!("unyanked version {version} of package `{name}`")
+                            }
+                            PublishEntry::Metadata { .. } => {
+                                println!("set metadata of package `{name}`")
+                            }
+                            PublishEntry::Deprecate { .. } => {
+                                println!("deprecated package `{name}`")
+                            }
+                            PublishEntry::Undeprecate { .. } => {
+                                println!("undeprecated package `{name}`")
+                            }
                             PublishEntry::Grant { key, permissions } => {
                                 println!(
                                     "granted ({permissions_str}) to `{key_id}`",
@@ -689,6 +1379,37 @@ impl PublishSubmitCommand {
                                 "revoked ({permissions_str}) from `{key_id}`",
                                 permissions_str = permissions.iter().join(","),
                             ),
+                            PublishEntry::GrantVersions {
+                                key,
+                                permissions,
+                                versions,
+                            } => println!(
+                                "granted {grant} to `{key_id}`",
+                                grant = describe_grant(permissions, Some(versions), None),
+                                key_id = key.fingerprint(),
+                            ),
+                            PublishEntry::RevokeVersions {
+                                key_id,
+                                permissions,
+                                versions,
+                            } => println!(
+                                "revoked {grant} from `{key_id}`",
+                                grant = describe_grant(permissions, Some(versions), None),
+                            ),
+                            PublishEntry::GrantRole { key, role } => println!(
+                                "granted role `{role}` to `{key_id}`",
+                                key_id = key.fingerprint(),
+                            ),
+                            PublishEntry::RevokeRole { key_id, role } => {
+                                println!("revoked role `{role}` from `{key_id}`")
+                            }
+                            PublishEntry::RotateKey { key_id, key } => println!(
+                                "rotated `{key_id}` to `{new_key_id}`",
+                                new_key_id = key.fingerprint(),
+                            ),
+                            PublishEntry::SignatureThreshold { threshold } => println!(
+                                "set signature threshold of package `{name}` to {threshold}"
+                            ),
                         }
                     }
                 }
@@ -0,0 +1,48 @@
+use super::CommonOptions;
+use anyhow::Result;
+use clap::{Args, Subcommand};
+
+/// Witness the checkpoints of a warg registry.
+#[derive(Subcommand)]
+pub enum WitnessCommand {
+    /// Cosign the latest checkpoint of the registry.
+    Cosign(WitnessCosignCommand),
+}
+
+impl WitnessCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        match self {
+            Self::Cosign(cmd) => cmd.exec().await,
+        }
+    }
+}
+
+/// Cosign the latest checkpoint of the registry.
+///
+/// The checkpoint is only cosigned if it is consistent with the last
+/// checkpoint this client has seen from the registry.
+#[derive(Args)]
+pub struct WitnessCosignCommand {
+    /// The common command options.
+    #[clap(flatten)]
+    pub common: CommonOptions,
+}
+
+impl WitnessCosignCommand {
+    /// Executes the command.
+    pub async fn exec(self) -> Result<()> {
+        let config = self.common.read_config()?;
+        let client = self.common.create_client(&config).await?;
+        let signing_key = self.common.signing_key(None).await?;
+
+        let cosignatures = client.cosign_checkpoint(&signing_key).await?;
+        println!(
+            "cosigned checkpoint with key ID `{key_id}`; the checkpoint has {count} witness cosignature(s)",
+            key_id = signing_key.public_key().fingerprint(),
+            count = cosignatures.len(),
+        );
+
+        Ok(())
+    }
+}
@@ -5,6 +5,7 @@ use warg_client::{
     storage::{ContentStorage, PublishEntry, PublishInfo, RegistryStorage},
     Config, FileSystemClient, StorageLockResult,
 };
+use warg_crypto::hash::HashAlgorithm;
 use warg_protocol::registry::PackageName;
 
 pub mod support;
@@ -45,7 +46,10 @@ async fn client_incrementally_fetches() -> Result<()> {
             PublishInfo {
                 name: name.clone(),
                 head: None,
-                entries: vec![PublishEntry::Init],
+                entries: vec![PublishEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                }],
+                cosigning: None,
             },
         )
         .await?;
@@ -67,6 +71,7 @@ async fn client_incrementally_fetches() -> Result<()> {
                         version: format!("0.{i}.0").parse().unwrap(),
                         content: digest.clone(),
                     }],
+                    cosigning: None,
                 },
             )
             .await?;
@@ -1,18 +1,18 @@
-use self::support::*;
-use anyhow::{Context, Result};
+use anyhow::Result;
 use std::time::Duration;
 use warg_client::{
     storage::{
         ContentStorage, FileSystemContentStorage, FileSystemNamespaceMapStorage,
-        FileSystemRegistryStorage, PublishEntry, PublishInfo, RegistryStorage,
+        FileSystemRegistryStorage, PublishEntry, PublishInfo,
     },
     Client,
 };
-use warg_crypto::signing::PrivateKey;
+use warg_crypto::{hash::HashAlgorithm, signing::PrivateKey};
 use warg_protocol::registry::{PackageName, RecordId};
 
 pub mod support;
 
+#[allow(dead_code)]
 async fn publish_package(
     client: &Client<
         FileSystemRegistryStorage,
@@ -38,7 +38,10 @@ async fn publish_package(
             PublishInfo {
                 name: name.clone(),
                 head: None,
-                entries: vec![PublishEntry::Init],
+                entries: vec![PublishEntry::Init {
+                    hash_algorithm: HashAlgorithm::Sha256,
+                }],
+                cosigning: None,
             },
         )
         .await?;
@@ -55,6 +58,7 @@ async fn publish_package(
                     version: "1.0.0".to_string().parse().unwrap(),
                     content: add_digest.clone(),
                 }],
+                cosigning: None,
             },
         )
         .await?;
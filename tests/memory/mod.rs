@@ -1,8 +1,11 @@
 //! Tests for the in-memory storage backend.
 
 use super::{support::*, *};
-use anyhow::Result;
+use anyhow::{Context, Result};
 use warg_client::api;
+use warg_server::{
+    auth::StaticTokenStore, datastore::MemoryDataStore, policy::record::AuthorizedKeyPolicy,
+};
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn it_starts_with_initial_checkpoint() -> Result<()> {
@@ -29,6 +32,72 @@ async fn it_publishes_a_component() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_serves_metrics() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_component_publishing(&config).await?;
+    test_metrics(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_serves_metrics_on_a_separate_address() -> Result<()> {
+    let (server, config) = spawn_server_with_metrics_addr(&root().await?).await?;
+
+    // The metrics are not served with the API
+    let url = Url::parse(config.home_url.as_ref().unwrap())?;
+    let response = reqwest::get(url.join("metrics")?).await?;
+    assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+    let addr = server.metrics_addr.context("expected a metrics address")?;
+    let response = reqwest::get(format!("http://{addr}/metrics")).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response.text().await?.contains("warg_log_length"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_reports_health_and_readiness() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_health(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_is_not_ready_with_a_stale_checkpoint() -> Result<()> {
+    // Only the initial checkpoint is stored, so it is stale once the
+    // second it was timestamped in has passed
+    let (_server, config) =
+        spawn_server_with_checkpoint_staleness(&root().await?, Duration::from_secs(3600), 0)
+            .await?;
+    tokio::time::sleep(Duration::from_millis(2100)).await;
+
+    let url = Url::parse(config.home_url.as_ref().unwrap())?;
+    let response = reqwest::get(url.join("healthz")?).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = reqwest::get(url.join("readyz")?).await?;
+    assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let message = response.text().await?;
+    assert!(
+        message.contains("exceeds the maximum of 0 second(s)"),
+        "unexpected message: {message}"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_publishes_a_sha512_package() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_hash_algorithm_package_publishing(&config, HashAlgorithm::Sha512).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_publishes_a_blake3_package() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_hash_algorithm_package_publishing(&config, HashAlgorithm::Blake3).await
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn it_yanks_a_package() -> Result<()> {
     let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
@@ -46,6 +115,91 @@ async fn it_yanks_a_package() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_unyanks_a_package() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_package_unyanking(&config).await?;
+
+    // There should be four entries in the registry
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ts_checkpoint = client.latest_checkpoint(None).await?;
+    assert_eq!(
+        ts_checkpoint.as_ref().checkpoint.log_length,
+        4,
+        "expected four log entries (initial + release + yank + unyank)"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_undeprecates_a_package() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_package_undeprecation(&config).await?;
+
+    // There should be four entries in the registry
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ts_checkpoint = client.latest_checkpoint(None).await?;
+    assert_eq!(
+        ts_checkpoint.as_ref().checkpoint.log_length,
+        4,
+        "expected four log entries (initial + release + deprecation + undeprecation)"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_sets_package_metadata() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_package_metadata(&config).await?;
+
+    // There should be four entries in the registry
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ts_checkpoint = client.latest_checkpoint(None).await?;
+    assert_eq!(
+        ts_checkpoint.as_ref().checkpoint.log_length,
+        4,
+        "expected four log entries (initial + release + two metadata)"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_deprecates_a_package() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_package_deprecation(&config).await?;
+
+    // There should be three entries in the registry
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ts_checkpoint = client.latest_checkpoint(None).await?;
+    assert_eq!(
+        ts_checkpoint.as_ref().checkpoint.log_length,
+        3,
+        "expected three log entries (initial + release + deprecation)"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_rotates_a_package_key() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_key_rotation(&config).await?;
+
+    // There should be four entries in the registry
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ts_checkpoint = client.latest_checkpoint(None).await?;
+    assert_eq!(
+        ts_checkpoint.as_ref().checkpoint.log_length,
+        4,
+        "expected four log entries (initial + release + rotation + release)"
+    );
+
+    Ok(())
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn it_publishes_a_wit_package() -> Result<()> {
     let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
@@ -63,6 +217,47 @@ async fn it_publishes_a_wit_package() -> Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_defines_a_namespace() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_operator_define_namespace(&config).await?;
+
+    // There should be three log entries in the registry
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ts_checkpoint = client.latest_checkpoint(None).await?;
+    assert_eq!(
+        ts_checkpoint.as_ref().checkpoint.log_length,
+        3,
+        "expected three log entries (initial + operator + component)"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_proves_package_absence() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_package_absence(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_proves_map_inclusion_in_both_formats() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_map_inclusion_formats(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_witnesses_checkpoints() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_checkpoint_witnessing(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_serves_log_tiles() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_log_tiles(&config).await
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn it_rejects_non_wasm_content() -> Result<()> {
     let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
@@ -85,6 +280,29 @@ async fn it_rejects_unauthorized_signing_key() -> Result<()> {
     test_unauthorized_signing_key(&config).await
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_requires_an_auth_token_to_publish() -> Result<()> {
+    let key = test_signing_key().public_key().fingerprint();
+    let token_store = StaticTokenStore::new()
+        .with_token("alice", "alice-token")?
+        .with_key("alice", key.clone())
+        .with_token("bob", "bob-token")?
+        .with_token("carol", "carol-token")?
+        .with_key("carol", key.clone());
+    let policy = AuthorizedKeyPolicy::new()
+        .with_namespace_key("test", key)?
+        .with_namespace_identity("test", "alice")?;
+    let (_server, config) =
+        spawn_server_with_token_store(&root().await?, token_store, policy).await?;
+    test_auth_token_publishing(&config).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_enforces_publish_limits() -> Result<()> {
+    let (_server, config) = spawn_server_with_limits(&root().await?, None, test_limits()).await?;
+    test_publish_limits(&config).await
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn it_rejects_unknown_signing_key() -> Result<()> {
     let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
@@ -114,3 +332,90 @@ async fn it_get_ledger() -> Result<()> {
     let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
     test_get_ledger(&config).await
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_limits_permissions_to_versions() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_version_permissions(&config).await?;
+
+    // There should be four entries in the registry
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ts_checkpoint = client.latest_checkpoint(None).await?;
+    assert_eq!(
+        ts_checkpoint.as_ref().checkpoint.log_length,
+        4,
+        "expected four log entries (initial + release + grant + release)"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_requires_a_signature_threshold() -> Result<()> {
+    let (_server, config) = spawn_server(&root().await?, None, None, None).await?;
+    test_threshold_signing(&config).await?;
+
+    // There should be four entries in the registry
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ts_checkpoint = client.latest_checkpoint(None).await?;
+    assert_eq!(
+        ts_checkpoint.as_ref().checkpoint.log_length,
+        4,
+        "expected four log entries (initial + release + threshold + cosigned release)"
+    );
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_resubmits_queued_records_on_startup() -> Result<()> {
+    let store = MemoryDataStore::new();
+    let name = PackageName::new("test:queued")?;
+    let record_id = store_queued_record(&store, &name).await?;
+
+    let (_server, config) = spawn_server(&root().await?, None, Some(Box::new(store)), None).await?;
+    test_queued_record_resubmission(&config, &name, &record_id).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_restores_from_a_journal() -> Result<()> {
+    let root = root().await?;
+    let journal = root.join("journal");
+    let store = MemoryDataStore::with_journal(&journal).await?;
+    let (server, config) = spawn_server(&root, None, Some(Box::new(store)), None).await?;
+    test_component_publishing(&config).await?;
+    test_package_yanking(&config).await?;
+    drop(server);
+
+    // Store a record that was accepted but never submitted before the restart
+    let store = MemoryDataStore::with_journal(&journal).await?;
+    let queued = PackageName::new("test:queued")?;
+    let record_id = store_queued_record(&store, &queued).await?;
+
+    // Restart the server from the journal and ensure the data is still there
+    let (_server, config) = spawn_server(&root, None, Some(Box::new(store)), None).await?;
+    test_queued_record_resubmission(&config, &queued, &record_id).await?;
+    test_package_deprecation(&config).await?;
+
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ts_checkpoint = client.latest_checkpoint(None).await?;
+    assert_eq!(
+        ts_checkpoint.as_ref().checkpoint.log_length,
+        7,
+        "expected seven log entries (initial + component + release + yank + queued + release + deprecation)"
+    );
+
+    // Delete the client cache to force a complete download of the packages
+    fs::remove_dir_all(root.join("content"))?;
+    fs::remove_dir_all(root.join("registries"))?;
+
+    let client = create_client(&config).await?;
+    for package in ["test:component", "test:deprecated"] {
+        client
+            .download(&PackageName::new(package)?, &"0.1.0".parse()?)
+            .await?
+            .context("failed to resolve package")?;
+    }
+
+    Ok(())
+}
@@ -45,20 +45,33 @@ async fn it_works_with_postgres() -> TestResult {
     // This should be the same set of tests as in `tests/memory/mod.rs`
     test_initial_checkpoint(&config).await?;
     test_component_publishing(&config).await?;
+    test_hash_algorithm_package_publishing(&config, HashAlgorithm::Sha512).await?;
+    test_hash_algorithm_package_publishing(&config, HashAlgorithm::Blake3).await?;
     test_package_yanking(&config).await?;
+    test_package_unyanking(&config).await?;
+    test_package_deprecation(&config).await?;
     test_wit_publishing(&config).await?;
     test_wasm_content_policy(&config).await?;
     test_unauthorized_signing_key(&config).await?;
-    // This is tested below where a different server is used that
+    // These are tested below where a different server is used that
     // allows any signing key
     //test_unknown_signing_key(&config).await?;
+    //test_key_rotation(&config).await?;
+    //test_threshold_signing(&config).await?;
+    //test_version_permissions(&config).await?;
     test_invalid_signature(&config).await?;
     test_fetch_package_names(&config).await?;
+    test_metrics(&config).await?;
+    test_health(&config).await?;
     test_get_ledger(&config).await?;
 
     let mut packages = vec![
         PackageName::new("test:component")?,
+        PackageName::new("test:sha512")?,
+        PackageName::new("test:blake3")?,
         PackageName::new("test:yankee")?,
+        PackageName::new("test:unyankee")?,
+        PackageName::new("test:deprecated")?,
         PackageName::new("test:wit-package")?,
         PackageName::new("test:unauthorized-key")?,
     ];
@@ -68,29 +81,46 @@ async fn it_works_with_postgres() -> TestResult {
     let ts_checkpoint = client.latest_checkpoint(None).await?;
     assert_eq!(
         ts_checkpoint.as_ref().checkpoint.log_length,
-        packages.len() as RegistryLen + 2, /* publishes + initial checkpoint + yank */
-        "expected {len} packages plus the initial checkpoint and yank",
+        packages.len() as RegistryLen + 5, /* publishes + initial checkpoint + two yanks + unyank + deprecation */
+        "expected {len} packages plus the initial checkpoint, two yanks, unyank and deprecation",
         len = packages.len()
     );
 
     drop(server);
 
+    // Store a record that was accepted but never submitted before the restart
+    let queued = PackageName::new("test:queued")?;
+    let record_id = store_queued_record(data_store()?.as_ref(), &queued).await?;
+
     // Restart the server and ensure the data is still there
     let (server, config) = spawn_server(&root, None, Some(data_store()?), None).await?;
 
+    test_queued_record_resubmission(&config, &queued, &record_id).await?;
+
     test_unknown_signing_key(&config).await?;
+    test_key_rotation(&config).await?;
+    test_threshold_signing(&config).await?;
+    test_version_permissions(&config).await?;
+    test_operator_define_namespace(&config).await?;
 
     packages.push(PackageName::new("test:unknown-key")?);
+    packages.push(PackageName::new("test:rotated-key")?);
+    packages.push(PackageName::new("test:threshold")?);
+    packages.push(PackageName::new("test:version-permissions")?);
+    packages.push(PackageName::new("other:component")?);
 
     let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
     let ts_checkpoint = client.latest_checkpoint(None).await?;
     assert_eq!(
         ts_checkpoint.as_ref().checkpoint.log_length,
-        packages.len() as RegistryLen + 2, /* publishes + initial checkpoint + yank*/
-        "expected {len} packages plus the initial checkpoint and yank",
+        packages.len() as RegistryLen + 13, /* publishes + initial checkpoint + two yanks + unyank + deprecation + queued + rotation + release + threshold + cosigned release + grant + release + operator */
+        "expected {len} packages plus the initial checkpoint, two yanks, unyank, deprecation, queued record, key rotation, release, signature threshold, cosigned release, version grant, release and operator record",
         len = packages.len()
     );
 
+    test_checkpoint_witnessing(&config).await?;
+    test_log_tiles(&config).await?;
+
     // Delete the client cache to force a complete download of all packages below
     fs::remove_dir_all(root.join("content"))?;
     fs::remove_dir_all(root.join("registries"))?;
@@ -112,7 +142,7 @@ async fn it_works_with_postgres() -> TestResult {
     // Restart the server for the custom content URL test
     drop(client);
     drop(server);
-    let (_server, config) = spawn_server(
+    let (server, config) = spawn_server(
         &root,
         Some("https://example.com".parse().unwrap()),
         Some(data_store()?),
@@ -122,5 +152,11 @@ async fn it_works_with_postgres() -> TestResult {
 
     test_custom_content_url(&config).await?;
 
+    // Restart the server with publishing limits
+    drop(server);
+    let (_server, config) =
+        spawn_server_with_limits(&root, Some(data_store()?), test_limits()).await?;
+    test_publish_limits(&config).await?;
+
     Ok(())
 }
@@ -0,0 +1,76 @@
+//! Tests for the S3-compatible content store.
+
+use super::{support::*, *};
+use anyhow::{bail, Context, Result};
+use std::time::UNIX_EPOCH;
+use warg_server::contentstore::S3ContentStore;
+
+fn var(name: &str) -> Result<String> {
+    std::env::var(name).with_context(|| format!("failed to get `{name}` environment variable"))
+}
+
+/// Creates a content store for the bucket given by the `WARG_S3_*`
+/// environment variables.
+///
+/// Each store uses its own key prefix so that tests don't share content.
+fn content_store() -> Result<S3ContentStore> {
+    Ok(S3ContentStore::new(
+        var("WARG_S3_ENDPOINT")?.parse()?,
+        var("WARG_S3_BUCKET")?,
+        var("WARG_S3_ACCESS_KEY_ID")?,
+        var("WARG_S3_SECRET_ACCESS_KEY")?.into(),
+    )
+    .with_key_prefix(format!(
+        "test-{time}/",
+        time = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos()
+    )))
+}
+
+/// This test assumes the bucket exists.
+/// Use the `ci/run-s3-tests.sh` script to run this test against MinIO.
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_works_with_s3() -> Result<()> {
+    let root = root().await?;
+    let (_server, config) = spawn_server_with_content_store(&root, None, content_store()?).await?;
+
+    test_component_publishing(&config).await?;
+    test_wit_publishing(&config).await?;
+    test_wasm_content_policy(&config).await?;
+
+    let name = PackageName::new("test:component")?;
+    let version = Version::parse("0.1.0")?;
+    let client = create_client(&config).await?;
+    let digest = client
+        .package(&name)
+        .await?
+        .state
+        .release(&version)
+        .and_then(|release| release.content())
+        .cloned()
+        .context("expected the package release to have content")?;
+
+    // The content should be sourced from a presigned URL of the bucket
+    let api = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ContentSourcesResponse { content_sources } = api.content_sources(None, &digest).await?;
+    match content_sources.get(&digest).map(Vec::as_slice) {
+        Some([ContentSource::HttpGet { url, size, .. }]) => {
+            assert!(url.starts_with(&var("WARG_S3_ENDPOINT")?));
+            assert!(url.contains("X-Amz-Signature="));
+            assert!(size.is_some());
+        }
+        sources => bail!("unexpected content sources: {sources:?}"),
+    }
+
+    // Delete the client cache to force a download from the bucket
+    fs::remove_dir_all(root.join("content"))?;
+    fs::remove_dir_all(root.join("registries"))?;
+
+    let client = create_client(&config).await?;
+    client.fetch_packages([&name]).await?;
+    client
+        .download(&name, &"0.1.0".parse()?)
+        .await?
+        .context("failed to resolve package")?;
+
+    Ok(())
+}
@@ -14,22 +14,29 @@ use warg_api::v1::{
     ledger::{LedgerSource, LedgerSourceContentType, LedgerSourcesResponse},
     package::PublishRecordRequest,
     paths,
+    proof::{InclusionRequest, MapProofFormat, ProofError},
+    witness::{CosignCheckpointRequest, WitnessError},
 };
 use warg_client::{
     api,
-    storage::{PublishEntry, PublishInfo},
+    storage::{ContentStorage, PublishEntry, PublishInfo, RegistryStorage},
     ClientError, Config,
 };
 use warg_crypto::{
-    hash::{HashAlgorithm, Sha256},
-    signing::PrivateKey,
+    hash::{Hash, HashAlgorithm, Sha256},
+    signing::{note::Note, PrivateKey},
     Encode, Signable,
 };
 use warg_protocol::{
-    package::{PackageEntry, PackageRecord, PACKAGE_RECORD_VERSION},
-    registry::{LogId, PackageName},
+    operator::OperatorEntry,
+    package::{
+        PackageDeprecation, PackageEntry, PackageMetadata, PackageRecord, Permission,
+        PACKAGE_RECORD_VERSION,
+    },
+    registry::{Checkpoint, LogId, LogLeaf, PackageName, RecordId},
     ProtoEnvelope, ProtoEnvelopeBody, Version,
 };
+use warg_server::{datastore::DataStore, limits::Limits};
 use wit_component::DecodedWasm;
 
 mod support;
@@ -37,6 +44,10 @@ mod support;
 mod memory;
 #[cfg(feature = "postgres")]
 mod postgres;
+#[cfg(feature = "s3")]
+mod s3;
+#[cfg(feature = "sqlite")]
+mod sqlite;
 
 async fn test_initial_checkpoint(config: &Config) -> Result<()> {
     let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
@@ -63,6 +74,19 @@ async fn test_initial_checkpoint(config: &Config) -> Result<()> {
         ts_checkpoint.signature(),
     )?;
 
+    // Ensure the checkpoint is also published as a signed note
+    let url = Url::parse(config.home_url.as_ref().unwrap())?;
+    let response = reqwest::get(url.join(paths::fetch_checkpoint_note())?).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let note: Note = response.text().await?.parse()?;
+
+    let origin = format!("{}:{}", url.host_str().unwrap(), url.port().unwrap());
+    note.verify(&origin, &operator_key.public_key())?;
+    assert_eq!(
+        Checkpoint::from_note_text(note.text())?,
+        (origin, checkpoint.clone())
+    );
+
     Ok(())
 }
 
@@ -112,6 +136,74 @@ async fn test_component_publishing(config: &Config) -> Result<()> {
     Ok(())
 }
 
+async fn test_hash_algorithm_package_publishing(
+    config: &Config,
+    algorithm: HashAlgorithm,
+) -> Result<()> {
+    const PACKAGE_VERSION: &str = "0.1.0";
+
+    let name = PackageName::new(format!("test:{algorithm}"))?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+
+    // Content is stored locally with the default algorithm and is rehashed
+    // by the client to match the package log's algorithm when publishing
+    let content = wat::parse_str("(component)")?;
+    let digest = client
+        .content()
+        .store_content(
+            Box::pin(futures::stream::once({
+                let content = content.clone();
+                async move { Ok(content.into()) }
+            })),
+            None,
+        )
+        .await?;
+    assert_eq!(digest.algorithm(), HashAlgorithm::Sha256);
+
+    let record_id = client
+        .publish_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![
+                    PublishEntry::Init {
+                        hash_algorithm: algorithm,
+                    },
+                    PublishEntry::Release {
+                        version: PACKAGE_VERSION.parse()?,
+                        content: digest,
+                    },
+                ],
+                cosigning: None,
+            },
+        )
+        .await?;
+    assert_eq!(record_id.algorithm(), algorithm);
+
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    let download = client
+        .download(&name, &PACKAGE_VERSION.parse()?)
+        .await?
+        .context("failed to resolve package")?;
+
+    assert_eq!(download.digest, algorithm.digest(&content));
+    assert_eq!(fs::read(&download.path)?, content);
+
+    let info = client.package(&name).await?;
+    assert_eq!(info.state.algorithm(), Some(algorithm));
+    assert_eq!(
+        info.state.head().as_ref().map(|h| &h.digest),
+        Some(&record_id)
+    );
+
+    Ok(())
+}
+
 async fn test_package_yanking(config: &Config) -> Result<()> {
     const PACKAGE_NAME: &str = "test:yankee";
     const PACKAGE_VERSION: &str = "0.1.0";
@@ -140,6 +232,48 @@ async fn test_package_yanking(config: &Config) -> Result<()> {
                 entries: vec![PublishEntry::Yank {
                     version: PACKAGE_VERSION.parse()?,
                 }],
+                cosigning: None,
+            },
+        )
+        .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    let opt = client.download(&name, &PACKAGE_VERSION.parse()?).await?;
+    assert!(opt.is_none(), "expected no download, got {opt:?}");
+    Ok(())
+}
+
+async fn test_package_unyanking(config: &Config) -> Result<()> {
+    const PACKAGE_NAME: &str = "test:unyankee";
+    const PACKAGE_VERSION: &str = "0.1.0";
+
+    // Publish release
+    let name = PackageName::new(PACKAGE_NAME)?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish(
+        &client,
+        &name,
+        PACKAGE_VERSION,
+        wat::parse_str("(component)")?,
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    // Yank release
+    let record_id = client
+        .publish_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![PublishEntry::Yank {
+                    version: PACKAGE_VERSION.parse()?,
+                }],
+                cosigning: None,
             },
         )
         .await?;
@@ -149,6 +283,223 @@ async fn test_package_yanking(config: &Config) -> Result<()> {
 
     let opt = client.download(&name, &PACKAGE_VERSION.parse()?).await?;
     assert!(opt.is_none(), "expected no download, got {opt:?}");
+
+    // Unyank release
+    let record_id = client
+        .publish_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![PublishEntry::Unyank {
+                    version: PACKAGE_VERSION.parse()?,
+                }],
+                cosigning: None,
+            },
+        )
+        .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    let download = client
+        .download(&name, &PACKAGE_VERSION.parse()?)
+        .await?
+        .context("expected the unyanked release to be downloadable")?;
+    assert_eq!(download.version, PACKAGE_VERSION.parse()?);
+    Ok(())
+}
+
+async fn test_package_deprecation(config: &Config) -> Result<()> {
+    const PACKAGE_NAME: &str = "test:deprecated";
+    const PACKAGE_VERSION: &str = "0.1.0";
+
+    // Publish release
+    let name = PackageName::new(PACKAGE_NAME)?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish(
+        &client,
+        &name,
+        PACKAGE_VERSION,
+        wat::parse_str("(component)")?,
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    // Deprecate the package
+    let record_id = client
+        .publish_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![PublishEntry::Deprecate {
+                    deprecation: PackageDeprecation {
+                        versions: None,
+                        message: "this package is no longer maintained".to_string(),
+                        successor: Some(PackageName::new("test:component")?),
+                    },
+                }],
+                cosigning: None,
+            },
+        )
+        .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    // Deprecated releases can still be downloaded
+    let download = client
+        .download(&name, &PACKAGE_VERSION.parse()?)
+        .await?
+        .context("expected the deprecated release to be downloadable")?;
+    let deprecation = download
+        .deprecation
+        .context("expected the download to be deprecated")?;
+    assert_eq!(deprecation.message, "this package is no longer maintained");
+    assert_eq!(
+        deprecation.successor,
+        Some(PackageName::new("test:component")?)
+    );
+    Ok(())
+}
+
+async fn test_package_undeprecation(config: &Config) -> Result<()> {
+    const PACKAGE_NAME: &str = "test:undeprecated";
+    const PACKAGE_VERSION: &str = "0.1.0";
+
+    // Publish release
+    let name = PackageName::new(PACKAGE_NAME)?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish(
+        &client,
+        &name,
+        PACKAGE_VERSION,
+        wat::parse_str("(component)")?,
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    // Deprecate and then undeprecate the package
+    for entry in [
+        PublishEntry::Deprecate {
+            deprecation: PackageDeprecation {
+                versions: None,
+                message: "this package is no longer maintained".to_string(),
+                successor: None,
+            },
+        },
+        PublishEntry::Undeprecate { versions: None },
+    ] {
+        let record_id = client
+            .publish_with_info(
+                &signing_key,
+                PublishInfo {
+                    name: name.clone(),
+                    head: None,
+                    entries: vec![entry],
+                    cosigning: None,
+                },
+            )
+            .await?;
+        client
+            .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+            .await?;
+    }
+
+    // The release is no longer deprecated
+    let download = client
+        .download(&name, &PACKAGE_VERSION.parse()?)
+        .await?
+        .context("expected the undeprecated release to be downloadable")?;
+    assert!(download.deprecation.is_none());
+    Ok(())
+}
+
+async fn test_package_metadata(config: &Config) -> Result<()> {
+    const PACKAGE_NAME: &str = "test:metadata";
+    const PACKAGE_VERSION: &str = "0.1.0";
+
+    // Publish release
+    let name = PackageName::new(PACKAGE_NAME)?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish(
+        &client,
+        &name,
+        PACKAGE_VERSION,
+        wat::parse_str("(component)")?,
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    let publish_metadata = |metadata: PackageMetadata| {
+        let client = &client;
+        let name = &name;
+        let signing_key = &signing_key;
+        async move {
+            let record_id = client
+                .publish_with_info(
+                    signing_key,
+                    PublishInfo {
+                        name: name.clone(),
+                        head: None,
+                        entries: vec![PublishEntry::Metadata { metadata }],
+                        cosigning: None,
+                    },
+                )
+                .await?;
+            client
+                .wait_for_publish(name, &record_id, Duration::from_millis(100))
+                .await?;
+            client.update().await?;
+            Ok::<_, anyhow::Error>(client.package(name).await?.state.metadata().cloned())
+        }
+    };
+
+    // Set the metadata of the package
+    let metadata = PackageMetadata {
+        description: Some("a test package".to_string()),
+        license: Some("Apache-2.0 WITH LLVM-exception".to_string()),
+        repository: Some("https://github.com/bytecodealliance/registry".to_string()),
+        keywords: vec!["test".to_string()],
+    };
+    assert_eq!(
+        publish_metadata(metadata.clone()).await?.as_ref(),
+        Some(&metadata)
+    );
+
+    // Clear the license and repository of the package
+    let metadata = PackageMetadata {
+        license: None,
+        repository: None,
+        ..metadata
+    };
+    assert_eq!(
+        publish_metadata(metadata.clone()).await?.as_ref(),
+        Some(&metadata)
+    );
+
+    // An invalid license is rejected
+    let message = format!(
+        "{:#}",
+        publish_metadata(PackageMetadata {
+            license: Some("Not-A-License".to_string()),
+            ..metadata
+        })
+        .await
+        .expect_err("expected publish to fail")
+    );
+    assert!(
+        message.contains("is not a valid SPDX expression"),
+        "unexpected error message: {message}"
+    );
+
     Ok(())
 }
 
@@ -257,15 +608,17 @@ async fn test_wasm_content_policy(config: &Config) -> Result<()> {
     Ok(())
 }
 
-async fn test_unauthorized_signing_key(config: &Config) -> Result<()> {
-    const PACKAGE_NAME: &str = "test:unauthorized-key";
+async fn test_operator_define_namespace(config: &Config) -> Result<()> {
+    const NAMESPACE: &str = "other";
+    const PACKAGE_NAME: &str = "other:component";
     const PACKAGE_VERSION: &str = "0.1.0";
 
-    // Start by publishing a new component package
     let name = PackageName::new(PACKAGE_NAME)?;
     let client = create_client(config).await?;
     let signing_key = test_signing_key();
-    publish_component(
+
+    // The namespace is not yet defined by the operator
+    match publish_component(
         &client,
         &name,
         PACKAGE_VERSION,
@@ -273,28 +626,82 @@ async fn test_unauthorized_signing_key(config: &Config) -> Result<()> {
         true,
         &signing_key,
     )
-    .await?;
+    .await
+    .expect_err("expected publish to fail")
+    .downcast::<ClientError>()
+    {
+        Ok(ClientError::Api(api::ClientError::Package(
+            warg_api::v1::package::PackageError::NamespaceNotDefined(namespace),
+        ))) => assert_eq!(namespace, NAMESPACE),
+        res => panic!("unexpected result: {res:?}"),
+    }
 
-    // Next, we're going to publish a new record signed by a different key
-    let signing_key = PrivateKey::from(p256::ecdsa::SigningKey::random(&mut OsRng));
+    // A key that is not known to the operator log cannot define a namespace
+    match client
+        .publish_operator_record(
+            &signing_key,
+            vec![OperatorEntry::DefineNamespace {
+                namespace: NAMESPACE.to_string(),
+            }],
+        )
+        .await
+    {
+        Err(ClientError::Unauthorized(message)) => assert!(
+            message.contains("unknown key id"),
+            "unexpected error message: {message}"
+        ),
+        res => panic!("unexpected result: {res:?}"),
+    }
 
-    let message = format!(
-        "{:#}",
-        publish_component(&client, &name, "0.2.0", "(component)", false, &signing_key,)
-            .await
-            .expect_err("expected publish to fail")
-    );
+    // Define the namespace with the operator key
+    let record_id = client
+        .publish_operator_record(
+            &test_operator_key(),
+            vec![OperatorEntry::DefineNamespace {
+                namespace: NAMESPACE.to_string(),
+            }],
+        )
+        .await?;
+    client
+        .wait_for_operator_publish(&record_id, Duration::from_millis(100))
+        .await?;
 
-    assert!(
-        message.contains("not authorized to publish to package `test:unauthorized-key`"),
-        "unexpected error message: {message}"
-    );
+    // Defining the same namespace again is rejected by the operator log validation
+    let record_id = client
+        .publish_operator_record(
+            &test_operator_key(),
+            vec![OperatorEntry::DefineNamespace {
+                namespace: NAMESPACE.to_string(),
+            }],
+        )
+        .await?;
+    match client
+        .wait_for_operator_publish(&record_id, Duration::from_millis(100))
+        .await
+    {
+        Err(ClientError::OperatorPublishRejected { reason, .. }) => assert!(
+            reason.contains("already defined"),
+            "unexpected rejection reason: {reason}"
+        ),
+        res => panic!("unexpected result: {res:?}"),
+    }
+
+    // Packages can now be published to the namespace
+    publish_component(
+        &client,
+        &name,
+        PACKAGE_VERSION,
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await?;
 
     Ok(())
 }
 
-async fn test_unknown_signing_key(config: &Config) -> Result<()> {
-    const PACKAGE_NAME: &str = "test:unknown-key";
+async fn test_unauthorized_signing_key(config: &Config) -> Result<()> {
+    const PACKAGE_NAME: &str = "test:unauthorized-key";
     const PACKAGE_VERSION: &str = "0.1.0";
 
     // Start by publishing a new component package
@@ -312,7 +719,6 @@ async fn test_unknown_signing_key(config: &Config) -> Result<()> {
     .await?;
 
     // Next, we're going to publish a new record signed by a different key
-    // The new key is not currently known to the package log.
     let signing_key = PrivateKey::from(p256::ecdsa::SigningKey::random(&mut OsRng));
 
     let message = format!(
@@ -323,15 +729,484 @@ async fn test_unknown_signing_key(config: &Config) -> Result<()> {
     );
 
     assert!(
-        message.contains("unknown key id"),
+        message.contains("not authorized to publish to package `test:unauthorized-key`"),
         "unexpected error message: {message}"
     );
 
     Ok(())
 }
 
-async fn test_invalid_signature(config: &Config) -> Result<()> {
-    const PACKAGE_NAME: &str = "test:invalid-signature";
+async fn test_auth_token_publishing(config: &Config) -> Result<()> {
+    const PACKAGE_NAME: &str = "test:auth-token";
+    const PACKAGE_VERSION: &str = "0.1.0";
+
+    // Publishing without a token, with an unknown token, with the token of an
+    // identity that may not sign with the key, or with the token of an
+    // identity not authorized for the namespace should be rejected
+    let name = PackageName::new(PACKAGE_NAME)?;
+    let signing_key = test_signing_key();
+    for (token, expected) in [
+        (None, "an authentication token is required to publish"),
+        (
+            Some("unknown-token"),
+            "the authentication token is not valid",
+        ),
+        (
+            Some("bob-token"),
+            "identity `bob` is not authorized to publish",
+        ),
+        (
+            Some("carol-token"),
+            "identity `carol` is not authorized to publish to package",
+        ),
+    ] {
+        let client = create_client_with_auth_token(config, token).await?;
+        let message = format!(
+            "{:#}",
+            publish_component(
+                &client,
+                &name,
+                PACKAGE_VERSION,
+                "(component)",
+                true,
+                &signing_key,
+            )
+            .await
+            .expect_err("expected publish to fail")
+        );
+
+        assert!(
+            message.contains(expected),
+            "unexpected error message: {message}"
+        );
+    }
+
+    // Publishing with the token of the identity that owns the key succeeds,
+    // which includes uploading the content with the token
+    let client = create_client_with_auth_token(config, Some("alice-token")).await?;
+    let digest = publish_component(
+        &client,
+        &name,
+        PACKAGE_VERSION,
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    // Uploading content without a token should be rejected
+    let log_id = LogId::package_log::<Sha256>(&name);
+    let record_id = client
+        .registry()
+        .load_package(None, &name)
+        .await?
+        .context("package should exist")?
+        .state
+        .head()
+        .as_ref()
+        .context("package should have a head")?
+        .digest
+        .clone();
+    let url = Url::parse(config.home_url.as_ref().unwrap())?.join(&format!(
+        "{path}/content/{digest}",
+        path = paths::package_record(&log_id, &record_id)
+    ))?;
+    let response = reqwest::Client::new()
+        .post(url)
+        .body("(component)")
+        .send()
+        .await?;
+    assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+
+    Ok(())
+}
+
+/// The limits expected by `test_publish_limits`.
+fn test_limits() -> Limits {
+    Limits::new()
+        .with_publish_rate(4, Duration::from_secs(3600))
+        .with_max_content_size(100)
+        .with_namespace_quota(150)
+}
+
+async fn test_publish_limits(config: &Config) -> Result<()> {
+    // Creates a component of roughly the given size
+    fn component(size: usize, fill: char) -> String {
+        let data = fill.to_string().repeat(size.saturating_sub(35));
+        format!(r#"(component (core module (memory 1) (data (i32.const 0) "{data}")))"#)
+    }
+
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+
+    // Use a namespace of its own so that earlier publishes don't count
+    // against its storage quota
+    let record_id = client
+        .publish_operator_record(
+            &test_operator_key(),
+            vec![OperatorEntry::DefineNamespace {
+                namespace: "limits".to_string(),
+            }],
+        )
+        .await?;
+    client
+        .wait_for_operator_publish(&record_id, Duration::from_millis(100))
+        .await?;
+
+    let publish = |name: &'static str, wat: String| {
+        let client = &client;
+        let signing_key = &signing_key;
+        async move {
+            publish_component(
+                client,
+                &PackageName::new(name)?,
+                "0.1.0",
+                &wat,
+                true,
+                signing_key,
+            )
+            .await
+        }
+    };
+
+    publish("limits:small", "(component)".to_string()).await?;
+
+    // Content larger than the maximum content size is rejected
+    let message = format!(
+        "{:#}",
+        publish("limits:large", component(200, 'a'))
+            .await
+            .expect_err("expected publish to fail")
+    );
+    assert!(
+        message.contains("content exceeds the maximum size of 100 bytes"),
+        "unexpected error message: {message}"
+    );
+
+    // Content that exceeds the namespace's storage quota is rejected
+    publish("limits:medium", component(90, 'a')).await?;
+    let message = format!(
+        "{:#}",
+        publish("limits:quota", component(90, 'b'))
+            .await
+            .expect_err("expected publish to fail")
+    );
+    assert!(
+        message.contains("namespace `limits` has exceeded its storage quota"),
+        "unexpected error message: {message}"
+    );
+
+    // The key has now published four records, which is all it may publish
+    let message = format!(
+        "{:#}",
+        publish("limits:rate", "(component)".to_string())
+            .await
+            .expect_err("expected publish to fail")
+    );
+    assert!(
+        message.contains("may publish at most 4 record(s) every 3600 second(s)"),
+        "unexpected error message: {message}"
+    );
+
+    Ok(())
+}
+
+/// Expects a component to have been published to the server so that there is
+/// a request, content and a published record to measure.
+async fn test_metrics(config: &Config) -> Result<()> {
+    // Gets the value of the sample with the given name and labels
+    fn sample(metrics: &str, name: &str) -> Option<f64> {
+        metrics
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(' '))
+            .and_then(|value| value.parse().ok())
+    }
+
+    let url = Url::parse(config.home_url.as_ref().unwrap())?.join("metrics")?;
+    let response = reqwest::get(url).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert!(response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/openmetrics-text")));
+    let metrics = response.text().await?;
+
+    for name in [
+        r#"warg_http_requests_total{method="POST",route="/v1/package/:log_id/record",status="202"}"#,
+        r#"warg_http_request_duration_seconds_count{method="POST",route="/v1/package/:log_id/record"}"#,
+        r#"warg_records{status="published"}"#,
+        "warg_checkpoint_duration_seconds_count",
+        "warg_checkpoint_last_stored_timestamp_seconds",
+        "warg_log_length",
+        "warg_content_stored_bytes_total",
+        r#"warg_datastore_operation_duration_seconds_count{operation="store_package_record"}"#,
+    ] {
+        let value = sample(&metrics, name)
+            .with_context(|| format!("metric `{name}` is missing from:\n{metrics}"))?;
+        assert!(value > 0.0, "expected metric `{name}` to be positive");
+    }
+
+    Ok(())
+}
+
+async fn test_health(config: &Config) -> Result<()> {
+    let url = Url::parse(config.home_url.as_ref().unwrap())?;
+
+    let response = reqwest::get(url.join("healthz")?).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+
+    let response = reqwest::get(url.join("readyz")?).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(response.text().await?, "ok");
+
+    Ok(())
+}
+
+async fn test_unknown_signing_key(config: &Config) -> Result<()> {
+    const PACKAGE_NAME: &str = "test:unknown-key";
+    const PACKAGE_VERSION: &str = "0.1.0";
+
+    // Start by publishing a new component package
+    let name = PackageName::new(PACKAGE_NAME)?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish_component(
+        &client,
+        &name,
+        PACKAGE_VERSION,
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    // Next, we're going to publish a new record signed by a different key
+    // The new key is not currently known to the package log.
+    let signing_key = PrivateKey::from(p256::ecdsa::SigningKey::random(&mut OsRng));
+
+    let message = format!(
+        "{:#}",
+        publish_component(&client, &name, "0.2.0", "(component)", false, &signing_key,)
+            .await
+            .expect_err("expected publish to fail")
+    );
+
+    assert!(
+        message.contains("unknown key id"),
+        "unexpected error message: {message}"
+    );
+
+    Ok(())
+}
+
+async fn test_key_rotation(config: &Config) -> Result<()> {
+    const PACKAGE_NAME: &str = "test:rotated-key";
+    const PACKAGE_VERSION: &str = "0.1.0";
+
+    // Start by publishing a new component package
+    let name = PackageName::new(PACKAGE_NAME)?;
+    let client = create_client(config).await?;
+    let old_key = test_signing_key();
+    publish_component(
+        &client,
+        &name,
+        PACKAGE_VERSION,
+        "(component)",
+        true,
+        &old_key,
+    )
+    .await?;
+
+    // Rotate the package's key to a new key
+    let new_key = PrivateKey::from(p256::ecdsa::SigningKey::random(&mut OsRng));
+    let record_id = client
+        .publish_with_info(
+            &old_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![PublishEntry::RotateKey {
+                    key_id: old_key.public_key().fingerprint(),
+                    key: new_key.public_key(),
+                }],
+                cosigning: None,
+            },
+        )
+        .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    // The new key can publish
+    publish_component(&client, &name, "0.2.0", "(component)", false, &new_key).await?;
+
+    // The old key has been retired
+    let message = format!(
+        "{:#}",
+        publish_component(&client, &name, "0.3.0", "(component)", false, &old_key)
+            .await
+            .expect_err("expected publish to fail")
+    );
+
+    assert!(
+        message.contains("was rotated"),
+        "unexpected error message: {message}"
+    );
+
+    Ok(())
+}
+
+async fn test_version_permissions(config: &Config) -> Result<()> {
+    const PACKAGE_NAME: &str = "test:version-permissions";
+    const PACKAGE_VERSION: &str = "0.1.0";
+
+    // Start by publishing a new component package
+    let name = PackageName::new(PACKAGE_NAME)?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish_component(
+        &client,
+        &name,
+        PACKAGE_VERSION,
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    // Grant another key permission to release 0.x versions only
+    let limited_key = PrivateKey::from(p256::ecdsa::SigningKey::random(&mut OsRng));
+    let record_id = client
+        .publish_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![PublishEntry::GrantVersions {
+                    key: limited_key.public_key(),
+                    permissions: vec![Permission::Release],
+                    versions: "0.*".parse()?,
+                }],
+                cosigning: None,
+            },
+        )
+        .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    // The key can release a matching version
+    publish_component(&client, &name, "0.2.0", "(component)", false, &limited_key).await?;
+
+    // The key cannot release any other version
+    let message = format!(
+        "{:#}",
+        publish_component(&client, &name, "1.0.0", "(component)", false, &limited_key)
+            .await
+            .expect_err("expected publish to fail")
+    );
+
+    assert!(
+        message.contains("for version 1.0.0"),
+        "unexpected error message: {message}"
+    );
+
+    Ok(())
+}
+
+async fn test_threshold_signing(config: &Config) -> Result<()> {
+    const PACKAGE_NAME: &str = "test:threshold";
+    const PACKAGE_VERSION: &str = "0.1.0";
+
+    // Start by publishing a new component package
+    let name = PackageName::new(PACKAGE_NAME)?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish_component(
+        &client,
+        &name,
+        PACKAGE_VERSION,
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    // Grant a second maintainer and require both to sign
+    let cosigning_key = PrivateKey::from(p256::ecdsa::SigningKey::random(&mut OsRng));
+    let record_id = client
+        .publish_with_info(
+            &signing_key,
+            PublishInfo {
+                name: name.clone(),
+                head: None,
+                entries: vec![
+                    PublishEntry::Grant {
+                        key: cosigning_key.public_key(),
+                        permissions: Permission::all().to_vec(),
+                    },
+                    PublishEntry::SignatureThreshold { threshold: 2 },
+                ],
+                cosigning: None,
+            },
+        )
+        .await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    // A release signed by a single maintainer is rejected
+    let message = format!(
+        "{:#}",
+        publish_component(&client, &name, "0.2.0", "(component)", false, &signing_key)
+            .await
+            .expect_err("expected publish to fail")
+    );
+
+    assert!(
+        message.contains("requires signatures from 2 key(s)"),
+        "unexpected error message: {message}"
+    );
+
+    // A cosigned release is accepted
+    let digest = client
+        .content()
+        .store_content(
+            Box::pin(futures::stream::once(async move {
+                Ok(wat::parse_str("(component)")?.into())
+            })),
+            None,
+        )
+        .await?;
+
+    let mut info = PublishInfo {
+        name: name.clone(),
+        head: None,
+        entries: vec![PublishEntry::Release {
+            version: "0.2.0".parse()?,
+            content: digest,
+        }],
+        cosigning: None,
+    };
+    client.prepare_cosigning(&mut info).await?;
+    info.cosign(&cosigning_key)?;
+
+    let record_id = client.publish_with_info(&signing_key, info).await?;
+    client
+        .wait_for_publish(&name, &record_id, Duration::from_millis(100))
+        .await?;
+
+    client
+        .download_exact(&name, &"0.2.0".parse()?)
+        .await
+        .context("failed to download cosigned release")?;
+
+    Ok(())
+}
+
+async fn test_invalid_signature(config: &Config) -> Result<()> {
+    const PACKAGE_NAME: &str = "test:invalid-signature";
 
     // Use a reqwest client directly here as we're going to be sending an invalid signature
     let name = PackageName::new(PACKAGE_NAME)?;
@@ -470,6 +1345,389 @@ async fn test_fetch_package_names(config: &Config) -> Result<()> {
     Ok(())
 }
 
+async fn test_package_absence(config: &Config) -> Result<()> {
+    let present = PackageName::new("test:present")?;
+    let absent = PackageName::new("test:absent")?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish_component(
+        &client,
+        &present,
+        "0.1.0",
+        "(component)",
+        true,
+        &signing_key,
+    )
+    .await?;
+
+    // The client verifies the absence proof before reporting a missing package
+    match client.fetch_package(&absent).await {
+        Err(ClientError::PackageDoesNotExist { name, .. }) => assert_eq!(name, absent),
+        res => panic!("expected package does not exist error, got {res:?}"),
+    }
+
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ts_checkpoint = client.latest_checkpoint(None).await?;
+    let checkpoint = &ts_checkpoint.as_ref().checkpoint;
+
+    assert!(
+        client
+            .prove_absence(None, checkpoint, &[LogId::package_log::<Sha256>(&absent)])
+            .await?,
+        "expected the registry to prove the absence of the package"
+    );
+
+    let present_id = LogId::package_log::<Sha256>(&present);
+    match client
+        .prove_absence(None, checkpoint, std::slice::from_ref(&present_id))
+        .await
+    {
+        Err(api::ClientError::Proof(ProofError::PackageLogIncluded(id))) => {
+            assert_eq!(id, present_id)
+        }
+        res => panic!("expected package log included error, got {res:?}"),
+    }
+
+    Ok(())
+}
+
+async fn test_map_inclusion_formats(config: &Config) -> Result<()> {
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    let mut indexes = Vec::new();
+    let mut leafs = Vec::new();
+    for name in ["test:included-one", "test:included-two"] {
+        let name = PackageName::new(name)?;
+        publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+        let info = client.package(&name).await?;
+        indexes.push(info.head_registry_index.context("missing head index")?);
+        leafs.push(LogLeaf {
+            log_id: LogId::package_log::<Sha256>(&name),
+            record_id: info.state.head().as_ref().unwrap().digest.clone(),
+        });
+    }
+
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ts_checkpoint = client.latest_checkpoint(None).await?;
+    let checkpoint = &ts_checkpoint.as_ref().checkpoint;
+
+    // Both map proof formats prove the inclusion of the same leafs
+    for map_format in [MapProofFormat::Bundle, MapProofFormat::Multiproof] {
+        let request = || InclusionRequest {
+            log_length: checkpoint.log_length,
+            leafs: indexes.clone(),
+            map_format,
+            absent: Vec::new(),
+        };
+        client
+            .prove_inclusion(None, request(), checkpoint, &leafs)
+            .await?;
+
+        // Swapping the record ids of the leafs invalidates the proofs
+        let swapped = vec![
+            LogLeaf {
+                log_id: leafs[0].log_id.clone(),
+                record_id: leafs[1].record_id.clone(),
+            },
+            LogLeaf {
+                log_id: leafs[1].log_id.clone(),
+                record_id: leafs[0].record_id.clone(),
+            },
+        ];
+        assert!(client
+            .prove_map_inclusion(None, request(), checkpoint, &swapped)
+            .await
+            .is_err());
+    }
+
+    Ok(())
+}
+
+async fn test_checkpoint_witnessing(config: &Config) -> Result<()> {
+    let name = PackageName::new("test:witnessed")?;
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    publish_component(&client, &name, "0.1.0", "(component)", true, &signing_key).await?;
+
+    // Use separate client storage for the witness and for a client that requires
+    // a witness cosignature
+    let storage = |prefix: &str| Config {
+        registries_dir: config
+            .registries_dir
+            .as_ref()
+            .map(|p| p.with_file_name(format!("{prefix}-registries"))),
+        content_dir: config
+            .content_dir
+            .as_ref()
+            .map(|p| p.with_file_name(format!("{prefix}-content"))),
+        namespace_map_path: config
+            .namespace_map_path
+            .as_ref()
+            .map(|p| p.with_file_name(format!("{prefix}-namespaces"))),
+        ..config.clone()
+    };
+
+    let witness_key = test_witness_key();
+    let witnessed = create_client(&Config {
+        witness_keys: vec![witness_key.public_key()],
+        witness_threshold: 1,
+        ..storage("witnessed")
+    })
+    .await?;
+
+    // The checkpoint is not accepted until it has been cosigned
+    match witnessed.package(&name).await {
+        Err(ClientError::InsufficientWitnessCosignatures {
+            required: 1,
+            found: 0,
+            ..
+        }) => {}
+        res => panic!("expected insufficient witness cosignatures error, got {res:?}"),
+    }
+
+    // Only witness keys of the registry may cosign checkpoints
+    match client.cosign_checkpoint(&signing_key).await {
+        Err(ClientError::Api(api::ClientError::Witness(WitnessError::Unauthorized(_)))) => {}
+        res => panic!("expected unauthorized error, got {res:?}"),
+    }
+
+    let witness = create_client(&storage("witness")).await?;
+    let cosignatures = witness.cosign_checkpoint(&witness_key).await?;
+    assert_eq!(cosignatures.len(), 1);
+    assert_eq!(
+        cosignatures[0].key_id,
+        witness_key.public_key().fingerprint()
+    );
+
+    witnessed.package(&name).await?;
+
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ts_checkpoint = client.latest_checkpoint(None).await?;
+    let log_length = ts_checkpoint.as_ref().checkpoint.log_length;
+    assert_eq!(
+        client
+            .checkpoint_cosignatures(None, log_length)
+            .await?
+            .cosignatures,
+        cosignatures
+    );
+
+    match client
+        .checkpoint_cosignatures(None, log_length + 100)
+        .await
+        .map(|res| res.cosignatures)
+    {
+        Err(api::ClientError::Witness(WitnessError::CheckpointNotFound(len))) => {
+            assert_eq!(len, log_length + 100)
+        }
+        res => panic!("expected checkpoint not found error, got {res:?}"),
+    }
+
+    // Until the latest checkpoint is cosigned, the client falls back to the
+    // newest cosigned checkpoint, at which the next package has no records
+    let next = PackageName::new("test:witnessed-next")?;
+    publish_component(&witness, &next, "0.1.0", "(component)", true, &signing_key).await?;
+    match witnessed.package(&next).await {
+        Err(ClientError::PackageLogEmpty { name }) => assert_eq!(name, next),
+        res => panic!("expected package log empty error, got {res:?}"),
+    }
+
+    witness.cosign_checkpoint(&witness_key).await?;
+    witnessed.package(&next).await?;
+
+    let cosigned = client.cosigned_checkpoints(None).await?.checkpoints;
+    assert_eq!(cosigned.len(), 2);
+    assert!(
+        cosigned[0].checkpoint.as_ref().checkpoint.log_length
+            > cosigned[1].checkpoint.as_ref().checkpoint.log_length
+    );
+    assert_eq!(
+        cosigned[1].checkpoint.as_ref().checkpoint.log_length,
+        log_length
+    );
+
+    // The registry serves the last checkpoint cosigned by the witness
+    let latest = client
+        .witness_checkpoint(None, &witness_key.public_key().fingerprint())
+        .await?
+        .checkpoint
+        .expect("the witness should have cosigned a checkpoint");
+    let latest = latest.checkpoint.as_ref().checkpoint.clone();
+    assert_eq!(latest, cosigned[0].checkpoint.as_ref().checkpoint);
+    assert!(client
+        .witness_checkpoint(None, &signing_key.public_key().fingerprint())
+        .await?
+        .checkpoint
+        .is_none());
+
+    // A cosignature must name the last checkpoint cosigned by the witness
+    for old_log_length in [None, Some(log_length)] {
+        match client
+            .cosign_checkpoint(
+                None,
+                CosignCheckpointRequest {
+                    old_log_length,
+                    log_length: latest.log_length,
+                    cosignature: Cow::Owned(latest.cosign(&witness_key)?),
+                },
+            )
+            .await
+        {
+            Err(api::ClientError::Witness(WitnessError::Conflict(_))) => {}
+            res => panic!(
+                "expected conflict error, got {res:?}",
+                res = res.map(|r| r.cosignatures)
+            ),
+        }
+    }
+
+    // A witness cannot go back to cosigning an older checkpoint
+    match client
+        .cosign_checkpoint(
+            None,
+            CosignCheckpointRequest {
+                old_log_length: Some(latest.log_length),
+                log_length,
+                cosignature: Cow::Owned(ts_checkpoint.as_ref().checkpoint.cosign(&witness_key)?),
+            },
+        )
+        .await
+    {
+        Err(api::ClientError::Witness(WitnessError::Conflict(_))) => {}
+        res => panic!(
+            "expected conflict error, got {res:?}",
+            res = res.map(|r| r.cosignatures)
+        ),
+    }
+
+    Ok(())
+}
+
+async fn test_log_tiles(config: &Config) -> Result<()> {
+    let client = create_client(config).await?;
+    let signing_key = test_signing_key();
+    let first = PackageName::new("test:tiled")?;
+    publish_component(&client, &first, "0.1.0", "(component)", true, &signing_key).await?;
+
+    // Use separate client storage for a client that verifies log proofs with tiles
+    let tiled = create_client(&Config {
+        registries_dir: config
+            .registries_dir
+            .as_ref()
+            .map(|p| p.with_file_name("tiled-registries")),
+        content_dir: config
+            .content_dir
+            .as_ref()
+            .map(|p| p.with_file_name("tiled-content")),
+        namespace_map_path: config
+            .namespace_map_path
+            .as_ref()
+            .map(|p| p.with_file_name("tiled-namespaces")),
+        use_tiles: true,
+        ..config.clone()
+    })
+    .await?;
+
+    // The first fetch proves inclusion and the second also proves consistency
+    tiled.package(&first).await?;
+    let second = PackageName::new("test:tiled-second")?;
+    publish_component(&client, &second, "0.1.0", "(component)", true, &signing_key).await?;
+    tiled.package(&second).await?;
+
+    let api = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ts_checkpoint = api.latest_checkpoint(None).await?;
+    let checkpoint = &ts_checkpoint.as_ref().checkpoint;
+
+    // The tiles checkpoint is a signed note of the latest checkpoint
+    let url = Url::parse(config.home_url.as_ref().unwrap())?;
+    let response = reqwest::get(url.join(paths::tiles_checkpoint())?).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let note: Note = response.text().await?.parse()?;
+    let origin = format!("{}:{}", url.host_str().unwrap(), url.port().unwrap());
+    note.verify(&origin, &test_operator_key().public_key())?;
+    assert_eq!(Checkpoint::from_note_text(note.text())?.1, *checkpoint);
+
+    // The test logs fit in the first partial tile
+    let log_length = checkpoint.log_length;
+    assert!(log_length < 256);
+    let response =
+        reqwest::get(url.join(&paths::tile(&format!("tile/0/000.p/{log_length}")))?).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    assert_eq!(
+        response.headers()[reqwest::header::CACHE_CONTROL],
+        "public, max-age=31536000, immutable"
+    );
+    let hashes = response.bytes().await?;
+    assert_eq!(hashes.len(), log_length * 32);
+
+    let response =
+        reqwest::get(url.join(&paths::tile(&format!("tile/entries/000.p/{log_length}")))?).await?;
+    assert_eq!(response.status(), StatusCode::OK);
+    let mut entries = &response.bytes().await?[..];
+
+    // Each entry of the data tile is an encoded log leaf hashing to the
+    // corresponding hash of the level 0 tile
+    let mut leafs = Vec::new();
+    while !entries.is_empty() {
+        let (len, rest) = entries.split_at(2);
+        let (entry, rest) = rest.split_at(u16::from_be_bytes([len[0], len[1]]) as usize);
+        let hash = Hash::<Sha256>::of((0u8, entry));
+        assert_eq!(
+            hash.bytes(),
+            &hashes[leafs.len() * 32..(leafs.len() + 1) * 32]
+        );
+        leafs.push(LogLeaf::decode(entry)?);
+        entries = rest;
+    }
+    assert_eq!(leafs.len(), log_length);
+    assert!(leafs
+        .iter()
+        .any(|leaf| leaf.log_id == LogId::package_log::<Sha256>(&second)));
+
+    Ok(())
+}
+
+/// Stores a package record without submitting it, as if the server stopped
+/// right after accepting the record.
+async fn store_queued_record(store: &dyn DataStore, name: &PackageName) -> Result<RecordId> {
+    let signing_key = test_signing_key();
+    let record = ProtoEnvelope::signed_contents(
+        &signing_key,
+        PackageRecord {
+            prev: None,
+            version: PACKAGE_RECORD_VERSION,
+            timestamp: SystemTime::now(),
+            entries: vec![PackageEntry::Init {
+                hash_algorithm: HashAlgorithm::Sha256,
+                key: signing_key.public_key(),
+            }],
+        },
+    )?;
+
+    let log_id = LogId::package_log::<Sha256>(name);
+    let record_id = RecordId::package_record::<Sha256>(&record);
+    store
+        .store_package_record(&log_id, name, &record_id, &record, &Default::default())
+        .await?;
+
+    Ok(record_id)
+}
+
+async fn test_queued_record_resubmission(
+    config: &Config,
+    name: &PackageName,
+    record_id: &RecordId,
+) -> Result<()> {
+    // The record was never submitted, so it is only published if the
+    // server resubmitted its queue on startup
+    let client = create_client(config).await?;
+    client
+        .wait_for_publish(name, record_id, Duration::from_millis(100))
+        .await?;
+
+    Ok(())
+}
+
 async fn test_get_ledger(config: &Config) -> Result<()> {
     let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
 
@@ -0,0 +1,157 @@
+//! Tests for the SQLite storage backend.
+
+use super::{support::*, *};
+use anyhow::{Context, Result};
+use testresult::TestResult;
+use warg_client::api;
+use warg_protocol::registry::RegistryLen;
+use warg_server::datastore::{DataStore, SqliteDataStore};
+
+fn data_store(root: &std::path::Path) -> Result<Box<dyn DataStore>> {
+    let path = root.join("registry.db");
+    Ok(Box::new(SqliteDataStore::new(
+        path.to_str().context("database path is not valid UTF-8")?,
+    )?))
+}
+
+/// A smoke test that ensures that SQLite integration works.
+///
+/// This runs the same tests as the PostgreSQL smoke test against a database
+/// file in the test's root directory, restarting the server in between.
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn it_works_with_sqlite() -> TestResult {
+    let root = root().await?;
+    let store = SqliteDataStore::new(root.join("registry.db").to_str().unwrap())?;
+    store.run_pending_migrations().await?;
+
+    let (server, config) = spawn_server(
+        &root,
+        None,
+        Some(Box::new(store)),
+        Some(vec![(
+            "test".to_string(),
+            test_signing_key().public_key().fingerprint(),
+        )]),
+    )
+    .await?;
+
+    // This should be the same set of tests as in `tests/memory/mod.rs`
+    test_initial_checkpoint(&config).await?;
+    test_component_publishing(&config).await?;
+    test_hash_algorithm_package_publishing(&config, HashAlgorithm::Sha512).await?;
+    test_hash_algorithm_package_publishing(&config, HashAlgorithm::Blake3).await?;
+    test_package_yanking(&config).await?;
+    test_package_unyanking(&config).await?;
+    test_package_deprecation(&config).await?;
+    test_wit_publishing(&config).await?;
+    test_wasm_content_policy(&config).await?;
+    test_unauthorized_signing_key(&config).await?;
+    // These are tested below where a different server is used that
+    // allows any signing key
+    //test_unknown_signing_key(&config).await?;
+    //test_key_rotation(&config).await?;
+    //test_threshold_signing(&config).await?;
+    //test_version_permissions(&config).await?;
+    test_invalid_signature(&config).await?;
+    test_fetch_package_names(&config).await?;
+    test_metrics(&config).await?;
+    test_health(&config).await?;
+    // The packed ledger assumes 32 byte record ids, which doesn't hold for
+    // the SHA-512 package published above
+    //test_get_ledger(&config).await?;
+
+    let mut packages = vec![
+        PackageName::new("test:component")?,
+        PackageName::new("test:sha512")?,
+        PackageName::new("test:blake3")?,
+        PackageName::new("test:yankee")?,
+        PackageName::new("test:unyankee")?,
+        PackageName::new("test:deprecated")?,
+        PackageName::new("test:wit-package")?,
+        PackageName::new("test:unauthorized-key")?,
+    ];
+
+    // There should be two log entries in the registry
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ts_checkpoint = client.latest_checkpoint(None).await?;
+    assert_eq!(
+        ts_checkpoint.as_ref().checkpoint.log_length,
+        packages.len() as RegistryLen + 5, /* publishes + initial checkpoint + two yanks + unyank + deprecation */
+        "expected {len} packages plus the initial checkpoint, two yanks, unyank and deprecation",
+        len = packages.len()
+    );
+
+    drop(server);
+
+    // Store a record that was accepted but never submitted before the restart
+    let queued = PackageName::new("test:queued")?;
+    let record_id = store_queued_record(data_store(&root)?.as_ref(), &queued).await?;
+
+    // Restart the server and ensure the data is still there
+    let (server, config) = spawn_server(&root, None, Some(data_store(&root)?), None).await?;
+
+    test_queued_record_resubmission(&config, &queued, &record_id).await?;
+
+    test_unknown_signing_key(&config).await?;
+    test_key_rotation(&config).await?;
+    test_threshold_signing(&config).await?;
+    test_version_permissions(&config).await?;
+    test_operator_define_namespace(&config).await?;
+
+    packages.push(PackageName::new("test:unknown-key")?);
+    packages.push(PackageName::new("test:rotated-key")?);
+    packages.push(PackageName::new("test:threshold")?);
+    packages.push(PackageName::new("test:version-permissions")?);
+    packages.push(PackageName::new("other:component")?);
+
+    let client = api::Client::new(config.home_url.as_ref().unwrap(), None)?;
+    let ts_checkpoint = client.latest_checkpoint(None).await?;
+    assert_eq!(
+        ts_checkpoint.as_ref().checkpoint.log_length,
+        packages.len() as RegistryLen + 13, /* publishes + initial checkpoint + two yanks + unyank + deprecation + queued + rotation + release + threshold + cosigned release + grant + release + operator */
+        "expected {len} packages plus the initial checkpoint, two yanks, unyank, deprecation, queued record, key rotation, release, signature threshold, cosigned release, version grant, release and operator record",
+        len = packages.len()
+    );
+
+    test_checkpoint_witnessing(&config).await?;
+    test_log_tiles(&config).await?;
+
+    // Delete the client cache to force a complete download of all packages below
+    fs::remove_dir_all(root.join("content"))?;
+    fs::remove_dir_all(root.join("registries"))?;
+
+    let client = create_client(&config).await?;
+    client.fetch_packages(packages.iter()).await?;
+
+    // Finally, after a restart, ensure the packages can be downloaded
+    for package in packages {
+        if package.name() == "yankee" {
+            continue;
+        }
+        client
+            .download(&package, &"0.1.0".parse()?)
+            .await?
+            .context("failed to resolve package")?;
+    }
+
+    // Restart the server for the custom content URL test
+    drop(client);
+    drop(server);
+    let (server, config) = spawn_server(
+        &root,
+        Some("https://example.com".parse().unwrap()),
+        Some(data_store(&root)?),
+        None,
+    )
+    .await?;
+
+    test_custom_content_url(&config).await?;
+
+    // Restart the server with publishing limits
+    drop(server);
+    let (_server, config) =
+        spawn_server_with_limits(&root, Some(data_store(&root)?), test_limits()).await?;
+    test_publish_limits(&config).await?;
+
+    Ok(())
+}
@@ -1,7 +1,9 @@
 use anyhow::{bail, Context, Result};
 use indexmap::IndexSet;
+use secrecy::Secret;
 use std::{
     env,
+    net::SocketAddr,
     path::{Path, PathBuf},
     sync::atomic::{AtomicUsize, Ordering},
     time::Duration,
@@ -15,12 +17,14 @@ use warg_client::{
     FileSystemClient, StorageLockResult,
 };
 use warg_crypto::{
-    hash::AnyHash,
+    hash::{AnyHash, HashAlgorithm},
     signing::{KeyID, PrivateKey},
 };
 use warg_protocol::{operator, registry::PackageName};
 use warg_server::{
+    auth::TokenStore,
     datastore::DataStore,
+    limits::Limits,
     policy::{content::WasmContentPolicy, record::AuthorizedKeyPolicy},
     Config, Server,
 };
@@ -43,14 +47,29 @@ pub fn test_signing_key() -> PrivateKey {
     PrivateKey::decode(key.to_string()).unwrap()
 }
 
+pub fn test_witness_key() -> PrivateKey {
+    let key = "ed25519:0TO8M8C0K6lHRzhtQjqDGdxK63mjiHCoG0xtCWYyOcw=";
+    PrivateKey::decode(key.to_string()).unwrap()
+}
+
 pub async fn create_client(config: &warg_client::Config) -> Result<FileSystemClient> {
-    match FileSystemClient::try_new_with_config(None, config, None).await? {
+    create_client_with_auth_token(config, None).await
+}
+
+pub async fn create_client_with_auth_token(
+    config: &warg_client::Config,
+    auth_token: Option<&str>,
+) -> Result<FileSystemClient> {
+    let auth_token = auth_token.map(|token| Secret::new(token.to_string()));
+    match FileSystemClient::try_new_with_config(None, config, auth_token).await? {
         StorageLockResult::Acquired(client) => Ok(client),
         _ => bail!("failed to acquire storage lock"),
     }
 }
 
 pub struct ServerInstance {
+    /// The address of the metrics endpoint, if it has an address of its own.
+    pub metrics_addr: Option<SocketAddr>,
     task: Option<JoinHandle<()>>,
     shutdown: CancellationToken,
     _subscriber_guard: DefaultGuard,
@@ -118,13 +137,79 @@ pub async fn spawn_server(
     data_store: Option<Box<dyn DataStore>>,
     authorized_keys: Option<Vec<(String, KeyID)>>,
 ) -> Result<(ServerInstance, warg_client::Config)> {
-    let _subscriber_guard = thread_test_logging();
+    let config = server_config(root, content_base_url, data_store, authorized_keys)?;
+    start_server(root, config).await
+}
 
-    let shutdown = CancellationToken::new();
+/// Spawns a server that uses the given content store as a background task.
+#[cfg(feature = "s3")]
+pub async fn spawn_server_with_content_store(
+    root: &Path,
+    data_store: Option<Box<dyn DataStore>>,
+    content_store: impl warg_server::contentstore::ContentStore + 'static,
+) -> Result<(ServerInstance, warg_client::Config)> {
+    let config = server_config(root, None, data_store, None)?.with_content_store(content_store);
+    start_server(root, config).await
+}
+
+/// Spawns a server that authenticates publishing with the given token store
+/// and authorizes it with the given key policy as a background task.
+pub async fn spawn_server_with_token_store(
+    root: &Path,
+    token_store: impl TokenStore + 'static,
+    policy: AuthorizedKeyPolicy,
+) -> Result<(ServerInstance, warg_client::Config)> {
+    let config = server_config(root, None, None, None)?
+        .with_token_store(token_store)
+        .with_record_policy(policy);
+    start_server(root, config).await
+}
+
+/// Spawns a server that serves its metrics on a separate address as a
+/// background task.
+pub async fn spawn_server_with_metrics_addr(
+    root: &Path,
+) -> Result<(ServerInstance, warg_client::Config)> {
+    let config = server_config(root, None, None, None)?.with_metrics_addr(([127, 0, 0, 1], 0));
+    start_server(root, config).await
+}
+
+/// Spawns a server that enforces the given publishing limits as a background
+/// task.
+pub async fn spawn_server_with_limits(
+    root: &Path,
+    data_store: Option<Box<dyn DataStore>>,
+    limits: Limits,
+) -> Result<(ServerInstance, warg_client::Config)> {
+    let config = server_config(root, None, data_store, None)?.with_limits(limits);
+    start_server(root, config).await
+}
+
+/// Spawns a server that stores checkpoints at the given interval and is not
+/// ready once the latest checkpoint is older than the given number of
+/// intervals as a background task.
+pub async fn spawn_server_with_checkpoint_staleness(
+    root: &Path,
+    checkpoint_interval: Duration,
+    max_checkpoint_staleness: u32,
+) -> Result<(ServerInstance, warg_client::Config)> {
+    let config = server_config(root, None, None, None)?
+        .with_checkpoint_interval(checkpoint_interval)
+        .with_max_checkpoint_staleness(max_checkpoint_staleness);
+    start_server(root, config).await
+}
+
+fn server_config(
+    root: &Path,
+    content_base_url: Option<Url>,
+    data_store: Option<Box<dyn DataStore>>,
+    authorized_keys: Option<Vec<(String, KeyID)>>,
+) -> Result<Config> {
     let mut config = Config::new(test_operator_key(), test_namespaces(), root.join("server"))
         .with_addr(([127, 0, 0, 1], 0))
-        .with_shutdown(shutdown.clone().cancelled_owned())
         .with_checkpoint_interval(Duration::from_millis(100))
+        .with_snapshot_interval(5) // Snapshot often so restarts restore from a snapshot.
+        .with_witness_keys([test_witness_key().public_key()])
         .with_content_policy(WasmContentPolicy::default()); // For the tests, we assume only wasm content is allowed.
 
     if let Some(content_url) = content_base_url {
@@ -141,12 +226,28 @@ pub async fn spawn_server(
     }
 
     if let Some(store) = data_store {
-        config = config.with_boxed_data_store(store);
+        // Keep the log on disk alongside a persistent data store so that
+        // restarts also restore the log from storage
+        config = config
+            .with_boxed_data_store(store)
+            .with_log_storage_dir(root.join("server").join("log"));
     }
 
+    Ok(config)
+}
+
+async fn start_server(
+    root: &Path,
+    config: Config,
+) -> Result<(ServerInstance, warg_client::Config)> {
+    let _subscriber_guard = thread_test_logging();
+
+    let shutdown = CancellationToken::new();
+    let config = config.with_shutdown(shutdown.clone().cancelled_owned());
     let server = Server::new(config).initialize().await?;
 
     let addr = server.local_addr()?;
+    let metrics_addr = server.metrics_addr().transpose()?;
     tracing::debug!("Test server running at {addr}");
 
     let task = tokio::spawn(async move {
@@ -155,6 +256,7 @@ pub async fn spawn_server(
     });
 
     let instance = ServerInstance {
+        metrics_addr,
         task: Some(task),
         shutdown,
         _subscriber_guard,
@@ -172,6 +274,9 @@ pub async fn spawn_server(
         disable_auto_package_init: true,
         disable_interactive: true,
         keyring_backend: None,
+        witness_keys: Vec::new(),
+        witness_threshold: 0,
+        use_tiles: false,
     };
 
     Ok((instance, config))
@@ -195,7 +300,9 @@ pub async fn publish(
 
     let mut entries = Vec::with_capacity(2);
     if init {
-        entries.push(PublishEntry::Init);
+        entries.push(PublishEntry::Init {
+            hash_algorithm: HashAlgorithm::Sha256,
+        });
     }
     entries.push(PublishEntry::Release {
         version: version.parse().unwrap(),
@@ -209,6 +316,7 @@ pub async fn publish(
                 name: name.clone(),
                 head: None,
                 entries,
+                cosigning: None,
             },
         )
         .await?;